@@ -0,0 +1,33 @@
+//! Headless run of the funnel preset, prints the achieved physics rate.
+//!
+//! `cargo run --release --example funnel_benchmark [steps]`
+
+use std::time::Instant;
+
+use perfect_collisions::{presets, World};
+
+const DT: f32 = 1. / 60.;
+
+fn main() {
+    let steps = std::env::args().nth(1).map_or(600, |steps| steps.parse().expect("steps must be a number"));
+
+    let mut world = World::new();
+    presets::funnel_pile(&mut world);
+
+    let start = Instant::now();
+    let mut slowest = 0f32;
+    let mut collisions = 0;
+    for _ in 0..steps {
+        let step_start = Instant::now();
+        if let Err(e) = world.step(DT) {
+            eprintln!("frame {}: {e}", world.frame);
+        }
+        slowest = slowest.max(step_start.elapsed().as_secs_f32());
+        collisions += world.resolved.len();
+    }
+    let elapsed = start.elapsed().as_secs_f32();
+
+    println!("{} objects, {steps} steps in {elapsed:.2} s", world.objects.len());
+    println!("physics rate: {:.1} Hz (slowest step {:.1} ms)", steps as f32 / elapsed, slowest * 1000.);
+    println!("collisions resolved: {collisions}, energy: {:.1}", world.total_energy());
+}
@@ -0,0 +1,35 @@
+//! Energy audit of the jointed chain preset for a range of joint iteration counts.
+//!
+//! `cargo run --release --example joint_audit [steps]`
+
+use perfect_collisions::{joint, presets};
+
+const DT: f32 = 1. / 60.;
+const ITERATIONS: [usize; 7] = [1, 2, 4, 8, 16, 32, 64];
+/// Largest energy deviation, relative to the best count's, still worth the fewer iterations
+const TOLERANCE: f32 = 1.5;
+
+fn main() {
+    let steps = std::env::args().nth(1).map_or(600, |steps| steps.parse().expect("steps must be a number"));
+
+    let audits = match joint::audit_iterations(presets::jointed_chain, &ITERATIONS, DT, steps) {
+        Ok(audits) => audits,
+        Err(e) => {
+            eprintln!("audit failed: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("{} links swung for {steps} steps", presets::CHAIN_LINKS);
+    println!("{:>10} {:>12} {:>14} {:>14}", "iterations", "energy drift", "max deviation", "max stretch");
+    for audit in &audits {
+        println!(
+            "{:>10} {:>12.3} {:>14.3} {:>14.4}",
+            audit.iterations, audit.energy_drift, audit.max_energy_deviation, audit.max_separation
+        );
+    }
+    let best = audits.iter().map(|audit| audit.max_energy_deviation).fold(f32::INFINITY, f32::min);
+    if let Some(fewest) = audits.iter().find(|audit| audit.max_energy_deviation <= best * TOLERANCE) {
+        println!("fewest iterations within {TOLERANCE}x of the best deviation: {}", fewest.iterations);
+    }
+}
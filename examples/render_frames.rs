@@ -0,0 +1,51 @@
+//! Headless run of a scene writing every few steps as a PNG frame, no window or GPU needed.
+//!
+//! `cargo run --release --example render_frames <scene.ron> <out dir> [steps] [every]`
+//!
+//! The frames go through the viewer's camera and drawing order, filled on the CPU by [`raster`].
+//! They are numbered, `ffmpeg -framerate 60 -i <out dir>/frame_%05d.png video.mp4` turns
+//! them into a video.
+
+use std::{fs, path::Path};
+
+use perfect_collisions::{
+    draw::View,
+    raster,
+    scene::Scene,
+    World,
+};
+
+const DT: f32 = 1. / 60.;
+const WIDTH: usize = 1280;
+const HEIGHT: usize = 720;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let usage = "usage: render_frames <scene.ron> <out dir> [steps] [every]";
+    let scene_path = args.next().expect(usage);
+    let out_dir = args.next().expect(usage);
+    let steps = args.next().map_or(600, |steps| steps.parse().expect("steps must be a number"));
+    let every = args.next().map_or(1, |every| every.parse::<usize>().expect("every must be a number").max(1));
+
+    let mut world = World::new();
+    Scene::load(&scene_path).unwrap_or_else(|e| panic!("{scene_path}: {e}")).load_into(&mut world);
+    fs::create_dir_all(&out_dir).expect("couldn't create the output directory");
+
+    // The camera stays where the scene starts, objects flying off leave the frame
+    let view = View::fit(&raster::bounds(&world));
+
+    let mut written = 0;
+    for step in 0..=steps {
+        if step % every == 0 {
+            let path = Path::new(&out_dir).join(format!("frame_{written:05}.png"));
+            raster::render(&world, &view, WIDTH, HEIGHT).save_png(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+            written += 1;
+        }
+        if step < steps {
+            if let Err(e) = world.step(DT) {
+                eprintln!("frame {}: {e}", world.frame);
+            }
+        }
+    }
+    println!("{written} frames of {} objects in {out_dir}", world.objects.len());
+}
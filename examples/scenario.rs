@@ -0,0 +1,42 @@
+//! Headless runner for the assertions embedded in scene files, fails if any of them does.
+//!
+//! `cargo run --release --example scenario <scene.ron>...`
+
+use std::process::ExitCode;
+
+use perfect_collisions::{scenario, scene::Scene, World};
+
+const DT: f32 = 1. / 60.;
+
+fn main() -> ExitCode {
+    let mut failed = 0;
+    for path in std::env::args().skip(1) {
+        let scene = match Scene::load(&path) {
+            Ok(scene) => scene,
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                failed += 1;
+                continue;
+            }
+        };
+        let assertions = scene.assertions.clone();
+        let mut world = World::new();
+        scene.load_into(&mut world);
+
+        for (assertion, outcome) in assertions.iter().zip(scenario::run(&mut world, &assertions, DT)) {
+            match outcome {
+                Ok(()) => println!("{path}: ok   {assertion}"),
+                Err(failure) => {
+                    println!("{path}: FAIL {assertion}: {failure}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("{failed} failed");
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,67 @@
+//! Periodic autosave to a temp scene file.
+//!
+//! The file is removed when the app exits normally, so finding it on startup means the previous
+//! session crashed and its state can be offered for restoring.
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use super::*;
+use scene::Scene;
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+const AUTOSAVE_FILE_NAME: &str = "perfect-collisions-autosave.ron";
+
+pub struct Autosave {
+    path: PathBuf,
+    last_save: Instant,
+    /// Scene left behind by a session which didn't exit cleanly
+    pub pending_restore: Option<Scene>,
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(AUTOSAVE_FILE_NAME);
+        let pending_restore = if path.exists() {
+            match Scene::load(&path) {
+                Ok(scene) => {
+                    info!(path = %path.display(), "found autosave from a previous session");
+                    Some(scene)
+                }
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "ignoring unreadable autosave");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Self {
+            path,
+            last_save: Instant::now(),
+            pending_restore,
+        }
+    }
+
+    /// Saves the world if the interval passed, the old file is kept while a restore is pending
+    pub fn tick(&mut self, world: &World) {
+        if self.pending_restore.is_some() || self.last_save.elapsed() < AUTOSAVE_INTERVAL {
+            return;
+        }
+        self.last_save = Instant::now();
+
+        if let Err(e) = Scene::from_world(world).save(&self.path) {
+            warn!(path = %self.path.display(), error = %e, "autosave failed");
+        }
+    }
+
+    /// Removes the autosave, called on clean exit
+    pub fn discard(&self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
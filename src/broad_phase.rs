@@ -0,0 +1,203 @@
+//! Statistics of the broad phase and advice on tuning it.
+//!
+//! The broad phase sweeps the objects' swept bounding boxes along one [`SweepAxis`], boxes
+//! overlapping along it and passing the bounds check become candidates for the exact query.
+//! [`World::broad_phase`] counts for the last step how many candidates every object got, how many
+//! of them the narrow phase turned down and what keeping the sweep structures sorted cost.
+//! [`advise`] turns the numbers into [`Advice`] once they cross the thresholds below.
+
+use core::fmt;
+
+use super::*;
+
+/// Candidate share the exact query has to reject before the pair cache is suggested
+const FALSE_POSITIVE_THRESHOLD: f32 = 0.8;
+/// Fewer exact queries than this per step aren't worth tuning
+const MIN_NARROW_CHECKS: usize = 32;
+/// The other axis is suggested once boxes overlap along it at most this often in comparison
+const AXIS_OVERLAP_RATIO: f32 = 0.5;
+/// Fewer overlaps along the sweep axis than this per object are cheap whatever the axis
+const MIN_OVERLAPS_PER_OBJECT: f32 = 2.;
+/// Boxes moved per object and step above which batched resolution is suggested
+const UPDATES_PER_OBJECT: f32 = 4.;
+
+/// Axis the broad phase sorts the boxes along
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum SweepAxis {
+    #[default]
+    X,
+    Y,
+}
+
+impl SweepAxis {
+    /// Extent of `aabb` along the axis
+    pub fn span(self, aabb: &Aabb) -> (f32, f32) {
+        match self {
+            Self::X => (aabb.min.x, aabb.max.x),
+            Self::Y => (aabb.min.y, aabb.max.y),
+        }
+    }
+    pub fn other(self) -> Self {
+        match self {
+            Self::X => Self::Y,
+            Self::Y => Self::X,
+        }
+    }
+}
+
+/// Counts of the last step, objects skipped by collision detection count as zero
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BroadPhaseStats {
+    /// Candidates each object looked up, again every time a collision changed its path
+    pub candidates: Vec<usize>,
+    /// Candidates of two objects without continuous detection, checked for overlap at the end
+    pub discrete: usize,
+    /// Candidates the pair cache knew to be apart
+    pub cached: usize,
+    /// Candidates the exact query ran on
+    pub narrow_checks: usize,
+    /// Exact queries which found an impact
+    pub impacts: usize,
+    /// Boxes moved in the sweep structures after collisions
+    pub updates: usize,
+    /// Pairs of swept boxes overlapping along x and along y
+    pub overlaps: [usize; 2],
+    /// Seconds spent sorting the boxes into the sweep structures, zero without `std`
+    pub build_time: f32,
+    /// Seconds spent moving boxes, zero without `std`
+    pub update_time: f32,
+}
+
+impl BroadPhaseStats {
+    pub fn total_candidates(&self) -> usize {
+        self.candidates.iter().sum()
+    }
+    pub fn max_candidates(&self) -> usize {
+        self.candidates.iter().copied().max().unwrap_or(0)
+    }
+    pub fn mean_candidates(&self) -> f32 {
+        self.total_candidates() as f32 / self.candidates.len().max(1) as f32
+    }
+    /// Share of the exact queries which found no impact
+    pub fn false_positive_rate(&self) -> f32 {
+        if self.narrow_checks == 0 {
+            return 0.;
+        }
+        1. - self.impacts as f32 / self.narrow_checks as f32
+    }
+    /// Adds the counts of a world made of the objects at `indices`, e.g. an island
+    pub fn absorb(&mut self, other: &Self, indices: &[usize]) {
+        for (&i, candidates) in indices.iter().zip(&other.candidates) {
+            self.candidates[i] += candidates;
+        }
+        self.discrete += other.discrete;
+        self.cached += other.cached;
+        self.narrow_checks += other.narrow_checks;
+        self.impacts += other.impacts;
+        self.updates += other.updates;
+        self.overlaps = [0, 1].map(|axis| self.overlaps[axis] + other.overlaps[axis]);
+        self.build_time += other.build_time;
+        self.update_time += other.update_time;
+    }
+}
+
+/// A setting worth changing according to the last step's [`BroadPhaseStats`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Advice {
+    /// The boxes are spread out along the other axis, sweeping along it finds fewer candidates
+    SwitchAxis { axis: SweepAxis },
+    /// Most exact queries find nothing, caching separated pairs skips them next step
+    EnablePairCache { false_positive_rate: f32 },
+    /// Collisions keep moving boxes, batches move each one once
+    BatchCollisions { updates_per_object: f32 },
+}
+
+impl fmt::Display for Advice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SwitchAxis { axis } => write!(f, "sweep along {axis:?}, the boxes overlap less often along it"),
+            Self::EnablePairCache { false_positive_rate } => write!(
+                f,
+                "enable the pair cache, {:.0}% of the exact queries find no impact",
+                false_positive_rate * 100.
+            ),
+            Self::BatchCollisions { updates_per_object } => write!(
+                f,
+                "batch collisions, boxes are moved {updates_per_object:.1} times per object and step"
+            ),
+        }
+    }
+}
+
+/// Suggestions for `world` based on its last step, empty while nothing crosses a threshold
+pub fn advise(world: &World) -> Vec<Advice> {
+    let stats = &world.broad_phase;
+    let objects = stats.candidates.len().max(1) as f32;
+    let mut advice = vec![];
+    let axis = world.sweep_axis;
+    let overlaps = |axis: SweepAxis| stats.overlaps[axis as usize] as f32;
+    if overlaps(axis) >= MIN_OVERLAPS_PER_OBJECT * objects && overlaps(axis.other()) <= AXIS_OVERLAP_RATIO * overlaps(axis) {
+        advice.push(Advice::SwitchAxis { axis: axis.other() });
+    }
+    let false_positive_rate = stats.false_positive_rate();
+    if !world.pair_cache.enabled && stats.narrow_checks >= MIN_NARROW_CHECKS && false_positive_rate > FALSE_POSITIVE_THRESHOLD {
+        advice.push(Advice::EnablePairCache { false_positive_rate });
+    }
+    let updates_per_object = stats.updates as f32 / objects;
+    if world.batch_tolerance.is_none() && updates_per_object > UPDATES_PER_OBJECT {
+        advice.push(Advice::BatchCollisions { updates_per_object });
+    }
+    advice
+}
+
+/// Number of pairs of `boxes` overlapping along x and along y
+pub fn axis_overlaps(boxes: &[Aabb]) -> [usize; 2] {
+    [SweepAxis::X, SweepAxis::Y].map(|axis| {
+        let mut spans = boxes.iter().map(|aabb| axis.span(aabb)).collect::<Vec<_>>();
+        spans.sort_unstable_by_key(|&(min, max)| (F32Ord(min), F32Ord(max)));
+        // Every overlapping pair is counted at the box starting first, the other one starts inside it
+        spans
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, max))| spans[i + 1..].partition_point(|&(min, _)| min <= max))
+            .sum()
+    })
+}
+
+#[test]
+fn test_broad_phase_stats_and_advice() {
+    let boxes = [
+        Aabb { min: vec2(0., 0.), max: vec2(1., 1.) },
+        Aabb { min: vec2(0.5, 5.), max: vec2(1.5, 6.) },
+        Aabb { min: vec2(1., 0.5), max: vec2(2., 1.5) },
+    ];
+    assert_eq!(axis_overlaps(&boxes), [3, 1]);
+
+    // A column of boxes falling onto a floor, along x every box overlaps every other one
+    let column = |axis: SweepAxis| {
+        let mut world = World::new();
+        world.sweep_axis = axis;
+        world.objects.push(Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(4., 1.))));
+        for i in 0..8 {
+            let y = 1. + 3. * i as f32;
+            world.objects.push(Object::new(vec2(0., y), vec2(0., -4.), 0., Polygon::rectangle(vec2(1., 1.))));
+        }
+        world.step(0.5).unwrap();
+        world
+    };
+    let world = column(SweepAxis::X);
+    let stats = &world.broad_phase;
+    assert_eq!(stats.candidates.len(), 9);
+    // The floor never looks anything up, the boxes find it and their neighbours
+    assert_eq!(stats.candidates[0], 0);
+    assert_eq!(stats.narrow_checks + stats.cached + stats.discrete, stats.total_candidates());
+    assert!(stats.impacts > 0 && stats.updates > 0 && stats.false_positive_rate() > 0.);
+    assert!(stats.overlaps[0] > 2 * stats.overlaps[1]);
+    assert_eq!(advise(&world), [Advice::SwitchAxis { axis: SweepAxis::Y }]);
+
+    // Sweeping along y finds fewer candidates and the same collisions
+    let swept_y = column(SweepAxis::Y);
+    assert!(swept_y.broad_phase.total_candidates() < stats.total_candidates());
+    assert_eq!(swept_y.resolved, world.resolved);
+    assert!(advise(&swept_y).is_empty());
+}
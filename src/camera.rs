@@ -1,5 +1,7 @@
 use std::ops::Deref;
 
+use perfect_collisions::draw::View;
+
 use super::*;
 
 #[derive(Default)]
@@ -17,4 +19,40 @@ impl Camera {
     pub fn screen_to_world(&self, screen_pos: Vec2) -> Vec2 {
         self.inv_transform(screen_pos)
     }
+    /// egui position of a world point, `screen` is the egui screen rect
+    pub fn world_to_egui(&self, point: Vec2, screen: egui::Rect) -> egui::Pos2 {
+        let pixel = self.view().to_pixel(point, vec2(screen.width(), screen.height()));
+        egui::pos2(screen.left() + pixel.x, screen.top() + pixel.y)
+    }
+    /// Centers `aabb` and zooms so its longer side fills the screen minus the margin
+    pub fn fit(&mut self, aabb: &Aabb) {
+        let view = View::fit(aabb);
+        self.look_at(view.focus, view.zoom);
+    }
+    /// The camera as [`raster`](perfect_collisions::raster) frames take it
+    pub fn view(&self) -> View {
+        View {
+            focus: self.focus(),
+            zoom: self.zoom(),
+        }
+    }
+    /// World point in the middle of the screen
+    pub fn focus(&self) -> Vec2 {
+        self.inv_transform(Vec2::ZERO)
+    }
+    /// World space box the screen shows
+    pub fn visible_area(&self) -> Aabb {
+        self.view().visible_area()
+    }
+    pub fn zoom(&self) -> f32 {
+        self.scale.x
+    }
+    /// Puts `focus` in the middle of the screen at the given zoom
+    pub fn look_at(&mut self, focus: Vec2, zoom: f32) {
+        self.0 = GTransform {
+            center: -focus * zoom,
+            rotation: 0.,
+            scale: Vec2::splat(zoom),
+        };
+    }
 }
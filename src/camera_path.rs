@@ -0,0 +1,146 @@
+//! Keyframed camera animations for recording demos.
+//!
+//! A path is a list of keyframes, each one the camera's focus and zoom at a playback time. Between
+//! two keyframes the focus moves and the zoom changes geometrically, shaped by the later
+//! keyframe's easing.
+
+use strum::IntoEnumIterator;
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, strum::Display, strum::EnumIter)]
+pub enum Easing {
+    Linear,
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            // Smoothstep, starts and ends at rest
+            Easing::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe {
+    /// Seconds since the start of playback
+    pub time: f32,
+    pub focus: Vec2,
+    pub zoom: f32,
+    /// Easing of the move from the previous keyframe to this one
+    pub easing: Easing,
+}
+
+#[derive(Default)]
+pub struct CameraPath {
+    /// Sorted by time
+    pub keyframes: Vec<Keyframe>,
+    /// Playback time while playing
+    pub playhead: Option<f32>,
+}
+
+impl CameraPath {
+    /// Adds the current view at `time`, replacing a keyframe at the same time
+    pub fn insert(&mut self, time: f32, camera: &Camera) {
+        let keyframe = Keyframe {
+            time,
+            focus: camera.focus(),
+            zoom: camera.zoom(),
+            easing: Easing::EaseInOut,
+        };
+        self.keyframes.retain(|other| other.time != time);
+        self.keyframes.push(keyframe);
+        self.sort();
+    }
+    pub fn sort(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    /// Focus and zoom at playback time `time`, held constant outside the keyframes
+    pub fn sample(&self, time: f32) -> Option<(Vec2, f32)> {
+        let first = self.keyframes.first()?;
+        if time <= first.time {
+            return Some((first.focus, first.zoom));
+        }
+        let Some(next) = self.keyframes.iter().position(|keyframe| keyframe.time > time) else {
+            let last = self.keyframes.last()?;
+            return Some((last.focus, last.zoom));
+        };
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+        let t = to.easing.apply((time - from.time) / (to.time - from.time));
+        let zoom = from.zoom * (to.zoom / from.zoom).powf(t);
+        Some((from.focus.lerp(to.focus, t), zoom))
+    }
+
+    pub fn play(&mut self) {
+        self.playhead = Some(0.);
+    }
+    pub fn stop(&mut self) {
+        self.playhead = None;
+    }
+    /// Advances playback and moves the camera, playback stops after the last keyframe
+    pub fn update(&mut self, dt: f32, camera: &mut Camera) {
+        let Some(playhead) = self.playhead.map(|playhead| playhead + dt) else {
+            return;
+        };
+        if let Some((focus, zoom)) = self.sample(playhead) {
+            camera.look_at(focus, zoom);
+        }
+        self.playhead = (playhead <= self.duration()).then_some(playhead);
+    }
+
+    /// Timeline editor, `new_keyframe_time` is where the next keyframe is added
+    pub fn show(&mut self, ui: &mut egui::Ui, camera: &Camera, new_keyframe_time: &mut f32) {
+        ui.horizontal(|ui| {
+            if self.playhead.is_some() {
+                if ui.button("Stop").clicked() {
+                    self.stop();
+                }
+            } else if ui.add_enabled(self.keyframes.len() > 1, egui::Button::new("Play")).clicked() {
+                self.play();
+            }
+            let progress = self.playhead.map_or(0., |playhead| playhead / self.duration().max(f32::EPSILON));
+            ui.add(egui::ProgressBar::new(progress).text(format!("{:.1} / {:.1} s", self.playhead.unwrap_or(0.), self.duration())));
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(new_keyframe_time).clamp_range(0.0..=f32::MAX).speed(0.1).suffix(" s"));
+            if ui.button("Add keyframe from view").clicked() {
+                self.insert(*new_keyframe_time, camera);
+                *new_keyframe_time += 1.;
+            }
+        });
+
+        let mut removed = None;
+        let mut retimed = false;
+        for (i, keyframe) in self.keyframes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                retimed |= ui
+                    .add(egui::DragValue::new(&mut keyframe.time).clamp_range(0.0..=f32::MAX).speed(0.1).suffix(" s"))
+                    .changed();
+                egui::ComboBox::from_id_source(("easing", i))
+                    .selected_text(keyframe.easing.to_string())
+                    .show_ui(ui, |ui| {
+                        for easing in Easing::iter() {
+                            ui.selectable_value(&mut keyframe.easing, easing, easing.to_string());
+                        }
+                    });
+                ui.label(format!("({:.2}, {:.2}) x{:.3}", keyframe.focus.x, keyframe.focus.y, keyframe.zoom));
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.keyframes.remove(i);
+        }
+        if retimed {
+            self.sort();
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Checkpoints of the world's state and structured diffs between two of them.
+//!
+//! A [`Checkpoint`] keeps every object's kinematic state and the pairs that collided in the step
+//! before it. [`Checkpoint::diff`] matches the objects of two checkpoints by index and reports how
+//! far each one drifted and which contacts appeared or disappeared, e.g. to measure how much two
+//! solver configurations diverge when started from the same scene.
+
+use alloc::collections::BTreeSet;
+
+use super::*;
+use world::ObjectState;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub time: f32,
+    pub frame: usize,
+    pub states: Vec<ObjectState>,
+    /// Index pairs, lower index first, that collided in the step before the checkpoint
+    pub contacts: BTreeSet<(usize, usize)>,
+}
+
+/// Change of one object present in both checkpoints, the later state minus the earlier one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObjectDelta {
+    pub index: usize,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub rotation: f32,
+    pub rot_velocity: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CheckpointDiff {
+    /// Objects whose state differs, in index order
+    pub objects: Vec<ObjectDelta>,
+    /// Indices only the other checkpoint has
+    pub added: Vec<usize>,
+    /// Indices only this checkpoint has
+    pub removed: Vec<usize>,
+    pub contacts_gained: Vec<(usize, usize)>,
+    pub contacts_lost: Vec<(usize, usize)>,
+}
+
+impl Checkpoint {
+    pub fn capture(world: &World) -> Self {
+        Self {
+            time: world.time_elapsed,
+            frame: world.frame,
+            states: world.snapshot_states(),
+            contacts: world
+                .resolved
+                .iter()
+                .map(|toi| (toi.object_1.min(toi.object_2), toi.object_1.max(toi.object_2)))
+                .collect(),
+        }
+    }
+
+    /// What changed from `self` to `other`
+    pub fn diff(&self, other: &Self) -> CheckpointDiff {
+        let objects = self
+            .states
+            .iter()
+            .zip(&other.states)
+            .filter(|(before, after)| before != after)
+            .map(|(before, after)| ObjectDelta {
+                index: before.index,
+                position: after.position - before.position,
+                velocity: after.velocity - before.velocity,
+                rotation: after.rotation - before.rotation,
+                rot_velocity: after.rot_velocity - before.rot_velocity,
+            })
+            .collect();
+        CheckpointDiff {
+            objects,
+            added: (self.states.len()..other.states.len()).collect(),
+            removed: (other.states.len()..self.states.len()).collect(),
+            contacts_gained: other.contacts.difference(&self.contacts).copied().collect(),
+            contacts_lost: self.contacts.difference(&other.contacts).copied().collect(),
+        }
+    }
+}
+
+impl CheckpointDiff {
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.contacts_gained.is_empty()
+            && self.contacts_lost.is_empty()
+    }
+    /// Farthest any object drifted, the divergence in one number
+    pub fn max_position_delta(&self) -> f32 {
+        self.objects.iter().map(|delta| delta.position.length()).fold(0., f32::max)
+    }
+    pub fn max_velocity_delta(&self) -> f32 {
+        self.objects.iter().map(|delta| delta.velocity.length()).fold(0., f32::max)
+    }
+}
+
+#[test]
+fn test_checkpoint_diff() {
+    let scene = |collision_margin| {
+        let mut world = World::new();
+        world.collision_margin = collision_margin;
+        world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., Polygon::regular(4)));
+        world.objects.push(Object::new(vec2(4., 0.3), Vec2::ZERO, 0., Polygon::regular(4)));
+        world.objects.push(Object::new(vec2(0., 5.), Vec2::ZERO, 0., Polygon::regular(3)));
+        world
+    };
+    let mut world = scene(world::DEFAULT_COLLISION_MARGIN);
+    let start = Checkpoint::capture(&world);
+    assert!(start.diff(&start.clone()).is_empty());
+
+    // The same run with and without a skin, the faces meet flat so the margin decides which
+    // corner leads and only the colliding pair diverges
+    let mut skinless = scene(0.);
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+        skinless.step(1. / 60.).unwrap();
+    }
+    let diff = Checkpoint::capture(&world).diff(&Checkpoint::capture(&skinless));
+    assert_eq!(diff.objects.iter().map(|delta| delta.index).collect::<Vec<_>>(), [0, 1]);
+    assert!(diff.max_position_delta() > 0.);
+    // Both conserve momentum, the pair diverges in opposite directions
+    assert!((diff.objects[0].velocity + diff.objects[1].velocity).length() < 1e-4);
+
+    // Contacts and objects coming and going
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., Polygon::regular(4)));
+    world.objects.push(Object::new(vec2(2.5, 0.), Vec2::ZERO, 0., Polygon::regular(4)));
+    let before = Checkpoint::capture(&world);
+    world.step(0.5).unwrap();
+    world.objects.push(Object::new(vec2(0., 5.), Vec2::ZERO, 0., Polygon::regular(3)));
+    let after = Checkpoint::capture(&world);
+    let diff = before.diff(&after);
+    assert_eq!(diff.contacts_gained, [(0, 1)]);
+    assert_eq!(diff.added, [2]);
+    assert!(after.diff(&before).removed == [2] && after.diff(&before).contacts_lost == [(0, 1)]);
+    assert!((diff.objects[1].velocity.x - 2.).abs() < 1e-3);
+}
@@ -0,0 +1,554 @@
+//! Commands controlling a simulation, the one path every front end changes the world through.
+//!
+//! The viewer's buttons, its console and command scripts all turn into a [`Command`], and
+//! recordings keep the ones issued before each step. Every command has a one line text form, e.g.
+//! `spawn circle 0 0 vel 3 1` or `set gravity 0 -9.81`, which [`Command::parse`] reads and
+//! `Display` writes. A script is a list of them, one per line, with `#` starting a comment.
+//! [`Command::run`] applies the ones that only touch the [`World`] and hands the rest (pausing,
+//! files) back to the front end.
+
+use alloc::string::{String, ToString};
+use core::{fmt, str::SplitWhitespace};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Circle,
+    Capsule,
+    Box,
+    Triangle,
+    Regular(usize),
+}
+
+impl Shape {
+    /// Unit sized object centered on `position`, circles and capsules are exact
+    /// [`object::Rounded`] ones
+    pub fn object(self, position: Vec2, velocity: Vec2, rotation: f32) -> Object {
+        let polygon = match self {
+            Self::Circle => return Object::new_rounded(position, velocity, rotation, object::Rounded::Circle { radius: 1. }),
+            Self::Capsule => return Object::capsule(position, velocity, rotation, 2., 0.5),
+            Self::Box => Polygon::rectangle(vec2(1., 1.)),
+            Self::Triangle => Polygon::regular(3),
+            Self::Regular(sides) => Polygon::regular(sides),
+        };
+        Object::new(position, velocity, rotation, polygon)
+    }
+}
+
+/// Property of one object [`Command::SetProperty`] changes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Property {
+    Position(Vec2),
+    Velocity(Vec2),
+    Rotation(f32),
+    RotVelocity(f32),
+    /// Scales the inertia along
+    Mass(f32),
+    Restitution(f32),
+    /// Multiple of the world's gravity the object falls with
+    GravityScale(f32),
+    /// Drawing order, see [`Object::z_index`]
+    ZIndex(i32),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Command {
+    Spawn {
+        shape: Shape,
+        position: Vec2,
+        velocity: Vec2,
+        rotation: f32,
+        restitution: f32,
+    },
+    Delete {
+        object: usize,
+    },
+    SetProperty {
+        object: usize,
+        property: Property,
+    },
+    ApplyImpulse {
+        object: usize,
+        impulse: Vec2,
+        /// World point the impulse acts at, the center when `None`
+        point: Option<Vec2>,
+    },
+    ApplyAngularImpulse {
+        object: usize,
+        impulse: f32,
+    },
+    /// Pauses a single object, see [`Object::pause`]
+    Freeze {
+        object: usize,
+    },
+    Unfreeze {
+        object: usize,
+    },
+    /// Breaks the object into fragments, see [`World::fracture`]
+    Shatter {
+        object: usize,
+        pieces: usize,
+        /// Kinetic energy breaking it takes, negative for an explosion
+        energy: f32,
+    },
+    /// Gravity of the world, see [`World::gravity`]
+    SetGravity(Vec2),
+    SetMargin(f32),
+    /// Strength of the mutual attraction, `None` turns it off
+    SetAttraction(Option<f32>),
+    /// Restarts the world's random streams from the seed, see [`rng`]
+    SetSeed(u64),
+    Pause,
+    Resume,
+    /// Writes the scene to the path
+    Save(String),
+    /// Replaces the scene with the one at the path
+    LoadScene(String),
+    /// Runs the script at the path
+    Run(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    UnknownCommand(String),
+    UnknownShape(String),
+    UnknownSetting(String),
+    /// The named argument is missing
+    Missing(&'static str),
+    NotANumber(String),
+    /// Left over after a complete command
+    Unexpected(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no command given"),
+            Self::UnknownCommand(word) => write!(f, "unknown command '{word}'"),
+            Self::UnknownShape(word) => write!(f, "unknown shape '{word}', try circle, capsule, box, triangle or polygon <sides>"),
+            Self::UnknownSetting(word) => write!(
+                f,
+                "unknown setting '{word}', try gravity, margin, attraction, seed or an object index followed by \
+                 position, velocity, rotation, spin, mass, restitution, gravity or z"
+            ),
+            Self::Missing(argument) => write!(f, "missing {argument}"),
+            Self::NotANumber(word) => write!(f, "'{word}' isn't a number"),
+            Self::Unexpected(word) => write!(f, "unexpected '{word}'"),
+        }
+    }
+}
+
+/// Why a parsed command couldn't be applied, the world is left as it was
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommandError {
+    /// Index of an object the command refers to that doesn't exist
+    NoSuchObject(usize),
+    /// Masses have to be finite and positive
+    InvalidMass(f32),
+    /// Static and kinematic objects have no mass to set, they move the same whatever hits them
+    NotDynamic(usize),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchObject(object) => write!(f, "there's no object {object}"),
+            Self::InvalidMass(mass) => write!(f, "mass {mass} isn't finite and positive"),
+            Self::NotDynamic(object) => write!(f, "object {object} is static or kinematic, its mass can't be set"),
+        }
+    }
+}
+
+struct Words<'a>(SplitWhitespace<'a>);
+
+impl<'a> Words<'a> {
+    fn word(&mut self, name: &'static str) -> Result<&'a str, ParseError> {
+        self.0.next().ok_or(ParseError::Missing(name))
+    }
+    fn parsed<T: core::str::FromStr>(&mut self, name: &'static str) -> Result<T, ParseError> {
+        let word = self.word(name)?;
+        word.parse().map_err(|_| ParseError::NotANumber(word.to_string()))
+    }
+    fn vector(&mut self, x: &'static str, y: &'static str) -> Result<Vec2, ParseError> {
+        Ok(vec2(self.parsed(x)?, self.parsed(y)?))
+    }
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        let mut words = Words(line.split_whitespace());
+        let command = match words.0.next().ok_or(ParseError::Empty)? {
+            "spawn" => {
+                let shape = match words.word("shape")? {
+                    "circle" => Shape::Circle,
+                    "capsule" => Shape::Capsule,
+                    "box" => Shape::Box,
+                    "triangle" => Shape::Triangle,
+                    "polygon" => {
+                        let sides = words.word("side count")?;
+                        match sides.parse() {
+                            Ok(sides) if sides >= 3 => Shape::Regular(sides),
+                            _ => return Err(ParseError::NotANumber(sides.to_string())),
+                        }
+                    }
+                    shape => return Err(ParseError::UnknownShape(shape.to_string())),
+                };
+                let (mut velocity, mut rotation, mut restitution) = (Vec2::ZERO, 0., 1.);
+                let position = words.vector("x", "y")?;
+                // Optional and in any order, the last one given wins
+                while let Some(option) = words.0.next() {
+                    match option {
+                        "vel" => velocity = words.vector("velocity x", "velocity y")?,
+                        "rot" => rotation = words.parsed("rotation")?,
+                        "bounce" => restitution = words.parsed("restitution")?,
+                        word => return Err(ParseError::Unexpected(word.to_string())),
+                    }
+                }
+                Self::Spawn {
+                    shape,
+                    position,
+                    velocity,
+                    rotation,
+                    restitution,
+                }
+            }
+            "delete" => Self::Delete {
+                object: words.parsed("object index")?,
+            },
+            "impulse" => {
+                let object = words.parsed("object index")?;
+                let impulse = words.vector("impulse x", "impulse y")?;
+                let point = match words.0.next() {
+                    Some("at") => Some(words.vector("point x", "point y")?),
+                    Some(word) => return Err(ParseError::Unexpected(word.to_string())),
+                    None => None,
+                };
+                Self::ApplyImpulse { object, impulse, point }
+            }
+            "spin" => Self::ApplyAngularImpulse {
+                object: words.parsed("object index")?,
+                impulse: words.parsed("angular impulse")?,
+            },
+            "freeze" => Self::Freeze {
+                object: words.parsed("object index")?,
+            },
+            "unfreeze" => Self::Unfreeze {
+                object: words.parsed("object index")?,
+            },
+            "shatter" => Self::Shatter {
+                object: words.parsed("object index")?,
+                pieces: words.parsed("pieces")?,
+                energy: words.parsed("energy")?,
+            },
+            "set" => match words.word("setting")? {
+                "gravity" => Self::SetGravity(words.vector("gravity x", "gravity y")?),
+                "margin" => Self::SetMargin(words.parsed("margin")?),
+                "attraction" => match words.word("strength")? {
+                    "off" => Self::SetAttraction(None),
+                    strength => Self::SetAttraction(Some(
+                        strength.parse().map_err(|_| ParseError::NotANumber(strength.to_string()))?,
+                    )),
+                },
+                "seed" => Self::SetSeed(words.parsed("seed")?),
+                setting => {
+                    let object = setting.parse().map_err(|_| ParseError::UnknownSetting(setting.to_string()))?;
+                    let property = match words.word("property")? {
+                        "position" => Property::Position(words.vector("x", "y")?),
+                        "velocity" => Property::Velocity(words.vector("velocity x", "velocity y")?),
+                        "rotation" => Property::Rotation(words.parsed("rotation")?),
+                        "spin" => Property::RotVelocity(words.parsed("spin")?),
+                        "mass" => Property::Mass(words.parsed("mass")?),
+                        "restitution" => Property::Restitution(words.parsed("restitution")?),
+                        "gravity" => Property::GravityScale(words.parsed("gravity scale")?),
+                        "z" => Property::ZIndex(words.parsed("z index")?),
+                        property => return Err(ParseError::UnknownSetting(property.to_string())),
+                    };
+                    Self::SetProperty { object, property }
+                }
+            },
+            "pause" => Self::Pause,
+            "resume" => Self::Resume,
+            "save" => Self::Save(words.word("path")?.to_string()),
+            "load" => Self::LoadScene(words.word("path")?.to_string()),
+            "run" => Self::Run(words.word("path")?.to_string()),
+            command => return Err(ParseError::UnknownCommand(command.to_string())),
+        };
+        match words.0.next() {
+            Some(word) => Err(ParseError::Unexpected(word.to_string())),
+            None => Ok(command),
+        }
+    }
+
+    /// Parses every line that isn't blank or a comment, errors carry the line's number from one
+    pub fn parse_script(script: &str) -> Result<Vec<Self>, (usize, ParseError)> {
+        script
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or_default()))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| Self::parse(line).map_err(|e| (number, e)))
+            .collect()
+    }
+
+    /// Applies the command if it only changes `world`, otherwise returns it for the front end
+    pub fn run(self, world: &mut World) -> Result<Option<Self>, CommandError> {
+        match self {
+            Self::Spawn {
+                shape,
+                position,
+                velocity,
+                rotation,
+                restitution,
+            } => {
+                let mut object = shape.object(position, velocity, rotation);
+                object.restitution = restitution;
+                // Collides from the start of the coming step instead of the one after
+                world.spawn_mid_step(object, world.time_elapsed);
+            }
+            Self::Delete { object } => {
+                if object >= world.objects.len() {
+                    return Err(CommandError::NoSuchObject(object));
+                }
+                world.remove_object(object);
+            }
+            Self::SetProperty { object: index, property } => {
+                let object = world.objects.get_mut(index).ok_or(CommandError::NoSuchObject(index))?;
+                match property {
+                    Property::Position(position) => object.position = position,
+                    Property::Velocity(velocity) => match &mut object.paused {
+                        Some((resumed, _)) => *resumed = velocity,
+                        None => object.velocity = velocity,
+                    },
+                    Property::Rotation(rotation) => object.rotation = rotation,
+                    Property::RotVelocity(rot_velocity) => match &mut object.paused {
+                        Some((_, resumed)) => *resumed = rot_velocity,
+                        None => object.rot_velocity = rot_velocity,
+                    },
+                    Property::Mass(mass) => {
+                        if !mass.is_finite() || mass <= 0. {
+                            return Err(CommandError::InvalidMass(mass));
+                        }
+                        if !object.is_dynamic() {
+                            return Err(CommandError::NotDynamic(index));
+                        }
+                        object.inertia *= mass / object.mass;
+                        object.mass = mass;
+                    }
+                    Property::Restitution(restitution) => object.restitution = restitution,
+                    Property::GravityScale(scale) => object.gravity_scale = scale,
+                    Property::ZIndex(z_index) => object.z_index = z_index,
+                }
+                object.refresh_transform();
+            }
+            Self::ApplyImpulse { object, impulse, point } => {
+                let object = world.objects.get_mut(object).ok_or(CommandError::NoSuchObject(object))?;
+                let point = point.unwrap_or(object.position);
+                object.apply_impulse_at_point(impulse, point);
+            }
+            Self::ApplyAngularImpulse { object, impulse } => {
+                world.objects.get_mut(object).ok_or(CommandError::NoSuchObject(object))?.apply_angular_impulse(impulse);
+            }
+            Self::Freeze { object } => world.objects.get_mut(object).ok_or(CommandError::NoSuchObject(object))?.pause(),
+            Self::Unfreeze { object } => world.objects.get_mut(object).ok_or(CommandError::NoSuchObject(object))?.resume(),
+            Self::Shatter { object, pieces, energy } => {
+                if object >= world.objects.len() {
+                    return Err(CommandError::NoSuchObject(object));
+                }
+                world.fracture(object, pieces, energy);
+            }
+            Self::SetGravity(gravity) => world.gravity = gravity,
+            Self::SetMargin(margin) => {
+                world.collision_margin = margin.max(0.);
+                world.pair_cache.clear();
+            }
+            Self::SetAttraction(constant) => world.gravitational_constant = constant,
+            Self::SetSeed(seed) => world.rng = rng::RngService::new(seed),
+            command => return Ok(Some(command)),
+        }
+        Ok(None)
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Circle => write!(f, "circle"),
+            Self::Capsule => write!(f, "capsule"),
+            Self::Box => write!(f, "box"),
+            Self::Triangle => write!(f, "triangle"),
+            Self::Regular(sides) => write!(f, "polygon {sides}"),
+        }
+    }
+}
+
+/// The text form [`Command::parse`] reads back, floats print exactly
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spawn {
+                shape,
+                position,
+                velocity,
+                rotation,
+                restitution,
+            } => write!(
+                f,
+                "spawn {shape} {} {} vel {} {} rot {rotation} bounce {restitution}",
+                position.x, position.y, velocity.x, velocity.y
+            ),
+            Self::Delete { object } => write!(f, "delete {object}"),
+            Self::SetProperty { object, property } => match property {
+                Property::Position(position) => write!(f, "set {object} position {} {}", position.x, position.y),
+                Property::Velocity(velocity) => write!(f, "set {object} velocity {} {}", velocity.x, velocity.y),
+                Property::Rotation(rotation) => write!(f, "set {object} rotation {rotation}"),
+                Property::RotVelocity(rot_velocity) => write!(f, "set {object} spin {rot_velocity}"),
+                Property::Mass(mass) => write!(f, "set {object} mass {mass}"),
+                Property::Restitution(restitution) => write!(f, "set {object} restitution {restitution}"),
+                Property::GravityScale(scale) => write!(f, "set {object} gravity {scale}"),
+                Property::ZIndex(z_index) => write!(f, "set {object} z {z_index}"),
+            },
+            Self::ApplyImpulse { object, impulse, point } => {
+                write!(f, "impulse {object} {} {}", impulse.x, impulse.y)?;
+                match point {
+                    Some(point) => write!(f, " at {} {}", point.x, point.y),
+                    None => Ok(()),
+                }
+            }
+            Self::ApplyAngularImpulse { object, impulse } => write!(f, "spin {object} {impulse}"),
+            Self::Freeze { object } => write!(f, "freeze {object}"),
+            Self::Unfreeze { object } => write!(f, "unfreeze {object}"),
+            Self::Shatter { object, pieces, energy } => write!(f, "shatter {object} {pieces} {energy}"),
+            Self::SetGravity(gravity) => write!(f, "set gravity {} {}", gravity.x, gravity.y),
+            Self::SetMargin(margin) => write!(f, "set margin {margin}"),
+            Self::SetAttraction(Some(constant)) => write!(f, "set attraction {constant}"),
+            Self::SetAttraction(None) => write!(f, "set attraction off"),
+            Self::SetSeed(seed) => write!(f, "set seed {seed}"),
+            Self::Pause => write!(f, "pause"),
+            Self::Resume => write!(f, "resume"),
+            Self::Save(path) => write!(f, "save {path}"),
+            Self::LoadScene(path) => write!(f, "load {path}"),
+            Self::Run(path) => write!(f, "run {path}"),
+        }
+    }
+}
+
+#[test]
+fn test_command_parse_and_run() {
+    assert_eq!(
+        Command::parse("spawn circle 0 0 vel 3 1"),
+        Ok(Command::Spawn {
+            shape: Shape::Circle,
+            position: vec2(0., 0.),
+            velocity: vec2(3., 1.),
+            rotation: 0.,
+            restitution: 1.,
+        })
+    );
+    assert!(matches!(Command::parse("spawn capsule 1 2"), Ok(Command::Spawn { shape: Shape::Capsule, .. })));
+    assert_eq!(Shape::Capsule.to_string(), "capsule");
+    assert_eq!(Command::parse("  set gravity 0 -9.81 "), Ok(Command::SetGravity(vec2(0., -9.81))));
+    assert_eq!(Command::parse("save foo.ron"), Ok(Command::Save("foo.ron".into())));
+    assert_eq!(Command::parse("set attraction off"), Ok(Command::SetAttraction(None)));
+    assert_eq!(Command::parse("set seed 42"), Ok(Command::SetSeed(42)));
+    assert_eq!(Command::parse("set seed -1"), Err(ParseError::NotANumber("-1".into())));
+    assert_eq!(Command::parse(""), Err(ParseError::Empty));
+    assert_eq!(Command::parse("spawn box 1"), Err(ParseError::Missing("y")));
+    assert_eq!(Command::parse("spawn polygon 2 0 0"), Err(ParseError::NotANumber("2".into())));
+    assert_eq!(Command::parse("pause now"), Err(ParseError::Unexpected("now".into())));
+    assert_eq!(Command::parse("set 1 colour red"), Err(ParseError::UnknownSetting("colour".into())));
+
+    let script = "# a box falling on a triangle\nspawn triangle 0 0\n\nspawn box 0 3 vel 0 -1 # falls\nset gravity 0 -1\npause\n";
+    let commands = Command::parse_script(script).unwrap();
+    assert_eq!(commands.len(), 4);
+    assert_eq!(Command::parse_script("pause\nflip"), Err((2, ParseError::UnknownCommand("flip".into()))));
+
+    let mut world = World::new();
+    let handed_back = commands.into_iter().filter_map(|command| command.run(&mut world).unwrap()).collect::<Vec<_>>();
+    assert_eq!(handed_back, [Command::Pause]);
+    world.step(0.1).unwrap();
+    assert_eq!(world.objects.len(), 2);
+    assert_eq!(world.gravity, vec2(0., -1.));
+    assert!(world.objects.iter().all(|object| object.total_acceleration() == vec2(0., -1.)));
+    let float = Command::SetProperty {
+        object: 1,
+        property: Property::GravityScale(0.),
+    };
+    assert_eq!(Command::parse("set 1 gravity 0"), Ok(float.clone()));
+    assert_eq!(float.run(&mut world), Ok(None));
+    world.step(0.1).unwrap();
+    assert_eq!(world.objects[1].total_acceleration(), Vec2::ZERO);
+    // Reseeding repeats the draws of a fresh world with that seed
+    world.rng.next_u64(rng::Stream::Spawn);
+    assert_eq!(Command::SetSeed(5).run(&mut world), Ok(None));
+    assert_eq!(world.rng, rng::RngService::new(5));
+
+    // Every command reads back from its text form
+    let edits = [
+        Command::Spawn {
+            shape: Shape::Regular(5),
+            position: vec2(0.1, -2.),
+            velocity: vec2(1. / 3., 0.),
+            rotation: 0.7,
+            restitution: 0.5,
+        },
+        Command::SetProperty {
+            object: 1,
+            property: Property::Mass(2.),
+        },
+        Command::SetProperty {
+            object: 0,
+            property: Property::ZIndex(-2),
+        },
+        Command::ApplyImpulse {
+            object: 0,
+            impulse: vec2(0., 1.),
+            point: Some(vec2(0.5, 0.)),
+        },
+        Command::ApplyAngularImpulse { object: 0, impulse: -0.25 },
+        Command::Freeze { object: 0 },
+        Command::Shatter {
+            object: 0,
+            pieces: 3,
+            energy: -0.5,
+        },
+        Command::Delete { object: 1 },
+    ];
+    for command in &edits {
+        assert_eq!(Command::parse(&command.to_string()).as_ref(), Ok(command));
+    }
+    let inertia = world.objects[1].inertia;
+    let [spawn, mass, z_index, impulse, spin, freeze, shatter, delete] = edits;
+    for command in [spawn, mass, z_index, impulse, spin] {
+        assert_eq!(command.run(&mut world), Ok(None));
+    }
+    assert_eq!(world.objects[1].inertia, 2. * inertia);
+    assert_eq!(world.objects[0].z_index, -2);
+    assert_ne!(world.objects[0].rot_velocity, 0.);
+    // A frozen object holds still and keeps the spin it resumes with
+    assert_eq!(freeze.run(&mut world), Ok(None));
+    assert!(world.objects[0].is_paused() && world.objects[0].rot_velocity == 0.);
+    assert_ne!(world.objects[0].motion().1, 0.);
+    assert_eq!(delete.run(&mut world), Ok(None));
+    assert_eq!(world.objects.len(), 1);
+    assert_eq!(Command::Delete { object: 1 }.run(&mut world), Err(CommandError::NoSuchObject(1)));
+    // Masses that would break the solver are turned down, as are ones for static objects
+    let set_mass = |object, mass| Command::SetProperty {
+        object,
+        property: Property::Mass(mass),
+    };
+    for mass in [0., -1., f32::INFINITY] {
+        assert_eq!(set_mass(0, mass).run(&mut world), Err(CommandError::InvalidMass(mass)));
+    }
+    assert!(matches!(set_mass(0, f32::NAN).run(&mut world), Err(CommandError::InvalidMass(_))));
+    world.objects.push(Object::new_static(vec2(0., -5.), 0., Polygon::rectangle(vec2(4., 1.))));
+    assert_eq!(set_mass(1, 2.).run(&mut world), Err(CommandError::NotDynamic(1)));
+    assert!(world.objects[1].mass.is_infinite());
+    world.objects.pop();
+    // The triangle breaks into a fragment per edge
+    assert_eq!(shatter.run(&mut world), Ok(None));
+    assert_eq!(world.objects.len(), 3);
+}
@@ -0,0 +1,112 @@
+//! Optional projection restoring the total momentum and kinetic energy after every step.
+//!
+//! The momentum error is removed by shifting every dynamic object's velocity by the same amount,
+//! so each object takes a share proportional to its mass. The energy error is then removed by
+//! scaling the velocities relative to the center of mass, which leaves the momentum alone. The
+//! correction isn't local: a collision in one corner nudges objects everywhere. External effects
+//! like accelerations, controllers and kinematic pushes get undone too, as do the losses of
+//! inelastic bounces and friction.
+
+use super::*;
+
+/// Momentum and kinetic energy (translational and rotational) of the dynamic objects
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Totals {
+    pub momentum: Vec2,
+    pub energy: f32,
+    pub mass: f32,
+}
+
+impl Totals {
+    pub fn of(objects: &[Object]) -> Self {
+        let mut totals = Self {
+            momentum: Vec2::ZERO,
+            energy: 0.,
+            mass: 0.,
+        };
+        for object in dynamic(objects) {
+            totals.momentum += object.mass * object.velocity;
+            totals.energy += kinetic_energy(object);
+            totals.mass += object.mass;
+        }
+        totals
+    }
+}
+
+/// What the last projection changed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Correction {
+    /// Momentum added
+    pub momentum: Vec2,
+    /// Energy added
+    pub energy: f32,
+}
+
+/// Changes the velocities of `objects` so their totals match `target`
+pub(crate) fn project(objects: &mut [Object], target: Totals) -> Correction {
+    let before = Totals::of(objects);
+    if before.mass == 0. {
+        return Correction::default();
+    }
+    let shift = (target.momentum - before.momentum) / before.mass;
+    for object in objects.iter_mut().filter(|object| is_dynamic(object)) {
+        object.velocity += shift;
+    }
+
+    // Energy of the center of mass motion can't change without changing the momentum
+    let com_velocity = target.momentum / before.mass;
+    let bulk_energy = 0.5 * before.mass * com_velocity.length_squared();
+    let relative_energy = Totals::of(objects).energy - bulk_energy;
+    if relative_energy > f32::EPSILON {
+        let scale = ((target.energy - bulk_energy).max(0.) / relative_energy).sqrt();
+        for object in objects.iter_mut().filter(|object| is_dynamic(object)) {
+            object.velocity = com_velocity + (object.velocity - com_velocity) * scale;
+            object.rot_velocity *= scale;
+        }
+    }
+
+    let after = Totals::of(objects);
+    Correction {
+        momentum: after.momentum - before.momentum,
+        energy: after.energy - before.energy,
+    }
+}
+
+fn dynamic(objects: &[Object]) -> impl Iterator<Item = &Object> {
+    objects.iter().filter(|object| is_dynamic(object))
+}
+
+/// Frozen and paused objects keep the velocities they'll resume with
+fn is_dynamic(object: &Object) -> bool {
+    object.mass.is_finite() && !object.frozen && !object.is_paused()
+}
+
+pub fn kinetic_energy(object: &Object) -> f32 {
+    0.5 * object.mass * object.velocity.length_squared() + 0.5 * object.inertia * object.rot_velocity * object.rot_velocity
+}
+
+#[test]
+fn test_projection_restores_totals() {
+    let mut world = World {
+        enforce_conservation: true,
+        ..World::new()
+    };
+    let mut pushed = Object::new(vec2(-3., 0.), vec2(1., 0.), 0., Polygon::regular(4));
+    // An external force the projection has to cancel
+    pushed.acceleration = vec2(0., -0.2);
+    let mut heavy = Object::new(vec2(3., 0.2), vec2(-1., 0.), 0.3, Polygon::regular(5));
+    heavy.mass = 3.;
+    heavy.inertia *= 3.;
+    heavy.rot_velocity = 1.;
+    world.objects = vec![pushed, heavy];
+    let initial = Totals::of(&world.objects);
+
+    for _ in 0..240 {
+        world.step(1. / 60.).unwrap();
+        let totals = Totals::of(&world.objects);
+        assert!(totals.momentum.distance(initial.momentum) < 1e-4);
+        assert!((totals.energy - initial.energy).abs() < initial.energy * 1e-4);
+    }
+    assert!(world.objects.iter().all(|object| object.collided > 0));
+    assert!(world.last_correction.momentum.y > 0.);
+}
@@ -0,0 +1,111 @@
+//! Dropdown console for typed commands, see [`perfect_collisions::command`].
+//!
+//! Toggled with the backtick key. Submitted lines are parsed here, running them is up to the
+//! simulator, which reports back through [`Console::print`].
+
+use std::collections::VecDeque;
+
+use perfect_collisions::command::Command;
+
+use super::*;
+
+const CONSOLE_LINES: usize = 100;
+const CONSOLE_HEIGHT: f32 = 200.;
+
+pub struct Console {
+    open: bool,
+    /// Set when opened or after a submit, the input takes the keyboard
+    focus: bool,
+    input: String,
+    lines: VecDeque<String>,
+    /// Submitted lines, oldest first, up and down recall them
+    history: Vec<String>,
+    /// Position in `history` while recalling, `None` while typing a new line
+    recalled: Option<usize>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            focus: false,
+            input: String::new(),
+            lines: VecDeque::new(),
+            history: vec![],
+            recalled: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.focus = self.open;
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        if self.lines.len() == CONSOLE_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.into());
+    }
+
+    /// Draws the console and returns the command submitted this frame
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<Command> {
+        if !self.open {
+            return None;
+        }
+        egui::TopBottomPanel::top("console")
+            .exact_height(CONSOLE_HEIGHT)
+            .show(ctx, |ui| {
+                let input = egui::TextEdit::singleline(&mut self.input)
+                    .hint_text("spawn circle 0 0 vel 3 1, set gravity 0 -9.81, pause, save foo.ron")
+                    .desired_width(f32::INFINITY);
+                let response = ui.add(input);
+                // The toggle key gets typed too
+                self.input.retain(|c| c != '`');
+                if std::mem::take(&mut self.focus) {
+                    response.request_focus();
+                }
+                self.recall(ui);
+                egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                    for line in &self.lines {
+                        ui.monospace(line);
+                    }
+                });
+                if !(response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter))) {
+                    return None;
+                }
+                self.submit()
+            })
+            .inner
+    }
+
+    fn recall(&mut self, ui: &egui::Ui) {
+        let (up, down) = ui.input(|input| (input.key_pressed(egui::Key::ArrowUp), input.key_pressed(egui::Key::ArrowDown)));
+        let recalled = match (up, down, self.recalled) {
+            (true, _, None) => self.history.len().checked_sub(1),
+            (true, _, Some(i)) => Some(i.saturating_sub(1)),
+            (_, true, Some(i)) => (i + 1 < self.history.len()).then_some(i + 1),
+            _ => return,
+        };
+        self.recalled = recalled;
+        self.input = recalled.map_or_else(String::new, |i| self.history[i].clone());
+    }
+
+    fn submit(&mut self) -> Option<Command> {
+        let line = std::mem::take(&mut self.input);
+        self.recalled = None;
+        self.focus = true;
+        if line.trim().is_empty() {
+            return None;
+        }
+        self.print(format!("> {line}"));
+        self.history.push(line.clone());
+        match Command::parse(&line) {
+            Ok(command) => Some(command),
+            Err(e) => {
+                self.print(e.to_string());
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Contact graph overlay and inspector.
+//!
+//! Pairs that collided within the last [`ContactGraph::persistence`] steps are edges, objects
+//! connected through them form islands. Edges are drawn between the objects' centers in their
+//! island's color, the islands are listed as a tree, largest first.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use super::*;
+
+const DEFAULT_PERSISTENCE: usize = 10;
+/// Hue step between consecutive islands, the golden ratio keeps neighbours apart
+const HUE_STEP: f32 = 0.618_034;
+
+struct Edge {
+    last_frame: usize,
+    contacts: usize,
+}
+
+pub struct ContactGraph {
+    /// Steps an edge survives without a new contact
+    pub persistence: usize,
+    edges: HashMap<(usize, usize), Edge>,
+    /// Object count the edges were recorded with, removals and loads shift the indices
+    object_count: usize,
+    /// Object indices per island, largest island first
+    islands: Vec<Vec<usize>>,
+    island_of: HashMap<usize, usize>,
+}
+
+impl ContactGraph {
+    pub fn new() -> Self {
+        Self {
+            persistence: DEFAULT_PERSISTENCE,
+            edges: HashMap::new(),
+            object_count: 0,
+            islands: vec![],
+            island_of: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.edges.clear();
+        self.islands.clear();
+        self.island_of.clear();
+    }
+
+    /// Adds the contacts of the last step, drops the stale ones and regroups the islands
+    pub fn record(&mut self, world: &World) {
+        if world.objects.len() != self.object_count {
+            self.clear();
+            self.object_count = world.objects.len();
+        }
+        for toi in &world.resolved {
+            let pair = (toi.object_1.min(toi.object_2), toi.object_1.max(toi.object_2));
+            let edge = self.edges.entry(pair).or_insert(Edge { last_frame: 0, contacts: 0 });
+            edge.last_frame = world.frame;
+            edge.contacts += 1;
+        }
+        self.edges.retain(|_, edge| world.frame - edge.last_frame <= self.persistence);
+        self.group_islands();
+    }
+
+    fn group_islands(&mut self) {
+        let mut neighbours: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &(a, b) in self.edges.keys() {
+            neighbours.entry(a).or_default().push(b);
+            neighbours.entry(b).or_default().push(a);
+        }
+        self.island_of.clear();
+        self.islands.clear();
+        let mut starts = neighbours.keys().copied().collect::<Vec<_>>();
+        starts.sort_unstable();
+        for start in starts {
+            if self.island_of.contains_key(&start) {
+                continue;
+            }
+            let mut island = vec![];
+            let mut stack = vec![start];
+            self.island_of.insert(start, usize::MAX);
+            while let Some(i) = stack.pop() {
+                island.push(i);
+                for &j in &neighbours[&i] {
+                    if let Entry::Vacant(entry) = self.island_of.entry(j) {
+                        entry.insert(usize::MAX);
+                        stack.push(j);
+                    }
+                }
+            }
+            island.sort_unstable();
+            self.islands.push(island);
+        }
+        self.islands.sort_by_key(|island| std::cmp::Reverse(island.len()));
+        for (index, island) in self.islands.iter().enumerate() {
+            for &i in island {
+                self.island_of.insert(i, index);
+            }
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.edges.capacity() * std::mem::size_of::<((usize, usize), Edge)>()
+            + self.island_of.capacity() * std::mem::size_of::<(usize, usize)>()
+            + memory::vec_bytes(&self.islands)
+            + self.islands.iter().map(memory::vec_bytes).sum::<usize>()
+    }
+
+    pub fn draw(&self, world: &World, queue: &mut DrawQueue, camera: &Camera) {
+        // Same on-screen thickness at every zoom
+        let thickness = 0.004 / camera.scale.x;
+        for &(a, b) in self.edges.keys() {
+            let (Some(object_a), Some(object_b)) = (world.objects.get(a), world.objects.get(b)) else {
+                continue;
+            };
+            let edge = object_b.position - object_a.position;
+            let line = Shape::from_line(edge.length(), thickness)
+                .set_color(island_color(self.island_of[&a]))
+                .apply(GTransform::from_translation(object_a.position).rotate(edge.y.atan2(edge.x)))
+                .apply(camera.0);
+            queue.add(Depth::ContactGraph, line);
+        }
+    }
+
+    /// Islands with their objects and contacts, returns the object clicked
+    pub fn show(&self, ui: &mut egui::Ui, world: &World) -> Option<usize> {
+        let mut clicked = None;
+        ui.label(format!("{} contacts in {} islands", self.edges.len(), self.islands.len()));
+        for (index, island) in self.islands.iter().enumerate() {
+            let color = island_color(index);
+            let title = egui::RichText::new(format!("Island {index}: {} objects", island.len()))
+                .color(egui::Color32::from_rgb((color.r * 255.) as u8, (color.g * 255.) as u8, (color.b * 255.) as u8));
+            egui::CollapsingHeader::new(title).id_source(("island", index)).show(ui, |ui| {
+                for &i in island {
+                    let Some(object) = world.objects.get(i) else {
+                        continue;
+                    };
+                    egui::CollapsingHeader::new(object_name(i, object)).id_source(("island object", i)).show(ui, |ui| {
+                        if ui.small_button("Select").clicked() {
+                            clicked = Some(i);
+                        }
+                        for (&(a, b), edge) in self.edges.iter().filter(|((a, b), _)| *a == i || *b == i) {
+                            let other = if a == i { b } else { a };
+                            let name = world.objects.get(other).map_or_else(|| format!("#{other}"), |o| object_name(other, o));
+                            ui.label(format!("{name}: {} contacts, last in step {}", edge.contacts, edge.last_frame));
+                        }
+                    });
+                }
+            });
+        }
+        clicked
+    }
+}
+
+fn island_color(index: usize) -> Color {
+    let hue = (index as f32 * HUE_STEP).fract() * 6.;
+    let rising = hue.fract();
+    let (r, g, b) = match hue as usize {
+        0 => (1., rising, 0.),
+        1 => (1. - rising, 1., 0.),
+        2 => (0., 1., rising),
+        3 => (0., 1. - rising, 1.),
+        4 => (rising, 0., 1.),
+        _ => (1., 0., 1. - rising),
+    };
+    Color::from_rgb(r, g, b)
+}
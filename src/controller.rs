@@ -0,0 +1,123 @@
+//! User behavior attached to individual objects, e.g. thrusters, homing or PID controllers.
+//!
+//! [`World::step`] runs every object's [`Controller`] after the kinematic objects are driven and
+//! before anything is integrated. Controllers aren't part of saved scenes.
+
+use alloc::boxed::Box;
+use core::fmt;
+
+use super::*;
+
+/// `Send` so objects can be built and moved off the main thread
+pub trait Controller: ControllerClone + Send {
+    /// Called once per step with the object it's attached to and the step's `dt`
+    fn control(&mut self, object: &mut ObjectHandle, dt: f32);
+}
+
+/// Lets objects stay `Clone`, implemented for every `Clone` controller
+pub trait ControllerClone {
+    fn clone_box(&self) -> Box<dyn Controller>;
+}
+
+impl<T: Controller + Clone + 'static> ControllerClone for T {
+    fn clone_box(&self) -> Box<dyn Controller> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Controller> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl fmt::Debug for dyn Controller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Controller")
+    }
+}
+
+/// Access a controller gets to its object, the state can be read but only changed through forces
+/// and velocities so shapes and cached transforms stay valid during the step
+pub struct ObjectHandle<'a> {
+    /// Position of the object in [`World::objects`]
+    pub index: usize,
+    object: &'a mut Object,
+    dt: f32,
+}
+
+impl<'a> ObjectHandle<'a> {
+    pub(crate) fn new(index: usize, object: &'a mut Object, dt: f32) -> Self {
+        Self { index, object, dt }
+    }
+
+    /// Applies a constant force through the center of mass for the step
+    pub fn apply_force(&mut self, force: Vec2) {
+        self.object.apply_impulse(force * self.dt);
+    }
+    /// Applies a constant force at a world space point for the step
+    pub fn apply_force_at_point(&mut self, force: Vec2, world_point: Vec2) {
+        self.object.apply_impulse_at_point(force * self.dt, world_point);
+    }
+    /// Applies a constant torque for the step
+    pub fn apply_torque(&mut self, torque: f32) {
+        self.object.apply_torque(torque, self.dt);
+    }
+    pub fn apply_impulse(&mut self, impulse: Vec2) {
+        self.object.apply_impulse(impulse);
+    }
+    pub fn set_velocity(&mut self, velocity: Vec2) {
+        self.object.velocity = velocity;
+    }
+    pub fn set_rot_velocity(&mut self, rot_velocity: f32) {
+        self.object.rot_velocity = rot_velocity;
+    }
+}
+
+impl Deref for ObjectHandle<'_> {
+    type Target = Object;
+    fn deref(&self) -> &Self::Target {
+        self.object
+    }
+}
+
+#[test]
+fn test_controllers_run_before_integration() {
+    /// Pushes right until it has spent its fuel
+    #[derive(Clone)]
+    struct Thruster {
+        force: f32,
+        fuel: f32,
+    }
+    impl Controller for Thruster {
+        fn control(&mut self, object: &mut ObjectHandle, dt: f32) {
+            if self.fuel > 0. {
+                object.apply_force(vec2(self.force, 0.));
+                self.fuel -= dt;
+            }
+        }
+    }
+
+    let mut world = World::new();
+    let mut rocket = Object::new(vec2(0., 0.), Vec2::ZERO, 0., Polygon::regular(4));
+    rocket.mass = 2.;
+    rocket.controller = Some(Box::new(Thruster { force: 4., fuel: 0.5 }));
+    world.objects.push(rocket);
+
+    world.step(0.25).unwrap();
+    // Already moving during the first step
+    assert!((world.objects[0].velocity.x - 0.5).abs() < 1e-6);
+    assert!(world.objects[0].position.x > 0.);
+
+    let copy = world.objects[0].clone();
+    world.step(0.25).unwrap();
+    world.step(0.25).unwrap();
+    // Out of fuel after two steps
+    assert!((world.objects[0].velocity.x - 1.).abs() < 1e-6);
+
+    // The copy keeps the controller with the fuel it had left
+    world.objects = vec![copy];
+    world.step(0.25).unwrap();
+    world.step(0.25).unwrap();
+    assert!((world.objects[0].velocity.x - 1.).abs() < 1e-6);
+}
@@ -0,0 +1,81 @@
+//! Postmortem dumps for solver panics and tunneling certificate violations.
+//!
+//! The panic hook only records the message, the dump itself is written by the frame update
+//! which catches the unwind while it still has access to the world. Violations are dumped with
+//! the scene from before the step, loading it and stepping once reproduces them.
+
+use std::{
+    collections::VecDeque,
+    io,
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use perfect_collisions::certificate::Certificate;
+use serde::Serialize;
+
+use super::*;
+use scene::Scene;
+
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Serialize)]
+pub struct CrashDump {
+    pub panic: Option<String>,
+    pub frame: usize,
+    pub scene: Scene,
+    pub pending_collisions: Vec<Toi>,
+    pub recent_collisions: VecDeque<Toi>,
+}
+
+impl CrashDump {
+    /// Writes the dump into the temp directory and returns its path
+    pub fn write(&self) -> io::Result<PathBuf> {
+        write_dump(self, "crash")
+    }
+}
+
+#[derive(Serialize)]
+pub struct ViolationDump {
+    /// Length of the step that violated the certificate
+    pub dt: f32,
+    /// The world before the step
+    pub scene: Scene,
+    pub certificate: Certificate,
+}
+
+impl ViolationDump {
+    /// Writes the dump into the temp directory and returns its path
+    pub fn write(&self) -> io::Result<PathBuf> {
+        write_dump(self, "violation")
+    }
+}
+
+fn write_dump(dump: &impl Serialize, kind: &str) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!("perfect-collisions-{kind}-{timestamp}.ron"));
+
+    let text = ron::ser::to_string_pretty(dump, ron::ser::PrettyConfig::default())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// Takes the message of the last panic, if the hook saw one
+pub fn take_last_panic() -> Option<String> {
+    LAST_PANIC.lock().ok()?.take()
+}
+
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Ok(mut last_panic) = LAST_PANIC.lock() {
+            *last_panic = Some(info.to_string());
+        }
+        default_hook(info);
+    }));
+}
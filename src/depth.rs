@@ -0,0 +1,21 @@
+//! Drawing order of the frame, see [`perfect_collisions::draw`].
+
+pub use perfect_collisions::draw::Depth;
+use perfect_collisions::draw;
+
+use super::*;
+
+/// Shapes of the frame by [`Depth`], handed to the graphics once it's complete
+#[derive(Default)]
+pub struct DrawQueue(draw::DrawQueue<Geometry<Txts>>);
+
+impl DrawQueue {
+    pub fn add(&mut self, depth: Depth, shape: Shape<Txts>) {
+        self.0.add(depth, shape.into());
+    }
+
+    /// Hands the queued shapes to `graphics` from the bottom up and empties the queue
+    pub fn flush(&mut self, graphics: &mut Graphics<Txts>) {
+        self.0.flush(|geometry| graphics.add_geometry(geometry));
+    }
+}
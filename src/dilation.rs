@@ -0,0 +1,74 @@
+//! Regions where time runs slower or faster for the objects inside.
+//!
+//! An object's time scale is sampled at its position when a step starts and holds for the whole
+//! step. For the step its velocities are scaled to global time (the acceleration twice), so the
+//! swept volumes and times of impact are all ordered on the one global clock, and scaled back to
+//! local time afterwards. Scripted objects keep global time.
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TimeRegion {
+    pub area: Aabb,
+    /// Local seconds per global second, `0.5` runs at half speed
+    pub scale: f32,
+}
+
+/// Scale below which regions are clamped, zero would lose the velocities when scaling back
+pub const MIN_TIME_SCALE: f32 = 0.01;
+
+/// Product of the scales of the regions containing `point`, overlapping regions compound
+pub fn time_scale_at(regions: &[TimeRegion], point: Vec2) -> f32 {
+    regions
+        .iter()
+        .filter(|region| region.area.contains(point))
+        .map(|region| region.scale.max(MIN_TIME_SCALE))
+        .product()
+}
+
+/// Moves the objects' velocities from local to global time, see the module docs
+pub(crate) fn dilate(objects: &mut [Object], regions: &[TimeRegion]) {
+    for object in objects.iter_mut().filter(|object| object.is_dynamic()) {
+        let scale = time_scale_at(regions, object.position);
+        object.time_scale = scale;
+        object.velocity *= scale;
+        object.rot_velocity *= scale;
+        object.acceleration *= scale * scale;
+        object.gravity *= scale * scale;
+    }
+}
+
+/// Undoes [`dilate`]
+pub(crate) fn contract(objects: &mut [Object]) {
+    for object in objects.iter_mut() {
+        let scale = core::mem::replace(&mut object.time_scale, 1.);
+        object.velocity /= scale;
+        object.rot_velocity /= scale;
+        object.acceleration /= scale * scale;
+        object.gravity /= scale * scale;
+    }
+}
+
+#[test]
+fn test_dilated_objects_collide_later() {
+    let mut world = World::new();
+    world.time_regions.push(TimeRegion {
+        area: Aabb::new(vec2(-2., -2.), vec2(1.4, 2.)),
+        scale: 0.5,
+    });
+    // Squares touching once their centers are 2 apart
+    let square = Polygon::rectangle(vec2(2., 2.));
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., square.clone()));
+    world.objects.push(Object::new(vec2(4., 0.), Vec2::ZERO, 0., square));
+
+    world.step(1.5).unwrap();
+    // Half the speed, half the distance, and the velocity is kept
+    assert!((world.objects[0].position.x - 1.5).abs() < 1e-2);
+    assert_eq!(world.objects[0].velocity, vec2(2., 0.));
+    assert!(world.resolved.is_empty());
+
+    // Out of the region the object catches up at full speed and hits at 1.75
+    world.step(1.).unwrap();
+    let first = world.resolved.iter().map(|toi| toi.time).fold(f32::INFINITY, f32::min);
+    assert!((first - 1.75).abs() < 1e-2);
+}
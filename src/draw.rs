@@ -0,0 +1,121 @@
+//! Camera and drawing order of a frame, shared by the viewer and [`raster`].
+//!
+//! The screen spans -1..1 on both axes whatever its size in pixels, a [`View`] maps the world onto
+//! it. The graphics have no depth buffer, whatever is added later covers what came before.
+//! Instead of relying on the order the draw functions run in, every shape is queued with its
+//! [`Depth`] and the queue hands them over sorted once the frame is complete.
+
+use super::*;
+
+/// Part of the screen left empty around a fitted box, on each side
+const FIT_MARGIN: f32 = 0.1;
+
+/// Camera without rotation, what the viewer's camera transform does to world points
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    /// World point in the middle of the screen
+    pub focus: Vec2,
+    /// Screen units per world unit
+    pub zoom: f32,
+}
+
+impl View {
+    /// Centers `aabb` and zooms so its longer side fills the screen minus the margin
+    pub fn fit(aabb: &Aabb) -> Self {
+        let extent = (aabb.max - aabb.min).max_element().max(f32::EPSILON);
+        Self {
+            focus: (aabb.min + aabb.max) / 2.,
+            zoom: 2. * (1. - 2. * FIT_MARGIN) / extent,
+        }
+    }
+    pub fn to_screen(&self, point: Vec2) -> Vec2 {
+        (point - self.focus) * self.zoom
+    }
+    pub fn to_world(&self, screen: Vec2) -> Vec2 {
+        screen / self.zoom + self.focus
+    }
+    /// World space box the screen shows
+    pub fn visible_area(&self) -> Aabb {
+        Aabb::from_points(&[self.to_world(vec2(-1., -1.)), self.to_world(vec2(1., 1.))])
+    }
+    /// Pixel coordinates of a world point on a screen `size` pixels large, y grows downwards
+    pub fn to_pixel(&self, point: Vec2, size: Vec2) -> Vec2 {
+        let screen = self.to_screen(point);
+        vec2((screen.x + 1.) / 2. * size.x, (1. - screen.y) / 2. * size.y)
+    }
+}
+
+/// Layers of a frame from the bottom up, shapes of the same depth keep the order they came in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Depth {
+    Background,
+    /// Swept volumes stay under every object
+    SweptVolume,
+    /// Selected objects go over the rest, then higher [`Object::z_index`] over lower
+    Object { selected: bool, z_index: i32 },
+    Particles,
+    HeatMap,
+    ContactGraph,
+    /// Contact points, impulse arrows, time regions and predicted paths
+    Debug,
+}
+
+impl Depth {
+    pub fn of(object: &Object, selected: bool) -> Self {
+        Self::Object {
+            selected,
+            z_index: object.z_index,
+        }
+    }
+}
+
+/// Shapes of a frame waiting for it to be complete, whatever the graphics draw
+pub struct DrawQueue<T> {
+    shapes: Vec<(Depth, T)>,
+}
+
+impl<T> Default for DrawQueue<T> {
+    fn default() -> Self {
+        Self { shapes: vec![] }
+    }
+}
+
+impl<T> DrawQueue<T> {
+    pub fn add(&mut self, depth: Depth, shape: T) {
+        self.shapes.push((depth, shape));
+    }
+    /// Hands the queued shapes to `draw` from the bottom up and empties the queue
+    pub fn flush(&mut self, mut draw: impl FnMut(T)) {
+        // Stable, so ties keep their order
+        self.shapes.sort_by_key(|(depth, _)| *depth);
+        for (_, shape) in self.shapes.drain(..) {
+            draw(shape);
+        }
+    }
+}
+
+#[test]
+fn test_view_and_order() {
+    let view = View::fit(&Aabb::from_points(&[vec2(-4., -1.), vec2(6., 1.)]));
+    assert_eq!(view.focus, vec2(1., 0.));
+    // The longer side spans 80% of the screen
+    assert!(view.to_screen(vec2(6., 0.)).abs_diff_eq(vec2(0.8, 0.), 1e-6));
+    assert!(view.to_world(view.to_screen(vec2(2., 3.))).abs_diff_eq(vec2(2., 3.), 1e-5));
+    assert_eq!(view.to_pixel(view.focus, vec2(200., 100.)), vec2(100., 50.));
+    let area = view.visible_area();
+    assert!(area.min.abs_diff_eq(vec2(-5.25, -6.25), 1e-5) && area.max.abs_diff_eq(vec2(7.25, 6.25), 1e-5));
+
+    let mut queue = DrawQueue::default();
+    let mut raised = Object::new(Vec2::ZERO, Vec2::ZERO, 0., Polygon::regular(3));
+    raised.z_index = 1;
+    let plain = Object::new(Vec2::ZERO, Vec2::ZERO, 0., Polygon::regular(3));
+    queue.add(Depth::Debug, "debug");
+    queue.add(Depth::of(&raised, false), "raised");
+    queue.add(Depth::of(&plain, true), "selected");
+    queue.add(Depth::of(&plain, false), "first");
+    queue.add(Depth::of(&plain, false), "second");
+    queue.add(Depth::Background, "background");
+    let mut order = vec![];
+    queue.flush(|shape| order.push(shape));
+    assert_eq!(order, ["background", "first", "second", "raised", "selected", "debug"]);
+}
@@ -0,0 +1,172 @@
+//! Optional audit of the energy every resolved collision leaves its two objects with.
+//!
+//! A collision with restitution `e` turns exactly the share `1 - e²` of the kinetic energy of the
+//! approach along the normal into heat, friction only ever takes more. With [`World::energy_audit`]
+//! set, the kinetic energy (translational and rotational) of the two objects right before and after
+//! each collision is compared with that expectation, anything it doesn't explain adds to a running
+//! drift and beyond the tolerance counts as a violation. Energies are measured in the frame of the
+//! contact point of an immovable partner, static and scripted objects give and take none in it.
+
+use tracing::warn;
+
+use super::*;
+use world::effective_mass_inverse;
+
+/// Share of the energy before a collision its unexplained change may reach
+pub const DEFAULT_ENERGY_TOLERANCE: f32 = 1e-3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnergyAudit {
+    /// Share of the energy before a collision its unexplained change may reach before it's a
+    /// violation
+    pub tolerance: f32,
+    /// Unexplained energy summed over the checked collisions since the audit started, friction
+    /// losses included
+    pub drift: f32,
+    /// Collisions checked since the audit started
+    pub checked: usize,
+    /// Violations since the audit started
+    pub violations: usize,
+    /// Collisions of the last step
+    pub records: Vec<EnergyRecord>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnergyRecord {
+    pub collision: Toi,
+    pub before: f32,
+    pub after: f32,
+    /// `before` less what the restitution takes
+    pub expected: f32,
+    /// Whether friction could take more
+    pub friction: bool,
+}
+
+impl EnergyRecord {
+    pub fn unexplained(&self) -> f32 {
+        self.after - self.expected
+    }
+}
+
+/// Energy of a collision's objects right before it, see [`EnergyAudit::record`]
+pub(crate) struct Snapshot {
+    frame: Vec2,
+    before: f32,
+    expected: f32,
+    friction: bool,
+}
+
+impl Snapshot {
+    pub(crate) fn take(a: &Object, b: &Object, point: Vec2, normal: Vec2, surface: Material) -> Self {
+        let frame = [a, b]
+            .iter()
+            .find(|object| object.effective_mass().is_infinite())
+            .map_or(Vec2::ZERO, |object| object.velocity_at_point(point));
+        let before = energy(a, frame) + energy(b, frame);
+        let approach = (a.velocity_at_point(point) - b.velocity_at_point(point)).dot(normal).min(0.);
+        let restitution = surface.restitution;
+        let loss = 0.5 * (1. - restitution * restitution) * approach * approach / effective_mass_inverse(a, b, point, normal);
+        Self {
+            frame,
+            before,
+            expected: before - loss,
+            friction: surface.static_friction > 0. || surface.dynamic_friction > 0.,
+        }
+    }
+}
+
+/// Kinetic energy of `object` moving relative to `frame`, none for immovable objects
+fn energy(object: &Object, frame: Vec2) -> f32 {
+    if object.effective_mass().is_infinite() {
+        return 0.;
+    }
+    0.5 * object.mass * (object.velocity - frame).length_squared() + 0.5 * object.inertia * object.rot_velocity * object.rot_velocity
+}
+
+impl EnergyAudit {
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance,
+            drift: 0.,
+            checked: 0,
+            violations: 0,
+            records: vec![],
+        }
+    }
+    /// Whether the record's energy changed by more than the tolerance allows, losses to friction
+    /// aside
+    pub fn is_violation(&self, record: &EnergyRecord) -> bool {
+        let limit = self.tolerance * record.before;
+        let unexplained = record.unexplained();
+        unexplained > limit || (!record.friction && unexplained < -limit)
+    }
+    /// Records `collision` between `a` and `b`, resolved since `snapshot` was taken
+    pub(crate) fn record(&mut self, collision: Toi, snapshot: Snapshot, a: &Object, b: &Object) {
+        let record = EnergyRecord {
+            collision,
+            before: snapshot.before,
+            after: energy(a, snapshot.frame) + energy(b, snapshot.frame),
+            expected: snapshot.expected,
+            friction: snapshot.friction,
+        };
+        self.checked += 1;
+        self.drift += record.unexplained();
+        if self.is_violation(&record) {
+            self.violations += 1;
+            warn!(
+                object_1 = collision.object_1,
+                object_2 = collision.object_2,
+                time = collision.time,
+                before = record.before,
+                after = record.after,
+                expected = record.expected,
+                "collision changed the energy unexpectedly"
+            );
+        }
+        self.records.push(record);
+    }
+}
+
+impl Default for EnergyAudit {
+    fn default() -> Self {
+        Self::new(DEFAULT_ENERGY_TOLERANCE)
+    }
+}
+
+#[test]
+fn test_energy_audit() {
+    // Spinning polygons colliding off center, elastic and then losing half their approach speed
+    for restitution in [1., 0.5] {
+        let mut world = World::new();
+        world.energy_audit = Some(EnergyAudit::default());
+        let mut a = Object::new(vec2(-2., 0.3), vec2(2., 0.), 0.2, Polygon::regular(5));
+        a.rot_velocity = 3.;
+        a.restitution = restitution;
+        let mut b = Object::new(vec2(2., -0.2), vec2(-1., 0.5), 0., Polygon::regular(3));
+        b.mass = 2.;
+        b.inertia *= 2.;
+        world.objects.extend([a, b]);
+        world.step(3.).unwrap();
+
+        let audit = world.energy_audit.as_ref().unwrap();
+        assert!(!audit.records.is_empty());
+        assert_eq!((audit.checked, audit.violations), (audit.records.len(), 0), "{audit:?}");
+        assert!(audit.drift.abs() < 1e-3, "{audit:?}");
+        let lost = audit.records.iter().any(|record| record.expected < record.before - 1e-3);
+        assert_eq!(lost, restitution < 1.);
+    }
+
+    // An impulse from nowhere is caught
+    let mut audit = EnergyAudit::default();
+    let a = Object::new(Vec2::ZERO, vec2(1., 0.), 0., Polygon::rectangle(vec2(1., 1.)));
+    let b = Object::new(vec2(2., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1., 1.)));
+    let snapshot = Snapshot::take(&a, &b, vec2(1., 0.), vec2(-1., 0.), a.material());
+    let mut pushed = b.clone();
+    pushed.velocity = vec2(2., 0.);
+    let mut world = World::new();
+    world.objects.extend([a.clone(), b]);
+    world.step(1.).unwrap();
+    audit.record(world.resolved[0], snapshot, &a, &pushed);
+    assert_eq!(audit.violations, 1);
+    assert!((audit.drift - 2.).abs() < 1e-5);
+}
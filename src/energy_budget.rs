@@ -0,0 +1,104 @@
+//! Optional breakdown of every step's change in kinetic energy by what caused it.
+//!
+//! With [`World::energy_budget`] set, the kinetic energy of the dynamic objects is measured between
+//! the phases of a step: the kicks of the fields, the controllers, the joints, the collision pass
+//! and the conservation projection. Collisions and the objects joining or leaving mid-step are
+//! measured on their own around each event, what's left of the collision pass is the work of the
+//! fields while the objects move between collisions. The parts add up to the change over the step,
+//! so a drifting total shows which of them it comes from.
+//!
+//! Energies are in the objects' local time, see [`dilation`].
+
+use super::*;
+use conservation::kinetic_energy;
+#[cfg(test)]
+use joint::Joint;
+
+/// Where the kinetic energy changed during a step, each part is the energy it added
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnergyBudget {
+    /// Accelerations, gravity, gravity sources and mutual gravity, along with the integrator's
+    /// error moving the objects between collisions
+    pub fields: f32,
+    /// What the objects' controllers did
+    pub controllers: f32,
+    pub joints: f32,
+    /// Bounces, friction and the overlaps resolved at the end of the step
+    pub collisions: f32,
+    /// Projection of [`World::enforce_conservation`]
+    pub corrections: f32,
+    /// Objects spawned mid-step, less the ones removed over the collision limit
+    pub population: f32,
+    /// Kinetic energy as of the last measurement
+    measured: f32,
+}
+
+impl EnergyBudget {
+    /// Names of the [`parts`](Self::parts), in their order
+    pub const PARTS: [&'static str; 6] = ["Fields", "Controllers", "Joints", "Collisions", "Corrections", "Spawns and removals"];
+
+    pub fn parts(&self) -> [f32; 6] {
+        [self.fields, self.controllers, self.joints, self.collisions, self.corrections, self.population]
+    }
+    /// Change of the kinetic energy over the step
+    pub fn total(&self) -> f32 {
+        self.parts().iter().sum()
+    }
+    /// Budget of a step starting with kinetic energy `energy`
+    pub(crate) fn start(energy: f32) -> Self {
+        Self {
+            measured: energy,
+            ..Self::default()
+        }
+    }
+    /// Books the change since the last measurement, now at `energy`, to `part`
+    pub(crate) fn settle(&mut self, energy: f32, part: fn(&mut Self) -> &mut f32) {
+        *part(self) += energy - self.measured;
+        self.measured = energy;
+    }
+    /// Books a change measured on its own, e.g. around one collision, to `part`
+    pub(crate) fn add(&mut self, change: f32, part: fn(&mut Self) -> &mut f32) {
+        *part(self) += change;
+        self.measured += change;
+    }
+}
+
+/// Kinetic energy of `object` in its local time, zero unless it's dynamic like in
+/// [`World::total_energy`]
+pub(crate) fn local_energy(object: &Object) -> f32 {
+    if !object.is_dynamic() {
+        return 0.;
+    }
+    kinetic_energy(object) / (object.time_scale * object.time_scale)
+}
+
+#[test]
+fn test_budget_adds_up() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    world.energy_budget = Some(EnergyBudget::default());
+    world.objects.push(Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(10., 1.))));
+    let mut ball = Object::new(vec2(-2., 2.), Vec2::ZERO, 0., Polygon::regular(8));
+    ball.restitution = 0.5;
+    world.objects.push(ball);
+    // A pendulum swinging from the world, the joint turns its fall sideways
+    world.objects.push(Object::new(vec2(3., 2.), Vec2::ZERO, 0., Polygon::rectangle(vec2(0.2, 0.2))));
+    world.joints.push(Joint::pin(2, vec2(-1., 0.), vec2(2., 2.)));
+
+    let mut sums = [0.; 6];
+    for _ in 0..90 {
+        let before = world.total_energy();
+        world.step(1. / 60.).unwrap();
+        let budget = world.energy_budget.unwrap();
+        let change = world.total_energy() - before;
+        assert!((budget.total() - change).abs() < 1e-3 * before.max(1.), "{budget:?} vs {change}");
+        for (sum, part) in sums.iter_mut().zip(budget.parts()) {
+            *sum += part;
+        }
+    }
+    let [fields, controllers, joints, collisions, corrections, population] = sums;
+    assert!(fields > 0., "falling speeds the objects up");
+    assert!(collisions < 0., "the half elastic ball lost energy bouncing");
+    assert!(joints != 0.);
+    assert_eq!((controllers, corrections, population), (0., 0., 0.));
+}
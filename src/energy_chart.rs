@@ -0,0 +1,106 @@
+//! Stacked chart of where the kinetic energy changed over the last steps.
+//!
+//! Every step's [`EnergyBudget`] is a bar split into its parts, gains stacked up and losses down
+//! from zero, with the net change as a line through them. When the total drifts, the part it
+//! comes from sticks out.
+
+use std::collections::VecDeque;
+
+use egui::plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use perfect_collisions::energy_budget::EnergyBudget;
+
+use super::*;
+
+/// Steps the chart covers
+const HISTORY_STEPS: usize = 300;
+const PLOT_HEIGHT: f32 = 200.;
+/// Colors of the budget's parts, in the order of [`EnergyBudget::PARTS`]
+const PART_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(90, 160, 230),
+    egui::Color32::from_rgb(230, 160, 60),
+    egui::Color32::from_rgb(160, 110, 220),
+    egui::Color32::from_rgb(220, 80, 80),
+    egui::Color32::from_rgb(90, 200, 120),
+    egui::Color32::from_rgb(160, 160, 160),
+];
+
+pub struct EnergyChart {
+    /// Budgets of the last steps with the frames they led to, oldest first
+    steps: VecDeque<(usize, EnergyBudget)>,
+}
+
+impl EnergyChart {
+    pub fn new() -> Self {
+        Self {
+            steps: VecDeque::with_capacity(HISTORY_STEPS),
+        }
+    }
+
+    /// Keeps the budget of the world's last step, once per step
+    pub fn record(&mut self, world: &World) {
+        let Some(budget) = world.energy_budget else {
+            return;
+        };
+        if self.steps.back().is_some_and(|(frame, _)| *frame == world.frame) {
+            return;
+        }
+        if self.steps.len() == HISTORY_STEPS {
+            self.steps.pop_front();
+        }
+        self.steps.push_back((world.frame, budget));
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        memory::deque_bytes(&self.steps)
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) {
+        let mut sums = [0f32; 6];
+        for (_, budget) in &self.steps {
+            for (sum, part) in sums.iter_mut().zip(budget.parts()) {
+                *sum += part;
+            }
+        }
+        ui.label(format!("Over the last {} steps: {:+.3e} J", self.steps.len(), sums.iter().sum::<f32>()));
+        egui::Grid::new("energy_budget_sums").show(ui, |ui| {
+            for (name, sum) in EnergyBudget::PARTS.iter().zip(sums) {
+                ui.label(*name);
+                ui.label(format!("{sum:+.3e} J"));
+                ui.end_row();
+            }
+        });
+
+        // Stacked by hand, gains and losses grow away from zero on their own sides
+        let (mut gains, mut losses) = (vec![0f32; self.steps.len()], vec![0f32; self.steps.len()]);
+        let charts = EnergyBudget::PARTS
+            .iter()
+            .enumerate()
+            .map(|(part, name)| {
+                let bars = self
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (frame, budget))| {
+                        let value = budget.parts()[part];
+                        let base = if value >= 0. { &mut gains[i] } else { &mut losses[i] };
+                        let bar = Bar::new(*frame as f64, value as f64).base_offset(*base as f64);
+                        *base += value;
+                        bar
+                    })
+                    .collect();
+                BarChart::new(bars).width(1.).color(PART_COLORS[part]).name(name)
+            })
+            .collect::<Vec<_>>();
+        let net = self
+            .steps
+            .iter()
+            .map(|(frame, budget)| [*frame as f64, budget.total() as f64])
+            .collect::<PlotPoints>();
+        Plot::new("energy_budget").height(PLOT_HEIGHT).legend(Legend::default()).show(ui, |plot_ui| {
+            for chart in charts {
+                plot_ui.bar_chart(chart);
+            }
+            plot_ui.line(Line::new(net).color(egui::Color32::WHITE).name("Net change"));
+        });
+    }
+}
@@ -0,0 +1,28 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionError {
+    /// Fewer than three vertices, non-finite coordinates, no area or clockwise winding
+    DegenerateShape,
+    NonConvex,
+    /// An object's position, velocity or rotation stopped being finite
+    NaNDetected { object: usize },
+    /// A step resolved more collisions than its budget without the queue draining
+    NoConvergence { resolved: usize },
+}
+
+impl fmt::Display for CollisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DegenerateShape => write!(f, "shape is degenerate"),
+            Self::NonConvex => write!(f, "shape isn't convex"),
+            Self::NaNDetected { object } => write!(f, "object {object} has a non-finite state"),
+            Self::NoConvergence { resolved } => {
+                write!(f, "collision resolution didn't converge after {resolved} collisions")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CollisionError {}
@@ -0,0 +1,117 @@
+//! Object states as CSV or JSON for post-processing outside the app.
+//!
+//! The CSV columns use the names [`import`](crate::import) reads, an exported state can be spawned
+//! again (with the default shapes).
+
+use core::fmt::Write;
+use std::{fs, io, path::Path};
+
+use super::*;
+use world::ObjectState;
+
+const CSV_HEADER: &str = "index,name,time,x,y,vx,vy,rotation,rot_velocity,mass";
+
+pub fn to_csv(states: &[ObjectState]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    for state in states {
+        // Commas would shift the columns
+        let name = state.name.as_deref().unwrap_or_default().replace(',', " ");
+        write!(
+            csv,
+            "\n{},{name},{},{},{},{},{},{},{},{}",
+            state.index,
+            state.time,
+            state.position.x,
+            state.position.y,
+            state.velocity.x,
+            state.velocity.y,
+            state.rotation,
+            state.rot_velocity,
+            state.mass
+        )
+        .unwrap();
+    }
+    csv.push('\n');
+    csv
+}
+
+/// Array of flat objects with the same keys as the CSV columns, unnamed objects have no `name`
+pub fn to_json(states: &[ObjectState]) -> String {
+    let mut json = String::from("[");
+    for (i, state) in states.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str("\n  {");
+        write!(json, "\"index\": {}, ", state.index).unwrap();
+        if let Some(name) = &state.name {
+            write!(json, "\"name\": \"{}\", ", json_escape(name)).unwrap();
+        }
+        let numbers = [
+            ("time", state.time),
+            ("x", state.position.x),
+            ("y", state.position.y),
+            ("vx", state.velocity.x),
+            ("vy", state.velocity.y),
+            ("rotation", state.rotation),
+            ("rot_velocity", state.rot_velocity),
+            ("mass", state.mass),
+        ];
+        for (j, (key, value)) in numbers.into_iter().enumerate() {
+            let separator = if j + 1 < numbers.len() { ", " } else { "" };
+            // JSON has no infinities, kinematic objects have an infinite mass
+            if value.is_finite() {
+                write!(json, "\"{key}\": {value}{separator}").unwrap();
+            } else {
+                write!(json, "\"{key}\": \"{value}\"{separator}").unwrap();
+            }
+        }
+        json.push('}');
+    }
+    json.push_str("\n]\n");
+    json
+}
+
+/// Writes `states` as CSV or JSON depending on the extension of `path`
+pub fn save(states: &[ObjectState], path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let text = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => to_csv(states),
+        Some("json") => to_json(states),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "expected a .csv or .json path")),
+    };
+    fs::write(path, text)
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn test_export_round_trip() {
+    let mut world = World::new();
+    let mut named = Object::new(vec2(1., 2.), vec2(3., -4.), 0.5, Polygon::regular(4));
+    named.name = Some("ball_A".into());
+    world.objects.push(named);
+    world.objects.push(Object::new(vec2(-1., 0.), Vec2::ZERO, 0., Polygon::regular(4)));
+    let states = world.snapshot_states();
+
+    for rows in [import::parse_csv(&to_csv(&states)).unwrap(), import::parse_json(&to_json(&states)).unwrap()] {
+        let objects = import::to_objects(&rows).unwrap();
+        for (object, state) in objects.iter().zip(&states) {
+            assert_eq!(object.position, state.position);
+            assert_eq!(object.velocity, state.velocity);
+            assert_eq!(object.rotation, state.rotation);
+            assert_eq!(object.name, state.name);
+        }
+    }
+}
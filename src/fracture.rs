@@ -0,0 +1,130 @@
+//! Breaking objects into fragments.
+//!
+//! [`shatter`] cuts an object's outline into wedges meeting at its centroid, each becoming an
+//! object of its own with the share of the mass its area covers. The fragments carry on the
+//! object's momentum exactly and its kinetic energy less the energy the fracture took: relative to
+//! the center of mass they keep the spin of the whole plus a sampled outward push, scaled so the
+//! energy adds up. [`World::fracture`] replaces an object by its fragments.
+
+use super::*;
+use conservation::kinetic_energy;
+use rng::{RngService, Stream};
+
+/// Fewer wedges could span half a turn around the centroid or more and lose their convexity
+pub const MIN_PIECES: usize = 3;
+
+/// Fragments of `object` in `pieces` wedges of consecutive edges, between [`MIN_PIECES`] and one
+/// per edge. `energy` is what breaking it takes out of the kinetic energy, negative for an
+/// explosion adding to it. When the object moves too slowly to pay for it, the fragments carry on
+/// together with the velocity of the whole.
+///
+/// Outward pushes are drawn from [`Stream::Spawn`], the object needs a finite mass.
+pub fn shatter(object: &Object, pieces: usize, energy: f32, rng: &mut RngService) -> Vec<Object> {
+    let outline = object.shape.points.iter().map(|&p| object.to_world(p)).collect::<Vec<_>>();
+    let n = outline.len();
+    let hub = polygon_centroid(&outline);
+    let pieces = pieces.clamp(MIN_PIECES.min(n), n);
+    let area = polygon_area(&outline);
+
+    let mut fragments = (0..pieces)
+        .map(|k| {
+            let wedge = core::iter::once(hub)
+                .chain((k * n / pieces..=(k + 1) * n / pieces).map(|i| outline[i % n]))
+                .collect::<Vec<_>>();
+            let center = polygon_centroid(&wedge);
+            let points = wedge.iter().map(|&p| p - center).collect::<Vec<_>>();
+            let mass = object.mass * polygon_area(&points) / area;
+            // The cut from the hub is new, the other edges keep their materials
+            let edge_materials = core::iter::once(None)
+                .chain((k * n / pieces..(k + 1) * n / pieces).map(|i| object.shape.edge_material(i % n)))
+                .collect();
+            let mut fragment = Object::new(center, Vec2::ZERO, 0., Polygon { points, edge_materials });
+            fragment.mass = mass;
+            fragment.inertia = polygon_moment_of_inertia(&fragment.shape.points, mass);
+            fragment.acceleration = object.acceleration;
+            fragment.gravity_scale = object.gravity_scale;
+            fragment.restitution = object.restitution;
+            fragment.static_friction = object.static_friction;
+            fragment.dynamic_friction = object.dynamic_friction;
+            fragment.cur_time = object.cur_time;
+            fragment.layer = object.layer;
+            fragment.team = object.team;
+            fragment.z_index = object.z_index;
+            fragment.ccd = object.ccd;
+            fragment
+        })
+        .collect::<Vec<_>>();
+
+    // Velocities relative to the center of mass, the wedge's share of the spin and a push away
+    // from the hub, less their net momentum
+    let mass = fragments.iter().map(|fragment| fragment.mass).sum::<f32>();
+    let mut relative = fragments
+        .iter()
+        .map(|fragment| {
+            let push = (fragment.position - hub).normalize_or_zero() * rng.range(Stream::Spawn, 0.5..1.);
+            object.rot_velocity * (fragment.position - object.position).perp() + push
+        })
+        .collect::<Vec<_>>();
+    let drift = fragments.iter().zip(&relative).map(|(fragment, w)| *w * fragment.mass).sum::<Vec2>() / mass;
+    relative.iter_mut().for_each(|w| *w -= drift);
+
+    let relative_energy = fragments
+        .iter()
+        .zip(&relative)
+        .map(|(fragment, w)| {
+            0.5 * fragment.mass * w.length_squared() + 0.5 * fragment.inertia * object.rot_velocity * object.rot_velocity
+        })
+        .sum::<f32>();
+    let velocity = object.velocity * object.mass / mass;
+    let remaining = kinetic_energy(object) - energy - 0.5 * mass * velocity.length_squared();
+    let scale = if remaining > 0. && relative_energy > 0. {
+        (remaining / relative_energy).sqrt()
+    } else {
+        0.
+    };
+    for (fragment, w) in fragments.iter_mut().zip(relative) {
+        fragment.velocity = velocity + w * scale;
+        fragment.rot_velocity = object.rot_velocity * scale;
+    }
+    fragments
+}
+
+#[test]
+fn test_fracture_conserves_momentum_and_energy() {
+    let momentum = |objects: &[Object]| objects.iter().map(|object| object.velocity * object.mass).sum::<Vec2>();
+    let energy = |objects: &[Object]| objects.iter().map(kinetic_energy).sum::<f32>();
+    let mut rng = RngService::new(7);
+
+    let mut hexagon = Object::new(vec2(1., 2.), vec2(3., -1.), 0.3, Polygon::regular(6));
+    hexagon.mass = 2.;
+    hexagon.inertia *= 2.;
+    hexagon.rot_velocity = 4.;
+    let fragments = shatter(&hexagon, 4, 1.5, &mut rng);
+    assert_eq!(fragments.len(), 4);
+    assert!((fragments.iter().map(|fragment| fragment.mass).sum::<f32>() - 2.).abs() < 1e-5);
+    assert!(momentum(&fragments).abs_diff_eq(momentum(&[hexagon.clone()]), 1e-4));
+    assert!((energy(&fragments) - (kinetic_energy(&hexagon) - 1.5)).abs() < 1e-3);
+    // The wedges tile the outline
+    let area = fragments.iter().map(|fragment| polygon_area(&fragment.shape.points)).sum::<f32>();
+    assert!((area - polygon_area(&hexagon.shape.points)).abs() < 1e-4);
+    assert!(fragments.iter().all(|fragment| fragment.shape.validate().is_ok()));
+
+    // An explosion at rest adds its energy and throws the fragments apart
+    let resting = Object::new(Vec2::ZERO, Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 1.)));
+    let fragments = shatter(&resting, 8, -2., &mut rng);
+    assert_eq!(fragments.len(), 4);
+    assert!(momentum(&fragments).length() < 1e-4);
+    assert!((energy(&fragments) - 2.).abs() < 1e-3);
+    assert!(fragments.iter().all(|fragment| fragment.velocity.dot(fragment.position) > 0.));
+
+    // Breaking costs more than the motion relative to the center of mass holds
+    let fragments = shatter(&hexagon, 6, 100., &mut rng);
+    assert!(fragments.iter().all(|fragment| fragment.velocity.abs_diff_eq(vec2(3., -1.), 1e-5)));
+
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(5., 0.), Vec2::ZERO, 0., Polygon::regular(3)));
+    world.objects.push(hexagon);
+    assert_eq!(world.fracture(0, 3, 0.), 1..4);
+    assert_eq!(world.objects[0].position, vec2(1., 2.));
+    world.step(0.1).unwrap();
+}
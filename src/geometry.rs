@@ -1,67 +1,714 @@
+// General purpose math, not every helper has a user in the app yet
+#![allow(dead_code)]
+
+use core::ops::Range;
+
 use super::*;
 
+/// Sag below which [`first_crossing`] stops bisecting
+pub const CROSSING_TOLERANCE: f32 = 1e-6;
+/// Bisections [`first_crossing`] makes at most, for pieces where the function hugs zero
+const MAX_BISECTIONS: usize = 16;
+
 pub trait Vec2Ext {
     fn angle(&self) -> f32;
-    fn rotate_rad(&self, angle: f32) -> Vec2;
 }
 
 impl Vec2Ext for Vec2 {
     fn angle(&self) -> f32 {
         Vec2::X.angle_between(*self)
     }
-    fn rotate_rad(&self, angle: f32) -> Vec2 {
+}
+
+/// Rotation stored as its cosine and sine, so the trigonometry runs once per angle change
+/// instead of once per vertex
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rot2 {
+    pub cos: f32,
+    pub sin: f32,
+}
+
+impl Default for Rot2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Rot2 {
+    pub const IDENTITY: Rot2 = Rot2 { cos: 1., sin: 0. };
+
+    pub fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self { cos, sin }
+    }
+    /// Angle in [-π, π]
+    pub fn angle(&self) -> f32 {
+        self.sin.atan2(self.cos)
+    }
+    pub fn apply(&self, v: Vec2) -> Vec2 {
         vec2(
-            self.x * angle.cos() - self.y * angle.sin(),
-            self.x * angle.sin() + self.y * angle.cos(),
+            v.x * self.cos - v.y * self.sin,
+            v.x * self.sin + v.y * self.cos,
         )
     }
+    /// Rotation applying `other` first and `self` second
+    pub fn compose(&self, other: Rot2) -> Rot2 {
+        Rot2 {
+            cos: self.cos * other.cos - self.sin * other.sin,
+            sin: self.sin * other.cos + self.cos * other.sin,
+        }
+    }
+    pub fn inverse(&self) -> Rot2 {
+        Rot2 {
+            cos: self.cos,
+            sin: -self.sin,
+        }
+    }
+    /// Interpolates along the shorter arc, `t` = 0 gives `self`, `t` = 1 gives `other`
+    pub fn slerp(&self, other: Rot2, t: f32) -> Rot2 {
+        Rot2::from_angle(lerp_angle(self.angle(), other.angle(), t))
+    }
 }
 
-pub fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
-    let mut stack = Vec::<Vec2>::new();
+/// Wraps an angle into (-π, π]
+pub fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(2. * PI);
+    if wrapped > PI {
+        wrapped - 2. * PI
+    } else {
+        wrapped
+    }
+}
+
+/// Signed angle of the shortest rotation from `from` to `to`, in (-π, π]
+pub fn angle_difference(from: f32, to: f32) -> f32 {
+    wrap_angle(to - from)
+}
+
+/// Interpolates between two angles along the shorter arc, the result is wrapped
+pub fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    wrap_angle(from + angle_difference(from, to) * t)
+}
+
+/// Rigid 2D transform
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2 {
+    pub translation: Vec2,
+    pub rotation: Rot2,
+}
 
-    points.sort_by(|a, b| {
-        if a.y == b.y {
-            a.x.partial_cmp(&b.x).unwrap()
-        } else {
-            a.y.partial_cmp(&b.y).unwrap()
+impl Transform2 {
+    pub fn new(translation: Vec2, angle: f32) -> Self {
+        Self {
+            translation,
+            rotation: Rot2::from_angle(angle),
         }
-    });
-    points.dedup();
+    }
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        self.rotation.apply(point) + self.translation
+    }
+    pub fn apply_inverse(&self, point: Vec2) -> Vec2 {
+        self.rotation.inverse().apply(point - self.translation)
+    }
+}
+
+/// Axis aligned bounding box, boundaries count as inside
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    /// Box containing nothing, the identity for `union`
+    pub const EMPTY: Aabb = Aabb {
+        min: Vec2::splat(f32::INFINITY),
+        max: Vec2::splat(f32::NEG_INFINITY),
+    };
+
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+    pub fn from_points(points: &[Vec2]) -> Self {
+        points.iter().fold(Self::EMPTY, |aabb, p| Self {
+            min: aabb.min.min(*p),
+            max: aabb.max.max(*p),
+        })
+    }
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+    pub fn expanded_by(&self, margin: f32) -> Self {
+        Self {
+            min: self.min - Vec2::splat(margin),
+            max: self.max + Vec2::splat(margin),
+        }
+    }
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
+    }
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.
+    }
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+}
+
+/// Line segment from `a` to `b`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub a: Vec2,
+    pub b: Vec2,
+}
+
+impl Segment {
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        Self { a, b }
+    }
+    pub fn direction(&self) -> Vec2 {
+        self.b - self.a
+    }
+    pub fn length(&self) -> f32 {
+        self.direction().length()
+    }
+    /// Point at parameter `u`, 0 is `a` and 1 is `b`
+    pub fn at(&self, u: f32) -> Vec2 {
+        self.a + self.direction() * u
+    }
+    /// Intersection point of two segments, parallel segments never intersect
+    pub fn intersect_segment(&self, other: &Segment) -> Option<Vec2> {
+        let (t, u) = line_params(self.a, self.direction(), other.a, other.direction())?;
+        ((0. ..=1.).contains(&t) && (0. ..=1.).contains(&u)).then(|| self.at(t))
+    }
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        let direction = self.direction();
+        let length_sq = direction.length_squared();
+        if length_sq == 0. {
+            return self.a;
+        }
+        self.at(((point - self.a).dot(direction) / length_sq).clamp(0., 1.))
+    }
+    /// Closest pair of points, the first one lies on `self` and the second on `other`
+    pub fn closest_points(&self, other: &Segment) -> (Vec2, Vec2) {
+        if let Some(intersection) = self.intersect_segment(other) {
+            return (intersection, intersection);
+        }
+        // Non-intersecting segments in 2D are closest at one of the endpoints
+        [
+            (self.a, other.closest_point(self.a)),
+            (self.b, other.closest_point(self.b)),
+            (self.closest_point(other.a), other.a),
+            (self.closest_point(other.b), other.b),
+        ]
+        .into_iter()
+        .min_by(|(a1, b1), (a2, b2)| a1.distance_squared(*b1).total_cmp(&a2.distance_squared(*b2)))
+        .unwrap()
+    }
+}
 
-    let p0 = points[0];
+/// Closest point on the boundary of a polygon, along with the index of the edge starting at that
+/// vertex. Returns `None` for an empty polygon.
+pub fn closest_point_on_polygon(polygon: &[Vec2], point: Vec2) -> Option<(Vec2, usize)> {
+    (0..polygon.len())
+        .map(|i| {
+            let edge = Segment::new(polygon[i], polygon[(i + 1) % polygon.len()]);
+            (edge.closest_point(point), i)
+        })
+        .min_by(|(a, _), (b, _)| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+}
 
-    points[1..].sort_by(|a, b| {
-        let a = (*a - p0).angle();
-        let b = (*b - p0).angle();
-        a.partial_cmp(&b).unwrap()
-    });
+/// Half-line `origin + direction * t` for `t >= 0`, `direction` isn't required to be normalized
+/// so `t` is measured in multiples of it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2,
+    pub direction: Vec2,
+}
 
-    for point in points {
-        while stack.len() > 1
-            && (stack[stack.len() - 1] - stack[stack.len() - 2])
-                .angle_between(point - stack[stack.len() - 2])
-                <= 0.
+impl Ray {
+    pub fn new(origin: Vec2, direction: Vec2) -> Self {
+        Self { origin, direction }
+    }
+    pub fn at(&self, t: f32) -> Vec2 {
+        self.origin + self.direction * t
+    }
+    /// Ray parameter of the hit, parallel segments are never hit
+    pub fn intersect_segment(&self, segment: &Segment) -> Option<f32> {
+        let (t, u) = line_params(self.origin, self.direction, segment.a, segment.direction())?;
+        (t >= 0. && (0. ..=1.).contains(&u)).then_some(t)
+    }
+    /// Closest hit against the edges of a polygon, returns the ray parameter and the index of the
+    /// edge starting at that vertex
+    pub fn intersect_polygon(&self, polygon: &[Vec2]) -> Option<(f32, usize)> {
+        (0..polygon.len())
+            .filter_map(|i| {
+                let edge = Segment::new(polygon[i], polygon[(i + 1) % polygon.len()]);
+                self.intersect_segment(&edge).map(|t| (t, i))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+/// Solves `p + d * t = q + e * u` for `(t, u)`, `None` when the lines are parallel
+fn line_params(p: Vec2, d: Vec2, q: Vec2, e: Vec2) -> Option<(f32, f32)> {
+    let denominator = d.perp_dot(e);
+    if denominator.abs() <= f32::EPSILON * d.length() * e.length() {
+        return None;
+    }
+    let offset = q - p;
+    Some((offset.perp_dot(e) / denominator, offset.perp_dot(d) / denominator))
+}
+
+/// Coefficients a surface collides with, see [`Object::restitution`] and its neighbours
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Material {
+    pub restitution: f32,
+    pub static_friction: f32,
+    pub dynamic_friction: f32,
+}
+
+impl Material {
+    /// Coefficients of a contact between the two surfaces, the geometric means
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            restitution: (self.restitution * other.restitution).sqrt(),
+            static_friction: (self.static_friction * other.static_friction).sqrt(),
+            dynamic_friction: (self.dynamic_friction * other.dynamic_friction).sqrt(),
+        }
+    }
+}
+
+/// Object space outline of a shape, vertices anti-clockwise
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<Vec2>,
+    /// Surfaces of single edges overriding the object's material, indexed like the edges by
+    /// their first vertex. Missing entries and `None` collide with the object's own.
+    pub edge_materials: Vec<Option<Material>>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Vec2>) -> Self {
+        Self {
+            points,
+            edge_materials: vec![],
+        }
+    }
+    /// The shape with the edge starting at vertex `edge` made of `material`, e.g. the bouncy top
+    /// of a trampoline
+    pub fn with_edge_material(mut self, edge: usize, material: Material) -> Self {
+        if self.edge_materials.len() <= edge {
+            self.edge_materials.resize(edge + 1, None);
+        }
+        self.edge_materials[edge] = Some(material);
+        self
+    }
+    /// Override of the edge starting at vertex `edge`, if any
+    pub fn edge_material(&self, edge: usize) -> Option<Material> {
+        self.edge_materials.get(edge).copied().flatten()
+    }
+    /// Axis aligned rectangle centered on the origin
+    pub fn rectangle(size: Vec2) -> Self {
+        let half = size / 2.;
+        Self::new(vec![vec2(-half.x, -half.y), vec2(half.x, -half.y), half, vec2(-half.x, half.y)])
+    }
+    /// Regular polygon inscribed in the unit circle with its first vertex on +x
+    pub fn regular(sides: usize) -> Self {
+        let points = (0..sides)
+            .map(|i| {
+                let angle = (i as f32 / sides as f32) * 2. * PI;
+                vec2(angle.cos(), angle.sin())
+            })
+            .collect();
+        Self::new(points)
+    }
+    /// Checks the shape is something the solver can handle, a convex anti-clockwise polygon
+    pub fn validate(&self) -> Result<(), CollisionError> {
+        if self.points.len() < 3
+            || !self.points.iter().all(|p| p.is_finite())
+            || polygon_signed_area(&self.points) <= f32::EPSILON
         {
-            stack.pop();
+            return Err(CollisionError::DegenerateShape);
+        }
+        let n = self.points.len();
+        let convex = (0..n).all(|i| {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % n];
+            let c = self.points[(i + 2) % n];
+            (b - a).perp_dot(c - b) >= 0.
+        });
+        if !convex {
+            return Err(CollisionError::NonConvex);
+        }
+        Ok(())
+    }
+}
+
+/// Winding number test, works for non-convex polygons of either orientation
+pub fn polygon_contains_point(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut winding = 0;
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let side = (b - a).perp_dot(point - a);
+        if a.y <= point.y {
+            if b.y > point.y && side > 0. {
+                winding += 1;
+            }
+        } else if b.y <= point.y && side < 0. {
+            winding -= 1;
+        }
+    }
+    winding != 0
+}
+
+/// True when `inner` lies entirely inside `outer`
+pub fn polygon_contains_polygon(outer: &[Vec2], inner: &[Vec2]) -> bool {
+    if !inner.iter().all(|p| polygon_contains_point(outer, *p)) {
+        return false;
+    }
+    // With a non-convex outer polygon the vertices can be inside while an edge leaves it
+    let edges = |polygon: &[Vec2]| {
+        (0..polygon.len())
+            .map(|i| Segment::new(polygon[i], polygon[(i + 1) % polygon.len()]))
+            .collect::<Vec<_>>()
+    };
+    let outer_edges = edges(outer);
+    edges(inner).iter().all(|inner_edge| {
+        outer_edges
+            .iter()
+            .all(|outer_edge| inner_edge.intersect_segment(outer_edge).is_none())
+    })
+}
+
+/// Signed area by the shoelace formula, positive for anti-clockwise polygons
+pub fn polygon_signed_area(polygon: &[Vec2]) -> f32 {
+    (0..polygon.len())
+        .map(|i| polygon[i].perp_dot(polygon[(i + 1) % polygon.len()]))
+        .sum::<f32>()
+        / 2.
+}
+
+pub fn polygon_area(polygon: &[Vec2]) -> f32 {
+    polygon_signed_area(polygon).abs()
+}
+
+pub fn polygon_perimeter(polygon: &[Vec2]) -> f32 {
+    (0..polygon.len())
+        .map(|i| polygon[i].distance(polygon[(i + 1) % polygon.len()]))
+        .sum()
+}
+
+/// Area centroid, falls back to the vertex average for degenerate polygons
+pub fn polygon_centroid(polygon: &[Vec2]) -> Vec2 {
+    let area = polygon_signed_area(polygon);
+    if area.abs() <= f32::EPSILON {
+        return polygon.iter().copied().sum::<Vec2>() / polygon.len().max(1) as f32;
+    }
+    let weighted_sum = (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            (a + b) * a.perp_dot(b)
+        })
+        .sum::<Vec2>();
+    weighted_sum / (6. * area)
+}
+
+/// Polar second moment of area about the origin, multiply by density to get the moment of inertia
+fn polygon_second_moment_about_origin(polygon: &[Vec2]) -> f32 {
+    let signed = (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a.perp_dot(b) * (a.dot(a) + a.dot(b) + b.dot(b))
+        })
+        .sum::<f32>()
+        / 12.;
+    signed.abs()
+}
+
+/// Polar second moment of area about the centroid
+pub fn polygon_second_moment(polygon: &[Vec2]) -> f32 {
+    polygon_second_moment_about_origin(polygon)
+        - polygon_area(polygon) * polygon_centroid(polygon).length_squared()
+}
+
+/// Moment of inertia of a uniform polygon of the given mass about the origin of its coordinates,
+/// infinite for polygons without area so they never pick up spin
+pub fn polygon_moment_of_inertia(polygon: &[Vec2], mass: f32) -> f32 {
+    let area = polygon_area(polygon);
+    if area <= f32::EPSILON {
+        return f32::INFINITY;
+    }
+    mass * polygon_second_moment_about_origin(polygon) / area
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+    pub fn contains(&self, point: Vec2) -> bool {
+        // Relative tolerance, points on the circle come out of the construction with rounding errors
+        self.center.distance(point) <= self.radius * (1. + 1e-5) + 1e-6
+    }
+    fn from_diameter(a: Vec2, b: Vec2) -> Self {
+        Self::new((a + b) / 2., a.distance(b) / 2.)
+    }
+    /// Circumcircle, falls back to the widest diameter for collinear points
+    fn from_triangle(a: Vec2, b: Vec2, c: Vec2) -> Self {
+        let (ab, ac) = (b - a, c - a);
+        let denominator = 2. * ab.perp_dot(ac);
+        if denominator.abs() <= f32::EPSILON {
+            return [Self::from_diameter(a, b), Self::from_diameter(a, c), Self::from_diameter(b, c)]
+                .into_iter()
+                .max_by(|x, y| x.radius.total_cmp(&y.radius))
+                .unwrap();
+        }
+        let offset = vec2(
+            ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+            ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+        ) / denominator;
+        Self::new(a + offset, offset.length())
+    }
+}
+
+/// Smallest circle containing all points (Welzl's algorithm in its iterative form).
+///
+/// The points aren't shuffled so results are deterministic, the worst case is cubic which doesn't
+/// matter for polygon sized inputs.
+pub fn min_enclosing_circle(points: &[Vec2]) -> Circle {
+    let Some(&first) = points.first() else {
+        return Circle::new(Vec2::ZERO, 0.);
+    };
+    let mut circle = Circle::new(first, 0.);
+    for i in 1..points.len() {
+        if circle.contains(points[i]) {
+            continue;
+        }
+        circle = Circle::new(points[i], 0.);
+        for j in 0..i {
+            if circle.contains(points[j]) {
+                continue;
+            }
+            circle = Circle::from_diameter(points[i], points[j]);
+            for k in 0..j {
+                if !circle.contains(points[k]) {
+                    circle = Circle::from_triangle(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+    circle
+}
+
+/// Oriented bounding box, `rotation` maps the box's local axes to world axes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+    pub rotation: Rot2,
+}
+
+impl Obb {
+    pub fn area(&self) -> f32 {
+        4. * self.half_extents.x * self.half_extents.y
+    }
+    /// Anti-clockwise corners in world space
+    pub fn corners(&self) -> [Vec2; 4] {
+        let (hx, hy) = (self.half_extents.x, self.half_extents.y);
+        [vec2(-hx, -hy), vec2(hx, -hy), vec2(hx, hy), vec2(-hx, hy)].map(|p| self.rotation.apply(p) + self.center)
+    }
+}
+
+/// Largest distance between two points of a convex polygon (anti-clockwise, e.g. from `convex_hull`)
+pub fn polygon_diameter(hull: &[Vec2]) -> f32 {
+    let n = hull.len();
+    if n < 3 {
+        return if n == 2 { hull[0].distance(hull[1]) } else { 0. };
+    }
+    let mut best = 0f32;
+    let mut j = 1;
+    for i in 0..n {
+        let (a, b) = (hull[i], hull[(i + 1) % n]);
+        let edge = b - a;
+        // Antipodal vertex of the edge, it only ever moves forward around the hull
+        while edge.perp_dot(hull[(j + 1) % n] - a) > edge.perp_dot(hull[j] - a) {
+            j = (j + 1) % n;
+        }
+        best = best.max(a.distance(hull[j])).max(b.distance(hull[j]));
+    }
+    best
+}
+
+/// Smallest distance between two parallel lines enclosing a convex polygon (anti-clockwise)
+pub fn min_width(hull: &[Vec2]) -> f32 {
+    let n = hull.len();
+    if n < 3 {
+        return 0.;
+    }
+    let mut best = f32::INFINITY;
+    let mut j = 1;
+    for i in 0..n {
+        let a = hull[i];
+        let edge = hull[(i + 1) % n] - a;
+        while edge.perp_dot(hull[(j + 1) % n] - a) > edge.perp_dot(hull[j] - a) {
+            j = (j + 1) % n;
+        }
+        best = best.min(edge.perp_dot(hull[j] - a) / edge.length());
+    }
+    best
+}
+
+/// Minimum area oriented bounding box of a convex polygon (anti-clockwise). One side of the
+/// optimal box is always collinear with a polygon edge, so only edge directions are tried.
+pub fn min_area_obb(hull: &[Vec2]) -> Obb {
+    let n = hull.len();
+    if n < 3 {
+        let aabb = Aabb::from_points(hull);
+        return Obb {
+            center: if n == 0 { Vec2::ZERO } else { aabb.center() },
+            half_extents: if n == 0 { Vec2::ZERO } else { aabb.size() / 2. },
+            rotation: Rot2::IDENTITY,
+        };
+    }
+
+    let mut best: Option<Obb> = None;
+    // Calipers touching the far side, the forward-most and the backward-most vertex
+    let (mut top, mut right, mut left) = (1, 1, 1);
+    for i in 0..n {
+        let a = hull[i];
+        let u = (hull[(i + 1) % n] - a).normalize();
+        let v = u.perp();
+        let along = |k: usize| (hull[k] - a).dot(u);
+        let across = |k: usize| (hull[k] - a).dot(v);
+
+        while along((right + 1) % n) > along(right) {
+            right = (right + 1) % n;
+        }
+        if i == 0 {
+            top = right;
+        }
+        while across((top + 1) % n) > across(top) {
+            top = (top + 1) % n;
         }
-        stack.push(point);
+        if i == 0 {
+            left = top;
+        }
+        while along((left + 1) % n) < along(left) {
+            left = (left + 1) % n;
+        }
+
+        let (min_u, max_u, max_v) = (along(left), along(right), across(top));
+        let candidate = Obb {
+            center: a + u * (min_u + max_u) / 2. + v * max_v / 2.,
+            half_extents: vec2(max_u - min_u, max_v) / 2.,
+            rotation: Rot2 { cos: u.x, sin: u.y },
+        };
+        if best.is_none_or(|best| candidate.area() < best.area()) {
+            best = Some(candidate);
+        }
+    }
+    best.unwrap()
+}
+
+/// Anti-clockwise convex hull starting at the lowest point, collinear points left out
+///
+/// Built as a chain up the right side and one back down the left side, which tell turns apart by
+/// cross products alone, so points almost on top of each other, e.g. the start and end poses of a
+/// barely moving object, can't be sorted out of order as they could by their angles.
+pub fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
     }
 
-    stack
+    // Points not turning anti-clockwise from the last two drop the last one
+    let turns_left = |hull: &[Vec2], point: Vec2| {
+        let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+        (b - a).perp_dot(point - a) > 0.
+    };
+    let mut hull = Vec::<Vec2>::with_capacity(points.len() + 1);
+    for &point in &points {
+        while hull.len() >= 2 && !turns_left(&hull, point) {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    // The left chain never pops into the right one
+    let right = hull.len() + 1;
+    for &point in points.iter().rev().skip(1) {
+        while hull.len() >= right && !turns_left(&hull, point) {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    // Back at the lowest point
+    hull.pop();
+    hull
+}
+
+/// Convex polygon of at most `max_vertices` vertices containing `hull`, built by repeatedly
+/// replacing the edge whose removal adds the least area with the meeting point of its neighbours.
+/// `hull` has to be convex and anti-clockwise, triangles are returned as they are.
+pub fn simplify_convex_outer(hull: &[Vec2], max_vertices: usize) -> Vec<Vec2> {
+    let mut points = hull.to_vec();
+    while points.len() > max_vertices.max(3) {
+        let n = points.len();
+        let mut best: Option<(f32, usize, Vec2)> = None;
+        for i in 0..n {
+            let prev = points[(i + n - 1) % n];
+            let a = points[i];
+            let b = points[(i + 1) % n];
+            let next = points[(i + 2) % n];
+            // The neighbouring edges are extended past `a` and `b`, they meet unless they diverge
+            let Some((t, u)) = line_params(a, a - prev, b, b - next) else {
+                continue;
+            };
+            if t < 0. || u < 0. {
+                continue;
+            }
+            let apex = a + (a - prev) * t;
+            let added_area = polygon_area(&[a, apex, b]);
+            if best.is_none_or(|(area, ..)| added_area < area) {
+                best = Some((added_area, i, apex));
+            }
+        }
+        let Some((_, i, apex)) = best else {
+            break;
+        };
+        points[i] = apex;
+        points.remove((i + 1) % n);
+    }
+    points
 }
 
 /// Accepts two sets of points sorted in anti-clockwise order
 ///
-/// Returns true if the two sets of points are separatable by a line
-pub fn sat_collision_detect(convex_hull1: &Vec<Vec2>, convex_hull2: &Vec<Vec2>) -> bool {
+/// Returns true if the two sets of points overlap, i.e. they aren't separable by a line
+pub fn sat_collision_detect(convex_hull1: &[Vec2], convex_hull2: &[Vec2]) -> bool {
     let num_vertices1 = convex_hull1.len();
     let num_vertices2 = convex_hull2.len();
 
     // Iterate through all axes of the first convex hull
     for i in 0..num_vertices1 {
         let axis = (convex_hull1[(i + 1) % num_vertices1] - convex_hull1[i])
-            .rotate_rad(-90.)
+            .perp()
             .normalize();
         let (min1, max1) = project(convex_hull1, axis);
         let (min2, max2) = project(convex_hull2, axis);
@@ -75,7 +722,7 @@ pub fn sat_collision_detect(convex_hull1: &Vec<Vec2>, convex_hull2: &Vec<Vec2>)
     // Iterate through all axes of the second convex hull
     for i in 0..num_vertices2 {
         let axis = (convex_hull2[(i + 1) % num_vertices2] - convex_hull2[i])
-            .rotate_rad(-90.)
+            .perp()
             .normalize();
         let (min1, max1) = project(convex_hull1, axis);
         let (min2, max2) = project(convex_hull2, axis);
@@ -86,16 +733,132 @@ pub fn sat_collision_detect(convex_hull1: &Vec<Vec2>, convex_hull2: &Vec<Vec2>)
         }
     }
 
-    return true;
+    true
+}
+
+/// Axis of least overlap of two convex hulls and the overlap along it, `None` if they're separated
+///
+/// The axis points from the second hull towards the first.
+pub fn sat_penetration(convex_hull1: &[Vec2], convex_hull2: &[Vec2]) -> Option<(Vec2, f32)> {
+    let mut least: Option<(Vec2, f32)> = None;
+    for hull in [convex_hull1, convex_hull2] {
+        for i in 0..hull.len() {
+            let axis = (hull[(i + 1) % hull.len()] - hull[i]).perp().normalize();
+            let (min1, max1) = project(convex_hull1, axis);
+            let (min2, max2) = project(convex_hull2, axis);
+            let depth = max1.min(max2) - min1.max(min2);
+            if depth < 0. {
+                return None;
+            }
+            if least.is_none_or(|(_, least_depth)| depth < least_depth) {
+                let axis = if min1 + max1 < min2 + max2 { -axis } else { axis };
+                least = Some((axis, depth));
+            }
+        }
+    }
+    least
+}
+
+/// Closest pair of points of two convex outlines, the first one on `a`, `None` when they overlap
+///
+/// Outlines of one or two vertices are a point and a segment, e.g. the cores of rounded shapes.
+pub fn convex_closest_points(a: &[Vec2], b: &[Vec2]) -> Option<(Vec2, Vec2)> {
+    fn edges(points: &[Vec2]) -> impl Iterator<Item = Segment> + '_ {
+        (0..points.len()).map(|i| Segment::new(points[i], points[(i + 1) % points.len()]))
+    }
+    let encloses = |outline: &[Vec2], other: &[Vec2]| {
+        outline.len() >= 3 && other.first().is_some_and(|&point| polygon_contains_point(outline, point))
+    };
+    if encloses(a, b) || encloses(b, a) {
+        return None;
+    }
+    let closest = edges(a)
+        .flat_map(|edge| edges(b).map(move |other| edge.closest_points(&other)))
+        .min_by(|(a1, b1), (a2, b2)| a1.distance_squared(*b1).total_cmp(&a2.distance_squared(*b2)))?;
+    (closest.0 != closest.1).then_some(closest)
+}
+
+/// Whether two convex outlines overlap once rounded by their radii, without rounding it's
+/// [`sat_collision_detect`]
+pub fn rounded_overlap(a: &[Vec2], radius_a: f32, b: &[Vec2], radius_b: f32) -> bool {
+    if radius_a + radius_b == 0. {
+        return sat_collision_detect(a, b);
+    }
+    convex_closest_points(a, b).is_none_or(|(p, q)| p.distance(q) < radius_a + radius_b)
+}
+
+/// Earliest `t` in `range` where `f` drops from positive to zero or below and `accept(t)` holds
+///
+/// `curvature` bounds `|f''|` over the range, so `f` can't sag further than `curvature * len² / 8`
+/// below the chord between a piece's ends. Pieces this rules a crossing out of are skipped, the
+/// rest are bisected until the sag is within [`CROSSING_TOLERANCE`] and the crossing is then
+/// interpolated linearly, exactly for a linear `f`.
+pub fn first_crossing(
+    f: impl Fn(f32) -> f32,
+    accept: impl Fn(f32) -> bool,
+    range: Range<f32>,
+    curvature: f32,
+) -> Option<f32> {
+    let mut pieces = vec![(range.start, range.end, f(range.start), f(range.end), 0)];
+    while let Some((lo, hi, f_lo, f_hi, depth)) = pieces.pop() {
+        let sag = curvature * (hi - lo) * (hi - lo) / 8.;
+        // Positive throughout, or never rising above zero to drop from
+        if f_lo.min(f_hi) - sag > 0. || f_lo.max(f_hi) + sag <= 0. {
+            continue;
+        }
+        if sag <= CROSSING_TOLERANCE || depth == MAX_BISECTIONS {
+            if f_lo > 0. && f_hi <= 0. {
+                let t = lo + (hi - lo) * f_lo / (f_lo - f_hi);
+                if accept(t) {
+                    return Some(t);
+                }
+            }
+            continue;
+        }
+        let mid = (lo + hi) / 2.;
+        let f_mid = f(mid);
+        // The earlier half is popped first
+        pieces.push((mid, hi, f_mid, f_hi, depth + 1));
+        pieces.push((lo, mid, f_lo, f_mid, depth + 1));
+    }
+    None
+}
+
+/// [`first_crossing`] for a quadratic `f`, which three samples pin down, so its roots are solved
+/// for instead of bisected
+pub fn first_quadratic_crossing(f: impl Fn(f32) -> f32, accept: impl Fn(f32) -> bool, range: Range<f32>) -> Option<f32> {
+    let (lo, len) = (range.start, range.end - range.start);
+    let (f_lo, f_mid, f_hi) = (f(lo), f(lo + len / 2.), f(range.end));
+    // f(lo + s·len) = f_lo + b·s + c·s² for s in 0..1
+    let c = 2. * (f_hi - 2. * f_mid + f_lo);
+    let b = f_hi - f_lo - c;
+    let roots = if c.abs() <= f32::EPSILON * (f_lo.abs() + f_mid.abs() + f_hi.abs()) {
+        [-f_lo / b, f32::NAN]
+    } else {
+        let discriminant = b * b - 4. * c * f_lo;
+        if discriminant < 0. {
+            return None;
+        }
+        // The form that doesn't cancel, a zero `q` leaves NaN or infinite roots to be filtered
+        let q = -(b + b.signum() * discriminant.sqrt()) / 2.;
+        let (r1, r2) = (q / c, f_lo / q);
+        [r1.min(r2), r1.max(r2)]
+    };
+    // Only roots where `f` is falling are crossings
+    roots
+        .into_iter()
+        .filter(|&s| s > 0. && s <= 1. && b + 2. * c * s < 0.)
+        .map(|s| lo + s * len)
+        .find(|&t| accept(t))
 }
 
 // Helper function to find the minimum and maximum extent of a shape when projected onto an axis
-fn project(convex_hull: &Vec<Vec2>, axis: Vec2) -> (f32, f32) {
+fn project(convex_hull: &[Vec2], axis: Vec2) -> (f32, f32) {
     let mut min = axis.dot(convex_hull[0]);
     let mut max = min;
 
-    for i in 1..convex_hull.len() {
-        let projection = axis.dot(convex_hull[i]);
+    for point in &convex_hull[1..] {
+        let projection = axis.dot(*point);
         if projection < min {
             min = projection;
         }
@@ -133,3 +896,433 @@ fn test_convex_hull() {
         ]
     );
 }
+
+#[test]
+fn test_rot2() {
+    let quarter = Rot2::from_angle(PI / 2.);
+    assert!((quarter.apply(Vec2::X) - Vec2::Y).length() < 1e-6);
+    assert!((quarter.apply(vec2(1., 2.)) - vec2(-2., 1.)).length() < 1e-6);
+
+    let a = Rot2::from_angle(0.7);
+    let b = Rot2::from_angle(-2.1);
+    assert!((a.compose(b).angle() - (0.7 - 2.1)).abs() < 1e-6);
+    assert!((a.compose(a.inverse()).angle()).abs() < 1e-6);
+    assert!((a.inverse().apply(a.apply(vec2(3., -4.))) - vec2(3., -4.)).length() < 1e-6);
+
+    assert!((Rot2::from_angle(PI).angle().abs() - PI).abs() < 1e-6);
+    assert_eq!(Rot2::default().apply(vec2(3., -4.)), vec2(3., -4.));
+}
+
+#[test]
+fn test_wrap_angle() {
+    assert_eq!(wrap_angle(0.), 0.);
+    assert_eq!(wrap_angle(PI), PI);
+    assert!((wrap_angle(-PI) - PI).abs() < 1e-6);
+    assert!((wrap_angle(3. * PI / 2.) + PI / 2.).abs() < 1e-6);
+    assert!((wrap_angle(-3. * PI / 2.) - PI / 2.).abs() < 1e-6);
+
+    for i in -400..=400 {
+        let angle = i as f32 * 0.1;
+        let wrapped = wrap_angle(angle);
+        assert!(wrapped > -PI && wrapped <= PI, "{angle} wrapped to {wrapped}");
+        // Same direction as the input
+        assert!((Rot2::from_angle(angle).apply(Vec2::X) - Rot2::from_angle(wrapped).apply(Vec2::X)).length() < 1e-4);
+    }
+}
+
+#[test]
+fn test_angle_difference() {
+    assert!((angle_difference(0.1, 0.3) - 0.2).abs() < 1e-6);
+    assert!((angle_difference(0.3, 0.1) + 0.2).abs() < 1e-6);
+    // Across the ±π seam the short way round is taken
+    assert!((angle_difference(PI - 0.1, -PI + 0.1) - 0.2).abs() < 1e-5);
+    assert!((angle_difference(-PI + 0.1, PI - 0.1) + 0.2).abs() < 1e-5);
+    assert!((angle_difference(0., 2. * PI)).abs() < 1e-5);
+
+    for i in -60..=60 {
+        for j in -60..=60 {
+            let (from, to) = (i as f32 * 0.11, j as f32 * 0.13);
+            let diff = angle_difference(from, to);
+            assert!(diff > -PI && diff <= PI);
+            assert!(wrap_angle(from + diff - to).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn test_slerp() {
+    let from = Rot2::from_angle(PI - 0.2);
+    let to = Rot2::from_angle(-PI + 0.2);
+
+    assert!((from.slerp(to, 0.).angle() - from.angle()).abs() < 1e-5);
+    assert!(angle_difference(from.slerp(to, 1.).angle(), to.angle()).abs() < 1e-5);
+    // Halfway through the short arc is π, not 0
+    assert!((from.slerp(to, 0.5).angle().abs() - PI).abs() < 1e-5);
+    assert!((lerp_angle(0., PI / 2., 0.5) - PI / 4.).abs() < 1e-6);
+}
+
+#[test]
+fn test_aabb() {
+    let a = Aabb::from_points(&[vec2(0., 0.), vec2(2., 1.), vec2(1., -1.)]);
+    assert_eq!(a, Aabb::new(vec2(0., -1.), vec2(2., 1.)));
+
+    let b = Aabb::new(vec2(2., 1.), vec2(3., 3.));
+    let c = Aabb::new(vec2(2.5, -3.), vec2(3., -2.));
+    // Touching boxes overlap
+    assert!(a.overlaps(&b) && b.overlaps(&a));
+    assert!(!a.overlaps(&c) && !c.overlaps(&a));
+    assert!(a.expanded_by(1.).overlaps(&c));
+
+    assert_eq!(a.union(&c), Aabb::new(vec2(0., -3.), vec2(3., 1.)));
+    assert_eq!(Aabb::EMPTY.union(&a), a);
+    assert!(!Aabb::EMPTY.overlaps(&a));
+
+    assert!(a.contains(vec2(2., 0.)));
+    assert!(!a.contains(vec2(2.1, 0.)));
+}
+
+#[test]
+fn test_segment_intersection() {
+    let horizontal = Segment::new(vec2(0., 0.), vec2(2., 0.));
+    let vertical = Segment::new(vec2(1., -1.), vec2(1., 1.));
+    assert_eq!(horizontal.intersect_segment(&vertical), Some(vec2(1., 0.)));
+    assert_eq!(vertical.intersect_segment(&horizontal), Some(vec2(1., 0.)));
+
+    let short = Segment::new(vec2(1., 0.5), vec2(1., 1.));
+    assert_eq!(horizontal.intersect_segment(&short), None);
+
+    let parallel = Segment::new(vec2(0., 1.), vec2(2., 1.));
+    assert_eq!(horizontal.intersect_segment(&parallel), None);
+}
+
+#[test]
+fn test_ray_intersection() {
+    let wall = Segment::new(vec2(2., -1.), vec2(2., 1.));
+    assert_eq!(Ray::new(vec2(0., 0.), vec2(1., 0.)).intersect_segment(&wall), Some(2.));
+    // t is in units of the direction
+    assert_eq!(Ray::new(vec2(0., 0.), vec2(4., 0.)).intersect_segment(&wall), Some(0.5));
+    assert_eq!(Ray::new(vec2(0., 0.), vec2(-1., 0.)).intersect_segment(&wall), None);
+    assert_eq!(Ray::new(vec2(0., 0.), vec2(0., 1.)).intersect_segment(&wall), None);
+
+    let square = vec![vec2(1., -1.), vec2(3., -1.), vec2(3., 1.), vec2(1., 1.)];
+    assert_eq!(Ray::new(vec2(0., 0.), vec2(1., 0.)).intersect_polygon(&square), Some((1., 3)));
+    assert_eq!(Ray::new(vec2(2., 0.), vec2(0., 1.)).intersect_polygon(&square), Some((1., 2)));
+    assert_eq!(Ray::new(vec2(0., 0.), vec2(0., 1.)).intersect_polygon(&square), None);
+}
+
+#[test]
+fn test_closest_point_on_segment() {
+    let segment = Segment::new(vec2(0., 0.), vec2(2., 0.));
+    assert_eq!(segment.closest_point(vec2(1., 3.)), vec2(1., 0.));
+    assert_eq!(segment.closest_point(vec2(-1., 1.)), vec2(0., 0.));
+    assert_eq!(segment.closest_point(vec2(5., -1.)), vec2(2., 0.));
+    let point = Segment::new(vec2(1., 1.), vec2(1., 1.));
+    assert_eq!(point.closest_point(vec2(5., -1.)), vec2(1., 1.));
+}
+
+#[test]
+fn test_closest_point_properties() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(952);
+    let mut random_point = || vec2(rng.gen_range(-10. ..10.), rng.gen_range(-10. ..10.));
+
+    for _ in 0..500 {
+        let segment = Segment::new(random_point(), random_point());
+        let other = Segment::new(random_point(), random_point());
+        let point = random_point();
+
+        // No sampled point of the segment is closer than the reported one
+        let closest = segment.closest_point(point);
+        for k in 0..=20 {
+            let sample = segment.at(k as f32 / 20.);
+            assert!(closest.distance(point) <= sample.distance(point) + 1e-4);
+        }
+        // The result lies on the segment
+        assert!((segment.closest_point(closest) - closest).length() < 1e-4);
+
+        let (on_segment, on_other) = segment.closest_points(&other);
+        let distance = on_segment.distance(on_other);
+        for k in 0..=20 {
+            for l in 0..=20 {
+                let sample_distance = segment.at(k as f32 / 20.).distance(other.at(l as f32 / 20.));
+                assert!(distance <= sample_distance + 1e-4);
+            }
+        }
+        // Symmetric up to the order of the pair
+        let (on_other_rev, on_segment_rev) = other.closest_points(&segment);
+        assert!((on_other_rev.distance(on_segment_rev) - distance).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_closest_point_on_polygon() {
+    let square = vec![vec2(0., 0.), vec2(2., 0.), vec2(2., 2.), vec2(0., 2.)];
+    assert_eq!(closest_point_on_polygon(&square, vec2(1., -3.)), Some((vec2(1., 0.), 0)));
+    assert_eq!(closest_point_on_polygon(&square, vec2(3., 1.)), Some((vec2(2., 1.), 1)));
+    // Inside points snap to the nearest edge
+    assert_eq!(closest_point_on_polygon(&square, vec2(1., 1.8)), Some((vec2(1., 2.), 2)));
+    assert_eq!(closest_point_on_polygon(&[], vec2(1., 1.)), None);
+}
+
+#[test]
+fn test_polygon_contains_point() {
+    let square = vec![vec2(0., 0.), vec2(2., 0.), vec2(2., 2.), vec2(0., 2.)];
+    assert!(polygon_contains_point(&square, vec2(1., 1.)));
+    assert!(!polygon_contains_point(&square, vec2(3., 1.)));
+    assert!(!polygon_contains_point(&square, vec2(1., -0.5)));
+
+    let clockwise = square.iter().rev().copied().collect::<Vec<_>>();
+    assert!(polygon_contains_point(&clockwise, vec2(1., 1.)));
+
+    // U shape, the notch is outside
+    let u_shape = vec![
+        vec2(0., 0.),
+        vec2(3., 0.),
+        vec2(3., 3.),
+        vec2(2., 3.),
+        vec2(2., 1.),
+        vec2(1., 1.),
+        vec2(1., 3.),
+        vec2(0., 3.),
+    ];
+    assert!(polygon_contains_point(&u_shape, vec2(0.5, 2.)));
+    assert!(polygon_contains_point(&u_shape, vec2(2.5, 2.)));
+    assert!(!polygon_contains_point(&u_shape, vec2(1.5, 2.)));
+}
+
+#[test]
+fn test_polygon_contains_polygon() {
+    let outer = vec![vec2(0., 0.), vec2(4., 0.), vec2(4., 4.), vec2(0., 4.)];
+    let inner = vec![vec2(1., 1.), vec2(2., 1.), vec2(2., 2.)];
+    let crossing = vec![vec2(3., 3.), vec2(5., 3.), vec2(5., 5.)];
+    assert!(polygon_contains_polygon(&outer, &inner));
+    assert!(!polygon_contains_polygon(&inner, &outer));
+    assert!(!polygon_contains_polygon(&outer, &crossing));
+
+    // Both ends inside the U's arms but the edge between them crosses the notch
+    let u_shape = vec![
+        vec2(0., 0.),
+        vec2(3., 0.),
+        vec2(3., 3.),
+        vec2(2., 3.),
+        vec2(2., 1.),
+        vec2(1., 1.),
+        vec2(1., 3.),
+        vec2(0., 3.),
+    ];
+    let bridge = vec![vec2(0.5, 2.), vec2(2.5, 2.), vec2(2.5, 2.5)];
+    assert!(!polygon_contains_polygon(&u_shape, &bridge));
+}
+
+#[test]
+fn test_polygon_properties() {
+    let rectangle = vec![vec2(1., 1.), vec2(4., 1.), vec2(4., 3.), vec2(1., 3.)];
+    assert_eq!(polygon_signed_area(&rectangle), 6.);
+    let clockwise = rectangle.iter().rev().copied().collect::<Vec<_>>();
+    assert_eq!(polygon_signed_area(&clockwise), -6.);
+    assert_eq!(polygon_area(&clockwise), 6.);
+
+    assert_eq!(polygon_perimeter(&rectangle), 10.);
+    assert!((polygon_centroid(&rectangle) - vec2(2.5, 2.)).length() < 1e-5);
+    assert!((polygon_centroid(&clockwise) - vec2(2.5, 2.)).length() < 1e-5);
+
+    // Rectangle about its center: A * (w² + h²) / 12
+    assert!((polygon_second_moment(&rectangle) - 6. * (9. + 4.) / 12.).abs() < 1e-4);
+    assert!((polygon_second_moment(&clockwise) - 6. * (9. + 4.) / 12.).abs() < 1e-4);
+    // Parallel axis theorem for the inertia about the origin
+    let about_origin = 2. * (13. / 12. + vec2(2.5, 2.).length_squared());
+    assert!((polygon_moment_of_inertia(&rectangle, 2.) - about_origin).abs() < 1e-4);
+
+    // Right triangle with legs 3 and 6, centroid at a third of the legs
+    let triangle = vec![vec2(0., 0.), vec2(3., 0.), vec2(0., 6.)];
+    assert_eq!(polygon_area(&triangle), 9.);
+    assert!((polygon_centroid(&triangle) - vec2(1., 2.)).length() < 1e-5);
+    assert!((polygon_second_moment(&triangle) - 9. * (9. + 36.) / 18.).abs() < 1e-4);
+
+    let segment = vec![vec2(0., 0.), vec2(1., 0.)];
+    assert_eq!(polygon_moment_of_inertia(&segment, 1.), f32::INFINITY);
+}
+
+#[test]
+fn test_min_enclosing_circle() {
+    let square = vec![vec2(0., 0.), vec2(2., 0.), vec2(2., 2.), vec2(0., 2.)];
+    let circle = min_enclosing_circle(&square);
+    assert!((circle.center - vec2(1., 1.)).length() < 1e-5);
+    assert!((circle.radius - 2f32.sqrt()).abs() < 1e-5);
+
+    // Obtuse triangle, the circle is spanned by the longest side
+    let obtuse = vec![vec2(-2., 0.), vec2(2., 0.), vec2(0., 0.5)];
+    let circle = min_enclosing_circle(&obtuse);
+    assert!((circle.center - Vec2::ZERO).length() < 1e-5);
+    assert!((circle.radius - 2.).abs() < 1e-5);
+
+    let collinear = vec![vec2(0., 0.), vec2(1., 1.), vec2(3., 3.)];
+    let circle = min_enclosing_circle(&collinear);
+    assert!((circle.center - vec2(1.5, 1.5)).length() < 1e-5);
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(955);
+    for _ in 0..200 {
+        let points = (0..rng.gen_range(1..12))
+            .map(|_| vec2(rng.gen_range(-5. ..5.), rng.gen_range(-5. ..5.)))
+            .collect::<Vec<_>>();
+        let circle = min_enclosing_circle(&points);
+        assert!(points.iter().all(|p| circle.contains(*p)));
+        // Never bigger than the circle around the bounding box
+        let aabb = Aabb::from_points(&points);
+        assert!(circle.radius <= aabb.size().length() / 2. + 1e-4);
+    }
+
+    assert_eq!(min_enclosing_circle(&[]).radius, 0.);
+}
+
+#[test]
+fn test_rotating_calipers() {
+    let rotation = Rot2::from_angle(0.3);
+    let rectangle = [vec2(-2., -1.), vec2(2., -1.), vec2(2., 1.), vec2(-2., 1.)]
+        .map(|p| rotation.apply(p) + vec2(5., 3.))
+        .to_vec();
+
+    assert!((polygon_diameter(&rectangle) - 20f32.sqrt()).abs() < 1e-4);
+    assert!((min_width(&rectangle) - 2.).abs() < 1e-4);
+
+    let obb = min_area_obb(&rectangle);
+    assert!((obb.area() - 8.).abs() < 1e-3);
+    assert!((obb.center - vec2(5., 3.)).length() < 1e-4);
+    for corner in obb.corners() {
+        assert!(rectangle.iter().any(|p| p.distance(corner) < 1e-4));
+    }
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(956);
+    for _ in 0..100 {
+        let points = (0..rng.gen_range(3..20))
+            .map(|_| vec2(rng.gen_range(-5. ..5.), rng.gen_range(-5. ..5.)))
+            .collect::<Vec<_>>();
+        let hull = convex_hull(points);
+        if hull.len() < 3 {
+            continue;
+        }
+
+        let brute_diameter = hull
+            .iter()
+            .flat_map(|a| hull.iter().map(move |b| a.distance(*b)))
+            .fold(0., f32::max);
+        assert!((polygon_diameter(&hull) - brute_diameter).abs() < 1e-4);
+
+        let brute_width = (0..hull.len())
+            .map(|i| {
+                let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                hull.iter().map(|p| (b - a).perp_dot(*p - a) / (b - a).length()).fold(0., f32::max)
+            })
+            .fold(f32::INFINITY, f32::min);
+        assert!((min_width(&hull) - brute_width).abs() < 1e-4);
+
+        // Contains every vertex and isn't bigger than the axis aligned box
+        let obb = min_area_obb(&hull);
+        let corners = obb.corners();
+        assert!(hull.iter().all(|p| {
+            polygon_contains_point(&corners, *p) || closest_point_on_polygon(&corners, *p).unwrap().0.distance(*p) < 1e-3
+        }));
+        let aabb_size = Aabb::from_points(&hull).size();
+        assert!(obb.area() <= aabb_size.x * aabb_size.y + 1e-3);
+    }
+}
+
+#[test]
+fn test_polygon_validate() {
+    assert_eq!(Polygon::regular(5).validate(), Ok(()));
+    assert_eq!(Polygon::regular(2).validate(), Err(CollisionError::DegenerateShape));
+    // Collinear vertices have no area
+    let line = Polygon::new(vec![vec2(0., 0.), vec2(1., 0.), vec2(2., 0.)]);
+    assert_eq!(line.validate(), Err(CollisionError::DegenerateShape));
+    let mut clockwise = Polygon::regular(4);
+    clockwise.points.reverse();
+    assert_eq!(clockwise.validate(), Err(CollisionError::DegenerateShape));
+    let mut nan = Polygon::regular(4);
+    nan.points[1].x = f32::NAN;
+    assert_eq!(nan.validate(), Err(CollisionError::DegenerateShape));
+
+    let arrow = Polygon::new(vec![vec2(0., 0.), vec2(2., 1.), vec2(0., 2.), vec2(1., 1.)]);
+    assert_eq!(arrow.validate(), Err(CollisionError::NonConvex));
+}
+
+#[test]
+fn test_simplify_convex_outer() {
+    let octagon = Polygon::regular(8).points;
+    let square = simplify_convex_outer(&octagon, 4);
+    assert_eq!(square.len(), 4);
+    assert_eq!(Polygon::new(square.clone()).validate(), Ok(()));
+    // Every original vertex stays inside or on the boundary
+    assert!(octagon.iter().all(|p| {
+        polygon_contains_point(&square, *p) || closest_point_on_polygon(&square, *p).unwrap().0.distance(*p) < 1e-5
+    }));
+    assert!(polygon_area(&square) >= polygon_area(&octagon));
+
+    let triangle = Polygon::regular(3).points;
+    assert_eq!(simplify_convex_outer(&triangle, 2), triangle);
+}
+
+#[test]
+fn test_first_crossing() {
+    // Linear, the crossing is exact
+    let t = first_crossing(|t| 1. - 2. * t, |_| true, 0. ..1., 0.).unwrap();
+    assert!((t - 0.5).abs() < 1e-6);
+    // Positive at both ends but dipping below zero in between
+    let dip = |t: f32| (t - 0.5) * (t - 0.5) - 0.01;
+    let t = first_crossing(dip, |_| true, 0. ..1., 2.).unwrap();
+    assert!((t - 0.4).abs() < 1e-3);
+    // The first crossing is rejected, the next entry after rising again is found
+    let wave = |t: f32| (t * 10.).cos();
+    let t = first_crossing(wave, |t| t > 1., 0. ..2., 100.).unwrap();
+    assert!((t - 9. * PI / 20.).abs() < 1e-3);
+    assert_eq!(first_crossing(|t| 1. + t, |_| true, 0. ..1., 0.), None);
+    // Starting below zero isn't a crossing
+    assert_eq!(first_crossing(|t| -1. - t, |_| true, 0. ..1., 0.), None);
+}
+
+#[test]
+fn test_first_quadratic_crossing() {
+    let dip = |t: f32| (t - 0.5) * (t - 0.5) - 0.01;
+    let t = first_quadratic_crossing(dip, |_| true, 0. ..1.).unwrap();
+    assert!((t - 0.4).abs() < 1e-5);
+    // Rising out of the negative first, only the way back down counts
+    let arc = |t: f32| 0.1 - (t - 0.5) * (t - 0.5);
+    let t = first_quadratic_crossing(arc, |_| true, 0. ..1.).unwrap();
+    assert!((t - (0.5 + 0.1f32.sqrt())).abs() < 1e-5);
+    assert_eq!(first_quadratic_crossing(dip, |t| t > 0.5, 0. ..1.), None);
+    let t = first_quadratic_crossing(|t| 1. - 2. * t, |_| true, 0. ..1.).unwrap();
+    assert!((t - 0.5).abs() < 1e-6);
+    assert_eq!(first_quadratic_crossing(|t| 1. + t * t, |_| true, 0. ..1.), None);
+}
+
+#[test]
+fn test_sat_penetration() {
+    let square = [vec2(0., 0.), vec2(2., 0.), vec2(2., 2.), vec2(0., 2.)];
+    let shifted = square.map(|p| p + vec2(1.5, 0.2));
+    let (axis, depth) = sat_penetration(&shifted, &square).unwrap();
+    assert!(axis.abs_diff_eq(vec2(1., 0.), 1e-6));
+    assert!((depth - 0.5).abs() < 1e-6);
+    // Swapped, the axis still points towards the first hull
+    let (axis, _) = sat_penetration(&square, &shifted).unwrap();
+    assert!(axis.abs_diff_eq(vec2(-1., 0.), 1e-6));
+
+    let apart = square.map(|p| p + vec2(3., 0.));
+    assert_eq!(sat_penetration(&apart, &square), None);
+}
+
+#[test]
+fn test_convex_closest_points() {
+    let square = [vec2(0., 0.), vec2(1., 0.), vec2(1., 1.), vec2(0., 1.)];
+    let point = [vec2(3., 0.5)];
+    assert_eq!(convex_closest_points(&point, &square), Some((vec2(3., 0.5), vec2(1., 0.5))));
+    // A segment crossing the square and a point inside it overlap
+    let segment = [vec2(-1., 0.5), vec2(2., 0.5)];
+    assert_eq!(convex_closest_points(&segment, &square), None);
+    assert_eq!(convex_closest_points(&[vec2(0.5, 0.5)], &square), None);
+
+    assert!(rounded_overlap(&point, 2.5, &square, 0.));
+    assert!(!rounded_overlap(&point, 1.5, &square, 0.25));
+    assert!(rounded_overlap(&point, 1.5, &[vec2(4., 0.5)], 0.));
+}
@@ -0,0 +1,174 @@
+//! Mutual (n-body) gravitation, radial gravity fields and trajectory prediction.
+//!
+//! With [`World::gravitational_constant`] set, every pair of dynamic objects attracts each other.
+//! The attraction changes the velocities before the positions move with them, which keeps orbits
+//! from spiraling outwards, [`Integrator::Leapfrog`](integrator::Integrator::Leapfrog) splits it
+//! around the motion for tighter energy bounds.
+//!
+//! [`World::gravity_sources`] pull dynamic objects towards fixed centers instead, e.g. planets
+//! they can stand on. Like the uniform [`World::gravity`] the pull is sampled at the start of
+//! every step and held for its length.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// Added to squared distances so close passes don't fling objects away
+const SOFTENING: f32 = 0.1;
+
+/// Fixed center pulling dynamic objects towards it with the gravity of a uniform ball of `radius`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct GravitySource {
+    pub center: Vec2,
+    /// Gravitational parameter, the pull at distance `r` outside the ball is `strength / r²`
+    pub strength: f32,
+    /// Inside it the pull falls off linearly to zero at the center
+    pub radius: f32,
+}
+
+impl GravitySource {
+    /// Source whose pull at `radius`, e.g. a planet's surface, is `surface_gravity`
+    pub fn planet(center: Vec2, radius: f32, surface_gravity: f32) -> Self {
+        Self {
+            center,
+            strength: surface_gravity * radius * radius,
+            radius,
+        }
+    }
+    /// Acceleration of an object at `point`
+    pub fn acceleration_at(&self, point: Vec2) -> Vec2 {
+        let offset = point - self.center;
+        let distance = offset.length().max(self.radius);
+        -offset * (self.strength / (distance * distance * distance))
+    }
+    /// Potential energy per unit of mass at `point`, zero far away
+    pub fn potential_at(&self, point: Vec2) -> f32 {
+        let distance = point.distance(self.center);
+        if distance >= self.radius {
+            -self.strength / distance
+        } else {
+            -self.strength * (3. * self.radius * self.radius - distance * distance) / (2. * self.radius * self.radius * self.radius)
+        }
+    }
+}
+
+/// Changes the velocities by the mutual attraction over `dt`, objects with infinite mass, paused
+/// or on frozen layers neither attract nor get attracted
+pub(crate) fn mutual_gravity_kick(objects: &mut [Object], constant: f32, dt: f32) {
+    let attracting = |object: &Object| object.mass.is_finite() && !object.frozen && !object.is_paused();
+    for i in 0..objects.len() {
+        let (head, tail) = objects.split_at_mut(i + 1);
+        let a = &mut head[i];
+        if !attracting(a) {
+            continue;
+        }
+        for b in tail.iter_mut().filter(|b| attracting(b)) {
+            let offset = b.position - a.position;
+            let distance_squared = offset.length_squared() + SOFTENING * SOFTENING;
+            // Acceleration per unit of the attracting mass
+            let pull = offset * (constant * dt / (distance_squared * distance_squared.sqrt()));
+            a.velocity += pull * b.mass;
+            b.velocity -= pull * a.mass;
+        }
+    }
+}
+
+/// Gravitational potential energy of every pair of dynamic objects, with the same softening as
+/// the attraction
+pub fn potential_energy(objects: &[Object], constant: f32) -> f32 {
+    let dynamic = objects.iter().filter(|object| object.mass.is_finite()).collect::<Vec<_>>();
+    let mut energy = 0.;
+    for (i, a) in dynamic.iter().enumerate() {
+        for b in &dynamic[i + 1..] {
+            let distance = (a.position.distance_squared(b.position) + SOFTENING * SOFTENING).sqrt();
+            energy -= constant * a.mass * b.mass / distance;
+        }
+    }
+    energy
+}
+
+/// Positions object `index` passes through over the next `steps` steps of `dt`, simulated on a copy
+/// of the world including its collisions
+pub fn predict_path(world: &World, index: usize, dt: f32, steps: usize) -> Vec<Vec2> {
+    let mut future = World {
+        collision_margin: world.collision_margin,
+        simplified: world.simplified,
+        batch_tolerance: world.batch_tolerance,
+        gravitational_constant: world.gravitational_constant,
+        integrator: world.integrator,
+        joint_iterations: world.joint_iterations,
+        // Removals would shift the index
+        collision_limit: None,
+        ..World::new()
+    };
+    future.load(world.objects.clone(), world.time_elapsed);
+    future.joints = world.joints.clone();
+    future.gravity_sources = world.gravity_sources.clone();
+    let mut path = vec![];
+    for _ in 0..steps {
+        if future.step(dt).is_err() {
+            break;
+        }
+        path.push(future.objects[index].position);
+    }
+    path
+}
+
+#[test]
+fn test_two_body_orbit() {
+    const CONSTANT: f32 = 0.1;
+    const SUN_MASS: f32 = 1000.;
+    const RADIUS: f32 = 10.;
+    const DT: f32 = 1. / 60.;
+
+    let speed = (CONSTANT * SUN_MASS / RADIUS).sqrt();
+    let mut sun = Object::new(Vec2::ZERO, Vec2::ZERO, 0., Polygon::regular(8));
+    sun.mass = SUN_MASS;
+    let planet = Object::new(vec2(RADIUS, 0.), vec2(0., speed), 0., Polygon::regular(8));
+    let mut world = World {
+        gravitational_constant: Some(CONSTANT),
+        ..World::new()
+    };
+    world.load(vec![sun, planet], 0.);
+
+    let period = 2. * PI * RADIUS / speed;
+    let steps = (period / DT) as usize;
+    let predicted = predict_path(&world, 1, DT, steps);
+    for (step, expected) in predicted.iter().enumerate() {
+        world.step(DT).unwrap();
+        assert_eq!(world.objects[1].position, *expected, "prediction differs in step {step}");
+        let distance = world.objects[1].position.distance(world.objects[0].position);
+        assert!((distance - RADIUS).abs() < RADIUS * 0.05, "distance {distance} in step {step}");
+    }
+    // Back around to the start after a period
+    assert!(world.objects[1].position.distance(vec2(RADIUS, 0.)) < 1.);
+}
+
+#[test]
+fn test_gravity_source() {
+    let source = GravitySource::planet(vec2(1., -2.), 2., 5.);
+    // The pull is continuous at the surface and the slope of the potential on both sides of it
+    for distance in [0.5, 2., 3.] {
+        let point = source.center + vec2(0.6, 0.8) * distance;
+        let h = 1e-2;
+        let slope = (source.potential_at(point + vec2(h, 0.)) - source.potential_at(point - vec2(h, 0.))) / (2. * h);
+        assert!((source.acceleration_at(point).x + slope).abs() < 1e-2, "{distance}");
+    }
+    let surface = source.center + vec2(0., 2.);
+    assert!(source.acceleration_at(surface).abs_diff_eq(vec2(0., -5.), 1e-5));
+    assert!(source.acceleration_at(source.center).length() < 1e-6);
+
+    // A leapfrog orbit around it keeps its energy
+    let speed = (source.strength / 6.).sqrt();
+    let mut world = World {
+        integrator: integrator::Integrator::Leapfrog,
+        ..World::new()
+    };
+    world.load(vec![Object::new(source.center + vec2(6., 0.), vec2(0., speed), 0., Polygon::regular(8))], 0.);
+    world.gravity_sources.push(source);
+    let start = world.mechanical_energy();
+    for _ in 0..600 {
+        world.step(1. / 60.).unwrap();
+        assert!((world.mechanical_energy() - start).abs() < start.abs() * 1e-3);
+    }
+}
@@ -0,0 +1,99 @@
+//! Collision heat map overlay.
+//!
+//! Contact points of the last [`HeatMap::window`] seconds are binned into a coarse grid, cells are
+//! drawn with an opacity proportional to their share of the hottest cell.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::*;
+
+const DEFAULT_CELL_SIZE: f32 = 0.5;
+const DEFAULT_WINDOW: f32 = 5.;
+/// Opacity of the hottest cell
+const MAX_ALPHA: f32 = 0.6;
+
+type Cell = (i32, i32);
+
+pub struct HeatMap {
+    /// Side of a grid cell in world units
+    pub cell_size: f32,
+    /// Seconds of simulation time a collision stays counted
+    pub window: f32,
+    /// Contact points with the simulation time they happened at, oldest first
+    events: VecDeque<(f32, Vec2)>,
+    counts: HashMap<Cell, u32>,
+}
+
+impl HeatMap {
+    pub fn new() -> Self {
+        Self {
+            cell_size: DEFAULT_CELL_SIZE,
+            window: DEFAULT_WINDOW,
+            events: VecDeque::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.counts.clear();
+    }
+
+    /// Adds the collisions resolved in the last step and forgets the ones older than the window
+    pub fn record(&mut self, resolved: &[Toi], time_elapsed: f32) {
+        if self.events.back().is_some_and(|(time, _)| *time > time_elapsed) {
+            // A scene was loaded, the old times don't mean anything anymore
+            self.clear();
+        }
+        for toi in resolved {
+            *self.counts.entry(self.cell_of(toi.point)).or_default() += 1;
+            self.events.push_back((toi.time, toi.point));
+        }
+        while let Some(&(time, point)) = self.events.front() {
+            if time >= time_elapsed - self.window {
+                break;
+            }
+            self.events.pop_front();
+            let cell = self.cell_of(point);
+            if let Some(count) = self.counts.get_mut(&cell) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&cell);
+                }
+            }
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        memory::deque_bytes(&self.events) + self.counts.capacity() * std::mem::size_of::<(Cell, u32)>()
+    }
+
+    /// Bins the counted collisions again, needed after `cell_size` changes
+    pub fn rebin(&mut self) {
+        self.counts.clear();
+        for &(_, point) in &self.events {
+            *self.counts.entry(self.cell_of(point)).or_default() += 1;
+        }
+    }
+
+    pub fn draw(&self, queue: &mut DrawQueue, camera: &Camera) {
+        let Some(&hottest) = self.counts.values().max() else {
+            return;
+        };
+        for (&(x, y), &count) in &self.counts {
+            let heat = count as f32 / hottest as f32;
+            // Yellow for the few, red for the hottest
+            let color = Color::from_rgb(1., 1. - heat, 0.).set_alpha(heat * MAX_ALPHA);
+            let square = Shape::from_square()
+                .set_color(color)
+                .apply(GTransform::from_translation(vec2(x as f32, y as f32) * self.cell_size).inflate(self.cell_size))
+                .apply(camera.0);
+            queue.add(Depth::HeatMap, square);
+        }
+    }
+
+    fn cell_of(&self, point: Vec2) -> Cell {
+        let cell = (point / self.cell_size).floor();
+        (cell.x as i32, cell.y as i32)
+    }
+}
@@ -0,0 +1,155 @@
+//! Batch spawning from tables of initial conditions, e.g. lattices generated by external scripts.
+//!
+//! Every row is one object: `x`, `y` are required, `vx`, `vy`, `rotation`, `rot_velocity`, `mass`,
+//! `sides`, `radius`, `restitution` and `name` are optional. The shape is a regular polygon with
+//! `sides` vertices (4 by default) on a circle of `radius` (1 by default), bounces are elastic
+//! unless `restitution` is below 1.
+//!
+//! CSV needs a header row naming the columns, JSON is an array of objects with those keys. JSON is
+//! read with the RON parser, which accepts it as long as there are no `null`s.
+
+use std::{fs, io, path::Path};
+
+use serde::Deserialize;
+
+use super::*;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct SpawnRow {
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub vx: f32,
+    #[serde(default)]
+    pub vy: f32,
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default)]
+    pub rot_velocity: f32,
+    #[serde(default = "default_mass")]
+    pub mass: f32,
+    #[serde(default = "default_sides")]
+    pub sides: usize,
+    #[serde(default = "default_radius")]
+    pub radius: f32,
+    #[serde(default = "default_restitution")]
+    pub restitution: f32,
+    /// Empty for unnamed objects
+    #[serde(default)]
+    pub name: String,
+}
+
+fn default_mass() -> f32 {
+    1.
+}
+fn default_sides() -> usize {
+    4
+}
+fn default_radius() -> f32 {
+    1.
+}
+fn default_restitution() -> f32 {
+    1.
+}
+
+impl SpawnRow {
+    pub fn to_object(&self) -> Result<Object, CollisionError> {
+        let mut shape = Polygon::regular(self.sides);
+        shape.points.iter_mut().for_each(|p| *p *= self.radius);
+        let mut object = Object::try_new(vec2(self.x, self.y), vec2(self.vx, self.vy), self.rotation, shape)?;
+        object.mass = self.mass;
+        object.inertia = polygon_moment_of_inertia(&object.shape.points, self.mass);
+        object.rot_velocity = self.rot_velocity;
+        object.restitution = self.restitution;
+        object.name = (!self.name.is_empty()).then(|| self.name.clone());
+        Ok(object)
+    }
+}
+
+/// Objects of a `.csv` or `.json` table, picked by the extension
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Object>> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let rows = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => parse_csv(&text)?,
+        Some("json") => parse_json(&text)?,
+        _ => return Err(invalid_data("expected a .csv or .json file")),
+    };
+    to_objects(&rows)
+}
+
+pub fn to_objects(rows: &[SpawnRow]) -> io::Result<Vec<Object>> {
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| row.to_object().map_err(|e| invalid_data(format!("row {}: {e}", i + 1))))
+        .collect()
+}
+
+pub fn parse_json(text: &str) -> io::Result<Vec<SpawnRow>> {
+    let value = ron::from_str::<ron::Value>(text).map_err(invalid_data)?;
+    value.into_rust().map_err(invalid_data)
+}
+
+pub fn parse_csv(text: &str) -> io::Result<Vec<SpawnRow>> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines.next().ok_or_else(|| invalid_data("missing header row"))?;
+    let columns = header.split(',').map(str::trim).collect::<Vec<_>>();
+
+    lines
+        .map(|(line_index, line)| {
+            let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+            if fields.len() != columns.len() {
+                return Err(invalid_data(format!(
+                    "line {}: expected {} fields, found {}",
+                    line_index + 1,
+                    columns.len(),
+                    fields.len()
+                )));
+            }
+            // Reuse the serde defaults by going through a map of the present fields
+            let map = columns
+                .iter()
+                .zip(&fields)
+                .filter(|(_, field)| !field.is_empty())
+                .map(|(column, field)| {
+                    let value = match *column {
+                        "name" => ron::Value::String(field.to_string()),
+                        _ => ron::from_str::<ron::Value>(field)
+                            .map_err(|e| invalid_data(format!("line {}: {column}: {e}", line_index + 1)))?,
+                    };
+                    Ok((ron::Value::String(column.to_string()), value))
+                })
+                .collect::<io::Result<ron::Map>>()?;
+            ron::Value::Map(map)
+                .into_rust()
+                .map_err(|e| invalid_data(format!("line {}: {e}", line_index + 1)))
+        })
+        .collect()
+}
+
+fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+#[test]
+fn test_parse_tables() {
+    let csv = "x, y, vx, mass, sides, name\n0, 1, 2.5, 3, 3, ball_A\n\n4, 5, , , ,\n";
+    let rows = parse_csv(csv).unwrap();
+    let json = r#"[{"x": 0, "y": 1, "vx": 2.5, "mass": 3, "sides": 3, "name": "ball_A"}, {"x": 4, "y": 5}]"#;
+    assert_eq!(parse_json(json).unwrap(), rows);
+
+    assert_eq!((rows[1].mass, rows[1].sides, rows[1].radius, rows[1].restitution), (1., 4, 1., 1.));
+
+    let objects = to_objects(&rows).unwrap();
+    assert_eq!(objects[0].shape.points.len(), 3);
+    assert_eq!(objects[0].velocity, vec2(2.5, 0.));
+    assert_eq!(objects[0].mass, 3.);
+    assert_eq!(objects[0].name.as_deref(), Some("ball_A"));
+    assert_eq!(objects[1].name, None);
+
+    assert!(parse_csv("x, y\n1\n").is_err());
+    assert!(to_objects(&parse_csv("x, y, sides\n0, 0, 2\n").unwrap()).is_err());
+}
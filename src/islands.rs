@@ -0,0 +1,235 @@
+//! Per-island substepping.
+//!
+//! Objects whose swept bounding circles stay apart during a step can't interact, the groups that
+//! can (islands) are stepped as separate worlds. Islands moving fast relative to their size or
+//! crowded with candidate pairs take several shorter substeps, calm ones a single step, and all of
+//! them end the step at the same time.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use broad_phase::BroadPhaseStats;
+use conservation::Totals;
+use world::RECENT_COLLISIONS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SubstepPolicy {
+    /// Distance an object may move relative to the rest of its island per substep, in its
+    /// bounding radii
+    pub max_travel: f32,
+    /// Candidate pairs an island may have per substep
+    pub pairs_per_substep: usize,
+    pub max_substeps: usize,
+}
+
+impl Default for SubstepPolicy {
+    fn default() -> Self {
+        Self {
+            max_travel: 0.5,
+            pairs_per_substep: 32,
+            max_substeps: 8,
+        }
+    }
+}
+
+/// An island's objects and the number of candidate pairs among them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Island {
+    pub objects: Vec<usize>,
+    pub pairs: usize,
+}
+
+/// Groups the objects whose swept bounding circles over `dt` touch, directly or through others
+pub fn find_islands(objects: &[Object], dt: f32, margin: f32) -> Vec<Island> {
+    let boxes = objects
+        .iter()
+        .map(|object| {
+            let start = object.to_world(object.bounding_circle.center);
+            let end = start + object.velocity * dt;
+            // Room for the acceleration bending the path
+            let reach = object.bounding_circle.radius + margin + object.total_acceleration().length() * dt * dt / 2.;
+            Aabb::new(start.min(end), start.max(end)).expanded_by(reach)
+        })
+        .collect::<Vec<_>>();
+    let mut order = (0..objects.len()).collect::<Vec<_>>();
+    order.sort_unstable_by(|&a, &b| boxes[a].min.x.total_cmp(&boxes[b].min.x));
+
+    let mut parents = (0..objects.len()).collect::<Vec<_>>();
+    let mut pairs = vec![];
+    let mut active: Vec<usize> = vec![];
+    for &i in &order {
+        active.retain(|&j| boxes[j].max.x >= boxes[i].min.x);
+        for &j in &active {
+            if boxes[i].overlaps(&boxes[j]) {
+                pairs.push((i, j));
+                let (root_i, root_j) = (root(&mut parents, i), root(&mut parents, j));
+                parents[root_i] = root_j;
+            }
+        }
+        active.push(i);
+    }
+
+    let mut island_of = vec![usize::MAX; objects.len()];
+    let mut islands: Vec<Island> = vec![];
+    for i in 0..objects.len() {
+        let root = root(&mut parents, i);
+        if island_of[root] == usize::MAX {
+            island_of[root] = islands.len();
+            islands.push(Island { objects: vec![], pairs: 0 });
+        }
+        islands[island_of[root]].objects.push(i);
+    }
+    for (i, _) in pairs {
+        let root = root(&mut parents, i);
+        islands[island_of[root]].pairs += 1;
+    }
+    islands
+}
+
+fn root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Number of substeps `island` takes over `dt`
+pub fn substeps(island: &Island, objects: &[Object], dt: f32, policy: &SubstepPolicy) -> usize {
+    let mean_velocity =
+        island.objects.iter().map(|&i| objects[i].velocity).sum::<Vec2>() / island.objects.len() as f32;
+    let travel = island
+        .objects
+        .iter()
+        .map(|&i| {
+            let object = &objects[i];
+            let radius = object.bounding_circle.radius.max(f32::EPSILON);
+            let speed = (object.velocity - mean_velocity).length() + object.rot_velocity.abs() * radius;
+            speed * dt / radius
+        })
+        .fold(0., f32::max);
+    let for_travel = (travel / policy.max_travel).ceil() as usize;
+    let for_pairs = island.pairs.div_ceil(policy.pairs_per_substep.max(1));
+    for_travel.max(for_pairs).clamp(1, policy.max_substeps.max(1))
+}
+
+/// [`World::step`] with every island stepped on its own, see the module docs
+pub(crate) fn step_islands(world: &mut World, dt: f32, policy: &SubstepPolicy) -> Result<(), CollisionError> {
+    let totals = world.enforce_conservation.then(|| Totals::of(&world.objects));
+    let islands = find_islands(&world.objects, dt, world.collision_margin);
+    let substep_counts = islands.iter().map(|island| substeps(island, &world.objects, dt, policy)).collect::<Vec<_>>();
+    let start = world.time_elapsed;
+    let end = start + dt;
+    world.time_elapsed = end;
+    world.frame += 1;
+    world.resolved.clear();
+    world.broad_phase = BroadPhaseStats {
+        candidates: vec![0; world.objects.len()],
+        ..BroadPhaseStats::default()
+    };
+    // Islands are separate worlds with caches of their own, the indices don't carry over
+    world.pair_cache.clear();
+
+    let mut objects = core::mem::take(&mut world.objects).into_iter().map(Some).collect::<Vec<_>>();
+    let mut result = Ok(());
+    for (island, substeps) in islands.iter().zip(substep_counts) {
+        let mut sub = World {
+            collision_margin: world.collision_margin,
+            simplified: world.simplified,
+            batch_tolerance: world.batch_tolerance,
+            integrator: world.integrator,
+            sweep_axis: world.sweep_axis,
+            gravity: world.gravity,
+            gravity_sources: world.gravity_sources.clone(),
+            layers: world.layers.clone(),
+            teams: world.teams.clone(),
+            time_regions: world.time_regions.clone(),
+            // Removals would shift the indices, they're done once the islands are merged
+            collision_limit: None,
+            ..World::new()
+        };
+        sub.time_elapsed = start;
+        sub.objects = island.objects.iter().map(|&i| objects[i].take().unwrap()).collect();
+        for substep in 0..substeps {
+            // The last substep lands exactly on the step's end
+            let substep_dt = if substep + 1 == substeps { end - sub.time_elapsed } else { dt / substeps as f32 };
+            if let Err(e) = sub.step(substep_dt) {
+                if result.is_ok() {
+                    result = Err(match e {
+                        CollisionError::NaNDetected { object } => {
+                            CollisionError::NaNDetected { object: island.objects[object] }
+                        }
+                        e => e,
+                    });
+                }
+                break;
+            }
+            world.broad_phase.absorb(&sub.broad_phase, &island.objects);
+            for toi in &sub.resolved {
+                let toi = Toi {
+                    object_1: island.objects[toi.object_1],
+                    object_2: island.objects[toi.object_2],
+                    ..*toi
+                };
+                world.resolved.push(toi);
+                if world.recent_collisions.len() == RECENT_COLLISIONS {
+                    world.recent_collisions.pop_front();
+                }
+                world.recent_collisions.push_back(toi);
+            }
+        }
+        for (&i, object) in island.objects.iter().zip(sub.objects) {
+            objects[i] = Some(object);
+        }
+    }
+    world.objects = objects.into_iter().map(Option::unwrap).collect();
+    // The index of a non-finite object has to stay valid, see [`World::step`]
+    if matches!(result, Err(CollisionError::NaNDetected { .. })) {
+        return result;
+    }
+    world.remove_worn_out();
+    world.last_correction = match totals {
+        Some(totals) => conservation::project(&mut world.objects, totals),
+        None => Default::default(),
+    };
+    result
+}
+
+#[test]
+fn test_islands_substep_independently() {
+    let square = Polygon::rectangle(vec2(1., 1.));
+    let setup = || {
+        let mut world = World::new();
+        // A fast pair about to collide and a calm object far away
+        world.objects.push(Object::new(vec2(0., 0.), vec2(20., 0.), 0., square.clone()));
+        world.objects.push(Object::new(vec2(3., 0.), vec2(-20., 0.), 0., square.clone()));
+        world.objects.push(Object::new(vec2(0., 50.), vec2(0.1, 0.), 0., square.clone()));
+        world
+    };
+
+    let world = setup();
+    let islands = find_islands(&world.objects, 0.1, 0.);
+    assert_eq!(islands.len(), 2);
+    assert_eq!(islands[0], Island { objects: vec![0, 1], pairs: 1 });
+    let policy = SubstepPolicy::default();
+    assert!(substeps(&islands[0], &world.objects, 0.1, &policy) > 1);
+    assert_eq!(substeps(&islands[1], &world.objects, 0.1, &policy), 1);
+
+    // Exact impacts don't depend on the step length, the substepped run ends in the same state
+    let mut plain = setup();
+    let mut substepped = setup();
+    substepped.substeps = Some(policy);
+    for _ in 0..3 {
+        plain.step(0.1).unwrap();
+        substepped.step(0.1).unwrap();
+    }
+    assert_eq!(substepped.time_elapsed, plain.time_elapsed);
+    for (a, b) in substepped.objects.iter().zip(&plain.objects) {
+        assert!(a.position.abs_diff_eq(b.position, 1e-3));
+        assert!(a.velocity.abs_diff_eq(b.velocity, 1e-3));
+        assert_eq!(a.cur_time, b.cur_time);
+    }
+    assert_eq!(substepped.objects[0].collided, 1);
+}
@@ -0,0 +1,213 @@
+//! Ball-and-socket joints holding a point of one object to a point of another or of the world.
+//!
+//! Collisions are resolved exactly one by one, joints are the solver's only constraints solved
+//! iteratively. At the start of every step [`World::joint_iterations`] sweeps go over the joints
+//! in order, each applying the impulse that makes its anchors meet at the end of the step
+//! (sequential impulses). An impulse reaches only the two objects of its joint, so it takes many
+//! sweeps to travel along a long chain. With too few the chain stretches and its energy drifts,
+//! [`audit_iterations`] measures by how much.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// Sweeps over the joints per step, fewer let the chain preset drift over half again as far from
+/// its energy as the best of the counts up to 64 according to the `joint_audit` example
+pub const DEFAULT_JOINT_ITERATIONS: usize = 16;
+/// Share of the anchors' separation every step closes on top of keeping them together, higher
+/// overshoots and pumps energy into the joints
+const BAUMGARTE: f32 = 0.2;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Joint {
+    pub a: usize,
+    /// Point held, in `a`'s object space
+    pub anchor_a: Vec2,
+    /// `None` holds `anchor_a` to a fixed point of the world
+    pub b: Option<usize>,
+    /// Point held to, in `b`'s object space or in world space without `b`
+    pub anchor_b: Vec2,
+}
+
+impl Joint {
+    pub fn new(a: usize, anchor_a: Vec2, b: usize, anchor_b: Vec2) -> Self {
+        Self {
+            a,
+            anchor_a,
+            b: Some(b),
+            anchor_b,
+        }
+    }
+    /// Holds `anchor_a` of object `a` at `point`, e.g. the top of a pendulum
+    pub fn pin(a: usize, anchor_a: Vec2, point: Vec2) -> Self {
+        Self {
+            a,
+            anchor_a,
+            b: None,
+            anchor_b: point,
+        }
+    }
+    /// World positions of both anchors
+    pub fn anchors(&self, objects: &[Object]) -> (Vec2, Vec2) {
+        let a = objects[self.a].to_world(self.anchor_a);
+        let b = self.b.map_or(self.anchor_b, |b| objects[b].to_world(self.anchor_b));
+        (a, b)
+    }
+    /// Distance between the anchors, zero while the joint holds
+    pub fn separation(&self, objects: &[Object]) -> f32 {
+        let (a, b) = self.anchors(objects);
+        a.distance(b)
+    }
+    /// The joint once object `removed` is gone and the ones after it shifted down, `None` when it
+    /// held that object
+    pub(crate) fn without(mut self, removed: usize) -> Option<Self> {
+        let shift = |i: usize| if i > removed { i - 1 } else { i };
+        if self.a == removed || self.b == Some(removed) {
+            return None;
+        }
+        self.a = shift(self.a);
+        self.b = self.b.map(shift);
+        Some(self)
+    }
+}
+
+/// Inverse mass and inverse moment of inertia, zero for whatever the joints can't move
+fn inverse_mass(object: &Object) -> (f32, f32) {
+    if object.frozen {
+        return (0., 0.);
+    }
+    (1. / object.effective_mass(), 1. / object.effective_inertia())
+}
+
+/// Applies `iterations` sweeps of impulses over `joints`, `accelerations` bend the objects' paths
+/// over the coming step of `dt`
+pub(crate) fn solve(joints: &[Joint], objects: &mut [Object], accelerations: &[Vec2], dt: f32, iterations: usize) {
+    for _ in 0..iterations {
+        for joint in joints {
+            let (point_a, point_b) = joint.anchors(objects);
+            let a = &objects[joint.a];
+            let (mass_a, inertia_a) = inverse_mass(a);
+            let arm_a = point_a - a.position;
+            let mut velocity = -a.velocity_at_point(point_a);
+            let mut acceleration = -accelerations[joint.a];
+            let (mut mass_b, mut inertia_b, mut arm_b) = (0., 0., Vec2::ZERO);
+            if let Some(b) = joint.b {
+                let b = &objects[b];
+                (mass_b, inertia_b) = inverse_mass(b);
+                arm_b = point_b - b.position;
+                velocity += b.velocity_at_point(point_b);
+                acceleration += accelerations[joint.b.unwrap()];
+            }
+
+            // The anchors meet at the end of the step along the parabolas, less what the
+            // Baumgarte term leaves for later steps
+            let separation = point_b - point_a;
+            let target = -separation * (BAUMGARTE / dt) - acceleration * (dt / 2.);
+            // Effective mass matrix of the anchors' relative velocity, symmetric
+            let mass = mass_a + mass_b;
+            let xx = mass + inertia_a * arm_a.y * arm_a.y + inertia_b * arm_b.y * arm_b.y;
+            let yy = mass + inertia_a * arm_a.x * arm_a.x + inertia_b * arm_b.x * arm_b.x;
+            let xy = -inertia_a * arm_a.x * arm_a.y - inertia_b * arm_b.x * arm_b.y;
+            let determinant = xx * yy - xy * xy;
+            if determinant.abs() <= f32::EPSILON {
+                continue;
+            }
+            let change = target - velocity;
+            let impulse = vec2(yy * change.x - xy * change.y, xx * change.y - xy * change.x) / determinant;
+
+            let a = &mut objects[joint.a];
+            if !a.frozen {
+                a.apply_impulse_at_point(-impulse, point_a);
+            }
+            if let Some(b) = joint.b {
+                let b = &mut objects[b];
+                if !b.frozen {
+                    b.apply_impulse_at_point(impulse, point_b);
+                }
+            }
+        }
+    }
+}
+
+/// Energy and stretch of a jointed scene stepped with one iteration count, see [`audit_iterations`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IterationAudit {
+    pub iterations: usize,
+    /// [`World::mechanical_energy`] at the end less at the start
+    pub energy_drift: f32,
+    /// Largest difference to the starting energy after any step
+    pub max_energy_deviation: f32,
+    /// Largest distance between the anchors of a joint after any step
+    pub max_separation: f32,
+}
+
+/// Steps the scene `setup` builds `steps` times by `dt` once for every iteration count, e.g. to
+/// pick [`DEFAULT_JOINT_ITERATIONS`]
+pub fn audit_iterations(
+    setup: fn(&mut World),
+    iterations: &[usize],
+    dt: f32,
+    steps: usize,
+) -> Result<Vec<IterationAudit>, CollisionError> {
+    iterations
+        .iter()
+        .map(|&iterations| {
+            let mut world = World::new();
+            setup(&mut world);
+            world.joint_iterations = iterations;
+            let start = world.mechanical_energy();
+            let mut audit = IterationAudit {
+                iterations,
+                energy_drift: 0.,
+                max_energy_deviation: 0.,
+                max_separation: 0.,
+            };
+            for _ in 0..steps {
+                world.step(dt)?;
+                let energy = world.mechanical_energy();
+                audit.energy_drift = energy - start;
+                audit.max_energy_deviation = audit.max_energy_deviation.max((energy - start).abs());
+                let separation = world.joints.iter().map(|joint| joint.separation(&world.objects)).fold(0., f32::max);
+                audit.max_separation = audit.max_separation.max(separation);
+            }
+            Ok(audit)
+        })
+        .collect()
+}
+
+#[test]
+fn test_joints_hold_a_pendulum() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    // A bar pinned at its end, let go horizontally
+    world.objects.push(Object::new(vec2(1., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 0.2))));
+    world.joints.push(Joint::pin(0, vec2(-1., 0.), Vec2::ZERO));
+    let start = world.mechanical_energy();
+    let mut lowest = 0f32;
+    for _ in 0..60 {
+        world.step(1. / 60.).unwrap();
+        assert!(world.joints[0].separation(&world.objects) < 0.02);
+        lowest = lowest.min(world.objects[0].position.y);
+    }
+    // It swung down through the bottom, turning about the pin
+    assert!(lowest < -0.95 && world.objects[0].position.distance(Vec2::ZERO) < 1.02);
+    assert!((world.mechanical_energy() - start).abs() < 0.5);
+
+    // Removing an object drops its joints and shifts the others
+    let joint = Joint::new(1, Vec2::ZERO, 3, Vec2::X);
+    assert_eq!(joint.without(2), Some(Joint::new(1, Vec2::ZERO, 2, Vec2::X)));
+    assert_eq!(joint.without(3), None);
+    world.remove_object(0);
+    assert!(world.joints.is_empty());
+}
+
+#[test]
+fn test_chain_audit() {
+    let audits = audit_iterations(presets::jointed_chain, &[1, 16], 1. / 60., 90).unwrap();
+    let (few, many) = (audits[0], audits[1]);
+    assert_eq!((few.iterations, many.iterations), (1, 16));
+    // One sweep lets the chain stretch, more keep it together and its energy closer
+    assert!(many.max_separation < few.max_separation / 2., "{audits:?}");
+    assert!(many.max_separation < 0.05, "{audits:?}");
+    assert!(many.max_energy_deviation < few.max_energy_deviation, "{audits:?}");
+}
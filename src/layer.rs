@@ -0,0 +1,34 @@
+//! Authoring layers, e.g. background walls, dynamic debris and sensors.
+//!
+//! Every object belongs to one layer by index into [`World::layers`], objects pointing past the
+//! end behave like they're on a default layer. Objects on layers that don't simulate neither move
+//! nor collide, visibility and locking are for editors.
+
+use alloc::string::String;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    /// Objects can't be picked or pushed around in editors
+    pub locked: bool,
+    pub simulate: bool,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            visible: true,
+            locked: false,
+            simulate: true,
+        }
+    }
+}
+
+/// Layers of a new world, everything starts on the first one
+pub fn default_layers() -> alloc::vec::Vec<Layer> {
+    alloc::vec![Layer::new("Default")]
+}
@@ -0,0 +1,96 @@
+//! Exact continuous collision detection for 2D polygons.
+//!
+//! [`World`] steps a set of [`Object`]s and resolves every impact in chronological order,
+//! [`World::compute_toi`] exposes the time of impact query on its own.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("`no_std` builds need the `libm` feature for float math");
+
+extern crate alloc;
+
+pub mod broad_phase;
+pub mod certificate;
+pub mod checkpoint;
+pub mod command;
+pub mod conservation;
+pub mod controller;
+pub mod dilation;
+pub mod draw;
+pub mod energy_audit;
+pub mod energy_budget;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod export;
+pub mod fracture;
+pub mod geometry;
+pub mod gravity;
+#[cfg(feature = "std")]
+pub mod import;
+pub mod integrator;
+pub mod islands;
+pub mod joint;
+pub mod layer;
+pub mod lint;
+mod math;
+pub mod memory;
+pub mod object;
+pub mod observer;
+pub mod pair_cache;
+#[cfg(feature = "std")]
+pub mod prefab;
+pub mod presets;
+pub mod raster;
+#[cfg(feature = "std")]
+pub mod replay;
+pub mod restitution;
+pub mod rewind;
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod scenario;
+#[cfg(feature = "std")]
+pub mod scene;
+#[cfg(feature = "std")]
+pub mod session;
+pub mod team;
+pub mod timestep;
+pub mod trace;
+pub mod world;
+
+use alloc::{vec, vec::Vec};
+use core::{cmp::Ordering, f32::consts::PI, ops::Deref};
+
+use glam::{vec2, Vec2};
+
+use geometry::*;
+#[allow(unused_imports)]
+use math::*;
+pub use error::CollisionError;
+pub use object::Object;
+pub use world::{Toi, World};
+
+/// Totally ordered `f32` for sorted containers, NaN isn't expected
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct F32Ord(pub f32);
+
+impl Deref for F32Ord {
+    type Target = f32;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Eq for F32Ord {}
+
+impl PartialOrd for F32Ord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for F32Ord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
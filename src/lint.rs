@@ -0,0 +1,123 @@
+//! Self-test of a world's state between steps, a "physics linter".
+//!
+//! [`lint`] looks for states the solver allows but which usually mean something went wrong: shapes
+//! left overlapping, objects flung away at absurd speeds, values on their way to overflowing into
+//! infinity or NaN and queued collisions no object matches anymore. None of them stop a step, they
+//! are [`Warning`]s for a person to look at.
+
+use core::{cmp::Reverse, fmt};
+
+use super::*;
+
+/// Speed above which an object counts as flung away
+pub const MAX_SPEED: f32 = 1e3;
+/// Magnitude above which a value is on its way to overflowing, squaring it already would
+pub const NEAR_OVERFLOW: f32 = 1e18;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Warning {
+    /// The shapes overlap, skins aside
+    Overlap { a: usize, b: usize },
+    ExtremeVelocity { object: usize, speed: f32 },
+    /// `quantity` of the object is huge or already not finite, the next step reports it as NaN
+    NearOverflow { object: usize, quantity: &'static str, value: f32 },
+    /// A collision left in the queue whose objects changed paths or are gone since it was found
+    StaleCollision { toi: Toi },
+}
+
+impl Warning {
+    /// Objects the warning is about, e.g. to look at them
+    pub fn objects(&self) -> Vec<usize> {
+        match *self {
+            Self::Overlap { a, b } => vec![a, b],
+            Self::ExtremeVelocity { object, .. } | Self::NearOverflow { object, .. } => vec![object],
+            Self::StaleCollision { toi } => vec![toi.object_1, toi.object_2],
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overlap { a, b } => write!(f, "objects {a} and {b} overlap"),
+            Self::ExtremeVelocity { object, speed } => write!(f, "object {object} moves at {speed:.2e} u/s"),
+            Self::NearOverflow { object, quantity, value } => write!(f, "object {object} has a {quantity} of {value:.2e}"),
+            Self::StaleCollision { toi } => write!(
+                f,
+                "stale collision of objects {} and {} queued at {:.4}",
+                toi.object_1, toi.object_2, toi.time
+            ),
+        }
+    }
+}
+
+/// Everything suspicious about `world` right now, empty for a healthy one
+///
+/// Overlaps are checked for every pair, which is slow with many objects.
+pub fn lint(world: &World) -> Vec<Warning> {
+    let mut warnings = world
+        .interpenetrating_pairs()
+        .into_iter()
+        // Two immovable objects overlapping is how a level is built, not something to fix
+        .filter(|&(a, b)| world.objects[a].is_dynamic() || world.objects[b].is_dynamic())
+        .map(|(a, b)| Warning::Overlap { a, b })
+        .collect::<Vec<_>>();
+    for (i, object) in world.objects.iter().enumerate() {
+        let quantities = [
+            ("position", object.position.abs().max_element()),
+            ("velocity", object.velocity.abs().max_element()),
+            ("rotation", object.rotation.abs()),
+            ("angular velocity", object.rot_velocity.abs()),
+        ];
+        if let Some(&(quantity, value)) = quantities.iter().find(|(_, value)| value.is_nan() || *value > NEAR_OVERFLOW) {
+            warnings.push(Warning::NearOverflow { object: i, quantity, value });
+            continue;
+        }
+        let speed = object.velocity.length() + object.rot_velocity.abs() * object.bounding_circle.radius;
+        if speed > MAX_SPEED {
+            warnings.push(Warning::ExtremeVelocity { object: i, speed });
+        }
+    }
+    let stamp = |object: usize| world.objects.get(object).map(|object| object.updated);
+    warnings.extend(
+        world
+            .collision_queue
+            .iter()
+            .map(|Reverse(toi)| *toi)
+            .filter(|toi| stamp(toi.object_1) != Some(toi.object_1_col_stamp) || stamp(toi.object_2) != Some(toi.object_2_col_stamp))
+            .map(|toi| Warning::StaleCollision { toi }),
+    );
+    warnings
+}
+
+#[test]
+fn test_lint() {
+    let square = Polygon::rectangle(vec2(1., 1.));
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), Vec2::ZERO, 0., square.clone()));
+    world.objects.push(Object::new(vec2(3., 0.), vec2(1., 0.), 0., square.clone()));
+    world.objects.push(Object::new_static(vec2(0., -3.), 0., square.clone()));
+    assert_eq!(lint(&world), []);
+
+    world.objects.push(Object::new(vec2(0.5, 0.), Vec2::ZERO, 0., square.clone()));
+    world.objects[1].velocity = vec2(2e3, 0.);
+    world.objects[2].position.x = f32::INFINITY;
+    // Two statics overlapping is fine
+    world.objects.push(Object::new_static(vec2(0., -3.5), 0., square));
+    let warnings = lint(&world);
+    assert!(warnings.contains(&Warning::Overlap { a: 0, b: 3 }), "{warnings:?}");
+    assert!(warnings.contains(&Warning::ExtremeVelocity { object: 1, speed: 2e3 }), "{warnings:?}");
+    assert!(warnings.iter().any(|warning| matches!(warning, Warning::NearOverflow { object: 2, quantity: "position", .. })));
+    assert_eq!(warnings.len(), 3, "{warnings:?}");
+
+    // A queued collision of an object that has moved on since
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(-2., 0.), vec2(4., 0.), 0., Polygon::rectangle(vec2(1., 1.))));
+    world.objects.push(Object::new(vec2(2., 0.), vec2(-4., 0.), 0., Polygon::rectangle(vec2(1., 1.))));
+    let toi = world.check_collision_pair(0, 1, 1.).unwrap().unwrap();
+    world.collision_queue.push(Reverse(toi));
+    assert_eq!(lint(&world), []);
+    world.objects[0].updated += 1;
+    assert_eq!(lint(&world), [Warning::StaleCollision { toi }]);
+    assert_eq!(lint(&world)[0].objects(), [0, 1]);
+}
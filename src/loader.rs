@@ -0,0 +1,113 @@
+//! Loading scene files on a background thread.
+//!
+//! Parsing and building the objects (hulls, proxies, bounding circles) of big scenes takes long
+//! enough to freeze the window, the thread reports how many objects it built and checks for
+//! cancellation between them.
+
+use std::{
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use perfect_collisions::{dilation::TimeRegion, gravity::GravitySource, joint::Joint, replay::StepConfig, rng::RngService, team::Team};
+
+use super::*;
+use scene::Scene;
+
+#[derive(Default)]
+struct Progress {
+    built: AtomicUsize,
+    /// Object count, zero while the file is still parsed
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+/// Scene with its objects built
+pub struct LoadedScene {
+    pub objects: Vec<Object>,
+    pub time_elapsed: f32,
+    /// Empty for scenes from before layers
+    pub layers: Vec<Layer>,
+    /// `None` keeps the world's
+    pub gravity: Option<Vec2>,
+    pub teams: Vec<Team>,
+    pub joints: Vec<Joint>,
+    pub gravity_sources: Vec<GravitySource>,
+    pub time_regions: Vec<TimeRegion>,
+    /// `None` keeps the world's, like `rng`
+    pub settings: Option<StepConfig>,
+    pub rng: Option<RngService>,
+}
+
+/// `None` when the load was cancelled
+type Loaded = io::Result<Option<LoadedScene>>;
+
+pub struct SceneLoad {
+    pub path: PathBuf,
+    progress: Arc<Progress>,
+    handle: Option<JoinHandle<Loaded>>,
+}
+
+impl SceneLoad {
+    pub fn start(path: PathBuf) -> Self {
+        let progress = Arc::new(Progress::default());
+        let handle = std::thread::spawn({
+            let (path, progress) = (path.clone(), progress.clone());
+            move || load(path, &progress)
+        });
+        Self {
+            path,
+            progress,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Share of the objects built, `None` while the file is still parsed
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.progress.total.load(Ordering::Relaxed);
+        (total > 0).then(|| self.progress.built.load(Ordering::Relaxed) as f32 / total as f32)
+    }
+
+    /// The outcome once the thread finished, `None` before
+    pub fn poll(&mut self) -> Option<Loaded> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let outcome = self.handle.take()?.join().unwrap_or_else(|_| Err(io::Error::other("loader thread panicked")));
+        Some(outcome)
+    }
+}
+
+fn load(path: PathBuf, progress: &Progress) -> Loaded {
+    let scene = Scene::load(&path)?;
+    progress.total.store(scene.objects.len().max(1), Ordering::Relaxed);
+    let mut objects = Vec::with_capacity(scene.objects.len());
+    for scene_object in scene.objects {
+        if progress.cancelled.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        objects.push(Object::from(scene_object));
+        progress.built.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(Some(LoadedScene {
+        objects,
+        time_elapsed: scene.time_elapsed,
+        layers: scene.layers,
+        gravity: scene.gravity,
+        teams: scene.teams,
+        joints: scene.joints,
+        gravity_sources: scene.gravity_sources,
+        time_regions: scene.time_regions,
+        settings: scene.settings,
+        rng: scene.rng,
+    }))
+}
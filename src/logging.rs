@@ -0,0 +1,84 @@
+//! Structured logging setup.
+//!
+//! Output goes to stderr, filtered by `PERFECT_COLLISIONS_LOG` (same syntax as `RUST_LOG`),
+//! and the most recent lines are also kept in a [`LogBuffer`] for the in-app log window.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+
+use perfect_collisions::memory;
+use tracing::{field::Visit, Event, Level, Subscriber};
+use tracing_subscriber::{layer::Context, prelude::*, EnvFilter, Layer};
+
+const LOG_ENV_VAR: &str = "PERFECT_COLLISIONS_LOG";
+const DEFAULT_FILTER: &str = "perfect_collisions=info";
+const LOG_BUFFER_LINES: usize = 200;
+
+/// Shared ring buffer of formatted log lines
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<(Level, String)>>>);
+
+impl LogBuffer {
+    pub fn lines(&self) -> Vec<(Level, String)> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+    pub fn heap_bytes(&self) -> usize {
+        let lines = self.0.lock().unwrap();
+        memory::deque_bytes(&lines) + lines.iter().map(|(_, line)| line.capacity()).sum::<usize>()
+    }
+    fn push(&self, level: Level, line: String) {
+        let mut lines = self.0.lock().unwrap();
+        if lines.len() == LOG_BUFFER_LINES {
+            lines.pop_front();
+        }
+        lines.push_back((level, line));
+    }
+}
+
+/// Installs the global subscriber and returns the buffer feeding the egui log window
+///
+/// `set_global_default` is used instead of `init` so no `log` bridge gets installed,
+/// ellipsoid sets up its own `env_logger`.
+pub fn init() -> LogBuffer {
+    let buffer = LogBuffer::default();
+    let filter = EnvFilter::try_from_env(LOG_ENV_VAR).unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(BufferLayer(buffer.clone()));
+
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        eprintln!("Global tracing subscriber was already set");
+    }
+
+    buffer
+}
+
+struct BufferLayer(LogBuffer);
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = LineVisitor(String::new());
+        event.record(&mut visitor);
+        self.0.push(*event.metadata().level(), visitor.0);
+    }
+}
+
+struct LineVisitor(String);
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
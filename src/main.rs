@@ -1,16 +1,48 @@
-#![feature(async_fn_in_trait)]
-
+mod autosave;
 mod camera;
-mod object;
-
-use std::{cmp::Reverse, collections::{BinaryHeap, BTreeSet}, f32::consts::PI, time::Instant, ops::Deref};
-
+mod camera_path;
+mod console;
+mod contact_graph;
+mod crash;
+mod depth;
+mod energy_chart;
+mod heatmap;
+mod loader;
+mod logging;
+mod orbit;
+mod phase_plot;
+mod quadtree;
+mod render;
+mod rocket;
+mod settings;
+mod stats;
+mod theme;
+mod workspace;
+
+use std::{cmp::Reverse, collections::VecDeque, panic::AssertUnwindSafe, f32::consts::PI, time::Instant};
+
+use autosave::Autosave;
 use camera::Camera;
-use ellipsoid::prelude::{winit::event::MouseButton, winit::window::Window, *, egui::epaint::ahash::HashSet};
-use object::Object;
-
-mod geometry;
-use geometry::*;
+use camera_path::CameraPath;
+use console::Console;
+use contact_graph::ContactGraph;
+use depth::{Depth, DrawQueue};
+use energy_chart::EnergyChart;
+use heatmap::HeatMap;
+use ellipsoid::prelude::{winit::event::MouseButton, winit::window::Window, *};
+use loader::SceneLoad;
+use logging::LogBuffer;
+use orbit::OrbitView;
+use phase_plot::PhasePlot;
+use quadtree::QuadTree;
+use render::FillStyle;
+use rocket::Rocket;
+use settings::Settings;
+use stats::CollisionStats;
+use theme::Theme;
+use workspace::{Panels, Workspace};
+use perfect_collisions::{broad_phase::{self, Advice, SweepAxis}, certificate::Certificate, checkpoint::Checkpoint, command::{self, Command, Property}, export, geometry::*, import, dilation::TimeRegion, energy_audit::EnergyAudit, energy_budget::EnergyBudget, integrator::{interpolate_pose, Integrator}, islands::SubstepPolicy, layer::Layer, lint::{self, Warning}, memory::{self, WorldMemory}, object::CcdMode, prefab::{Prefab, PrefabLibrary}, presets, replay::{Recorder, Recording, Replay}, restitution::DropTest, rewind::{self, RewindBuffer}, rng::{RngService, Stream}, scene, session::{Input, Session, SessionPlayer, SessionRecorder}, timestep::{FixedTimestep, DEFAULT_MAX_STEPS}, trace::Tracer, world::TraversedVolume, CollisionError, F32Ord, Object, Toi, World};
+use tracing::{error, info, warn};
 
 
 #[repr(u32)]
@@ -24,79 +56,424 @@ pub enum AppTextures {
 
 impl Textures for AppTextures {}
 
-impl Into<u32> for AppTextures {
-    fn into(self) -> u32 {
-        self as u32
+impl From<AppTextures> for u32 {
+    fn from(texture: AppTextures) -> Self {
+        texture as u32
     }
 }
 
 type Txts = AppTextures;
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
-struct F32Ord(f32);
-
-impl Deref for F32Ord {
-    type Target = f32;
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
+const FLICK_STRENGTH: f32 = 2.;
+const SPIN_TORQUE: f32 = 5.;
+/// Speed above which objects switched to fast-only CCD in the inspector get exact impacts
+const DEFAULT_CCD_THRESHOLD: f32 = 5.;
+/// How far a right-click spawn may be moved to keep it from overlapping anything
+const SPAWN_NUDGE: f32 = 2.;
+/// Fragments of the inspector's shatter, at most one per edge
+const SHATTER_PIECES: usize = 6;
+/// Kinetic energy per unit of mass the inspector's shatter releases
+const SHATTER_ENERGY: f32 = 2.;
+/// Update time in seconds above which collisions fall back to simplified proxies
+const FRAME_BUDGET: f32 = 1. / 60.;
+/// Time window of collisions resolved together when batching is on
+const BATCH_TOLERANCE: f32 = 1e-4;
+/// Seconds an impulse arrow stays on screen
+const IMPULSE_ARROW_LIFETIME: f32 = 0.5;
+/// Arrow length in world units per unit of impulse
+const IMPULSE_ARROW_SCALE: f32 = 0.1;
+/// Camera scale above which arrows get their magnitude written next to them
+const IMPULSE_LABEL_ZOOM: f32 = 0.5;
+const LABEL_FONT_SIZE: f32 = 12.;
+const DEFAULT_DROP_HEIGHT: f32 = 2.;
+/// Contact points the debug overlay keeps, the oldest go first
+const DEBUG_POINTS: usize = 1000;
+const RECORDING_PATH: &str = "perfect-collisions-recording.ron";
+const SESSION_PATH: &str = "perfect-collisions-session.ron";
+const PREFAB_LIBRARY_PATH: &str = "perfect-collisions-prefabs.ron";
+const SCENE_PATH: &str = "perfect-collisions-scene.ron";
+
+/// Impulse of a resolved collision, drawn at the contact point until it expires
+struct ImpulseArrow {
+    point: Vec2,
+    impulse: Vec2,
+    remaining: f32,
 }
+/// Weight of the newest step in the smoothed physics rate
+const PHYSICS_RATE_SMOOTHING: f32 = 0.1;
+/// Steps per second when stepping at a fixed rate
+const FIXED_RATE: f32 = 120.;
 
-impl Eq for F32Ord {}
-
-impl Ord for F32Ord {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-    
 struct CollisionSimulator {
-    objects: Vec<Object>,
+    world: World,
     camera: Camera,
     graphics: Graphics<Txts>,
+    /// Shapes of the frame being drawn, see [`depth`]
+    draw_queue: DrawQueue,
     middle_clicked: bool,
     cursor_position: Vec2,
     last_cursor_position: Vec2,
     right_clicked: bool,
-    time_elapsed: f32,
+    /// World point where the current left-drag flick started
+    flick_start: Option<Vec2>,
+    /// Finished flick waiting for the next update, (start, end) in world space
+    pending_flick: Option<(Vec2, Vec2)>,
+    /// -1, 0 or 1 depending on held Q/E
+    spin_input: f32,
+    /// Restitution of the objects spawned with the right button
+    spawn_restitution: f32,
+    /// Seed "Reseed" restarts the random streams from, see [`perfect_collisions::rng`]
+    seed: u64,
     frame_rate: usize,
-    frame: usize,
-    debug_points: Vec<Vec2>,
+    /// Steps per second the solver alone could sustain, smoothed
+    physics_rate: f32,
+    /// Steps of fixed length, `None` steps once per frame by the frame time
+    fixed_step: Option<FixedTimestep>,
+    /// Objects before the last fixed step, frames are drawn between them and the current ones
+    render_start: Option<Vec<Object>>,
+    /// Outcome of the last certified step, see [`perfect_collisions::certificate`]
+    certificate: Option<Certificate>,
+    /// Violations since certifying was turned on, only the first violating step is dumped
+    certificate_violations: usize,
+    /// Points of the last resolved collisions, oldest first
+    debug_points: VecDeque<Vec2>,
+    /// Object boxes as of the last step, for culling and picking
+    quadtree: QuadTree,
+    impulse_arrows: Vec<ImpulseArrow>,
+    show_impulses: bool,
+    heat_map: HeatMap,
+    show_heat_map: bool,
+    contact_graph: ContactGraph,
+    show_contact_graph: bool,
+    stats: CollisionStats,
+    show_stats: bool,
+    /// Budgets of the last steps while the world keeps one, see
+    /// [`perfect_collisions::energy_budget`]
+    energy_chart: Option<EnergyChart>,
+    /// Suspicious states of the world after the last frame, see [`lint`]
+    lint_warnings: Vec<Warning>,
+    show_lint: bool,
+    phase_plot: PhasePlot,
+    show_phase_plot: bool,
+    show_restitution_wizard: bool,
+    drop_height: f32,
+    drop_restitution: f32,
+    /// Running restitution experiment
+    drop_test: Option<DropTest>,
+    /// Playable rocket, kept until it's gone from the world and its exhaust faded
+    rocket: Option<Rocket>,
+    /// Trajectory preview of the orbit scenario, dropped once its probe is gone
+    orbit: Option<OrbitView>,
+    recorder: Option<Recorder>,
+    /// Playback of a recording, replaces stepping and the tools while it's set
+    replay: Option<Replay>,
+    /// Inputs of the session being recorded, see [`perfect_collisions::session`]
+    session: Option<SessionRecorder>,
+    /// Playback of a recorded session, replaces the tools while it's set
+    session_player: Option<SessionPlayer>,
+    /// State the live world is diffed against
+    checkpoint: Option<Checkpoint>,
+    /// The world after each of the last steps, for scrubbing back, see [`perfect_collisions::rewind`]
+    rewind: Option<RewindBuffer>,
+    /// Stops stepping, the tools still work
+    paused: bool,
+    /// Set to advance a paused world by exactly one step in the next update
+    single_step: bool,
+    /// Simulated seconds per real second, the frame rate stays as it is
+    time_scale: f32,
+    console: Console,
+    /// Indices of the objects picked with shift-click, dropped whenever the object count changes
+    selection: Vec<usize>,
+    shift_held: bool,
+    /// Object count the selection was made with
+    selection_object_count: usize,
+    show_labels: bool,
+    settings: Settings,
+    fill_style: FillStyle,
+    show_outlines: bool,
+    camera_path: CameraPath,
+    show_camera_path: bool,
+    /// Playback time the next keyframe gets
+    new_keyframe_time: f32,
+    labels_selection_only: bool,
+    log_buffer: LogBuffer,
+    show_log: bool,
+    autosave: Autosave,
+    /// Scene file being loaded in the background
+    scene_load: Option<SceneLoad>,
+    prefabs: PrefabLibrary,
+    prefab_name: String,
+    /// Index of the prefab left-click stamps instead of flicking
+    stamping: Option<usize>,
+    stamp_rotation: f32,
+    /// File "Save scene" and "Load scene" use, JSON when it ends in `.json`
+    scene_path: String,
+    /// Name the session is saved under as a workspace, see [`workspace`]
+    workspace_name: String,
+    /// Saved workspaces, as of startup or the last save
+    workspaces: Vec<String>,
+    show_workspaces: bool,
 }
 
 impl App<Txts> for CollisionSimulator {
     async fn new(window: Window) -> Self {
+        crash::install_panic_hook();
+        let log_buffer = logging::init();
+        let args = Args::parse();
         let graphics = Graphics::new(window).await;
+        let settings = Settings::load();
+        graphics.egui_platform.context().set_visuals(settings.theme.visuals());
+        let workspaces = workspace::list();
         Self {
-            objects: vec![],
+            world: World {
+                frame_budget: Some(FRAME_BUDGET),
+                rng: RngService::new(args.seed.unwrap_or_default()),
+                tracer: Tracer::to_file(),
+                ..World::new()
+            },
             graphics,
+            draw_queue: DrawQueue::default(),
             middle_clicked: false,
             cursor_position: Vec2::ZERO,
             last_cursor_position: Vec2::ZERO,
             camera: Camera::default(),
-            time_elapsed: 0.,
-            frame: 0,
-            debug_points: vec![],
+            debug_points: VecDeque::with_capacity(DEBUG_POINTS),
+            quadtree: QuadTree::new(),
+            impulse_arrows: vec![],
+            show_impulses: false,
+            heat_map: HeatMap::new(),
+            show_heat_map: false,
+            contact_graph: ContactGraph::new(),
+            show_contact_graph: false,
+            stats: CollisionStats::new(),
+            show_stats: false,
+            energy_chart: None,
+            lint_warnings: vec![],
+            show_lint: false,
+            phase_plot: PhasePlot::new(),
+            show_phase_plot: false,
+            show_restitution_wizard: false,
+            drop_height: DEFAULT_DROP_HEIGHT,
+            drop_restitution: 1.,
+            drop_test: None,
+            rocket: None,
+            orbit: None,
+            recorder: None,
+            replay: None,
+            session: None,
+            session_player: None,
+            checkpoint: None,
+            rewind: None,
+            paused: false,
+            single_step: false,
+            time_scale: 1.,
+            console: Console::new(),
+            selection: vec![],
+            shift_held: false,
+            selection_object_count: 0,
+            show_labels: false,
+            settings,
+            fill_style: FillStyle::default(),
+            show_outlines: true,
+            camera_path: CameraPath::default(),
+            show_camera_path: false,
+            new_keyframe_time: 0.,
+            labels_selection_only: false,
             frame_rate: 0,
-            right_clicked: false
+            physics_rate: 0.,
+            fixed_step: Some(FixedTimestep::new(FIXED_RATE)),
+            render_start: None,
+            certificate: None,
+            certificate_violations: 0,
+            right_clicked: false,
+            flick_start: None,
+            pending_flick: None,
+            spin_input: 0.,
+            spawn_restitution: 1.,
+            seed: args.seed.unwrap_or_default(),
+            log_buffer,
+            show_log: false,
+            autosave: Autosave::new(),
+            // A scene passed on the command line loads while the window is already up
+            scene_load: args.scene.map(SceneLoad::start),
+            prefabs: PrefabLibrary::load(PREFAB_LIBRARY_PATH).unwrap_or_default(),
+            prefab_name: String::new(),
+            stamping: None,
+            stamp_rotation: 0.,
+            scene_path: SCENE_PATH.into(),
+            workspace_name: String::new(),
+            // Offered right away so an experiment can be picked up where it was left
+            show_workspaces: !workspaces.is_empty(),
+            workspaces,
         }
     }
 
     fn update(&mut self, dt: f32) {
+        let frame_start = Instant::now();
+        self.finish_scene_load();
         self.update_camera();
+        self.camera_path.update(dt, &mut self.camera);
+        if let Some(session) = &mut self.session {
+            session.camera(&self.world, self.camera.focus(), self.camera.zoom());
+        }
+        if self.replay.is_none() && self.session_player.is_none() {
+            self.apply_tools(dt);
+            self.spawn_objects();
+        }
 
         self.last_cursor_position = self.cursor_position;
 
         self.frame_rate = (1./dt) as usize;
-        self.time_elapsed += dt;
-        self.frame += 1;
-        self.update_collisions(dt);
-        self.update_objects(dt);
+        let step_start = Instant::now();
+        let mut diverged = false;
+        // Holds still while the probe's velocity is dragged
+        let held = self.orbit.as_ref().is_some_and(OrbitView::is_dragging);
+        let before = (self.world.certify && self.certificate_violations == 0).then(|| scene::Scene::from_world(&self.world));
+        let mut played = vec![];
+        // Replays and sessions being played back step as recorded, once per frame
+        let fixed_rate = self.fixed_step.is_some() && self.replay.is_none() && self.session_player.is_none();
+        let single_step = std::mem::take(&mut self.single_step) && self.paused;
+        let (steps, step_dt) = match &mut self.fixed_step {
+            Some(fixed_step) if fixed_rate && single_step => (1, fixed_step.dt()),
+            Some(fixed_step) if fixed_rate && !held && !self.paused => {
+                // Sped up, a frame is due more steps than at normal speed
+                fixed_step.max_steps = (DEFAULT_MAX_STEPS as f32 * self.time_scale.max(1.)).ceil() as usize;
+                (fixed_step.advance(dt * self.time_scale), fixed_step.dt())
+            }
+            _ if fixed_rate => (0, 0.),
+            _ => (1, dt * self.time_scale),
+        };
+        let render_start = &mut self.render_start;
+        let step = std::panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), CollisionError> {
+            for i in 0..steps {
+                if fixed_rate && i + 1 == steps {
+                    *render_start = Some(self.world.objects.clone());
+                }
+                match (&mut self.replay, &mut self.session_player, &mut self.recorder, &mut self.session) {
+                    _ if held || (self.paused && !single_step) => (),
+                    (Some(replay), ..) => diverged = !replay.is_finished() && !replay.step(&mut self.world),
+                    (None, Some(player), ..) => played = player.step(&mut self.world),
+                    (None, None, Some(recorder), _) => recorder.step(&mut self.world, step_dt)?,
+                    (None, None, None, Some(session)) => session.step(&mut self.world, step_dt)?,
+                    (None, None, None, None) => self.world.step(step_dt)?,
+                }
+                if let Some(rewind) = &mut self.rewind {
+                    rewind.record(&self.world);
+                }
+                if let Some(energy_chart) = &mut self.energy_chart {
+                    energy_chart.record(&self.world);
+                }
+            }
+            Ok(())
+        }));
+        if !fixed_rate {
+            self.render_start = None;
+        }
+        if steps > 0 {
+            let step_rate = steps as f32 / step_start.elapsed().as_secs_f32().max(f32::EPSILON);
+            self.physics_rate += (step_rate - self.physics_rate) * PHYSICS_RATE_SMOOTHING;
+        }
+        match step {
+            Ok(Ok(())) => (),
+            Ok(Err(CollisionError::NaNDetected { object })) => {
+                if let Some(name) = self.world.objects.get(object).map(|found| object_name(object, found)) {
+                    warn!(object, name, "removing object with a non-finite state");
+                    self.world.remove_object(object);
+                    self.forget_selection();
+                    self.record_edit();
+                } else {
+                    warn!(object, "non-finite state reported for an object that's gone");
+                }
+            }
+            Ok(Err(e)) => warn!(error = %e, "collision step failed"),
+            Err(panic) => {
+                self.write_crash_dump();
+                std::panic::resume_unwind(panic);
+            }
+        }
+        if let Some(certificate) = self.world.certificate.take() {
+            self.check_certificate(certificate, before, dt);
+        }
+        for toi in &self.world.resolved {
+            if self.debug_points.len() == DEBUG_POINTS {
+                self.debug_points.pop_front();
+            }
+            self.debug_points.push_back(toi.point);
+        }
+        for score in &self.world.scored {
+            let team = &self.world.teams[score.team];
+            info!(team = team.name, scorer = score.scorer, goal = score.goal, total = team.score, "scored");
+        }
+        self.impulse_arrows.retain_mut(|arrow| {
+            arrow.remaining -= dt;
+            arrow.remaining > 0.
+        });
+        if self.world.objects.len() != self.selection_object_count {
+            // Removals and loads shift the indices
+            self.selection.clear();
+        }
+        if diverged {
+            self.select_divergence();
+        }
+        self.apply_played(played);
+        if let Some(orbit) = &mut self.orbit {
+            orbit.update(&mut self.world, self.camera.screen_to_world(self.cursor_position));
+            if OrbitView::probe(&self.world).is_none() {
+                self.orbit = None;
+            }
+        }
+        self.quadtree = QuadTree::build(&self.world.objects);
+        if let Some(rocket) = &mut self.rocket {
+            rocket.update(dt, &mut self.world.rng);
+            if rocket.is_finished() {
+                self.rocket = None;
+            }
+        }
+        self.stats.record(&self.world.resolved);
+        if self.show_lint {
+            self.lint_warnings = lint::lint(&self.world);
+        }
+        if let Some(drop_test) = &mut self.drop_test {
+            drop_test.observe(&self.world);
+        }
+
+        if self.show_phase_plot {
+            self.phase_plot.record(self.selection.last().copied(), &self.world);
+        }
+        if self.show_contact_graph {
+            self.contact_graph.record(&self.world);
+        }
+        if self.show_heat_map {
+            self.heat_map.record(&self.world.resolved, self.world.time_elapsed);
+        }
+        if self.show_impulses {
+            self.impulse_arrows.extend(self.world.resolved.iter().map(|toi| ImpulseArrow {
+                point: toi.point,
+                impulse: toi.impulse * toi.normal,
+                remaining: IMPULSE_ARROW_LIFETIME,
+            }));
+        }
+        self.autosave.tick(&self.world);
+
+        self.world.report_frame_time(frame_start.elapsed().as_secs_f32());
+        self.world.tracer.span("frame", frame_start);
+        self.world.tracer.flush();
     }
     fn draw(&mut self) {
+        let background = Shape::<Txts>::from_square()
+            .set_color(self.settings.theme.palette().background)
+            .apply(GTransform::from_translation(Vec2::splat(-1.)).inflate(2.));
+        self.draw_queue.add(Depth::Background, background);
         self.draw_ui();
         self.draw_objects();
+        if let Some(rocket) = &self.rocket {
+            rocket.draw(&mut self.draw_queue, &self.camera);
+        }
+        if let Some(orbit) = &self.orbit {
+            orbit.draw(&self.world, &mut self.draw_queue, &self.camera, self.settings.theme.palette().debug);
+        }
         self.draw_debug();
+        self.draw_queue.flush(&mut self.graphics);
     }
 
     fn input(&mut self, event: &WindowEvent) -> bool {
@@ -104,6 +481,32 @@ impl App<Txts> for CollisionSimulator {
         // detect mouse up, and set clicked to None
         // detect mouse move, and set cursor_position to the mouse position
         match event {
+            // ellipsoid exits on these, anything else leaves the autosave behind for the next launch
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state: winit::event::ElementState::Pressed,
+                        virtual_keycode: Some(winit::event::VirtualKeyCode::Escape),
+                        ..
+                    },
+                ..
+            } => {
+                self.autosave.discard();
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state: winit::event::ElementState::Pressed,
+                        virtual_keycode: Some(winit::event::VirtualKeyCode::Grave),
+                        ..
+                    },
+                ..
+            } => {
+                self.console.toggle();
+            }
+            // Typing into the console or a text field doesn't steer anything
+            WindowEvent::KeyboardInput { .. } if self.graphics.egui_platform.context().wants_keyboard_input() => (),
             WindowEvent::MouseInput {
                 state,
                 button: MouseButton::Middle,
@@ -116,6 +519,95 @@ impl App<Txts> for CollisionSimulator {
                     self.middle_clicked = false;
                 }
             },
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let world_position = self.camera.screen_to_world(self.cursor_position);
+                match state {
+                    winit::event::ElementState::Pressed if self.shift_held => {
+                        if !self.graphics.egui_platform.context().wants_pointer_input() {
+                            self.toggle_selection(world_position);
+                        }
+                    }
+                    winit::event::ElementState::Pressed if self.stamping.is_some() => {
+                        if !self.graphics.egui_platform.context().wants_pointer_input() {
+                            self.stamp_prefab(world_position);
+                        }
+                    }
+                    winit::event::ElementState::Pressed => {
+                        if !self.graphics.egui_platform.context().wants_pointer_input() {
+                            let grabbed = self.orbit.as_mut().is_some_and(|orbit| orbit.start_drag(&self.world, &self.camera, world_position));
+                            if !grabbed {
+                                self.flick_start = Some(world_position);
+                            }
+                        }
+                    }
+                    winit::event::ElementState::Released => {
+                        if let Some(orbit) = &mut self.orbit {
+                            orbit.end_drag();
+                        }
+                        if let Some(start) = self.flick_start.take() {
+                            self.pending_flick = Some((start, world_position));
+                        }
+                    }
+                }
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state: winit::event::ElementState::Pressed,
+                        virtual_keycode: Some(winit::event::VirtualKeyCode::Space),
+                        ..
+                    },
+                ..
+            } => {
+                self.run_command(if self.paused { Command::Resume } else { Command::Pause });
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state: winit::event::ElementState::Pressed,
+                        virtual_keycode: Some(winit::event::VirtualKeyCode::Period),
+                        ..
+                    },
+                ..
+            } => self.step_once(),
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key @ (winit::event::VirtualKeyCode::Q | winit::event::VirtualKeyCode::E)),
+                        ..
+                    },
+                ..
+            } => {
+                let direction = if *key == winit::event::VirtualKeyCode::Q { 1. } else { -1. };
+                match state {
+                    winit::event::ElementState::Pressed => self.spin_input = direction,
+                    winit::event::ElementState::Released if self.spin_input == direction => self.spin_input = 0.,
+                    winit::event::ElementState::Released => (),
+                }
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state,
+                        virtual_keycode:
+                            Some(
+                                key @ (winit::event::VirtualKeyCode::Up
+                                | winit::event::VirtualKeyCode::Left
+                                | winit::event::VirtualKeyCode::Right),
+                            ),
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(rocket) = &mut self.rocket {
+                    rocket.key(*key, *state == winit::event::ElementState::Pressed);
+                }
+            }
             WindowEvent::MouseInput {
                 state,
                 button: MouseButton::Right,
@@ -128,6 +620,13 @@ impl App<Txts> for CollisionSimulator {
                     self.right_clicked = false;
                 }
             },
+            WindowEvent::DroppedFile(path) if path.extension().is_some_and(|extension| extension == "ron") || is_json_scene(path) => {
+                self.run_command(Command::LoadScene(path.display().to_string()));
+            }
+            WindowEvent::DroppedFile(path) => self.import_table(path),
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.shift_held = modifiers.shift();
+            }
             WindowEvent::CursorMoved { position, .. } => {
                 let x = position.x as f32 / self.graphics.window().inner_size().width as f32;
                 let y = position.y as f32 / self.graphics.window().inner_size().height as f32;
@@ -154,366 +653,1585 @@ impl App<Txts> for CollisionSimulator {
     }
 }
 
-struct TraversedVolume {
-    points: Vec<Vec2>,
-}
-
-impl TraversedVolume {
-    pub fn from_object(object: Object, target_time: f32) -> Self {
-        let mut future_object = object.clone();
-        future_object.update(target_time);
-
-        let points = convex_hull(
-            object
-                .shape
-                .points
-                .into_iter()
-                .map(|(p, _)| p.rotate_rad(object.rotation) + object.position)
-                .chain(
-                    future_object
-                        .shape
-                        .points
-                        .into_iter()
-                        .map(|(p, _)| p.rotate_rad(object.rotation) + future_object.position),
-                )
-                .collect::<Vec<_>>(),
-        );
-        Self { points }
+impl CollisionSimulator {
+    fn spawn_objects(&mut self) {
+        if self.right_clicked {
+            let rng = &mut self.world.rng;
+            let velocity = vec2(rng.range(Stream::Spawn, -0.5..0.5), rng.range(Stream::Spawn, -0.5..0.5)) * 5.;
+            let rotation = rng.range(Stream::Spawn, 0. ..PI * 2.);
+            let shape = command::Shape::Regular(rng.index(Stream::Shapes, 5) + 3);
+            let spawn = Command::Spawn {
+                shape,
+                position: self.camera.screen_to_world(self.cursor_position),
+                velocity,
+                rotation,
+                restitution: self.spawn_restitution,
+            };
+            // Crowded spots are skipped, holding the button fills the gaps as they open
+            if let Some(spawn) = self.free_spawn(spawn) {
+                self.run_command(spawn);
+            }
+        }
     }
-}
-
-#[derive(PartialEq, PartialOrd, Debug, Clone, Copy)]
-struct CollisionInfo {
-    time: f32,
-    object_1: usize,
-    object_1_col_stamp: usize,
-    point_1: usize,
-    object_2: usize,
-    object_2_col_stamp: usize,
-    line_2: usize
-}
-
-impl Eq for CollisionInfo {}
-
-impl Ord for CollisionInfo {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+    /// `spawn` moved to the closest position its object overlaps nothing at, `None` if there's no
+    /// room within [`SPAWN_NUDGE`]. Other commands are kept as they are.
+    fn free_spawn(&self, spawn: Command) -> Option<Command> {
+        let Command::Spawn { shape, position, velocity, rotation, restitution } = spawn else {
+            return Some(spawn);
+        };
+        let probe = shape.object(position, velocity, rotation);
+        let position = self.world.free_position(&probe, SPAWN_NUDGE)?;
+        Some(Command::Spawn { shape, position, velocity, rotation, restitution })
+    }
+    /// Keeps the certificate for display and dumps the first violating step
+    fn check_certificate(&mut self, certificate: Certificate, before: Option<scene::Scene>, dt: f32) {
+        if !certificate.is_valid() {
+            if let (0, Some(scene)) = (self.certificate_violations, before) {
+                let dump = crash::ViolationDump {
+                    dt,
+                    scene,
+                    certificate: certificate.clone(),
+                };
+                match dump.write() {
+                    Ok(path) => warn!(path = %path.display(), frame = certificate.frame, "step violated its certificate, wrote dump"),
+                    Err(e) => warn!(error = %e, "step violated its certificate, writing the dump failed"),
+                }
+            }
+            self.certificate_violations += certificate.violations.len();
+        }
+        self.certificate = Some(certificate);
+    }
+    fn write_crash_dump(&mut self) {
+        let dump = crash::CrashDump {
+            panic: crash::take_last_panic(),
+            frame: self.world.frame,
+            scene: scene::Scene::from_world(&self.world),
+            pending_collisions: self.world.collision_queue.iter().map(|Reverse(toi)| *toi).collect(),
+            recent_collisions: std::mem::take(&mut self.world.recent_collisions),
+        };
+        match dump.write() {
+            Ok(path) => error!(path = %path.display(), "simulation panicked, wrote crash dump"),
+            Err(e) => error!(error = %e, "simulation panicked, writing crash dump failed"),
+        }
     }
-}
 
-impl CollisionSimulator {
-    pub fn update_objects(&mut self, dt: f32) {
-        let mut active_objects = vec![];
+    /// Index of the object containing `point`, the one with the closest center if they overlap
+    fn object_at(&self, point: Vec2) -> Option<usize> {
+        self.quadtree
+            .query_point(point)
+            .into_iter()
+            // The tree is from the last step, objects may have been removed since
+            .filter_map(|i| Some((i, self.world.objects.get(i)?)))
+            .filter(|(_, object)| self.is_visible(object) && !self.world.layer_of(object).is_some_and(|layer| layer.locked))
+            .filter(|(_, object)| polygon_contains_point(&object.shape.points, object.to_local(point)))
+            .min_by_key(|(_, object)| F32Ord(object.position.distance_squared(point)))
+            .map(|(i, _)| i)
+    }
+    /// Impulse magnitudes when zoomed in and the object labels, painted by egui in screen space
+    fn draw_labels(&self) {
+        let context = self.graphics.egui_platform.context();
+        let painter = context.debug_painter();
+        let screen = context.screen_rect();
+        let font = egui::FontId::monospace(LABEL_FONT_SIZE);
+
+        if self.show_impulses && self.camera.scale.x > IMPULSE_LABEL_ZOOM {
+            for arrow in &self.impulse_arrows {
+                let tip = self.camera.world_to_egui(arrow.point + arrow.impulse * IMPULSE_ARROW_SCALE, screen);
+                let text = format!("{:.3}", arrow.impulse.length());
+                painter.text(tip, egui::Align2::LEFT_BOTTOM, text, font.clone(), egui::Color32::WHITE);
+            }
+        }
 
-        for object in std::mem::take(&mut self.objects) {
-            if object.collided > 100 {
+        if self.show_labels {
+            for (i, object) in self.world.objects.iter().enumerate() {
+                if self.labels_selection_only && !self.selection.contains(&i) || !self.is_visible(object) {
+                    continue;
+                }
+                let anchor = object.position + Vec2::Y * object.bounding_circle.radius;
+                let position = self.camera.world_to_egui(anchor, screen);
+                if !screen.contains(position) {
+                    continue;
+                }
+                let text = format!("{} v={:.2} m={}", object_name(i, object), object.velocity.length(), object.mass);
+                painter.text(position, egui::Align2::CENTER_BOTTOM, text, font.clone(), egui::Color32::YELLOW);
+            }
+        }
+    }
+    /// Drops a ball onto a floor and compares the measured coefficient of restitution to the
+    /// configured one
+    fn restitution_wizard(&mut self, ui: &mut egui::Ui) {
+        ui.label("1. Choose the height the ball is dropped from and its restitution");
+        ui.add(egui::Slider::new(&mut self.drop_height, 0.5..=10.).text("Drop height"));
+        ui.add(egui::Slider::new(&mut self.drop_restitution, 0.0..=1.).text("Restitution"));
+        ui.label("2. Run the drop, this replaces the current scene");
+        ui.horizontal(|ui| {
+            if ui.button("Drop").clicked() {
+                self.drop_test = Some(DropTest::setup(&mut self.world, self.drop_height, self.drop_restitution));
+                self.forget_selection();
+                self.record_edit();
+                if let Some(aabb) = self.objects_aabb(0..self.world.objects.len()) {
+                    self.camera.fit(&aabb);
+                }
+            }
+            if self.drop_test.is_some() && ui.button("Stop measuring").clicked() {
+                self.drop_test = None;
+            }
+        });
+        ui.label("3. Wait for a few bounces");
+        let Some(drop_test) = &self.drop_test else {
+            return;
+        };
+        ui.separator();
+        for (i, (height, restitution)) in drop_test.apex_heights.iter().zip(drop_test.bounce_restitutions()).enumerate() {
+            ui.label(format!("Bounce {}: apex {height:.4}, e = {restitution:.4}", i + 1));
+        }
+        match drop_test.restitution() {
+            Some(measured) => {
+                let expected = drop_test.configured;
+                ui.label(format!("Measured e = {measured:.4}, configured e = {expected:.4}"));
+                ui.label(format!("Error: {:+.2}%", (measured - expected) / expected * 100.));
+            }
+            None => {
+                ui.label("Waiting for the first bounce");
+            }
+        }
+    }
+    fn replay_window(&mut self, ui: &mut egui::Ui) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        ui.label(format!("Frame {} / {}", replay.frame, replay.recording.frames.len()));
+        let last_frame = replay.frame.checked_sub(1).and_then(|frame| replay.recording.frames.get(frame));
+        for command in last_frame.map_or(&[][..], |frame| &frame.commands) {
+            ui.monospace(command.to_string());
+        }
+        match &replay.divergence {
+            Some(divergence) => {
+                ui.colored_label(egui::Color32::RED, format!("Diverged at frame {}", divergence.frame));
+                egui::Grid::new("replay_diff").striped(true).show(ui, |ui| {
+                    ui.label("Object");
+                    ui.label("Recorded");
+                    ui.label("Replayed");
+                    ui.end_row();
+                    for diff in &divergence.diffs {
+                        ui.label(diff.index.to_string());
+                        for state in [&diff.recorded, &diff.replayed] {
+                            ui.label(match state {
+                                Some(state) => format!(
+                                    "p ({:.6}, {:.6})\nv ({:.6}, {:.6})\nθ {:.6} ω {:.6}",
+                                    state.position.x,
+                                    state.position.y,
+                                    state.velocity.x,
+                                    state.velocity.y,
+                                    state.rotation,
+                                    state.rot_velocity
+                                ),
+                                None => "missing".to_string(),
+                            });
+                        }
+                        ui.end_row();
+                    }
+                });
+            }
+            None if replay.is_finished() => {
+                ui.label("Replay matched the recording");
+            }
+            None => (),
+        }
+        if ui.button("Stop replay").clicked() {
+            self.replay = None;
+        }
+    }
+    fn rewind_window(&mut self, ui: &mut egui::Ui) {
+        let Some(rewind) = &self.rewind else {
+            return;
+        };
+        let (Some(first), Some(last)) = (rewind.get(0), rewind.get(rewind.len().wrapping_sub(1))) else {
+            ui.label("Nothing recorded yet, the buffer fills as the world steps");
+            return;
+        };
+        let (first_time, last_time, len) = (first.time, last.time, rewind.len());
+        // Between steps the world is at its newest snapshot unless it was scrubbed back
+        let current = rewind.position(self.world.frame).unwrap_or(len - 1);
+        let current_time = rewind.get(current).map_or(last_time, |snapshot| snapshot.time);
+        let mut index = current;
+        let slider = egui::Slider::new(&mut index, 0..=len - 1).show_value(false).text(format!("{:+.3} s", current_time - last_time));
+        let scrubbed = ui.add(slider).on_hover_text("Pauses and shows the world after that step, resuming steps on from there").changed();
+        ui.label(format!("{len} steps over {:.2} s", last_time - first_time));
+        ui.horizontal(|ui| {
+            if ui.add_enabled(current > 0, egui::Button::new("◀ Step back")).clicked() {
+                index = current - 1;
+            }
+            if ui.add_enabled(current + 1 < len, egui::Button::new("Step forward ▶")).clicked() {
+                index = current + 1;
+            }
+        });
+        if scrubbed || index != current {
+            if let Some(snapshot) = self.rewind.as_ref().and_then(|rewind| rewind.get(index)) {
+                snapshot.restore(&mut self.world);
+                self.render_start = None;
+                if self.world.objects.len() != self.selection_object_count {
+                    self.forget_selection();
+                }
+                self.record_edit();
+                if !self.paused {
+                    self.run_command(Command::Pause);
+                }
+            }
+        }
+        if let Some(rewind) = &mut self.rewind {
+            let seconds = egui::Slider::new(&mut rewind.seconds, 1.0..=30.).suffix(" s").text("Length");
+            ui.add(seconds).on_hover_text("Every step is a copy of all objects, long buffers of many objects take a lot of memory");
+        }
+    }
+    fn checkpoint_window(&mut self, ui: &mut egui::Ui) {
+        let Some(checkpoint) = &self.checkpoint else {
+            return;
+        };
+        let diff = checkpoint.diff(&Checkpoint::capture(&self.world));
+        ui.label(format!(
+            "Since frame {} ({:.3} s ago)",
+            checkpoint.frame,
+            self.world.time_elapsed - checkpoint.time
+        ));
+        ui.label(format!(
+            "{} changed, {} added, {} removed",
+            diff.objects.len(),
+            diff.added.len(),
+            diff.removed.len()
+        ));
+        ui.label(format!(
+            "Max |Δp| {:.6}, max |Δv| {:.6}",
+            diff.max_position_delta(),
+            diff.max_velocity_delta()
+        ));
+        ui.label(format!(
+            "Contacts: {} gained, {} lost",
+            diff.contacts_gained.len(),
+            diff.contacts_lost.len()
+        ));
+        let select = ui.add_enabled(!diff.objects.is_empty(), egui::Button::new("Select changed"));
+        if select.clicked() {
+            self.selection = diff.objects.iter().map(|delta| delta.index).collect();
+            self.selection_object_count = self.world.objects.len();
+        }
+        egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+            egui::Grid::new("checkpoint_diff").striped(true).show(ui, |ui| {
+                ui.label("Object");
+                ui.label("Δp");
+                ui.label("Δv");
+                ui.label("Δθ");
+                ui.label("Δω");
+                ui.end_row();
+                for delta in &diff.objects {
+                    ui.label(delta.index.to_string());
+                    ui.label(format!("({:.6}, {:.6})", delta.position.x, delta.position.y));
+                    ui.label(format!("({:.6}, {:.6})", delta.velocity.x, delta.velocity.y));
+                    ui.label(format!("{:.6}", delta.rotation));
+                    ui.label(format!("{:.6}", delta.rot_velocity));
+                    ui.end_row();
+                }
+            });
+        });
+    }
+    /// Every change the UI, the console and scripts make goes through here, the recorder notes
+    /// them and failures go to the console
+    fn run_command(&mut self, command: Command) {
+        // Spawns from the console and scripts are placed like the ones under the cursor, the
+        // recorded command has the position they ended up at
+        let Some(command) = self.free_spawn(command) else {
+            self.console.print("no room to spawn there");
+            return;
+        };
+        if let Some(recorder) = &mut self.recorder {
+            recorder.command(&command);
+        }
+        if let Some(session) = &mut self.session {
+            session.input(&self.world, Input::Command(command.clone()));
+        }
+        let object_count = self.world.objects.len();
+        let result = command.run(&mut self.world);
+        // Removals shift the indices after them, spawns leave the selection as it is
+        if self.world.objects.len() < object_count {
+            self.forget_selection();
+        }
+        let command = match result {
+            Ok(Some(command)) => command,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(error = %e, "command failed");
+                self.console.print(e.to_string());
+                return;
+            }
+        };
+        match command {
+            Command::Pause => self.paused = true,
+            Command::Resume => self.paused = false,
+            Command::Save(path) => match scene::Scene::from_world(&self.world).save(&path) {
+                Ok(()) => self.console.print(format!("saved {path}")),
+                Err(e) => self.console.print(format!("saving {path} failed: {e}")),
+            },
+            Command::LoadScene(path) => self.scene_load = Some(SceneLoad::start(path.into())),
+            Command::Run(path) => self.run_script(&path),
+            // Ran on the world already
+            _ => (),
+        }
+    }
+    /// Replaces the world with a preset
+    fn load_preset(&mut self, build: presets::Preset) {
+        build(&mut self.world);
+        self.forget_selection();
+        self.record_edit();
+    }
+    /// Notes a change made to the world outside of commands in the session being recorded, as a
+    /// snapshot of the whole scene the replay loads in its place
+    fn record_edit(&mut self) {
+        if let Some(session) = &mut self.session {
+            // Loading the snapshot empties the replay's cache, its contents can decide
+            // borderline pairs
+            self.world.pair_cache.clear();
+            session.input(&self.world, Input::Load(Box::new(scene::Scene::from_world(&self.world))));
+        }
+    }
+    /// Drops the selection once the objects were replaced or removed, the indices point elsewhere
+    /// now
+    fn forget_selection(&mut self) {
+        self.selection.clear();
+        self.selection_object_count = self.world.objects.len();
+    }
+    /// Pauses and advances the world by one step, at the fixed rate's step length if there's one
+    fn step_once(&mut self) {
+        if !self.paused {
+            self.run_command(Command::Pause);
+        }
+        self.single_step = true;
+    }
+    fn run_script(&mut self, path: &str) {
+        let script = match std::fs::read_to_string(path) {
+            Ok(script) => script,
+            Err(e) => {
+                self.console.print(format!("reading {path} failed: {e}"));
+                return;
+            }
+        };
+        let commands = match Command::parse_script(&script) {
+            Ok(commands) => commands,
+            Err((line, e)) => {
+                self.console.print(format!("{path}:{line}: {e}"));
+                return;
+            }
+        };
+        self.console.print(format!("running {} commands from {path}", commands.len()));
+        for command in commands {
+            // A script running itself would never end
+            if let Command::Run(nested) = &command {
+                self.console.print(format!("{path}: skipped running {nested}, scripts can't run scripts"));
                 continue;
             }
-            active_objects.push(object);
+            self.run_command(command);
         }
-        self.objects = active_objects;
-    
-        for object in &mut self.objects {
-            object.update(self.time_elapsed+0.001);
+    }
+    /// Selects the objects whose replayed state differs from the recording
+    fn select_divergence(&mut self) {
+        let Some(divergence) = self.replay.as_ref().and_then(|replay| replay.divergence.as_ref()) else {
+            return;
+        };
+        self.selection = divergence
+            .diffs
+            .iter()
+            .map(|diff| diff.index)
+            .filter(|&i| i < self.world.objects.len())
+            .collect();
+        self.selection_object_count = self.world.objects.len();
+        warn!(frame = divergence.frame, objects = divergence.diffs.len(), "replay diverged");
+    }
+    /// Applies the inputs a session replay hands back and ends it once it's through
+    fn apply_played(&mut self, played: Vec<Input>) {
+        for input in played {
+            // Commands already ran, pausing would only stall the replay
+            if let Input::Camera { focus, zoom } = input {
+                self.camera.look_at(focus, zoom);
+            }
         }
-
-        if self.right_clicked {
-            let spawning_object = Object::new(
-                self.camera.screen_to_world(self.cursor_position),
-                vec2(rand::random::<f32>()-0.5, rand::random::<f32>()-0.5) * 5.,
-                rand::random::<f32>() % (PI * 2.),
-                Shape::from_polygon(rand::random::<usize>() % 5 + 3),
-            );
-
-            self.objects.push(spawning_object);
+        let Some(player) = self.session_player.as_ref().filter(|player| player.is_finished()) else {
+            return;
+        };
+        match player.diverged_at {
+            Some(step) => warn!(step, "session replay diverged, something changed the world outside of commands"),
+            None => info!(steps = player.session.steps.len(), "session replay matched"),
         }
+        self.session_player = None;
     }
-    fn update_collisions(&mut self, dt: f32) {
-        println!("Number of objects: {}", self.objects.len());
-
-        let mut time_measure = Instant::now();
-        let mut collisions_pq = BinaryHeap::new();
-
-        let mut bounds = vec![];
-        let mut bounds_rev = vec![];
-
-        macro_rules! compute_x_bounds {
-            ($i: expr) => {
-                {
-                    let traversed_volume = TraversedVolume::from_object(self.objects[$i].clone(), self.time_elapsed);
-                    let x_s = traversed_volume.points.iter().map(|p| F32Ord(p.x)).collect::<Vec<_>>();
-                    let min_x = *x_s.iter().min().unwrap();
-                    let max_x = *x_s.iter().max().unwrap();
-                    (min_x, max_x)
+    /// Lists the linter's warnings, each one's objects a click away
+    fn lint_window(&mut self, ui: &mut egui::Ui) {
+        if self.lint_warnings.is_empty() {
+            ui.label("Nothing suspicious");
+            return;
+        }
+        let mut focus = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for warning in &self.lint_warnings {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::YELLOW, warning.to_string());
+                    if ui.small_button("Focus").clicked() {
+                        focus = Some(warning.objects());
+                    }
+                });
+            }
+        });
+        let Some(objects) = focus else {
+            return;
+        };
+        self.selection = objects.into_iter().filter(|&i| i < self.world.objects.len()).collect();
+        self.selection_object_count = self.world.objects.len();
+        // Objects flung towards infinity have no box worth fitting
+        if let Some(aabb) = self.objects_aabb(self.selection.iter().copied()).filter(|aabb| aabb.min.is_finite() && aabb.max.is_finite()) {
+            self.camera.fit(&aabb);
+        }
+    }
+    fn workspace(&self) -> Workspace {
+        Workspace {
+            scene: scene::Scene::from_world(&self.world),
+            camera_focus: self.camera.focus(),
+            camera_zoom: self.camera.zoom(),
+            paused: self.paused,
+            fixed_rate: self.fixed_step.map(|fixed_step| fixed_step.rate),
+            spawn_restitution: self.spawn_restitution,
+            fill_style: self.fill_style,
+            panels: Panels {
+                log: self.show_log,
+                stats: self.show_stats,
+                lint: self.show_lint,
+                camera_path: self.show_camera_path,
+                phase_plot: self.show_phase_plot,
+                restitution_wizard: self.show_restitution_wizard,
+                impulses: self.show_impulses,
+                labels: self.show_labels,
+                labels_selection_only: self.labels_selection_only,
+                heat_map: self.show_heat_map,
+                contact_graph: self.show_contact_graph,
+                outlines: self.show_outlines,
+            },
+            selection: self.selection.clone(),
+        }
+    }
+    fn open_workspace(&mut self, workspace: Workspace) {
+        workspace.scene.load_into(&mut self.world);
+        self.record_edit();
+        self.camera.look_at(workspace.camera_focus, workspace.camera_zoom);
+        self.paused = workspace.paused;
+        self.fixed_step = workspace.fixed_rate.map(FixedTimestep::new);
+        self.render_start = None;
+        self.spawn_restitution = workspace.spawn_restitution;
+        self.fill_style = workspace.fill_style;
+        let panels = workspace.panels;
+        self.show_log = panels.log;
+        self.show_stats = panels.stats;
+        self.show_lint = panels.lint;
+        self.show_camera_path = panels.camera_path;
+        self.show_phase_plot = panels.phase_plot;
+        self.show_restitution_wizard = panels.restitution_wizard;
+        self.show_impulses = panels.impulses;
+        self.show_labels = panels.labels;
+        self.labels_selection_only = panels.labels_selection_only;
+        self.show_heat_map = panels.heat_map;
+        self.show_contact_graph = panels.contact_graph;
+        self.show_outlines = panels.outlines;
+        // What the overlays gathered belongs to the previous world
+        self.lint_warnings.clear();
+        self.heat_map.clear();
+        self.contact_graph.clear();
+        let count = self.world.objects.len();
+        self.selection = workspace.selection.into_iter().filter(|&i| i < count).collect();
+        self.selection_object_count = count;
+    }
+    fn workspaces_window(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.workspace_name);
+            let save = ui.add_enabled(!self.workspace_name.is_empty(), egui::Button::new("Save"));
+            if save.on_hover_text("Saves the scene, camera, settings, open panels and selection").clicked() {
+                match self.workspace().save(&self.workspace_name) {
+                    Ok(()) => info!(name = %self.workspace_name, "saved workspace"),
+                    Err(e) => warn!(name = %self.workspace_name, error = %e, "saving the workspace failed"),
+                }
+                self.workspaces = workspace::list();
+            }
+        });
+        let mut deleted = false;
+        for name in self.workspaces.clone() {
+            ui.horizontal(|ui| {
+                ui.label(&name);
+                if ui.button("Open").clicked() {
+                    match Workspace::load(&name) {
+                        Ok(workspace) => {
+                            info!(name = %name, "opened workspace");
+                            self.open_workspace(workspace);
+                            self.workspace_name = name.clone();
+                        }
+                        Err(e) => warn!(name = %name, error = %e, "opening the workspace failed"),
+                    }
+                }
+                if ui.button("Delete").clicked() {
+                    if let Err(e) = workspace::delete(&name) {
+                        warn!(name = %name, error = %e, "deleting the workspace failed");
+                    }
+                    deleted = true;
                 }
+            });
+        }
+        if deleted {
+            self.workspaces = workspace::list();
+        }
+    }
+    fn save_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        match recorder.recording.save(RECORDING_PATH) {
+            Ok(()) => info!(path = RECORDING_PATH, frames = recorder.recording.frames.len(), "saved recording"),
+            Err(e) => warn!(path = RECORDING_PATH, error = %e, "saving the recording failed"),
+        }
+    }
+    fn save_prefab(&mut self) {
+        let Some(prefab) = Prefab::from_selection(self.prefab_name.trim(), &self.world.objects, &self.selection) else {
+            return;
+        };
+        self.prefabs.insert(prefab);
+        match self.prefabs.save(PREFAB_LIBRARY_PATH) {
+            Ok(()) => info!(path = PREFAB_LIBRARY_PATH, name = self.prefab_name.trim(), "saved prefab"),
+            Err(e) => warn!(path = PREFAB_LIBRARY_PATH, error = %e, "saving the prefab library failed"),
+        }
+    }
+    /// Places the chosen prefab at `point` and selects the new objects
+    fn stamp_prefab(&mut self, point: Vec2) {
+        let Some(prefab) = self.stamping.and_then(|i| self.prefabs.prefabs.get(i)) else {
+            return;
+        };
+        self.selection = prefab.stamp(&mut self.world, point, self.stamp_rotation).collect();
+        self.selection_object_count = self.world.objects.len();
+    }
+    fn prefabs_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.prefab_name);
+            let can_save = !self.selection.is_empty() && !self.prefab_name.trim().is_empty();
+            let save = ui.add_enabled(can_save, egui::Button::new("Save selection"));
+            if save.on_hover_text("Shift-click objects to select them").clicked() {
+                self.save_prefab();
             }
+        });
+        let mut removed = None;
+        for (i, prefab) in self.prefabs.prefabs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let stamping = self.stamping == Some(i);
+                let label = format!("{} ({} objects)", prefab.name, prefab.objects.len());
+                if ui.selectable_label(stamping, label).on_hover_text("Left-click places it").clicked() {
+                    self.stamping = (!stamping).then_some(i);
+                }
+                if ui.small_button("Delete").clicked() {
+                    removed = Some(i);
+                }
+            });
         }
-
-        for i in 0..self.objects.len() {
-            let bound = compute_x_bounds!(i);
-
-            bounds.push((bound.0, bound.1, i));
-            bounds_rev.push((bound.1, bound.0, i));
+        if let Some(i) = removed {
+            self.prefabs.prefabs.remove(i);
+            self.stamping = None;
+            if let Err(e) = self.prefabs.save(PREFAB_LIBRARY_PATH) {
+                warn!(path = PREFAB_LIBRARY_PATH, error = %e, "saving the prefab library failed");
+            }
         }
-
-        println!("Computing bounds took: {:?}", time_measure.elapsed());
-        time_measure = Instant::now();
-
-        let mut bounds_left_bt = BTreeSet::from_iter(bounds.clone());
-        let mut bounds_right_bt = BTreeSet::from_iter(bounds_rev);
-
-        println!("Tree construction: {:?}", time_measure.elapsed());
-
-        for i in 0..self.objects.len() {
-            let mut candidates = vec![];
-
-            // might contain duplicates (segments that are entirely inside) but we don't care, doesn't change anything
-            for bound in bounds_left_bt.range(bounds[i]..(bounds[i].1, F32Ord(0.), 0)) {
-                candidates.push(bound.2);
+        if self.stamping.is_some() {
+            ui.add(egui::Slider::new(&mut self.stamp_rotation, -PI..=PI).text("Stamp rotation"));
+        }
+    }
+    fn save_session(&mut self) {
+        let Some(recorder) = self.session.take() else {
+            return;
+        };
+        match recorder.session.save(SESSION_PATH) {
+            Ok(()) => info!(path = SESSION_PATH, inputs = recorder.session.inputs.len(), "saved session"),
+            Err(e) => warn!(path = SESSION_PATH, error = %e, "saving the session failed"),
+        }
+    }
+    fn start_session_replay(&mut self) {
+        match Session::load(SESSION_PATH) {
+            Ok(session) => {
+                info!(path = SESSION_PATH, steps = session.steps.len(), "replaying session");
+                self.paused = false;
+                self.session_player = Some(SessionPlayer::start(session, &mut self.world));
+            }
+            Err(e) => warn!(path = SESSION_PATH, error = %e, "loading the session failed"),
+        }
+    }
+    fn start_replay(&mut self) {
+        match Recording::load(RECORDING_PATH) {
+            Ok(recording) => {
+                info!(path = RECORDING_PATH, frames = recording.frames.len(), "replaying recording");
+                self.replay = Some(Replay::start(recording, &mut self.world));
             }
-            for bound in bounds_right_bt.range(bounds[i]..(bounds[i].1, F32Ord(0.), 0)) {
-                candidates.push(bound.2);
+            Err(e) => warn!(path = RECORDING_PATH, error = %e, "loading the recording failed"),
+        }
+    }
+    /// Writes the object states of the current frame as CSV and JSON to the working directory
+    fn export_states(&self) {
+        let states = self.world.snapshot_states();
+        for extension in ["csv", "json"] {
+            let path = format!("perfect-collisions-state-{}.{extension}", self.world.frame);
+            match export::save(&states, &path) {
+                Ok(()) => info!(path, "exported object states"),
+                Err(e) => warn!(path, error = %e, "export failed"),
             }
-            for candidate in candidates {
-                if let Some(col_info) = self.check_collision(i, candidate) {
-                    collisions_pq.push(Reverse(col_info));
+        }
+    }
+    fn is_visible(&self, object: &Object) -> bool {
+        self.world.layer_of(object).is_none_or(|layer| layer.visible)
+    }
+    fn time_regions_ui(&mut self, ui: &mut egui::Ui) {
+        let (mut removed, mut edited) = (None, false);
+        for (i, region) in self.world.time_regions.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                edited |= ui.add(egui::Slider::new(&mut region.scale, 0.1..=4.).logarithmic(true).text("Time scale")).changed();
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(i);
                 }
-                if let Some(col_info) = self.check_collision(candidate, i) {
-                    collisions_pq.push(Reverse(col_info));
+            });
+        }
+        if let Some(i) = removed {
+            self.world.time_regions.remove(i);
+            edited = true;
+        }
+        if ui.button("Add region in view").on_hover_text("Covers the middle of the view, half speed").clicked() {
+            let view = self.camera.visible_area();
+            let half_size = view.size() / 4.;
+            self.world.time_regions.push(TimeRegion {
+                area: Aabb::new(view.center() - half_size, view.center() + half_size),
+                scale: 0.5,
+            });
+            edited = true;
+        }
+        if edited {
+            self.record_edit();
+        }
+    }
+    fn broad_phase_ui(&mut self, ui: &mut egui::Ui) {
+        let mut edited = false;
+        egui::ComboBox::from_label("Sweep axis")
+            .selected_text(format!("{:?}", self.world.sweep_axis))
+            .show_ui(ui, |ui| {
+                for axis in [SweepAxis::X, SweepAxis::Y] {
+                    edited |= ui.selectable_value(&mut self.world.sweep_axis, axis, format!("{axis:?}")).changed();
                 }
+            });
+        let stats = &self.world.broad_phase;
+        let busiest = (0..stats.candidates.len()).max_by_key(|&i| stats.candidates[i]);
+        egui::Grid::new("broad_phase").striped(true).show(ui, |ui| {
+            let rows = [
+                ("Candidates", stats.total_candidates().to_string()),
+                ("Per object", format!("{:.1} mean, {} max", stats.mean_candidates(), stats.max_candidates())),
+                ("Exact queries", format!("{} ({} impacts)", stats.narrow_checks, stats.impacts)),
+                ("False positives", format!("{:.0}%", stats.false_positive_rate() * 100.)),
+                ("Cached / discrete", format!("{} / {}", stats.cached, stats.discrete)),
+                ("Overlaps x / y", format!("{} / {}", stats.overlaps[0], stats.overlaps[1])),
+                ("Tree build", format!("{:.2} ms", stats.build_time * 1000.)),
+                ("Tree updates", format!("{} in {:.2} ms", stats.updates, stats.update_time * 1000.)),
+            ];
+            for (name, value) in rows {
+                ui.label(name);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+        if let Some(i) = busiest.filter(|&i| stats.candidates[i] > 0) {
+            if ui.link(format!("Most candidates: object {i}")).clicked() {
+                self.selection = vec![i];
             }
         }
+        for advice in broad_phase::advise(&self.world) {
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::YELLOW, format!("Suggestion: {advice}"));
+                if ui.small_button("Apply").clicked() {
+                    match advice {
+                        Advice::SwitchAxis { axis } => self.world.sweep_axis = axis,
+                        Advice::EnablePairCache { .. } => self.world.pair_cache.enabled = true,
+                        Advice::BatchCollisions { .. } => self.world.batch_tolerance = Some(BATCH_TOLERANCE),
+                    }
+                    edited = true;
+                }
+            });
+        }
+        if edited {
+            self.record_edit();
+        }
+    }
+    fn layers_ui(&mut self, ui: &mut egui::Ui) {
+        let mut edited = false;
+        egui::Grid::new("layers").striped(true).show(ui, |ui| {
+            ui.label("Layer");
+            ui.label("Visible");
+            ui.label("Locked");
+            ui.label("Simulate");
+            ui.end_row();
+            for (i, layer) in self.world.layers.iter_mut().enumerate() {
+                edited |= ui.text_edit_singleline(&mut layer.name).changed();
+                edited |= ui.checkbox(&mut layer.visible, "").changed();
+                edited |= ui.checkbox(&mut layer.locked, "").changed();
+                edited |= ui.checkbox(&mut layer.simulate, "").changed();
+                let move_selection = ui.add_enabled(!self.selection.is_empty(), egui::Button::new("Move selection here"));
+                if move_selection.clicked() {
+                    for &selected in &self.selection {
+                        if let Some(object) = self.world.objects.get_mut(selected) {
+                            object.layer = i;
+                        }
+                    }
+                    edited = true;
+                }
+                ui.end_row();
+            }
+        });
+        if ui.button("Add layer").clicked() {
+            let name = format!("Layer {}", self.world.layers.len() + 1);
+            self.world.layers.push(Layer::new(name));
+            edited = true;
+        }
+        if edited {
+            self.record_edit();
+        }
+    }
+    /// Approximate heap bytes per subsystem
+    fn memory_breakdown(&self) -> Vec<(&'static str, usize)> {
+        let world = WorldMemory::of(&self.world);
+        let snapshots = self.recorder.as_ref().map_or(0, |recorder| recorder.recording.heap_bytes())
+            + self.replay.as_ref().map_or(0, |replay| replay.recording.heap_bytes())
+            + self.autosave.pending_restore.as_ref().map_or(0, scene::Scene::heap_bytes)
+            + self.rewind.as_ref().map_or(0, RewindBuffer::heap_bytes);
+        vec![
+            ("Objects", world.objects),
+            ("Shapes", world.shapes),
+            ("Collision structures", world.collisions),
+            ("Render quadtree", self.quadtree.heap_bytes()),
+            ("Debug points", memory::deque_bytes(&self.debug_points)),
+            ("Impulse arrows", memory::vec_bytes(&self.impulse_arrows)),
+            ("Heat map", self.heat_map.heap_bytes()),
+            ("Contact graph", self.contact_graph.heap_bytes()),
+            ("Histograms", self.stats.heap_bytes()),
+            ("Energy budget", self.energy_chart.as_ref().map_or(0, EnergyChart::heap_bytes)),
+            ("Phase plot", self.phase_plot.heap_bytes()),
+            ("Exhaust", self.rocket.as_ref().map_or(0, Rocket::heap_bytes)),
+            ("Log", self.log_buffer.heap_bytes()),
+            ("Recordings and snapshots", snapshots),
+        ]
+    }
+    /// Swaps in the scene the background load produced once it's done
+    fn finish_scene_load(&mut self) {
+        let Some(outcome) = self.scene_load.as_mut().and_then(SceneLoad::poll) else {
+            return;
+        };
+        let Some(load) = self.scene_load.take() else {
+            return;
+        };
+        let path = load.path.display();
+        match outcome {
+            Ok(Some(loaded)) => {
+                info!(path = %path, count = loaded.objects.len(), "loaded scene");
+                if let Some(settings) = loaded.settings {
+                    settings.apply(&mut self.world);
+                }
+                self.world.load(loaded.objects, loaded.time_elapsed);
+                self.forget_selection();
+                if !loaded.layers.is_empty() {
+                    self.world.layers = loaded.layers;
+                }
+                if let Some(gravity) = loaded.gravity {
+                    self.world.gravity = gravity;
+                }
+                self.world.teams = loaded.teams;
+                self.world.joints = loaded.joints;
+                self.world.gravity_sources = loaded.gravity_sources;
+                self.world.time_regions = loaded.time_regions;
+                if let Some(rng) = loaded.rng {
+                    self.world.rng = rng;
+                }
+                self.record_edit();
+            }
+            Ok(None) => info!(path = %path, "scene load cancelled"),
+            Err(e) => warn!(path = %path, error = %e, "loading the scene failed"),
+        }
+    }
+    /// Spawns the objects of a dropped table of initial conditions
+    fn import_table(&mut self, path: &std::path::Path) {
+        match import::load(path) {
+            Ok(mut objects) => {
+                info!(path = %path.display(), count = objects.len(), "imported objects");
+                for object in &mut objects {
+                    object.cur_time = self.world.time_elapsed;
+                }
+                self.world.objects.extend(objects);
+                self.record_edit();
+            }
+            Err(e) => warn!(path = %path.display(), error = %e, "import failed"),
+        }
+    }
+    fn toggle_selection(&mut self, point: Vec2) {
+        let Some(i) = self.object_at(point) else {
+            return;
+        };
+        if let Some(position) = self.selection.iter().position(|&selected| selected == i) {
+            self.selection.remove(position);
+        } else {
+            self.selection.push(i);
+        }
+        self.selection_object_count = self.world.objects.len();
+    }
+    /// World space box around the given objects, `None` when there are none
+    fn objects_aabb(&self, indices: impl Iterator<Item = usize>) -> Option<Aabb> {
+        indices
+            .filter_map(|i| self.world.objects.get(i))
+            .map(|object| {
+                Aabb::from_points(&object.shape.points.iter().map(|p| object.to_world(*p)).collect::<Vec<_>>())
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+    /// Left-drag flicks an object at the grabbed point, Q/E spin the hovered object
+    fn apply_tools(&mut self, dt: f32) {
+        if let Some((start, end)) = self.pending_flick.take() {
+            if let Some(i) = self.object_at(start) {
+                self.run_command(Command::ApplyImpulse {
+                    object: i,
+                    impulse: (end - start) * FLICK_STRENGTH,
+                    point: Some(start),
+                });
+            }
+        }
+        if self.spin_input != 0. {
+            if let Some(i) = self.object_at(self.camera.screen_to_world(self.cursor_position)) {
+                self.run_command(Command::ApplyAngularImpulse {
+                    object: i,
+                    impulse: self.spin_input * SPIN_TORQUE * dt,
+                });
+            }
+        }
+    }
 
-        println!("Detecting init collisions took {:?}", time_measure.elapsed());
-        time_measure = Instant::now();
-
-        while let Some(Reverse(col_info)) = collisions_pq.pop() {
-            if self.handle_collision(col_info) {
-                for i in [col_info.object_1, col_info.object_2] {
-                    let new_bound = compute_x_bounds!(i);
-                    let new_bound = (new_bound.0, new_bound.1, i);
-
-                    let old_bound = bounds[i];
-                    bounds[i] = new_bound;
-
-                    bounds_left_bt.remove(&old_bound);
-                    bounds_right_bt.remove(&(old_bound.1, old_bound.0, old_bound.2));
+    pub fn update_camera(&mut self) {
+        if self.middle_clicked {
+            let delta = (self.cursor_position - self.last_cursor_position) / self.camera.scale;
+            self.camera.0 = self.camera.translate(delta);
+        }
+    }
+    pub fn draw_ui(&mut self) {
+        if let Some(command) = self.console.show(&self.graphics.egui_platform.context()) {
+            self.run_command(command);
+        }
+        egui::Window::new("Simulation Info").show(&self.graphics.egui_platform.context(), |ui| {
+            ui.label(format!("Time: {}", self.world.time_elapsed));
+            ui.label(format!("Energy: {}", self.world.total_energy()));
+            ui.label(format!("Frame rate: {}", self.frame_rate));
+            ui.label(format!("Physics rate: {:.0} Hz", self.physics_rate));
+            ui.label(format!("Objects count: {}", self.world.objects.len()));
+            for team in &self.world.teams {
+                ui.label(format!("{}: {}", team.name, team.score));
+            }
+            ui.horizontal(|ui| {
+                let mut paused = self.paused;
+                if ui.checkbox(&mut paused, "Paused").on_hover_text("Space toggles it, the backtick key opens the console").changed() {
+                    self.run_command(if paused { Command::Pause } else { Command::Resume });
+                }
+                if ui.button("Step").on_hover_text("Advances the paused world by one step, the period key does too").clicked() {
+                    self.step_once();
+                }
+            });
+            let time_scale = egui::Slider::new(&mut self.time_scale, 0.1..=10.).logarithmic(true).suffix("x").text("Time scale");
+            ui.add(time_scale).on_hover_text("Simulated seconds per real second");
+            let spawn_restitution = egui::Slider::new(&mut self.spawn_restitution, 0.0..=1.).text("Spawn restitution");
+            ui.add(spawn_restitution).on_hover_text("Bounciness of the objects spawned with the right button");
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut self.seed).prefix("Seed: "));
+                let reseed = ui.button("Reseed").on_hover_text("Restarts the shapes, velocities and rotations of spawns from the seed");
+                if reseed.clicked() {
+                    self.run_command(Command::SetSeed(self.seed));
+                }
+            });
+            let cursor_world = self.camera.screen_to_world(self.cursor_position);
+            if let Some(i) = self.object_at(cursor_world) {
+                let velocity = self.world.objects[i].velocity_at_point(cursor_world);
+                ui.label(format!("Hovered point velocity: ({:.3}, {:.3})", velocity.x, velocity.y));
+            }
+            let mut margin = self.world.collision_margin;
+            if ui.add(egui::Slider::new(&mut margin, 0.0..=0.05).text("Collision margin")).changed() {
+                self.run_command(Command::SetMargin(margin));
+            }
+            let mut gravity = self.world.gravity.y;
+            if ui.add(egui::Slider::new(&mut gravity, -20.0..=0.).text("Gravity")).changed() {
+                self.run_command(Command::SetGravity(vec2(self.world.gravity.x, gravity)));
+            }
+            // Settings changed here rather than through commands are recorded as snapshots
+            let mut edited = false;
+            egui::ComboBox::from_label("Integrator")
+                .selected_text(format!("{:?}", self.world.integrator))
+                .show_ui(ui, |ui| {
+                    for integrator in [Integrator::Euler, Integrator::Leapfrog] {
+                        edited |= ui.selectable_value(&mut self.world.integrator, integrator, format!("{integrator:?}")).changed();
+                    }
+                });
+            let mut fixed = self.fixed_step.is_some();
+            let fixed_checkbox = ui.checkbox(&mut fixed, "Fixed physics rate");
+            let fixed_hover = "Steps at a fixed rate whatever the frame rate, the frames in between follow the motion";
+            if fixed_checkbox.on_hover_text(fixed_hover).changed() {
+                self.fixed_step = fixed.then(|| FixedTimestep::new(FIXED_RATE));
+                self.render_start = None;
+            }
+            if let Some(fixed_step) = &mut self.fixed_step {
+                let rate = egui::Slider::new(&mut fixed_step.rate, 5. ..=240.).logarithmic(true).suffix(" Hz").text("Steps per second");
+                if ui.add(rate).changed() {
+                    fixed_step.reset();
+                }
+            }
+            let certify = ui.checkbox(&mut self.world.certify, "Certify steps");
+            if certify.on_hover_text("Checks every pair for tunneling after each step, slow with many objects").changed() {
+                self.certificate = None;
+                self.certificate_violations = 0;
+            }
+            if let Some(certificate) = self.world.certify.then_some(self.certificate.as_ref()).flatten() {
+                ui.label(format!(
+                    "Pairs: {} collided, {} disjoint, {} cleared, {} separated",
+                    certificate.collided, certificate.disjoint, certificate.cleared, certificate.separated
+                ));
+                ui.label(format!("Violations: {}", self.certificate_violations));
+            }
+            let mut audit = self.world.energy_audit.is_some();
+            let audit_checkbox = ui.checkbox(&mut audit, "Audit collision energy");
+            if audit_checkbox.on_hover_text("Compares the energy every collision leaves with what its restitution takes").changed() {
+                self.world.energy_audit = audit.then(EnergyAudit::default);
+            }
+            if let Some(audit) = &self.world.energy_audit {
+                ui.label(format!("Energy drift: {:+.2e} J over {} collisions", audit.drift, audit.checked));
+                ui.label(format!("Violations: {}", audit.violations));
+            }
+            let mut budget = self.world.energy_budget.is_some();
+            let budget_checkbox = ui.checkbox(&mut budget, "Energy budget");
+            if budget_checkbox.on_hover_text("Charts which subsystems changed the kinetic energy every step").changed() {
+                self.world.energy_budget = budget.then(EnergyBudget::default);
+                self.energy_chart = budget.then(EnergyChart::new);
+                // Islands are stepped as one while a budget is kept
+                edited = true;
+            }
+            edited |= ui
+                .checkbox(&mut self.world.enforce_conservation, "Enforce conservation")
+                .on_hover_text("Restores the momentum and energy after every step, undoes external forces too")
+                .changed();
+            if self.world.enforce_conservation {
+                let correction = self.world.last_correction;
+                ui.label(format!(
+                    "Correction: Δp = {:.2e}, ΔE = {:+.2e}",
+                    correction.momentum.length(),
+                    correction.energy
+                ));
+            }
+            edited |= ui.checkbox(&mut self.world.pair_cache.enabled, "Pair cache").changed();
+            let mut substeps = self.world.substeps.is_some();
+            let substeps_checkbox = ui.checkbox(&mut substeps, "Island substeps");
+            if substeps_checkbox.on_hover_text("Fast or crowded groups of objects take shorter steps").changed() {
+                self.world.substeps = substeps.then(SubstepPolicy::default);
+                edited = true;
+            }
+            let mut adaptive_quality = self.world.frame_budget.is_some();
+            if ui.checkbox(&mut adaptive_quality, "Adaptive quality").changed() {
+                self.world.frame_budget = adaptive_quality.then_some(FRAME_BUDGET);
+            }
+            if self.world.simplified {
+                ui.label("Over budget, colliding simplified proxies");
+            }
+            let mut batching = self.world.batch_tolerance.is_some();
+            if ui.checkbox(&mut batching, "Batch independent collisions").changed() {
+                self.world.batch_tolerance = batching.then_some(BATCH_TOLERANCE);
+                edited = true;
+            }
+            if edited {
+                self.record_edit();
+            }
+            ui.label(format!("Cached pairs: {} ({} hits)", self.world.pair_cache.len(), self.world.pair_cache.hits));
+            let mut demo = None;
+            egui::ComboBox::from_label("Demo").selected_text("Load…").show_ui(ui, |ui| {
+                for (name, build) in presets::DEMOS {
+                    if ui.selectable_label(false, name).clicked() {
+                        demo = Some(build);
+                    }
+                }
+            });
+            if let Some(build) = demo {
+                self.load_preset(build);
+                if let Some(aabb) = self.objects_aabb(0..self.world.objects.len()) {
+                    self.camera.fit(&aabb);
+                }
+            }
+            if ui.button("Shaker table").clicked() {
+                self.load_preset(presets::shaker_table);
+            }
+            if ui.button("Funnel pile").clicked() {
+                self.load_preset(presets::funnel_pile);
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Thin wall bullet").clicked() {
+                    self.load_preset(presets::thin_wall_bullet);
+                }
+                if ui.button("Spinning needle").clicked() {
+                    self.load_preset(presets::spinning_needle);
+                }
+            });
+            if ui.button("Orbit slingshot").clicked() {
+                self.load_preset(presets::orbit_slingshot);
+                self.orbit = Some(OrbitView::new());
+                let view = Vec2::splat(14.);
+                self.camera.fit(&Aabb::new(-view, view));
+            }
+            if self.orbit.is_some() {
+                ui.label("Drag the tip of the probe's velocity arrow to change its orbit");
+            }
+            if ui.button("Jointed chain").clicked() {
+                self.load_preset(presets::jointed_chain);
+                let view = Vec2::splat(presets::JOINTED_CHAIN_VIEW);
+                self.camera.fit(&Aabb::new(-view, view));
+            }
+            if ui.button("Planet").clicked() {
+                self.load_preset(presets::planet);
+                let view = Vec2::splat(presets::PLANET_VIEW);
+                self.camera.fit(&Aabb::new(-view, view));
+            }
+            if ui.button("Team match").clicked() {
+                self.load_preset(presets::team_match);
+                let view = vec2(9., 5.);
+                self.camera.fit(&Aabb::new(-view, view));
+            }
+            if ui.button("Rocket in asteroid field").clicked() {
+                self.rocket = Some(Rocket::spawn(&mut self.world));
+                self.forget_selection();
+                self.record_edit();
+                let view = Vec2::splat(2. * presets::ASTEROID_CLEARING);
+                self.camera.fit(&Aabb::new(-view, view));
+            }
+            if self.rocket.as_ref().is_some_and(Rocket::is_alive) {
+                ui.label("Arrow up thrusts, left and right turn the rocket");
+            }
+            ui.label("Drop a .csv or .json table of objects on the window to spawn them, or a .ron or .json scene to load it");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.scene_path);
+                let save = ui.button("Save scene").on_hover_text("Objects, joints, settings and random streams, as JSON for .json files");
+                if save.clicked() {
+                    self.run_command(Command::Save(self.scene_path.clone()));
+                }
+                if ui.add_enabled(self.scene_load.is_none(), egui::Button::new("Load scene")).clicked() {
+                    self.run_command(Command::LoadScene(self.scene_path.clone()));
+                }
+            });
+            if ui.button("Export state").clicked() {
+                self.export_states();
+            }
+            ui.horizontal(|ui| {
+                if let Some(recorder) = &self.recorder {
+                    ui.label(format!("Recording, {} frames", recorder.recording.frames.len()));
+                    if ui.button("Stop & save").clicked() {
+                        self.save_recording();
+                    }
+                } else if self.replay.is_none() && self.session.is_none() && self.session_player.is_none() {
+                    if ui.button("Start recording").clicked() {
+                        self.recorder = Some(Recorder::start(&mut self.world));
+                    }
+                    if ui.button("Replay recording").clicked() {
+                        self.start_replay();
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                if let Some(session) = &self.session {
+                    let inputs = session.session.inputs.len();
+                    ui.label(format!("Recording session, {inputs} inputs"));
+                    if ui.button("Stop & save session").clicked() {
+                        self.save_session();
+                    }
+                } else if let Some(player) = &self.session_player {
+                    ui.label(format!("Replaying session, step {} / {}", player.step, player.session.steps.len()));
+                    if ui.button("Stop session replay").clicked() {
+                        self.session_player = None;
+                    }
+                } else if self.replay.is_none() && self.recorder.is_none() {
+                    let record = ui.button("Record session").on_hover_text("Records the inputs only, replays at any frame rate");
+                    if record.clicked() {
+                        self.session = Some(SessionRecorder::start(&mut self.world));
+                    }
+                    if ui.button("Replay session").clicked() {
+                        self.start_session_replay();
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Take checkpoint").clicked() {
+                    self.checkpoint = Some(Checkpoint::capture(&self.world));
+                }
+                if self.checkpoint.is_some() && ui.button("Clear checkpoint").clicked() {
+                    self.checkpoint = None;
+                }
+            });
+            let mut keep_steps = self.rewind.is_some();
+            let rewind_checkbox = ui.checkbox(&mut keep_steps, "Rewind buffer");
+            if rewind_checkbox.on_hover_text("Keeps the last seconds of steps to scrub back through").changed() {
+                self.rewind = keep_steps.then(|| RewindBuffer::new(rewind::DEFAULT_SECONDS));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Fit all").clicked() {
+                    if let Some(aabb) = self.objects_aabb(0..self.world.objects.len()) {
+                        self.camera.fit(&aabb);
+                    }
+                }
+                let fit_selection = ui.add_enabled(!self.selection.is_empty(), egui::Button::new("Fit selection"));
+                if fit_selection.on_hover_text("Shift-click objects to select them").clicked() {
+                    if let Some(aabb) = self.objects_aabb(self.selection.iter().copied()) {
+                        self.camera.fit(&aabb);
+                    }
+                }
+            });
+            ui.checkbox(&mut self.show_log, "Show log");
+            ui.checkbox(&mut self.show_stats, "Show collision histograms");
+            if ui.checkbox(&mut self.show_lint, "Physics linter").on_hover_text("Flags suspicious states after every frame").changed() {
+                self.lint_warnings.clear();
+            }
+            ui.checkbox(&mut self.show_camera_path, "Camera path");
+            ui.checkbox(&mut self.show_phase_plot, "Phase plot");
+            ui.checkbox(&mut self.show_restitution_wizard, "Restitution experiment");
+            ui.checkbox(&mut self.show_workspaces, "Workspaces");
+            ui.collapsing("Rendering", |ui| {
+                let theme = self.settings.theme;
+                egui::ComboBox::from_label("Theme")
+                    .selected_text(theme.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in <Theme as strum::IntoEnumIterator>::iter() {
+                            ui.selectable_value(&mut self.settings.theme, option, option.to_string());
+                        }
+                    });
+                if self.settings.theme != theme {
+                    ui.ctx().set_visuals(self.settings.theme.visuals());
+                    self.settings.save();
+                }
+                egui::ComboBox::from_label("Fill")
+                    .selected_text(self.fill_style.to_string())
+                    .show_ui(ui, |ui| {
+                        for style in <FillStyle as strum::IntoEnumIterator>::iter() {
+                            ui.selectable_value(&mut self.fill_style, style, style.to_string());
+                        }
+                    });
+                ui.add_enabled(self.fill_style != FillStyle::Wireframe, egui::Checkbox::new(&mut self.show_outlines, "Outlines"));
+            });
+            ui.collapsing("Layers", |ui| self.layers_ui(ui));
+            ui.collapsing("Prefabs", |ui| self.prefabs_ui(ui));
+            ui.collapsing("Time regions", |ui| self.time_regions_ui(ui));
+            ui.collapsing("Broad phase", |ui| self.broad_phase_ui(ui));
+            ui.collapsing("Memory", |ui| {
+                let breakdown = self.memory_breakdown();
+                egui::Grid::new("memory").striped(true).show(ui, |ui| {
+                    for (subsystem, bytes) in &breakdown {
+                        ui.label(*subsystem);
+                        ui.label(format_bytes(*bytes));
+                        ui.end_row();
+                    }
+                    ui.strong("Total");
+                    ui.strong(format_bytes(breakdown.iter().map(|(_, bytes)| bytes).sum()));
+                    ui.end_row();
+                });
+                if ui.button("Clear debug points").clicked() {
+                    self.debug_points.clear();
+                }
+            });
+            ui.collapsing("Debug overlay", |ui| {
+                ui.checkbox(&mut self.show_impulses, "Impulse arrows");
+                ui.checkbox(&mut self.show_labels, "Object labels");
+                if self.show_labels {
+                    ui.checkbox(&mut self.labels_selection_only, "Selected objects only");
+                }
+                if ui.checkbox(&mut self.show_heat_map, "Collision heat map").changed() {
+                    self.heat_map.clear();
+                }
+                if self.show_heat_map {
+                    let cell_slider = egui::Slider::new(&mut self.heat_map.cell_size, 0.1..=2.).text("Cell size");
+                    if ui.add(cell_slider).changed() {
+                        self.heat_map.rebin();
+                    }
+                    ui.add(egui::Slider::new(&mut self.heat_map.window, 1.0..=30.).text("Window (s)"));
+                }
+                if ui.checkbox(&mut self.show_contact_graph, "Contact graph").changed() {
+                    self.contact_graph.clear();
+                }
+                if self.show_contact_graph {
+                    let persistence = egui::Slider::new(&mut self.contact_graph.persistence, 1..=120).text("Persistence (steps)");
+                    ui.add(persistence);
+                }
+            });
+        });
 
-                    bounds_left_bt.insert(new_bound);
-                    bounds_right_bt.insert((new_bound.1, new_bound.0, new_bound.2));
+        self.draw_labels();
 
-                    let mut candidates = vec![];
+        if let Some(load) = &self.scene_load {
+            egui::Window::new("Loading scene").show(&self.graphics.egui_platform.context(), |ui| {
+                ui.label(load.path.display().to_string());
+                let progress = match load.fraction() {
+                    Some(fraction) => egui::ProgressBar::new(fraction).show_percentage(),
+                    None => egui::ProgressBar::new(0.).text("Parsing").animate(true),
+                };
+                ui.add(progress);
+                if ui.button("Cancel").clicked() {
+                    load.cancel();
+                }
+            });
+        }
 
-                    for bound in bounds_left_bt.range(new_bound..(new_bound.1, F32Ord(0.), 0)) {
-                        candidates.push(bound.2);
+        if self.autosave.pending_restore.is_some() {
+            egui::Window::new("Restore session").show(&self.graphics.egui_platform.context(), |ui| {
+                ui.label("The previous session didn't exit cleanly. Restore its last autosave?");
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        if let Some(scene) = self.autosave.pending_restore.take() {
+                            scene.load_into(&mut self.world);
+                            self.forget_selection();
+                            self.record_edit();
+                        }
                     }
-                    for bound in bounds_right_bt.range(new_bound..(new_bound.1, F32Ord(0.), 0)) {
-                        candidates.push(bound.2);
+                    if ui.button("Discard").clicked() {
+                        self.autosave.pending_restore = None;
                     }
+                });
+            });
+        }
 
-                    for candidate in candidates {
-                        if let Some(col_info) = self.check_collision(i, candidate) {
-                            collisions_pq.push(Reverse(col_info));
-                        }
-                        if let Some(col_info) = self.check_collision(candidate, i) {
-                            collisions_pq.push(Reverse(col_info));
-                        }
+        if let Some((i, object)) = self.selection.last().and_then(|&i| Some(i).zip(self.world.objects.get_mut(i))) {
+            let mut commands = vec![];
+            egui::Window::new("Inspector").show(&self.graphics.egui_platform.context(), |ui| {
+                ui.label(format!("Index: {i}"));
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    let mut name = object.name.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut name).changed() {
+                        object.name = (!name.is_empty()).then_some(name);
+                    }
+                });
+                ui.label(format!("Mass: {}", object.mass));
+                ui.label(format!("Position: ({:.3}, {:.3})", object.position.x, object.position.y));
+                let (velocity, _) = object.motion();
+                ui.label(format!("Velocity: ({:.3}, {:.3})", velocity.x, velocity.y));
+                ui.label(format!("Collisions: {} ({} impacts)", object.collided, object.impacts));
+                let mut restitution = object.restitution;
+                if ui.add(egui::Slider::new(&mut restitution, 0.0..=1.).text("Restitution")).changed() {
+                    commands.push(Command::SetProperty {
+                        object: i,
+                        property: Property::Restitution(restitution),
+                    });
+                }
+                let mut gravity_scale = object.gravity_scale;
+                let gravity_slider = egui::Slider::new(&mut gravity_scale, -1.0..=2.).text("Gravity scale");
+                if ui.add(gravity_slider).on_hover_text("0 floats, negative rises").changed() {
+                    commands.push(Command::SetProperty {
+                        object: i,
+                        property: Property::GravityScale(gravity_scale),
+                    });
+                }
+                let mut z_index = object.z_index;
+                ui.horizontal(|ui| {
+                    ui.label("Z index");
+                    let drag = ui.add(egui::DragValue::new(&mut z_index)).on_hover_text("Higher is drawn over lower");
+                    if drag.changed() {
+                        commands.push(Command::SetProperty {
+                            object: i,
+                            property: Property::ZIndex(z_index),
+                        });
                     }
+                });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("CCD")
+                        .selected_text(match object.ccd {
+                            CcdMode::Always => "Always",
+                            CcdMode::FastOnly(_) => "Fast only",
+                            CcdMode::Never => "Never",
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut object.ccd, CcdMode::Always, "Always");
+                            if ui.selectable_label(matches!(object.ccd, CcdMode::FastOnly(_)), "Fast only").clicked() {
+                                object.ccd = CcdMode::FastOnly(DEFAULT_CCD_THRESHOLD);
+                            }
+                            ui.selectable_value(&mut object.ccd, CcdMode::Never, "Never");
+                        });
+                    if let CcdMode::FastOnly(threshold) = &mut object.ccd {
+                        ui.add(egui::DragValue::new(threshold).speed(0.1).suffix(" u/s"));
+                    }
+                });
+                let mut paused = object.is_paused();
+                let pause_checkbox = ui.checkbox(&mut paused, "Paused");
+                if pause_checkbox.on_hover_text("Time stands still for it, it resumes with its velocity").changed() {
+                    commands.push(if paused { Command::Freeze { object: i } } else { Command::Unfreeze { object: i } });
+                }
+                let shatter = ui.add_enabled(object.mass.is_finite(), egui::Button::new("Shatter"));
+                let shatter = shatter.on_hover_text("Breaks it into pieces thrown apart by a small explosion");
+                if shatter.clicked() {
+                    commands.push(Command::Shatter {
+                        object: i,
+                        pieces: SHATTER_PIECES,
+                        energy: -SHATTER_ENERGY * object.mass,
+                    });
+                }
+                if ui.button("Delete").clicked() {
+                    commands.push(Command::Delete { object: i });
                 }
+            });
+            for command in commands {
+                self.run_command(command);
             }
         }
-        println!("Handling collisions + extra took: {:?}", time_measure.elapsed());
 
-    }
-    fn handle_collision(&mut self, col_info: CollisionInfo) -> bool {
-        let sharp_obj = &self.objects[col_info.object_1];
-        let other_obj = &self.objects[col_info.object_2];
-
-        if col_info.object_1_col_stamp != sharp_obj.updated || col_info.object_2_col_stamp != other_obj.updated {
-            return false;
+        if self.show_camera_path {
+            egui::Window::new("Camera path").show(&self.graphics.egui_platform.context(), |ui| {
+                self.camera_path.show(ui, &self.camera, &mut self.new_keyframe_time);
+            });
         }
 
-        let col_position = sharp_obj.shape.points[col_info.point_1].0.rotate_rad(sharp_obj.rotation)+sharp_obj.position+sharp_obj.velocity*(col_info.time-sharp_obj.cur_time);
-        self.debug_points.push(col_position);
+        if self.show_restitution_wizard {
+            egui::Window::new("Restitution experiment").show(&self.graphics.egui_platform.context(), |ui| {
+                self.restitution_wizard(ui);
+            });
+        }
 
-        let col_line_a = other_obj.shape.points[col_info.line_2].0.rotate_rad(other_obj.rotation);
-        let col_line_b = other_obj.shape.points[(col_info.line_2+1)%other_obj.shape.points.len()].0.rotate_rad(other_obj.rotation);
+        if self.rewind.is_some() {
+            egui::Window::new("Rewind").show(&self.graphics.egui_platform.context(), |ui| self.rewind_window(ui));
+        }
 
-        let normal = (col_line_a-col_line_b).perp().normalize();
+        if self.replay.is_some() {
+            egui::Window::new("Replay").show(&self.graphics.egui_platform.context(), |ui| {
+                self.replay_window(ui);
+            });
+        }
 
-        let rel_velocity = sharp_obj.velocity-other_obj.velocity;
+        if self.checkpoint.is_some() {
+            egui::Window::new("Checkpoint diff").show(&self.graphics.egui_platform.context(), |ui| {
+                self.checkpoint_window(ui);
+            });
+        }
 
-        let impulse_numerator = -2. * rel_velocity.dot(normal);
-        let impulse_denominator = (1./sharp_obj.mass) + (1./other_obj.mass);
-        let impulse = impulse_numerator / impulse_denominator;
+        if self.show_phase_plot {
+            egui::Window::new("Phase plot").show(&self.graphics.egui_platform.context(), |ui| {
+                self.phase_plot.show(ui);
+            });
+        }
 
-        self.objects[col_info.object_1].update(col_info.time);
-        self.objects[col_info.object_2].update(col_info.time);
+        if self.show_contact_graph {
+            let clicked = egui::Window::new("Contact graph")
+                .show(&self.graphics.egui_platform.context(), |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.contact_graph.show(ui, &self.world)).inner
+                })
+                .and_then(|response| response.inner.flatten());
+            if let Some(i) = clicked {
+                self.selection = vec![i];
+                self.selection_object_count = self.world.objects.len();
+            }
+        }
 
-        let mass1 = self.objects[col_info.object_1].mass;
-        let mass2 = self.objects[col_info.object_2].mass;
-        self.objects[col_info.object_1].velocity += impulse * normal / mass1;
-        self.objects[col_info.object_2].velocity -= impulse * normal / mass2;
+        if self.show_stats {
+            egui::Window::new("Collision histograms").show(&self.graphics.egui_platform.context(), |ui| {
+                self.stats.show(ui);
+            });
+        }
 
-        self.objects[col_info.object_1].collided += 1;
-        self.objects[col_info.object_2].collided += 1;
+        if let Some(energy_chart) = self.energy_chart.as_ref().filter(|_| self.world.energy_budget.is_some()) {
+            egui::Window::new("Energy budget").show(&self.graphics.egui_platform.context(), |ui| energy_chart.show(ui));
+        }
 
-        self.objects[col_info.object_1].position += normal * 0.005;
-        self.objects[col_info.object_2].position -= normal * 0.005;
+        if self.show_workspaces {
+            egui::Window::new("Workspaces").show(&self.graphics.egui_platform.context(), |ui| self.workspaces_window(ui));
+        }
 
-        true
-    }
+        if self.show_lint {
+            egui::Window::new("Warnings").show(&self.graphics.egui_platform.context(), |ui| self.lint_window(ui));
+        }
 
-    pub fn update_camera(&mut self) {
-        if self.middle_clicked {
-            let delta = (self.cursor_position - self.last_cursor_position) / self.camera.scale;
-            self.camera.0 = self.camera.translate(delta);
+        if self.show_log {
+            egui::Window::new("Log").show(&self.graphics.egui_platform.context(), |ui| {
+                if ui.button("Clear").clicked() {
+                    self.log_buffer.clear();
+                }
+                egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                    for (level, line) in self.log_buffer.lines() {
+                        ui.label(format!("{level:>5} {line}"));
+                    }
+                });
+            });
         }
     }
-    pub fn draw_ui(&mut self) {
-        egui::Window::new("Simulation Info").show(&self.graphics.egui_platform.context(), |ui| {
-            ui.label(format!("Time: {}", self.time_elapsed));
-            ui.label(format!("Energy: {}", self.total_energy()));
-            ui.label(format!("Frame rate: {}", self.frame_rate));
-            ui.label(format!("Objects count: {}", self.objects.len()));
-        });
-    }
     pub fn draw_objects(&mut self) {
-        for object in &self.objects {
-            let traversed_volume = TraversedVolume::from_object(object.clone(), self.time_elapsed+0.001);
-            self.graphics.add_geometry(
+        let palette = self.settings.theme.palette();
+        let mut visible = self.quadtree.query(&self.camera.visible_area());
+        // Keeps the overlapping objects stacked in the same order every frame
+        visible.sort_unstable();
+        visible.retain(|&i| self.world.objects.get(i).is_some_and(|object| self.is_visible(object)));
+        for (i, object) in visible.into_iter().map(|i| (i, &self.world.objects[i])) {
+            let traversed_volume = TraversedVolume::from_object(object.clone(), self.world.time_elapsed+0.001);
+            self.draw_queue.add(
+                Depth::SweptVolume,
                 Shape::new(
                     traversed_volume
                         .points
                 )
-                .set_texture(Txts::Blue)
-                .apply(self.camera.0)
-                .into(),
+                .set_color(palette.swept_volume)
+                .apply(self.camera.0),
             );
 
-            let object_gtransform =
-                GTransform::from_translation(object.position).rotate(object.rotation);
-            self.graphics.add_geometry(
-                object
-                    .shape
-                    .clone()
-                    .apply(object_gtransform)
-                    .apply(self.camera.0)
-                    .into(),
-            );
+            let selected = self.selection.contains(&i);
+            let depth = Depth::of(object, selected);
+            let team = object.team.and_then(|team| self.world.teams.get(team));
+            let color = match team {
+                _ if selected => palette.selection,
+                Some(team) => Color::from_rgb(team.color[0], team.color[1], team.color[2]),
+                None => palette.object,
+            };
+            let pose = self.render_pose(i);
+            let points = object.shape.points.iter().map(|p| pose.apply(*p)).collect::<Vec<_>>();
+            let fill = match self.fill_style {
+                FillStyle::Solid => Some(Shape::<Txts>::new(points.clone()).set_color(color)),
+                FillStyle::Textured => Some(Shape::<Txts>::new(points.clone()).set_texture(Txts::Blue).set_color(color)),
+                FillStyle::Wireframe => None,
+            };
+            if let Some(fill) = fill {
+                self.draw_queue.add(depth, fill.apply(self.camera.0));
+            }
+            // Wireframes are all outline, filled shapes get a contrasting border
+            let outline_color = match self.fill_style {
+                FillStyle::Wireframe => Some(color),
+                _ => self.show_outlines.then_some(palette.outline),
+            };
+            if let Some(outline_color) = outline_color {
+                for edge in render::outline(&points, outline_color, &self.camera) {
+                    self.draw_queue.add(depth, edge);
+                }
+            }
+        }
+    }
+    /// Where object `i` is drawn, between the last two fixed steps while stepping at a fixed rate
+    fn render_pose(&self, i: usize) -> Transform2 {
+        let object = &self.world.objects[i];
+        let start = self.render_start.as_ref().filter(|start| !self.paused && start.len() == self.world.objects.len());
+        match (start, &self.fixed_step) {
+            (Some(start), Some(fixed_step)) => interpolate_pose(&start[i], object, fixed_step.alpha()),
+            _ => object.transform,
         }
     }
     pub fn draw_debug(&mut self) {
+        if self.show_heat_map {
+            self.heat_map.draw(&mut self.draw_queue, &self.camera);
+        }
+        if self.show_contact_graph {
+            self.contact_graph.draw(&self.world, &mut self.draw_queue, &self.camera);
+        }
+        let palette = self.settings.theme.palette();
         for point in &self.debug_points {
-            let circle = Shape::from_circle(20).set_texture(Txts::Blue).apply(GTransform::from_translation(*point).inflate(0.05)).apply(self.camera.0);
-            self.graphics.add_geometry(circle.into());
+            let circle = Shape::from_circle(20).set_color(palette.debug).apply(GTransform::from_translation(*point).inflate(0.05)).apply(self.camera.0);
+            self.draw_queue.add(Depth::Debug, circle);
         }
-    }
-    pub fn total_energy(&mut self) -> f32 {
-        let mut total_energy = 0.;
-        for object in &self.objects {
-            total_energy += 0.5 * object.mass * object.velocity.length_squared();
+        // Same on-screen thickness at every zoom
+        let thickness = 0.004 / self.camera.scale.x;
+        for arrow in &self.impulse_arrows {
+            let length = arrow.impulse.length() * IMPULSE_ARROW_SCALE;
+            let angle = arrow.impulse.y.atan2(arrow.impulse.x);
+            let shaft = Shape::from_line(length, thickness)
+                .set_color(palette.debug)
+                .apply(GTransform::from_translation(arrow.point).rotate(angle))
+                .apply(self.camera.0);
+            // The triangle points along +y
+            let head = Shape::from_triangle()
+                .set_color(palette.debug)
+                .apply(
+                    GTransform::from_translation(arrow.point + arrow.impulse * IMPULSE_ARROW_SCALE)
+                        .rotate(angle - PI / 2.)
+                        .inflate(thickness * 4.),
+                )
+                .apply(self.camera.0);
+            self.draw_queue.add(Depth::Debug, shaft);
+            self.draw_queue.add(Depth::Debug, head);
         }
-        total_energy
-    }
-
-    fn check_collisions(&self, obj_id: usize) -> Vec<CollisionInfo> {
-        let mut result = vec![];
-
-        for i in 0..self.objects.len() {
-            if obj_id == i {
-                continue;
+        // Every jointed object's center is connected to its anchors
+        for joint in &self.world.joints {
+            let ends = [Some((joint.a, joint.anchor_a)), joint.b.map(|b| (b, joint.anchor_b))];
+            for (i, anchor) in ends.into_iter().flatten() {
+                let pose = self.render_pose(i);
+                let arm = pose.apply(anchor) - pose.translation;
+                let line = Shape::from_line(arm.length(), thickness)
+                    .set_color(palette.debug)
+                    .apply(GTransform::from_translation(pose.translation).rotate(arm.y.atan2(arm.x)))
+                    .apply(self.camera.0);
+                self.draw_queue.add(Depth::Debug, line);
             }
-            if let Some(collision) = self.check_collision(obj_id, i) {
-                result.push(collision);
+        }
+        // Flagged objects are outlined so they stand out of a crowd
+        for i in self.lint_warnings.iter().flat_map(Warning::objects) {
+            if let Some(object) = self.world.objects.get(i) {
+                let points = object.shape.points.iter().map(|p| object.to_world(*p)).collect::<Vec<_>>();
+                for edge in render::outline(&points, palette.debug, &self.camera) {
+                    self.draw_queue.add(Depth::Debug, edge);
+                }
             }
-            if let Some(collision) = self.check_collision(i, obj_id) {
-                result.push(collision);
+        }
+        for region in &self.world.time_regions {
+            let Aabb { min, max } = region.area;
+            let corners = [min, vec2(max.x, min.y), max, vec2(min.x, max.y)];
+            for (i, &corner) in corners.iter().enumerate() {
+                let edge = corners[(i + 1) % 4] - corner;
+                let line = Shape::from_line(edge.length(), thickness)
+                    .set_color(palette.debug)
+                    .apply(GTransform::from_translation(corner).rotate(edge.y.atan2(edge.x)))
+                    .apply(self.camera.0);
+                self.draw_queue.add(Depth::Debug, line);
             }
         }
-
-        result
     }
+}
 
-    /// Checks whether obj 1 collides with obj 2 with one of its corners
-    fn check_collision(&self, sharp_obj_id: usize, other_obj_id: usize) -> Option<CollisionInfo> {
-        let mut sharp_obj = self.objects[sharp_obj_id].clone();
-        let mut other_obj = self.objects[other_obj_id].clone();
-
-        let cur_time = sharp_obj.cur_time.max(other_obj.cur_time);
-
-        sharp_obj.position += sharp_obj.velocity * (cur_time-sharp_obj.cur_time);
-        other_obj.position += other_obj.velocity * (cur_time-other_obj.cur_time);
-
-        sharp_obj.velocity -= other_obj.velocity;
-        other_obj.velocity = Vec2::ZERO;
-
-        let sharp_obj_points = sharp_obj
-            .shape
-            .points
-            .iter()
-            .map(|(p, _)| p.rotate_rad(sharp_obj.rotation) + sharp_obj.position)
-            .collect::<Vec<_>>();
-
-        let other_obj_points = other_obj
-            .shape
-            .points
-            .iter()
-            .map(|(p, _)| p.rotate_rad(other_obj.rotation) + other_obj.position)
-            .collect::<Vec<_>>();
-
-
-        let mut collision: Option<CollisionInfo> = None;
+/// Whether a dropped `.json` file is a scene, tables of objects are arrays
+fn is_json_scene(path: &std::path::Path) -> bool {
+    scene::is_json(path)
+        && std::fs::read_to_string(path).is_ok_and(|text| text.trim_start().starts_with('{'))
+}
 
-        let check = |p: Vec2, v: Vec2, a: Vec2, b: Vec2| -> Option<f32> {
-            let slope_1 = (b.y - a.y) / (b.x - a.x);
-            let y_1 = a.y - a.x * slope_1;
-            let slope_2 = (v.y) / (v.x);
-            let y_2 = p.y-p.x * slope_2;
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f32 / 1024.),
+        _ => format!("{:.1} MiB", bytes as f32 / 1_048_576.),
+    }
+}
 
-            let intercept = (y_2 - y_1) / (slope_1 - slope_2);
+/// Command line of the viewer, `perfect-collisions [--seed <n>] [scene.ron]`
+#[derive(Default)]
+struct Args {
+    /// Scene loaded once the window is up
+    scene: Option<std::path::PathBuf>,
+    /// Seed of the world's random streams, 0 when not given
+    seed: Option<u64>,
+}
 
-            if intercept >= a.x.min(b.x) && intercept <= a.x.max(b.x) {
-                let time = (intercept - p.x) / (v.x) + cur_time;
-                if time > cur_time && time < self.time_elapsed {
-                    Some(time)
-                } else {
-                    None
+impl Args {
+    fn parse() -> Self {
+        let mut args = Self::default();
+        let mut words = std::env::args_os().skip(1);
+        while let Some(word) = words.next() {
+            if word == "--seed" {
+                match words.next().and_then(|seed| seed.to_str()?.parse().ok()) {
+                    Some(seed) => args.seed = Some(seed),
+                    None => warn!("--seed takes a whole number, ignoring it"),
                 }
             } else {
-                None
-            }
-        };
-
-        for (i, p) in sharp_obj_points.into_iter().enumerate() {
-            for j in 0..other_obj_points.len() {
-                let a = other_obj_points[j];
-                let b = other_obj_points[(j + 1) % other_obj_points.len()];
-
-                if let Some(time) = check(p, sharp_obj.velocity, a, b) {
-                    let candidate = CollisionInfo {
-                        time,
-                        object_1: sharp_obj_id,
-                        object_1_col_stamp: self.objects[sharp_obj_id].updated,
-                        point_1: i,
-                        object_2: other_obj_id,
-                        object_2_col_stamp: self.objects[other_obj_id].updated,
-                        line_2: j
-                    };
-                    if let Some(cur_answer) = &mut collision {
-                        *cur_answer = (*cur_answer).min(candidate);
-                    }
-                    else {
-                        collision = Some(candidate);
-                    }
-                }
+                args.scene = Some(word.into());
             }
         }
-
-        collision
+        args
     }
 }
 
+/// Name of the object if it has one, its index otherwise
+fn object_name(index: usize, object: &Object) -> String {
+    object.name.clone().unwrap_or_else(|| format!("#{index}"))
+}
+
 #[tokio::main]
 async fn main() {
     ellipsoid::run::<Txts, CollisionSimulator>().await;
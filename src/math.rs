@@ -0,0 +1,51 @@
+//! `f32` functions `core` doesn't have, `no_std` builds get them from `libm`.
+//!
+//! With `std` the inherent methods are used and this module is empty.
+
+/// Whenever `std` is linked anywhere in the build its inherent methods win, so the trait only
+/// gets used on real `no_std` targets
+#[cfg(not(feature = "std"))]
+#[allow(dead_code)]
+pub(crate) trait F32Ext {
+    fn sqrt(self) -> f32;
+    fn ceil(self) -> f32;
+    fn floor(self) -> f32;
+    fn sin(self) -> f32;
+    fn cos(self) -> f32;
+    fn sin_cos(self) -> (f32, f32);
+    fn atan2(self, other: f32) -> f32;
+    fn rem_euclid(self, rhs: f32) -> f32;
+}
+
+#[cfg(not(feature = "std"))]
+impl F32Ext for f32 {
+    fn sqrt(self) -> f32 {
+        libm::sqrtf(self)
+    }
+    fn ceil(self) -> f32 {
+        libm::ceilf(self)
+    }
+    fn floor(self) -> f32 {
+        libm::floorf(self)
+    }
+    fn sin(self) -> f32 {
+        libm::sinf(self)
+    }
+    fn cos(self) -> f32 {
+        libm::cosf(self)
+    }
+    fn sin_cos(self) -> (f32, f32) {
+        libm::sincosf(self)
+    }
+    fn atan2(self, other: f32) -> f32 {
+        libm::atan2f(self, other)
+    }
+    fn rem_euclid(self, rhs: f32) -> f32 {
+        let r = libm::fmodf(self, rhs);
+        if r < 0. {
+            r + rhs.abs()
+        } else {
+            r
+        }
+    }
+}
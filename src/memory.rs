@@ -0,0 +1,74 @@
+//! Approximate heap usage of the simulation, for spotting leaks.
+//!
+//! The numbers are the sizes of the allocations the containers hold, spare capacity included.
+//! Hash maps and trees are counted by their entries, their bookkeeping is left out.
+
+use alloc::collections::VecDeque;
+use core::mem::size_of;
+
+use super::*;
+
+pub fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * size_of::<T>()
+}
+
+pub fn deque_bytes<T>(deque: &VecDeque<T>) -> usize {
+    deque.capacity() * size_of::<T>()
+}
+
+/// Heap bytes of a [`World`] by subsystem
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorldMemory {
+    /// The object list and the objects' names
+    pub objects: usize,
+    /// Outlines and proxies
+    pub shapes: usize,
+    /// Collision queue, pair cache and the lists of resolved collisions
+    pub collisions: usize,
+}
+
+impl WorldMemory {
+    pub fn of(world: &World) -> Self {
+        let objects = vec_bytes(&world.objects)
+            + world
+                .objects
+                .iter()
+                .filter_map(|object| object.name.as_ref())
+                .map(|name| name.capacity())
+                .sum::<usize>();
+        let shapes = world
+            .objects
+            .iter()
+            .map(|object| vec_bytes(&object.shape.points) + object.proxy.as_ref().map_or(0, |proxy| vec_bytes(&proxy.points)))
+            .sum();
+        let collisions = world.collision_queue.capacity() * size_of::<Toi>()
+            + deque_bytes(&world.recent_collisions)
+            + vec_bytes(&world.resolved)
+            + world.pair_cache.heap_bytes();
+        Self {
+            objects,
+            shapes,
+            collisions,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.objects + self.shapes + self.collisions
+    }
+}
+
+#[test]
+fn test_world_memory_grows_with_objects() {
+    let mut world = World::new();
+    let empty = WorldMemory::of(&world);
+    for i in 0..10 {
+        world.objects.push(Object::new(vec2(i as f32 * 3., 0.), Vec2::ZERO, 0., Polygon::regular(12)));
+    }
+    world.objects[0].name = Some("named".into());
+    let full = WorldMemory::of(&world);
+
+    assert!(full.objects >= empty.objects + 10 * size_of::<Object>() + "named".len());
+    // The 12-gons have 4-vertex proxies
+    assert!(full.shapes >= 10 * 16 * size_of::<Vec2>());
+    assert_eq!(full.total(), full.objects + full.shapes + full.collisions);
+}
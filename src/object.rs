@@ -1,42 +1,499 @@
+use alloc::{borrow::Cow, boxed::Box, string::String};
+
+use controller::Controller;
+
 use super::*;
 
+/// Shapes with more vertices get a simplified proxy of this many vertices
+const PROXY_VERTICES: usize = 4;
+/// Sides of the polygon enclosing a rounded outline
+const ROUNDED_SIDES: usize = 32;
+
+/// Prescribed motion of a kinematic object, it has infinite mass and impulses don't move it
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Kinematic {
+    /// Keeps its current velocity
+    Constant,
+    /// Position follows `anchor + amplitude * sin(2π * frequency * time)`
+    Oscillate { anchor: Vec2, amplitude: Vec2, frequency: f32 },
+}
+
+impl Kinematic {
+    /// Prescribed position at `time`, `None` when the motion is just the current velocity
+    pub fn position_at(&self, time: f32) -> Option<Vec2> {
+        match *self {
+            Kinematic::Constant => None,
+            Kinematic::Oscillate { anchor, amplitude, frequency } => {
+                Some(anchor + amplitude * (2. * PI * frequency * time).sin())
+            }
+        }
+    }
+}
+
+/// How an object takes part in the simulation
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum BodyType {
+    /// Moved by its velocity, accelerations and collisions
+    #[default]
+    Dynamic,
+    /// Never moves, e.g. a floor or a wall. It has infinite mass, isn't integrated and the broad
+    /// phase keeps it in an index of its own.
+    Static,
+    /// Follows a prescribed motion, impulses don't move it
+    Kinematic(Kinematic),
+}
+
+/// Curved outline the solver collides exactly, the object's `shape` is only a polygon enclosing
+/// it for drawing, the bounds and the discrete overlap checks
+///
+/// The outline keeps a radius around a core of one or more points, the segments between them
+/// included, so collisions are exact distance queries between the cores.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Rounded {
+    Circle { radius: f32 },
+    /// Rounded around a segment along x from `-half_length` to `half_length`, e.g. a character
+    Capsule { half_length: f32, radius: f32 },
+}
+
+impl Rounded {
+    /// Points the outline is rounded around, in object space
+    pub fn core(&self) -> Vec<Vec2> {
+        match *self {
+            Self::Circle { .. } => vec![Vec2::ZERO],
+            Self::Capsule { half_length, .. } => vec![vec2(-half_length, 0.), vec2(half_length, 0.)],
+        }
+    }
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Circle { radius } | Self::Capsule { radius, .. } => radius,
+        }
+    }
+    /// Polygon whose edges touch the outline from outside
+    pub fn outline(&self) -> Polygon {
+        match *self {
+            Self::Circle { radius } => {
+                let mut polygon = Polygon::regular(ROUNDED_SIDES);
+                let circumradius = radius / (PI / ROUNDED_SIDES as f32).cos();
+                polygon.points.iter_mut().for_each(|p| *p *= circumradius);
+                polygon
+            }
+            Self::Capsule { half_length, radius } => {
+                // A cap around each end, from one side's vertex on the y axis to the other's
+                let circumradius = radius / (PI / ROUNDED_SIDES as f32).cos();
+                let cap = ROUNDED_SIDES / 2;
+                let points = [(half_length, -PI / 2.), (-half_length, PI / 2.)]
+                    .into_iter()
+                    .flat_map(|(x, start)| {
+                        (0..=cap).map(move |i| {
+                            let angle = start + PI * i as f32 / cap as f32;
+                            vec2(x, 0.) + Vec2::from_angle(angle) * circumradius
+                        })
+                    })
+                    .collect();
+                Polygon::new(points)
+            }
+        }
+    }
+    /// Moment of inertia about the origin of a uniform body of `mass`
+    pub fn moment_of_inertia(&self, mass: f32) -> f32 {
+        match *self {
+            Self::Circle { radius } => mass * radius * radius / 2.,
+            Self::Capsule { half_length, radius } => {
+                // A rectangle between two half discs, which sit with their centroids this far
+                // out from their flat sides
+                let (rectangle_area, disc_area) = (4. * half_length * radius, PI * radius * radius);
+                let area = rectangle_area + disc_area;
+                let (rectangle_mass, disc_mass) = (mass * rectangle_area / area, mass * disc_area / area);
+                let centroid = 4. * radius / (3. * PI);
+                let rectangle = rectangle_mass * (4. * half_length * half_length + 4. * radius * radius) / 12.;
+                rectangle + disc_mass * (radius * radius / 2. + half_length * half_length + 2. * half_length * centroid)
+            }
+        }
+    }
+}
+
+/// When an object's collisions are found by exact times of impact instead of discretely
+///
+/// A pair only falls back to the discrete check, overlaps at the end of the step pushed apart,
+/// when neither object asks for continuous detection. Slow bulky objects can skip the exact
+/// queries, fast or small ones still can't tunnel through them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CcdMode {
+    #[default]
+    Always,
+    /// Only while the fastest point of the object moves faster than this, in units per second
+    FastOnly(f32),
+    Never,
+}
+
 #[derive(Clone, Debug)]
 pub struct Object {
     pub mass: f32,
+    /// Moment of inertia about `position`
+    pub inertia: f32,
     pub position: Vec2,
     pub velocity: Vec2,
+    /// Acceleration of the object's own, on top of the world's gravity
     pub acceleration: Vec2,
+    /// Multiple of [`World::gravity`] the object falls with, 0 floats and negative values rise
+    pub gravity_scale: f32,
     pub rotation: f32,
     pub rot_velocity: f32,
-    pub shape: Shape<Txts>,
+    /// Fraction of the approach speed a collision turns back into separation speed, 1 bounces
+    /// perfectly elastic and 0 not at all, see [`world::contact_impulse`]
+    pub restitution: f32,
+    /// Friction coefficient below which a contact sticks instead of sliding, see
+    /// [`world::friction_impulse`]
+    pub static_friction: f32,
+    /// Friction coefficient of a sliding contact
+    pub dynamic_friction: f32,
+    /// Cached from `position` and `rotation`, refreshed by `update` and `refresh_transform`
+    pub transform: Transform2,
+    pub shape: Polygon,
+    /// Exact curved outline `shape` encloses, collided instead of it
+    pub rounded: Option<Rounded>,
+    /// Coarser outline enclosing `shape`, collided instead of it while the world is simplified
+    pub proxy: Option<Polygon>,
+    /// Smallest circle around the shape and its proxy, in object space
+    pub bounding_circle: Circle,
     pub cur_time: f32,
     pub updated: usize,
-    pub collided: usize
+    pub collided: usize,
+    /// Collisions that bounced the object hard, counted towards [`World::collision_limit`].
+    /// Resting contacts, slow bounces and resolved overlaps only count as `collided`.
+    pub impacts: usize,
+    pub body_type: BodyType,
+    /// Human-readable name for labels and logs, indices change as objects are removed
+    pub name: Option<String>,
+    /// Behavior run at the start of every step, see [`controller`]
+    pub controller: Option<Box<dyn Controller>>,
+    /// Index into [`World::layers`]
+    pub layer: usize,
+    /// Index into [`World::teams`], `None` for neutral objects
+    pub team: Option<usize>,
+    /// Touching it scores for the other teams, see [`team`]
+    pub goal: bool,
+    /// Drawing order in the viewer, higher is drawn over lower, ties go by index
+    pub z_index: i32,
+    pub ccd: CcdMode,
+    /// Velocity and rotational velocity a paused object resumes with, see [`Self::pause`]
+    pub(crate) paused: Option<(Vec2, f32)>,
+    /// Whether the object's layer doesn't simulate, refreshed at the start of every step
+    pub(crate) frozen: bool,
+    /// Local seconds per global second during the current step, see [`dilation`]
+    pub(crate) time_scale: f32,
+    /// The world's gravity times `gravity_scale`, refreshed at the start of every step, only
+    /// dynamic objects of finite mass fall
+    pub(crate) gravity: Vec2,
 }
 
 impl Object {
-    pub fn new(position: Vec2, velocity: Vec2, rotation: f32, shape: Shape<Txts>) -> Self {
+    pub fn new(position: Vec2, velocity: Vec2, rotation: f32, shape: Polygon) -> Self {
+        let mass = 1.;
+        let proxy = (shape.points.len() > PROXY_VERTICES)
+            .then(|| Polygon::new(simplify_convex_outer(&convex_hull(shape.points.clone()), PROXY_VERTICES)));
         Self {
-            mass: 1.,
+            mass,
+            inertia: polygon_moment_of_inertia(&shape.points, mass),
             position,
             velocity,
             acceleration: Vec2::ZERO,
+            gravity_scale: 1.,
             rotation,
             rot_velocity: 0.,
+            restitution: 1.,
+            static_friction: 0.,
+            dynamic_friction: 0.,
+            transform: Transform2::new(position, rotation),
+            bounding_circle: min_enclosing_circle(&proxy.as_ref().unwrap_or(&shape).points),
             shape,
+            rounded: None,
+            proxy,
             cur_time: 0.,
             updated: 0,
-            collided: 0
+            collided: 0,
+            impacts: 0,
+            body_type: BodyType::Dynamic,
+            name: None,
+            controller: None,
+            layer: 0,
+            team: None,
+            goal: false,
+            z_index: 0,
+            ccd: CcdMode::Always,
+            paused: None,
+            frozen: false,
+            time_scale: 1.,
+            gravity: Vec2::ZERO,
         }
     }
+    /// Object with the curved outline of `rounded`, see [`Rounded`]
+    pub fn new_rounded(position: Vec2, velocity: Vec2, rotation: f32, rounded: Rounded) -> Self {
+        let mut object = Self::new(position, velocity, rotation, rounded.outline());
+        // The exact outline is cheap enough, it never needs a proxy
+        object.proxy = None;
+        object.bounding_circle = min_enclosing_circle(&object.shape.points);
+        object.inertia = rounded.moment_of_inertia(object.mass);
+        object.rounded = Some(rounded);
+        object
+    }
+    pub fn circle(position: Vec2, velocity: Vec2, radius: f32) -> Self {
+        Self::new_rounded(position, velocity, 0., Rounded::Circle { radius })
+    }
+    /// Capsule `length` long end to end and twice `radius` wide, lying along x before `rotation`
+    pub fn capsule(position: Vec2, velocity: Vec2, rotation: f32, length: f32, radius: f32) -> Self {
+        let half_length = (length / 2. - radius).max(0.);
+        Self::new_rounded(position, velocity, rotation, Rounded::Capsule { half_length, radius })
+    }
+    /// Object with infinite mass following `motion`, e.g. a wall of a moving container
+    pub fn new_kinematic(position: Vec2, rotation: f32, shape: Polygon, motion: Kinematic) -> Self {
+        let mut object = Self::new(position, Vec2::ZERO, rotation, shape);
+        object.mass = f32::INFINITY;
+        object.inertia = f32::INFINITY;
+        object.body_type = BodyType::Kinematic(motion);
+        object
+    }
+    /// Object that never moves, e.g. a floor or a wall
+    pub fn new_static(position: Vec2, rotation: f32, shape: Polygon) -> Self {
+        let mut object = Self::new(position, Vec2::ZERO, rotation, shape);
+        object.mass = f32::INFINITY;
+        object.inertia = f32::INFINITY;
+        object.body_type = BodyType::Static;
+        object
+    }
+    pub fn is_dynamic(&self) -> bool {
+        self.body_type == BodyType::Dynamic && !self.is_paused()
+    }
+    /// Whether the object stays put with infinite mass, being static or paused
+    pub fn is_static(&self) -> bool {
+        self.body_type == BodyType::Static || self.is_paused()
+    }
+    /// Stops time for the object until [`Self::resume`], meanwhile it acts as static and the others
+    /// collide with it where it stands
+    pub fn pause(&mut self) {
+        if self.paused.is_none() {
+            self.paused = Some((self.velocity, self.rot_velocity));
+            self.velocity = Vec2::ZERO;
+            self.rot_velocity = 0.;
+        }
+    }
+    /// Lets a paused object move on with the velocities it had when paused
+    pub fn resume(&mut self) {
+        if let Some((velocity, rot_velocity)) = self.paused.take() {
+            self.velocity = velocity;
+            self.rot_velocity = rot_velocity;
+        }
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+    /// Velocity and rotational velocity, those it resumes with while paused
+    pub fn motion(&self) -> (Vec2, f32) {
+        self.paused.unwrap_or((self.velocity, self.rot_velocity))
+    }
+    /// Prescribed motion of a kinematic object
+    pub fn kinematic(&self) -> Option<Kinematic> {
+        match self.body_type {
+            BodyType::Kinematic(motion) => Some(motion),
+            _ => None,
+        }
+    }
+    /// Mass impulses act against, infinite for static and kinematic objects whatever `mass` is
+    pub fn effective_mass(&self) -> f32 {
+        if self.is_dynamic() {
+            self.mass
+        } else {
+            f32::INFINITY
+        }
+    }
+    /// Like [`Self::effective_mass`] for the moment of inertia
+    pub fn effective_inertia(&self) -> f32 {
+        if self.is_dynamic() {
+            self.inertia
+        } else {
+            f32::INFINITY
+        }
+    }
+    /// Like `new` but rejects shapes the solver can't handle
+    pub fn try_new(position: Vec2, velocity: Vec2, rotation: f32, shape: Polygon) -> Result<Self, CollisionError> {
+        shape.validate()?;
+        Ok(Self::new(position, velocity, rotation, shape))
+    }
+    /// Moves to `target_time` along the parabola of the constant acceleration
     pub fn update(&mut self, target_time: f32) {
+        let dt = target_time-self.cur_time;
+        let acceleration = self.total_acceleration();
+        // The drift covers the straight part, the rest is the bend towards the acceleration
+        self.position += acceleration * (dt * dt / 2.);
+        self.drift(target_time);
+        self.velocity += acceleration * dt;
+    }
+    /// Own acceleration plus gravity
+    pub fn total_acceleration(&self) -> Vec2 {
+        self.acceleration + self.gravity
+    }
+    /// Moves to `target_time` with the current velocities, `update` without the acceleration
+    pub fn drift(&mut self, target_time: f32) {
         assert!(target_time>=self.cur_time);
 
         let dt = target_time-self.cur_time;
 
         self.position += self.velocity * dt;
-        self.velocity += self.acceleration * dt;
+        self.rotation += self.rot_velocity * dt;
         self.cur_time += dt;
         self.updated += 1;
+        self.refresh_transform();
+    }
+    /// Needs to be called after `position` or `rotation` are changed directly
+    pub fn refresh_transform(&mut self) {
+        self.transform = Transform2::new(self.position, self.rotation);
+    }
+
+    /// Outline the solver collides, the proxy when `simplified` and the object has one
+    pub fn collider(&self, simplified: bool) -> &Polygon {
+        match &self.proxy {
+            Some(proxy) if simplified => proxy,
+            _ => &self.shape,
+        }
+    }
+
+    /// The object's own restitution and friction coefficients
+    pub fn material(&self) -> Material {
+        Material {
+            restitution: self.restitution,
+            static_friction: self.static_friction,
+            dynamic_friction: self.dynamic_friction,
+        }
+    }
+    /// Material of the collider's edge `edge`, the shape's override for it or the object's own
+    ///
+    /// Overrides belong to the edges of `shape`, proxies and rounded outlines have none.
+    pub fn surface(&self, edge: usize, simplified: bool) -> Material {
+        let own_edges = self.rounded.is_none() && !(simplified && self.proxy.is_some());
+        own_edges.then(|| self.shape.edge_material(edge)).flatten().unwrap_or_else(|| self.material())
+    }
+    /// Points the collided outline keeps a radius around, in object space, and the radius
+    ///
+    /// The collider's vertices and no radius unless the object is [`Rounded`].
+    pub fn core(&self, simplified: bool) -> (Cow<'_, [Vec2]>, f32) {
+        match &self.rounded {
+            Some(rounded) => (Cow::Owned(rounded.core()), rounded.radius()),
+            None => (Cow::Borrowed(&self.collider(simplified).points), 0.),
+        }
+    }
+    /// [`Self::core`] in world space
+    pub fn world_core(&self, simplified: bool) -> (Vec<Vec2>, f32) {
+        let (core, radius) = self.core(simplified);
+        (core.iter().map(|p| self.to_world(*p)).collect(), radius)
+    }
+
+    /// Whether the object's collisions need exact times of impact at its current velocities
+    pub fn needs_ccd(&self) -> bool {
+        match self.ccd {
+            CcdMode::Always => true,
+            CcdMode::FastOnly(threshold) => {
+                self.velocity.length() + self.rot_velocity.abs() * self.bounding_circle.radius > threshold
+            }
+            CcdMode::Never => false,
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.position.is_finite()
+            && self.velocity.is_finite()
+            && self.rotation.is_finite()
+            && self.rot_velocity.is_finite()
     }
+
+    pub fn to_world(&self, local: Vec2) -> Vec2 {
+        self.transform.apply(local)
+    }
+    pub fn to_local(&self, world: Vec2) -> Vec2 {
+        self.transform.apply_inverse(world)
+    }
+
+    /// Applies a constant torque for the duration `dt`
+    pub fn apply_torque(&mut self, torque: f32, dt: f32) {
+        self.apply_angular_impulse(torque * dt);
+    }
+    pub fn apply_angular_impulse(&mut self, impulse: f32) {
+        self.rot_velocity += impulse / self.effective_inertia();
+    }
+    pub fn apply_impulse(&mut self, impulse: Vec2) {
+        self.velocity += impulse / self.effective_mass();
+    }
+    /// Velocity of the material point currently at `world_point`, including the spin contribution
+    pub fn velocity_at_point(&self, world_point: Vec2) -> Vec2 {
+        self.velocity + self.rot_velocity * (world_point - self.position).perp()
+    }
+    /// Applies an impulse at a world space point, off-center impulses also change the spin
+    pub fn apply_impulse_at_point(&mut self, impulse: Vec2, world_point: Vec2) {
+        let lever_arm = world_point - self.position;
+        self.apply_impulse(impulse);
+        self.apply_angular_impulse(lever_arm.perp_dot(impulse));
+    }
+}
+
+#[test]
+fn test_velocity_at_point() {
+    let mut object = Object::new(vec2(1., 2.), vec2(3., -1.), 0., Polygon::regular(4));
+
+    // No spin, every point moves with the body
+    assert_eq!(object.velocity_at_point(vec2(5., 5.)), vec2(3., -1.));
+
+    object.rot_velocity = 2.;
+    // r = (1, 0), w x r = (0, 2)
+    assert_eq!(object.velocity_at_point(vec2(2., 2.)), vec2(3., 1.));
+    // r = (0, 1), w x r = (-2, 0)
+    assert_eq!(object.velocity_at_point(vec2(1., 3.)), vec2(1., -1.));
+    // r = (-1, -2), w x r = (4, -2)
+    assert_eq!(object.velocity_at_point(vec2(0., 0.)), vec2(7., -3.));
+    assert_eq!(object.velocity_at_point(object.position), object.velocity);
+}
+
+#[test]
+fn test_impulses_and_torques() {
+    let mut object = Object::new(Vec2::ZERO, Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 2.)));
+    object.mass = 2.;
+    object.inertia = 4.;
+    object.apply_torque(8., 0.5);
+    assert_eq!(object.rot_velocity, 1.);
+    object.apply_angular_impulse(-2.);
+    assert_eq!((object.velocity, object.rot_velocity), (Vec2::ZERO, 0.5));
+
+    // A kick to the right at the top edge pushes and turns clockwise, one at the center only pushes
+    object.apply_impulse_at_point(vec2(4., 0.), vec2(0., 1.));
+    assert_eq!((object.velocity, object.rot_velocity), (vec2(2., 0.), -0.5));
+    object.apply_impulse_at_point(vec2(0., 2.), object.position);
+    assert_eq!((object.velocity, object.rot_velocity), (vec2(2., 1.), -0.5));
+
+    let mut paused = object.clone();
+    paused.pause();
+    for mut object in [Object::new_static(Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 2.))), paused] {
+        let before = (object.velocity, object.rot_velocity);
+        object.apply_torque(8., 0.5);
+        object.apply_angular_impulse(3.);
+        object.apply_impulse_at_point(vec2(4., 0.), vec2(0., 1.));
+        assert_eq!((object.velocity, object.rot_velocity), before);
+    }
+}
+
+#[test]
+fn test_rounded_outlines() {
+    let capsule = Rounded::Capsule { half_length: 1., radius: 0.5 };
+    assert_eq!(capsule.core(), [vec2(-1., 0.), vec2(1., 0.)]);
+    // The polygon encloses the outline and hugs it
+    let outline = capsule.outline();
+    assert!(outline.validate().is_ok());
+    let extent = Aabb::from_points(&outline.points);
+    assert!(extent.max.x >= 1.5 && extent.max.x < 1.51 && extent.max.y >= 0.5 && extent.max.y < 0.51);
+    // Close to the enclosing polygon's inertia, a capsule without length is a circle
+    let inertia = capsule.moment_of_inertia(2.);
+    assert!((inertia / polygon_moment_of_inertia(&outline.points, 2.) - 1.).abs() < 0.02);
+    let circle = Rounded::Capsule { half_length: 0., radius: 0.5 }.moment_of_inertia(2.);
+    assert!((circle - Rounded::Circle { radius: 0.5 }.moment_of_inertia(2.)).abs() < 1e-6);
+
+    let object = Object::capsule(Vec2::ZERO, Vec2::ZERO, PI / 2., 3., 0.5);
+    assert_eq!(object.rounded, Some(capsule));
+    assert_eq!((object.proxy.as_ref(), object.inertia), (None, capsule.moment_of_inertia(1.)));
 }
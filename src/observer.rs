@@ -0,0 +1,154 @@
+//! Pull-based view of a running simulation for tools outside the renderer.
+//!
+//! An [`Observation`] is a self-contained copy of what a visualization needs after a step: every
+//! object's transform and velocity, the contacts the step resolved and the islands of objects that
+//! can interact. The thread stepping the world [`publish`](WorldObserver::publish)es one per step
+//! into a [`WorldObserver`], any number of other threads pull the newest whenever they are ready,
+//! e.g. a dashboard serializing it for a browser. Observations are numbered, a reader polling
+//! with the version it has only gets a new one when the world stepped since.
+
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use islands::find_islands;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ObservedObject {
+    pub name: Option<String>,
+    pub position: Vec2,
+    pub rotation: f32,
+    pub velocity: Vec2,
+    pub rot_velocity: f32,
+    pub dynamic: bool,
+}
+
+/// Collision resolved during the observed step
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    pub object_1: usize,
+    pub object_2: usize,
+    pub time: f32,
+    pub point: Vec2,
+    /// Pointing out of `object_2`
+    pub normal: Vec2,
+    pub impulse: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Observation {
+    /// Number of the observation, counting up with every publish
+    pub version: u64,
+    pub frame: usize,
+    pub time: f32,
+    /// Indexed like the world's objects
+    pub objects: Vec<ObservedObject>,
+    pub contacts: Vec<Contact>,
+    /// Indices of the objects that can interact over the next step of the same length, directly
+    /// or through others, see [`islands`]
+    pub islands: Vec<Vec<usize>>,
+}
+
+impl Observation {
+    /// The world as it is after a step of `dt`
+    pub fn of(world: &World, dt: f32) -> Self {
+        Self {
+            version: 0,
+            frame: world.frame,
+            time: world.time_elapsed,
+            objects: world
+                .objects
+                .iter()
+                .map(|object| ObservedObject {
+                    name: object.name.clone(),
+                    position: object.position,
+                    rotation: object.rotation,
+                    velocity: object.velocity,
+                    rot_velocity: object.rot_velocity,
+                    dynamic: object.is_dynamic(),
+                })
+                .collect(),
+            contacts: world
+                .resolved
+                .iter()
+                .map(|toi| Contact {
+                    object_1: toi.object_1,
+                    object_2: toi.object_2,
+                    time: toi.time,
+                    point: toi.point,
+                    normal: toi.normal,
+                    impulse: toi.impulse,
+                })
+                .collect(),
+            islands: find_islands(&world.objects, dt, world.collision_margin)
+                .into_iter()
+                .map(|island| island.objects)
+                .collect(),
+        }
+    }
+}
+
+/// Newest observation of a world shared between the thread stepping it and its readers, cloning
+/// the observer hands out another handle to the same one
+#[cfg(feature = "std")]
+#[derive(Clone, Default)]
+pub struct WorldObserver {
+    latest: Arc<Mutex<Option<Arc<Observation>>>>,
+}
+
+#[cfg(feature = "std")]
+impl WorldObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Replaces the newest observation with the world after a step of `dt`
+    pub fn publish(&self, world: &World, dt: f32) {
+        let mut observation = Observation::of(world, dt);
+        // Built outside the lock, readers only wait for the swap
+        let mut latest = self.latest.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        observation.version = latest.as_ref().map_or(1, |previous| previous.version + 1);
+        *latest = Some(Arc::new(observation));
+    }
+    pub fn latest(&self) -> Option<Arc<Observation>> {
+        self.latest.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+    /// The newest observation if it's newer than version `seen`, 0 before any was seen
+    pub fn poll(&self, seen: u64) -> Option<Arc<Observation>> {
+        self.latest().filter(|observation| observation.version > seen)
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_observer_across_threads() {
+    let observer = WorldObserver::new();
+    assert_eq!(observer.poll(0), None);
+
+    let reader = observer.clone();
+    let stepper = std::thread::spawn(move || {
+        let mut world = World::new();
+        world.objects.push(Object::new(vec2(-2., 0.), vec2(4., 0.), 0., Polygon::rectangle(vec2(1., 1.))));
+        world.objects.push(Object::new(vec2(2., 0.), vec2(-4., 0.), 0., Polygon::rectangle(vec2(1., 1.))));
+        world.objects.push(Object::new(vec2(0., 10.), Vec2::ZERO, 0., Polygon::regular(5)));
+        let mut contacts = 0;
+        for _ in 0..60 {
+            world.step(1. / 60.).unwrap();
+            contacts += world.resolved.len();
+            observer.publish(&world, 1. / 60.);
+        }
+        (world.frame, contacts)
+    });
+    let (frame, contacts) = stepper.join().unwrap();
+
+    let observation = reader.poll(0).unwrap();
+    assert_eq!((observation.version, observation.frame), (60, frame));
+    assert_eq!(reader.poll(observation.version), None);
+    assert_eq!(observation.objects.len(), 3);
+    assert!(observation.objects[0].velocity.x < 0., "the boxes bounced apart");
+    assert!(contacts > 0);
+    // Apart by now, every object is an island of its own
+    assert_eq!(observation.islands, [vec![0], vec![1], vec![2]]);
+}
@@ -0,0 +1,91 @@
+//! Orbit and slingshot scenario: the probe's predicted path and its draggable velocity vector.
+//!
+//! While the velocity arrow is dragged the simulation holds still, the probe takes the velocity
+//! the arrow points to and the prediction follows every move.
+
+use perfect_collisions::gravity;
+
+use super::*;
+
+const PREDICTION_DT: f32 = 1. / 60.;
+const PREDICTION_STEPS: usize = 600;
+/// Every n-th predicted position is drawn
+const PATH_STRIDE: usize = 3;
+/// World units of arrow per unit of velocity
+const VELOCITY_ARROW_SCALE: f32 = 1.;
+/// How close to the arrow tip a press grabs it, in screen units
+const GRAB_DISTANCE: f32 = 0.05;
+
+pub struct OrbitView {
+    prediction: Vec<Vec2>,
+    dragging: bool,
+}
+
+impl OrbitView {
+    pub fn new() -> Self {
+        Self {
+            prediction: vec![],
+            dragging: false,
+        }
+    }
+
+    /// Index of the probe, `None` once another scene replaced the scenario
+    pub fn probe(world: &World) -> Option<usize> {
+        world.objects.iter().position(|object| object.name.as_deref() == Some(presets::PROBE_NAME))
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Starts dragging when `point` is on the tip of the velocity arrow, returns whether it is
+    pub fn start_drag(&mut self, world: &World, camera: &Camera, point: Vec2) -> bool {
+        let Some(probe) = Self::probe(world).map(|i| &world.objects[i]) else {
+            return false;
+        };
+        let tip = probe.position + probe.velocity * VELOCITY_ARROW_SCALE;
+        self.dragging = tip.distance(point) * camera.zoom() < GRAB_DISTANCE;
+        self.dragging
+    }
+
+    pub fn end_drag(&mut self) {
+        self.dragging = false;
+    }
+
+    /// Points the dragged velocity arrow at `point` and predicts the probe's path again
+    pub fn update(&mut self, world: &mut World, point: Vec2) {
+        let Some(i) = Self::probe(world) else {
+            self.prediction.clear();
+            return;
+        };
+        if self.dragging {
+            let probe = &mut world.objects[i];
+            probe.velocity = (point - probe.position) / VELOCITY_ARROW_SCALE;
+        }
+        self.prediction = gravity::predict_path(world, i, PREDICTION_DT, PREDICTION_STEPS);
+    }
+
+    pub fn draw(&self, world: &World, queue: &mut DrawQueue, camera: &Camera, color: Color) {
+        let Some(probe) = Self::probe(world).map(|i| &world.objects[i]) else {
+            return;
+        };
+        // Same on-screen thickness at every zoom
+        let thickness = 0.003 / camera.zoom();
+        let path = std::iter::once(probe.position).chain(self.prediction.iter().step_by(PATH_STRIDE).copied());
+        let tip = probe.position + probe.velocity * VELOCITY_ARROW_SCALE;
+        let segments = path.clone().zip(path.skip(1)).chain([(probe.position, tip)]);
+        for (start, end) in segments {
+            let offset = end - start;
+            let segment = Shape::from_line(offset.length(), thickness)
+                .set_color(color)
+                .apply(GTransform::from_translation(start).rotate(offset.y.atan2(offset.x)))
+                .apply(camera.0);
+            queue.add(Depth::Debug, segment);
+        }
+        let handle = Shape::from_circle(12)
+            .set_color(color)
+            .apply(GTransform::from_translation(tip).inflate(GRAB_DISTANCE / camera.zoom() / 2.))
+            .apply(camera.0);
+        queue.add(Depth::Debug, handle);
+    }
+}
@@ -0,0 +1,129 @@
+//! Temporal coherence for the narrow phase.
+//!
+//! Pairs which passed the broad phase but didn't collide are remembered together with their
+//! relative configuration. As long as that configuration stays within a tolerance (e.g. bodies
+//! drifting along together) the narrow phase would give the same answer and is skipped.
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use super::*;
+
+/// Largest change in relative position/velocity/rotation still treated as the same configuration
+const TOLERANCE: f32 = 1e-4;
+/// Entries not looked at for this many frames are dropped
+const MAX_AGE: usize = 120;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PairConfig {
+    relative_position: Vec2,
+    relative_velocity: Vec2,
+    /// Bends the relative path, pairs falling together keep it at zero
+    relative_acceleration: Vec2,
+    rotations: Vec2,
+    rot_velocities: Vec2,
+    /// Length of the time window the pair was checked for
+    window: f32,
+}
+
+impl PairConfig {
+    pub fn new(a: &Object, b: &Object, end_time: f32) -> Self {
+        let time = a.cur_time.max(b.cur_time);
+        let position_a = a.position + a.velocity * (time - a.cur_time);
+        let position_b = b.position + b.velocity * (time - b.cur_time);
+        Self {
+            relative_position: position_a - position_b,
+            relative_velocity: a.velocity - b.velocity,
+            relative_acceleration: a.total_acceleration() - b.total_acceleration(),
+            rotations: vec2(a.rotation, b.rotation),
+            rot_velocities: vec2(a.rot_velocity, b.rot_velocity),
+            window: end_time - time,
+        }
+    }
+    /// Whether a check of `self` is answered by the earlier check of `cached`
+    fn covered_by(&self, cached: &PairConfig) -> bool {
+        self.window <= cached.window + TOLERANCE
+            && self.relative_position.abs_diff_eq(cached.relative_position, TOLERANCE)
+            && self.relative_velocity.abs_diff_eq(cached.relative_velocity, TOLERANCE)
+            && self.relative_acceleration.abs_diff_eq(cached.relative_acceleration, TOLERANCE)
+            && self.rotations.abs_diff_eq(cached.rotations, TOLERANCE)
+            && self.rot_velocities.abs_diff_eq(cached.rot_velocities, TOLERANCE)
+    }
+}
+
+struct Entry {
+    config: PairConfig,
+    last_checked_frame: usize,
+}
+
+pub struct PairCache {
+    pub enabled: bool,
+    entries: HashMap<(usize, usize), Entry>,
+    /// Narrow-phase checks skipped during the last frame
+    pub hits: usize,
+}
+
+impl Default for PairCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PairCache {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            entries: HashMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// Approximate heap bytes of the entries
+    pub fn heap_bytes(&self) -> usize {
+        self.entries.len() * core::mem::size_of::<((usize, usize), Entry)>()
+    }
+
+    /// Has to be called whenever object indices change
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// True when the pair is known not to collide in the current configuration. Pairs are
+    /// ordered, `config` has to be built with the lower index first.
+    pub fn is_separated(&mut self, pair: (usize, usize), config: &PairConfig, frame: usize) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match self.entries.get_mut(&pair) {
+            Some(entry) if config.covered_by(&entry.config) => {
+                entry.last_checked_frame = frame;
+                self.hits += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn record(&mut self, pair: (usize, usize), config: PairConfig, collided: bool, frame: usize) {
+        if collided {
+            self.entries.remove(&pair);
+        } else {
+            self.entries.insert(pair, Entry { config, last_checked_frame: frame });
+        }
+    }
+
+    /// Drops stale entries and resets the per-frame counter
+    pub fn start_frame(&mut self, frame: usize) {
+        self.hits = 0;
+        self.entries.retain(|_, entry| frame - entry.last_checked_frame <= MAX_AGE);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,122 @@
+//! Phase-space and trajectory plot of one object.
+//!
+//! Samples the tracked object every frame, the selection changing starts a new recording.
+
+use std::{collections::VecDeque, fs, io};
+
+use egui::plot::{Line, Plot, PlotPoints};
+
+use super::*;
+
+/// Oldest samples are dropped beyond this
+const MAX_SAMPLES: usize = 10_000;
+const PLOT_HEIGHT: f32 = 240.;
+
+#[derive(Clone, Copy, Debug, PartialEq, strum::Display, strum::EnumIter)]
+pub enum PhaseView {
+    #[strum(serialize = "x vs vx")]
+    X,
+    #[strum(serialize = "y vs vy")]
+    Y,
+    #[strum(serialize = "Trajectory")]
+    Trajectory,
+}
+
+#[derive(Clone, Copy)]
+struct Sample {
+    time: f32,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+pub struct PhasePlot {
+    pub view: PhaseView,
+    /// Index of the recorded object
+    target: Option<usize>,
+    samples: VecDeque<Sample>,
+}
+
+impl PhasePlot {
+    pub fn new() -> Self {
+        Self {
+            view: PhaseView::X,
+            target: None,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        memory::deque_bytes(&self.samples)
+    }
+
+    /// Samples `target`, switching targets clears the recording
+    pub fn record(&mut self, target: Option<usize>, world: &World) {
+        if target != self.target {
+            self.target = target;
+            self.samples.clear();
+        }
+        let Some(object) = target.and_then(|i| world.objects.get(i)) else {
+            return;
+        };
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            time: world.time_elapsed,
+            position: object.position,
+            velocity: object.velocity,
+        });
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let Some(target) = self.target else {
+            ui.label("Shift-click an object to plot it");
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(format!("Object #{target}, {} samples", self.samples.len()));
+            egui::ComboBox::from_id_source("phase_view")
+                .selected_text(self.view.to_string())
+                .show_ui(ui, |ui| {
+                    for view in <PhaseView as strum::IntoEnumIterator>::iter() {
+                        ui.selectable_value(&mut self.view, view, view.to_string());
+                    }
+                });
+            if ui.button("Export").clicked() {
+                let path = format!("perfect-collisions-phase-{target}.csv");
+                match self.export(&path) {
+                    Ok(()) => info!(path, "exported phase samples"),
+                    Err(e) => warn!(path, error = %e, "export failed"),
+                }
+            }
+        });
+
+        let points = self
+            .samples
+            .iter()
+            .map(|sample| {
+                let (x, y) = match self.view {
+                    PhaseView::X => (sample.position.x, sample.velocity.x),
+                    PhaseView::Y => (sample.position.y, sample.velocity.y),
+                    PhaseView::Trajectory => (sample.position.x, sample.position.y),
+                };
+                [x as f64, y as f64]
+            })
+            .collect::<PlotPoints>();
+        let plot = Plot::new("phase_plot").height(PLOT_HEIGHT);
+        // Trajectories are only readable undistorted
+        let plot = if self.view == PhaseView::Trajectory { plot.data_aspect(1.) } else { plot };
+        plot.show(ui, |plot_ui| plot_ui.line(Line::new(points)));
+    }
+
+    fn export(&self, path: &str) -> io::Result<()> {
+        let mut csv = String::from("time,x,y,vx,vy\n");
+        for sample in &self.samples {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                sample.time, sample.position.x, sample.position.y, sample.velocity.x, sample.velocity.y
+            ));
+        }
+        fs::write(path, csv)
+    }
+}
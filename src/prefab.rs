@@ -0,0 +1,123 @@
+//! Reusable groups of objects stamped into the world, stored in a library file as RON.
+//!
+//! A [`Prefab`] keeps its objects relative to their mean position, stamping places that origin at
+//! a point and turns the whole group, velocities and oscillation paths included.
+
+use std::{io, ops::Range, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use object::{BodyType, Kinematic};
+use scene::SceneObject;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Prefab {
+    pub name: String,
+    /// Positions relative to the prefab's origin, times relative to its creation
+    pub objects: Vec<SceneObject>,
+}
+
+impl Prefab {
+    /// Prefab of the objects at `indices`, `None` if none of them exist
+    pub fn from_selection(name: impl Into<String>, objects: &[Object], indices: &[usize]) -> Option<Self> {
+        let selected = indices.iter().filter_map(|&i| objects.get(i)).collect::<Vec<_>>();
+        if selected.is_empty() {
+            return None;
+        }
+        let origin = selected.iter().map(|object| object.position).sum::<Vec2>() / selected.len() as f32;
+        let objects = selected
+            .into_iter()
+            .map(|object| {
+                let mut scene_object = SceneObject::from(object);
+                scene_object.position -= origin;
+                scene_object.cur_time = 0.;
+                scene_object.collided = 0;
+                scene_object.impacts = 0;
+                if let BodyType::Kinematic(Kinematic::Oscillate { anchor, .. }) = scene_object.body_type_mut() {
+                    *anchor -= origin;
+                }
+                scene_object
+            })
+            .collect();
+        Some(Self {
+            name: name.into(),
+            objects,
+        })
+    }
+
+    /// Adds an instance with its origin at `position`, turned by `rotation`, and returns the
+    /// indices of the new objects
+    pub fn stamp(&self, world: &mut World, position: Vec2, rotation: f32) -> Range<usize> {
+        let turn = Vec2::from_angle(rotation);
+        let first = world.objects.len();
+        for scene_object in &self.objects {
+            let mut scene_object = scene_object.clone();
+            scene_object.position = position + turn.rotate(scene_object.position);
+            scene_object.velocity = turn.rotate(scene_object.velocity);
+            scene_object.acceleration = turn.rotate(scene_object.acceleration);
+            scene_object.rotation += rotation;
+            scene_object.cur_time = world.time_elapsed;
+            if let BodyType::Kinematic(Kinematic::Oscillate { anchor, amplitude, .. }) = scene_object.body_type_mut() {
+                *anchor = position + turn.rotate(*anchor);
+                *amplitude = turn.rotate(*amplitude);
+            }
+            world.objects.push(Object::from(scene_object));
+        }
+        first..world.objects.len()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PrefabLibrary {
+    pub prefabs: Vec<Prefab>,
+}
+
+impl PrefabLibrary {
+    /// Adds the prefab, replacing the one with the same name
+    pub fn insert(&mut self, prefab: Prefab) {
+        match self.prefabs.iter_mut().find(|existing| existing.name == prefab.name) {
+            Some(existing) => *existing = prefab,
+            None => self.prefabs.push(prefab),
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.iter().find(|prefab| prefab.name == name)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        scene::write_ron(self, path.as_ref())
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        scene::read_ron(path.as_ref())
+    }
+}
+
+#[test]
+fn test_prefab_keeps_relative_transforms() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(10., 0.), Vec2::ZERO, 0., Polygon::regular(4)));
+    world.objects.push(Object::new(vec2(1., 1.), vec2(1., 0.), 0., Polygon::regular(3)));
+    world.objects.push(Object::new(vec2(3., 1.), Vec2::ZERO, 0.5, Polygon::regular(5)));
+
+    let mut library = PrefabLibrary::default();
+    library.insert(Prefab::from_selection("pair", &world.objects, &[1, 2]).unwrap());
+    let text = ron::to_string(&library).unwrap();
+    let mut library = ron::from_str::<PrefabLibrary>(&text).unwrap();
+    let prefab = library.get("pair").unwrap();
+    assert_eq!(prefab.objects[0].position, vec2(-1., 0.));
+
+    let stamped = prefab.stamp(&mut world, vec2(0., 5.), PI / 2.);
+    assert_eq!(stamped, 3..5);
+    let (a, b) = (&world.objects[3], &world.objects[4]);
+    assert!((a.position - vec2(0., 4.)).length() < 1e-5);
+    assert!((b.position - vec2(0., 6.)).length() < 1e-5);
+    assert!((a.velocity - vec2(0., 1.)).length() < 1e-5);
+    assert!((b.rotation - (0.5 + PI / 2.)).abs() < 1e-5);
+    assert_eq!(a.shape.points.len(), 3);
+
+    // Saving under the same name replaces the prefab
+    library.insert(Prefab::from_selection("pair", &world.objects, &[0]).unwrap());
+    assert_eq!(library.prefabs.len(), 1);
+    assert!(Prefab::from_selection("none", &world.objects, &[7]).is_none());
+}
@@ -0,0 +1,674 @@
+//! Ready-made scenes for the viewer and benchmarks.
+
+use alloc::format;
+
+use super::*;
+use gravity::GravitySource;
+use joint::Joint;
+use object::{BodyType, Kinematic};
+use team::Team;
+
+const GRAVITY: Vec2 = Vec2::new(0., -9.81);
+
+/// Granular bed in a box shaken up and down, the big grain at the bottom slowly works its way
+/// up through the small ones (granular convection)
+pub fn shaker_table(world: &mut World) {
+    const INNER_SIZE: Vec2 = Vec2::new(10., 8.);
+    const WALL_THICKNESS: f32 = 0.5;
+    const SHAKE_AMPLITUDE: Vec2 = Vec2::new(0., 0.15);
+    const SHAKE_FREQUENCY: f32 = 4.;
+    const GRAIN_RADIUS: f32 = 0.2;
+
+    let half = INNER_SIZE / 2.;
+    let offset = half + Vec2::splat(WALL_THICKNESS / 2.);
+    let horizontal = vec2(INNER_SIZE.x + 2. * WALL_THICKNESS, WALL_THICKNESS);
+    let vertical = vec2(WALL_THICKNESS, INNER_SIZE.y);
+    let walls = [
+        (vec2(0., -offset.y), horizontal),
+        (vec2(0., offset.y), horizontal),
+        (vec2(-offset.x, 0.), vertical),
+        (vec2(offset.x, 0.), vertical),
+    ];
+
+    let mut objects = walls
+        .into_iter()
+        .map(|(anchor, size)| {
+            let motion = Kinematic::Oscillate {
+                anchor,
+                amplitude: SHAKE_AMPLITUDE,
+                frequency: SHAKE_FREQUENCY,
+            };
+            Object::new_kinematic(anchor, 0., Polygon::rectangle(size), motion)
+        })
+        .collect::<Vec<_>>();
+
+    let mut nut = Object::new(vec2(0., -half.y + 0.6), Vec2::ZERO, 0., regular_polygon(6, 0.5));
+    nut.mass = 4.;
+    nut.inertia *= 4.;
+    objects.push(nut);
+
+    let spacing = 2.5 * GRAIN_RADIUS;
+    let columns = ((INNER_SIZE.x - spacing) / spacing) as usize;
+    for row in 0..8 {
+        for column in 0..columns {
+            let position = vec2(
+                -half.x + spacing * (column as f32 + 1.),
+                -half.y + 1.6 + spacing * row as f32,
+            );
+            let shape = regular_polygon(3 + (row + column) % 4, GRAIN_RADIUS);
+            objects.push(Object::new(position, Vec2::ZERO, (row * columns + column) as f32, shape));
+        }
+    }
+
+    for object in &mut objects {
+        if object.is_dynamic() {
+            object.acceleration = GRAVITY;
+        }
+    }
+
+    world.gravitational_constant = None;
+    world.load(objects, 0.);
+}
+
+/// Number of grains poured by [`funnel_pile`]
+pub const FUNNEL_GRAINS: usize = 5000;
+
+/// Block of small round grains poured through a funnel onto a pile, the performance and
+/// stability benchmark
+pub fn funnel_pile(world: &mut World) {
+    const GRAIN_RADIUS: f32 = 0.05;
+    const GRAIN_SIDES: usize = 8;
+    const COLUMNS: usize = 50;
+    const FUNNEL_GAP: f32 = 1.;
+    const FUNNEL_SLOPE: f32 = PI / 5.;
+    const FUNNEL_LENGTH: f32 = 8.;
+    const WALL_THICKNESS: f32 = 0.2;
+    const FLOOR_SIZE: Vec2 = Vec2::new(30., 0.5);
+
+    let fixed = |position: Vec2, rotation: f32, size: Vec2| Object::new_static(position, rotation, Polygon::rectangle(size));
+
+    let slab = vec2(FUNNEL_LENGTH, WALL_THICKNESS);
+    // Slab centers sit half a slab up the slope from the lips of the spout
+    let along = vec2(FUNNEL_SLOPE.cos(), FUNNEL_SLOPE.sin()) * FUNNEL_LENGTH / 2.;
+    let lip = vec2(FUNNEL_GAP / 2. + WALL_THICKNESS, 0.);
+    let mut objects = vec![
+        fixed(vec2(0., -6.), 0., FLOOR_SIZE),
+        fixed(lip + along, FUNNEL_SLOPE, slab),
+        fixed(vec2(-lip.x - along.x, along.y), -FUNNEL_SLOPE, slab),
+    ];
+
+    let spacing = 2.5 * GRAIN_RADIUS;
+    let rows = FUNNEL_GRAINS.div_ceil(COLUMNS);
+    for row in 0..rows {
+        // Every other row is shifted so the grains don't settle in columns
+        let shift = (row % 2) as f32 * spacing / 2.;
+        for column in 0..COLUMNS.min(FUNNEL_GRAINS - row * COLUMNS) {
+            let position = vec2(
+                (column as f32 - COLUMNS as f32 / 2.) * spacing + shift,
+                FUNNEL_LENGTH * FUNNEL_SLOPE.sin() + 1. + row as f32 * spacing,
+            );
+            let shape = regular_polygon(GRAIN_SIDES, GRAIN_RADIUS);
+            let mut grain = Object::new(position, Vec2::ZERO, (row + column) as f32, shape);
+            grain.acceleration = GRAVITY;
+            objects.push(grain);
+        }
+    }
+
+    world.gravitational_constant = None;
+    world.load(objects, 0.);
+}
+
+/// Tunneling worst case: a bullet at 1 km/s against a wall 1 cm thick
+pub fn thin_wall_bullet(world: &mut World) {
+    let wall = Object::new_static(Vec2::ZERO, 0., Polygon::rectangle(vec2(0.01, 4.)));
+    let bullet = Object::new(vec2(-5., 0.3), vec2(1000., 0.), 0., Polygon::rectangle(vec2(0.05, 0.02)));
+    world.gravitational_constant = None;
+    world.load(vec![wall, bullet], 0.);
+}
+
+/// Tunneling worst case: a needle-thin triangle spinning fast while drifting into a box
+pub fn spinning_needle(world: &mut World) {
+    let needle_shape = Polygon::new(vec![vec2(-1., -0.01), vec2(1., 0.), vec2(-1., 0.01)]);
+    let mut needle = Object::new(vec2(-2., 0.), vec2(1., 0.), 0., needle_shape);
+    needle.rot_velocity = 60.;
+    let block = Object::new(vec2(1., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1., 1.)));
+    world.gravitational_constant = None;
+    world.load(vec![needle, block], 0.);
+}
+
+/// Empty patch at the center of [`asteroid_field`]
+pub const ASTEROID_CLEARING: f32 = 4.;
+
+/// Slowly drifting and tumbling asteroids without gravity around an empty patch at the origin
+pub fn asteroid_field(world: &mut World) {
+    const COUNT: usize = 60;
+    const FIELD_RADIUS: f32 = 20.;
+    const MIN_RADIUS: f32 = 0.4;
+    const MAX_RADIUS: f32 = 1.2;
+    const MAX_DRIFT: f32 = 0.5;
+    const MAX_SPIN: f32 = 1.;
+
+    // Sunflower spiral, evenly spread without a random number generator
+    let golden_angle = PI * (3. - 5f32.sqrt());
+    let (inner, outer) = (ASTEROID_CLEARING * ASTEROID_CLEARING, FIELD_RADIUS * FIELD_RADIUS);
+    let objects = (0..COUNT)
+        .map(|i| {
+            let t = (i as f32 + 0.5) / COUNT as f32;
+            let distance = (inner + (outer - inner) * t).sqrt();
+            let (sin, cos) = (i as f32 * golden_angle).sin_cos();
+            // Scrambled by another irrational step so neighbors don't look alike
+            let variation = (i as f32 * golden_angle / 2.).rem_euclid(1.);
+            let radius = MIN_RADIUS + (MAX_RADIUS - MIN_RADIUS) * variation;
+            let drift = vec2(-sin, cos) * MAX_DRIFT * (2. * variation - 1.);
+            let mut asteroid = Object::new(vec2(cos, sin) * distance, drift, i as f32, regular_polygon(5 + i % 4, radius));
+            asteroid.mass = radius * radius;
+            asteroid.inertia *= asteroid.mass;
+            asteroid.rot_velocity = MAX_SPIN * (1. - 2. * variation);
+            asteroid
+        })
+        .collect();
+
+    world.gravitational_constant = None;
+    world.load(objects, 0.);
+}
+
+/// Name of the small body in [`orbit_slingshot`]
+pub const PROBE_NAME: &str = "probe";
+
+/// A moon circling a sun under mutual gravity and a light probe on a slightly too slow orbit, a
+/// push at the right moment slings it past the moon
+pub fn orbit_slingshot(world: &mut World) {
+    const CONSTANT: f32 = 0.1;
+    const SUN_MASS: f32 = 1000.;
+    const MOON_MASS: f32 = 20.;
+    const MOON_DISTANCE: f32 = 12.;
+    const PROBE_DISTANCE: f32 = 5.;
+    /// Fraction of the circular orbit speed the probe starts with
+    const PROBE_SPEED: f32 = 0.8;
+
+    let circular_speed = |distance: f32| (CONSTANT * SUN_MASS / distance).sqrt();
+    let body = |name: &str, position: Vec2, velocity: Vec2, radius: f32, mass: f32| {
+        let mut body = Object::new(position, velocity, 0., regular_polygon(12, radius));
+        body.mass = mass;
+        body.inertia *= mass;
+        body.name = Some(name.into());
+        body
+    };
+    let moon_velocity = vec2(0., circular_speed(MOON_DISTANCE));
+    let objects = vec![
+        // Recoils against the moon so the pair's center of mass stays put
+        body("sun", Vec2::ZERO, -moon_velocity * MOON_MASS / SUN_MASS, 1.5, SUN_MASS),
+        body("moon", vec2(MOON_DISTANCE, 0.), moon_velocity, 0.5, MOON_MASS),
+        body(
+            PROBE_NAME,
+            vec2(0., -PROBE_DISTANCE),
+            vec2(circular_speed(PROBE_DISTANCE) * PROBE_SPEED, 0.),
+            0.15,
+            0.01,
+        ),
+    ];
+
+    world.gravitational_constant = Some(CONSTANT);
+    world.load(objects, 0.);
+}
+
+/// Two teams of three running at each other's goal across a walled pitch without gravity, a
+/// neutral ball sits in the way of one of them. Teammates pass through each other and their own
+/// goal, the players who reach the other goal score.
+pub fn team_match(world: &mut World) {
+    const PITCH: Vec2 = Vec2::new(16., 9.);
+    const WALL_THICKNESS: f32 = 0.5;
+    const GOAL_SIZE: Vec2 = Vec2::new(0.5, 4.);
+    const PLAYER_RADIUS: f32 = 0.4;
+    const PLAYER_SPEED: f32 = 3.;
+
+    let half = PITCH / 2.;
+    let offset = half + Vec2::splat(WALL_THICKNESS / 2.);
+    let horizontal = vec2(PITCH.x + 2. * WALL_THICKNESS, WALL_THICKNESS);
+    let vertical = vec2(WALL_THICKNESS, PITCH.y);
+    let fixed = |position: Vec2, size: Vec2| Object::new_static(position, 0., Polygon::rectangle(size));
+    let mut objects = vec![
+        fixed(vec2(0., -offset.y), horizontal),
+        fixed(vec2(0., offset.y), horizontal),
+        fixed(vec2(-offset.x, 0.), vertical),
+        fixed(vec2(offset.x, 0.), vertical),
+    ];
+
+    // Staggered lanes, the players only meet the ball, the walls and the goals
+    for (team, side, lanes) in [(0, -1., [-2.5, 0., 2.5]), (1, 1., [-1.25, 1.25, 3.5])] {
+        let mut goal = fixed(vec2(side * (half.x - GOAL_SIZE.x / 2.), 0.), GOAL_SIZE);
+        goal.team = Some(team);
+        goal.goal = true;
+        objects.push(goal);
+        for (lane, y) in lanes.into_iter().enumerate() {
+            let velocity = vec2(-side * PLAYER_SPEED, 0.);
+            let mut player = Object::new(vec2(side * half.x / 2., y), velocity, lane as f32, regular_polygon(6, PLAYER_RADIUS));
+            player.team = Some(team);
+            objects.push(player);
+        }
+    }
+    objects.push(Object::new(vec2(0., -2.5), Vec2::ZERO, 0., regular_polygon(10, PLAYER_RADIUS)));
+
+    world.gravitational_constant = None;
+    world.gravity = Vec2::ZERO;
+    world.load(objects, 0.);
+    world.teams = vec![Team::new("red", [0.9, 0.25, 0.2]), Team::new("blue", [0.2, 0.4, 0.95])];
+}
+
+/// Links of [`jointed_chain`]
+pub const CHAIN_LINKS: usize = 24;
+
+/// Half the side of the square the viewer frames [`jointed_chain`] in
+pub const JOINTED_CHAIN_VIEW: f32 = 16.;
+
+/// Chain of capsule links joined end to end by ball-and-socket joints, pinned at one end and let
+/// go horizontally so it whips down under gravity, the stress test of the joint solver
+pub fn jointed_chain(world: &mut World) {
+    const LINK_LENGTH: f32 = 0.5;
+    const LINK_RADIUS: f32 = 0.08;
+    /// Between the caps of neighboring links, they only touch in sharp bends
+    const GAP: f32 = 0.1;
+
+    let pitch = LINK_LENGTH + GAP;
+    let objects = (0..CHAIN_LINKS)
+        .map(|i| Object::capsule(vec2(pitch * (i as f32 + 0.5), 0.), Vec2::ZERO, 0., LINK_LENGTH, LINK_RADIUS))
+        .collect();
+    let end = vec2(pitch / 2., 0.);
+
+    world.gravitational_constant = None;
+    world.gravity = GRAVITY;
+    world.load(objects, 0.);
+    world.joints = core::iter::once(Joint::pin(0, -end, Vec2::ZERO))
+        .chain((1..CHAIN_LINKS).map(|i| Joint::new(i - 1, end, i, -end)))
+        .collect();
+}
+
+/// Radius of the planet in [`planet`]
+pub const PLANET_RADIUS: f32 = 5.;
+/// Half the side of the square the viewer frames [`planet`] in
+pub const PLANET_VIEW: f32 = 2. * PLANET_RADIUS + 2.;
+
+/// A static round planet pulling crates and balls scattered around it down onto its curved
+/// surface, and a moon in a low circular orbit skimming over them
+pub fn planet(world: &mut World) {
+    const SURFACE_GRAVITY: f32 = 9.81;
+    const CRATE_SIZE: f32 = 0.6;
+    const BALL_RADIUS: f32 = 0.3;
+    const DROP_HEIGHT: f32 = 2.;
+    const MOON_ALTITUDE: f32 = 4.;
+
+    // Sticky and dull so what lands on the surface stays where it landed
+    let settling = |mut object: Object| {
+        object.restitution = 0.2;
+        object.static_friction = 0.9;
+        object.dynamic_friction = 0.7;
+        object
+    };
+    let mut ground = settling(Object::circle(Vec2::ZERO, Vec2::ZERO, PLANET_RADIUS));
+    ground.body_type = BodyType::Static;
+    ground.mass = f32::INFINITY;
+    ground.inertia = f32::INFINITY;
+    let mut objects = vec![ground];
+    for k in 0..8 {
+        let angle = k as f32 * PI / 4.;
+        let position = Vec2::from_angle(angle) * (PLANET_RADIUS + DROP_HEIGHT);
+        objects.push(settling(if k % 2 == 0 {
+            Object::new(position, Vec2::ZERO, angle, Polygon::rectangle(Vec2::splat(CRATE_SIZE)))
+        } else {
+            Object::circle(position, Vec2::ZERO, BALL_RADIUS)
+        }));
+    }
+    let source = GravitySource::planet(Vec2::ZERO, PLANET_RADIUS, SURFACE_GRAVITY);
+    let orbit = PLANET_RADIUS + MOON_ALTITUDE;
+    let orbit_speed = (source.strength / orbit).sqrt();
+    objects.push(Object::circle(vec2(0., -orbit), vec2(orbit_speed, 0.), BALL_RADIUS));
+
+    world.gravitational_constant = None;
+    world.gravity = Vec2::ZERO;
+    world.load(objects, 0.);
+    world.gravity_sources = vec![source];
+}
+
+/// Balls of [`newtons_cradle`]
+pub const CRADLE_BALLS: usize = 5;
+
+/// Row of equal balls hanging from pinned strings, the first pulled out to the side. Let go, it
+/// swings into the row and the swing passes through it to the ball at the far end.
+pub fn newtons_cradle(world: &mut World) {
+    const BALL_RADIUS: f32 = 0.5;
+    const STRING_LENGTH: f32 = 4.;
+    /// Between neighbors at rest, the swing crosses it as a series of elastic collisions
+    const GAP: f32 = 0.02;
+    const PULL_ANGLE: f32 = PI / 4.;
+
+    let pitch = 2. * BALL_RADIUS + GAP;
+    let pivots = (0..CRADLE_BALLS)
+        .map(|i| vec2(pitch * (i as f32 - (CRADLE_BALLS - 1) as f32 / 2.), STRING_LENGTH))
+        .collect::<Vec<_>>();
+    let hanging = Vec2::from_angle(-PI / 2.) * STRING_LENGTH;
+    let pulled = Vec2::from_angle(-PI / 2. - PULL_ANGLE) * STRING_LENGTH;
+    let objects = pivots
+        .iter()
+        .enumerate()
+        .map(|(i, &pivot)| {
+            let mut ball = Object::circle(pivot + if i == 0 { pulled } else { hanging }, Vec2::ZERO, BALL_RADIUS);
+            ball.name = Some(format!("ball {}", i + 1));
+            ball
+        })
+        .collect::<Vec<_>>();
+    let joints = objects.iter().zip(&pivots).enumerate().map(|(i, (ball, &pivot))| Joint::pin(i, pivot - ball.position, pivot));
+    let joints = joints.collect();
+
+    world.gravitational_constant = None;
+    world.gravity = GRAVITY;
+    world.load(objects, 0.);
+    world.joints = joints;
+}
+
+/// Rack of fifteen balls on a walled table without gravity, seen from above, broken by a fast cue
+/// ball
+pub fn billiard_break(world: &mut World) {
+    const TABLE: Vec2 = Vec2::new(24., 12.);
+    const CUSHION_THICKNESS: f32 = 0.5;
+    const BALL_RADIUS: f32 = 0.3;
+    const ROWS: usize = 5;
+    const CUE_SPEED: f32 = 12.;
+
+    let half = TABLE / 2.;
+    let offset = half + Vec2::splat(CUSHION_THICKNESS / 2.);
+    let horizontal = vec2(TABLE.x + 2. * CUSHION_THICKNESS, CUSHION_THICKNESS);
+    let vertical = vec2(CUSHION_THICKNESS, TABLE.y);
+    let mut objects = [
+        (vec2(0., -offset.y), horizontal),
+        (vec2(0., offset.y), horizontal),
+        (vec2(-offset.x, 0.), vertical),
+        (vec2(offset.x, 0.), vertical),
+    ]
+    .into_iter()
+    .map(|(position, size)| {
+        let mut cushion = Object::new_static(position, 0., Polygon::rectangle(size));
+        cushion.restitution = 0.8;
+        cushion
+    })
+    .collect::<Vec<_>>();
+
+    // Each row one ball wider, nested in the gaps of the one before with a hair of room
+    let spacing = 2. * BALL_RADIUS + 0.01;
+    let row_pitch = spacing * (PI / 6.).cos();
+    let apex = vec2(half.x / 2., 0.);
+    for row in 0..ROWS {
+        for k in 0..=row {
+            let position = apex + vec2(row as f32 * row_pitch, (k as f32 - row as f32 / 2.) * spacing);
+            let mut ball = Object::circle(position, Vec2::ZERO, BALL_RADIUS);
+            ball.restitution = 0.95;
+            objects.push(ball);
+        }
+    }
+    // A little off the middle, a dead straight break stays symmetric
+    let mut cue = Object::circle(vec2(-half.x / 2., 0.02), vec2(CUE_SPEED, 0.), BALL_RADIUS);
+    cue.restitution = 0.95;
+    cue.name = Some("cue".into());
+    objects.push(cue);
+
+    world.gravitational_constant = None;
+    world.gravity = Vec2::ZERO;
+    world.load(objects, 0.);
+}
+
+/// Triangles hailing in staggered rows onto a floor, in sizes and turns spread by the golden
+/// ratio so the set is the same every time
+pub fn hail_of_triangles(world: &mut World) {
+    const FLOOR_WIDTH: f32 = 20.;
+    const COLUMNS: usize = 6;
+    const ROWS: usize = 4;
+    const ROW_SPACING: f32 = 2.;
+    const SIZE: f32 = 0.35;
+
+    let settling = |mut object: Object| {
+        object.restitution = 0.6;
+        object.static_friction = 0.4;
+        object.dynamic_friction = 0.3;
+        object
+    };
+    let mut objects = vec![settling(Object::new_static(vec2(0., -0.5), 0., Polygon::rectangle(vec2(FLOOR_WIDTH, 1.))))];
+
+    let golden = (5f32.sqrt() - 1.) / 2.;
+    let pitch = FLOOR_WIDTH * 0.8 / COLUMNS as f32;
+    for row in 0..ROWS {
+        for column in 0..COLUMNS {
+            let k = (row * COLUMNS + column) as f32;
+            let spread = k * golden - (k * golden).floor();
+            let stagger = pitch * row as f32 / ROWS as f32;
+            let position = vec2(pitch * (column as f32 - (COLUMNS - 1) as f32 / 2.) + stagger, 4. + ROW_SPACING * row as f32);
+            let shape = regular_polygon(3, SIZE * (0.7 + 0.6 * spread));
+            objects.push(settling(Object::new(position, vec2(0., -2.), 2. * PI * spread, shape)));
+        }
+    }
+
+    world.gravitational_constant = None;
+    world.gravity = GRAVITY;
+    world.load(objects, 0.);
+}
+
+/// Speeds [`wall_bounce`] throws its shapes at the wall with, slowest first
+pub const WALL_BOUNCE_SPEEDS: [f32; 4] = [2., 10., 50., 250.];
+
+/// Perfectly elastic ball, box, triangle and capsule thrown head-on at a static wall without
+/// gravity, in lanes of their own at speeds from [`WALL_BOUNCE_SPEEDS`]. Each should come back
+/// at the speed it hit with.
+pub fn wall_bounce(world: &mut World) {
+    const WALL_X: f32 = 3.;
+    const LANE_SPACING: f32 = 1.5;
+
+    let lanes = WALL_BOUNCE_SPEEDS.len() as f32;
+    let wall = Object::new_static(vec2(WALL_X, 0.), 0., Polygon::rectangle(vec2(0.5, LANE_SPACING * lanes)));
+    let mut objects = vec![wall];
+    for (lane, speed) in WALL_BOUNCE_SPEEDS.into_iter().enumerate() {
+        let position = vec2(0., LANE_SPACING * (lane as f32 - (lanes - 1.) / 2.));
+        let velocity = vec2(speed, 0.);
+        let (name, mut object) = match lane {
+            0 => ("ball", Object::circle(position, velocity, 0.4)),
+            1 => ("box", Object::new(position, velocity, 0., Polygon::rectangle(Vec2::splat(0.7)))),
+            2 => ("triangle", Object::new(position, velocity, 0., regular_polygon(3, 0.45))),
+            _ => ("capsule", Object::capsule(position, velocity, 0., 0.9, 0.2)),
+        };
+        object.name = Some(name.into());
+        objects.push(object);
+    }
+
+    world.gravitational_constant = None;
+    world.gravity = Vec2::ZERO;
+    world.load(objects, 0.);
+}
+
+/// Function building a preset into a world
+pub type Preset = fn(&mut World);
+
+/// Presets offered by name, e.g. in the viewer's demo list
+pub const DEMOS: [(&str, Preset); 4] = [
+    ("Newton's cradle", newtons_cradle),
+    ("Billiard break", billiard_break),
+    ("Hail of triangles", hail_of_triangles),
+    ("Wall bounce", wall_bounce),
+];
+
+fn regular_polygon(sides: usize, radius: f32) -> Polygon {
+    let mut polygon = Polygon::regular(sides);
+    polygon.points.iter_mut().for_each(|p| *p *= radius);
+    polygon
+}
+
+#[test]
+fn test_shaker_table_walls_follow_motion() {
+    let mut world = World::new();
+    shaker_table(&mut world);
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+    }
+    for wall in world.objects.iter().filter(|object| !object.is_dynamic()) {
+        let expected = wall.kinematic().unwrap().position_at(wall.cur_time).unwrap();
+        assert!(wall.position.distance(expected) < 1e-2);
+        assert_eq!(wall.rotation, 0.);
+    }
+    let nut = &world.objects[4];
+    assert!(nut.position.x.abs() < 5. && nut.position.y.abs() < 4.);
+}
+
+#[test]
+fn test_funnel_pile_layout() {
+    let mut world = World::new();
+    funnel_pile(&mut world);
+    let grains = world.objects.iter().filter(|object| object.is_dynamic()).collect::<Vec<_>>();
+    assert_eq!(grains.len(), FUNNEL_GRAINS);
+    // Every grain starts above the funnel and within its mouth
+    let funnel_top = world.objects[1..3]
+        .iter()
+        .flat_map(|wall| wall.shape.points.iter().map(|p| wall.to_world(*p).y))
+        .fold(f32::MIN, f32::max);
+    let mouth = world.objects[2].to_world(world.objects[2].shape.points[0]).x;
+    assert!(grains.iter().all(|grain| grain.position.y > funnel_top && grain.position.x.abs() < -mouth));
+}
+
+#[test]
+fn test_asteroid_field_layout() {
+    let mut world = World::new();
+    asteroid_field(&mut world);
+    assert!(world.interpenetrating_pairs().is_empty());
+    for asteroid in &world.objects {
+        let reach = asteroid.position.length() - asteroid.bounding_circle.radius;
+        assert!(reach > ASTEROID_CLEARING / 2.);
+        assert_eq!(asteroid.acceleration, Vec2::ZERO);
+    }
+}
+
+#[test]
+fn test_orbit_slingshot_moon_stays_on_orbit() {
+    let mut world = World::new();
+    orbit_slingshot(&mut world);
+    let distance = |world: &World| world.objects[1].position.distance(world.objects[0].position);
+    let start = distance(&world);
+    for _ in 0..600 {
+        world.step(1. / 60.).unwrap();
+        assert!((distance(&world) - start).abs() < start * 0.05);
+    }
+    assert_eq!(world.objects[2].name.as_deref(), Some(PROBE_NAME));
+}
+
+#[test]
+fn test_team_match_scores() {
+    let mut world = World::new();
+    team_match(&mut world);
+    // The walls and goals touch, the players start clear of everything
+    let mut pairs = world.interpenetrating_pairs().into_iter();
+    assert!(pairs.all(|(a, b)| world.objects[a].is_static() && world.objects[b].is_static()));
+    let goals = world.objects.iter().filter(|object| object.goal).map(|object| object.team).collect::<Vec<_>>();
+    assert_eq!(goals, [Some(0), Some(1)]);
+    // The lanes in front of the goals reach them in under four seconds
+    for _ in 0..240 {
+        world.step(1. / 60.).unwrap();
+    }
+    assert_eq!(world.teams.iter().map(|team| team.score).collect::<Vec<_>>(), [1, 2]);
+    assert!(world.objects.last().unwrap().collided > 0);
+}
+
+#[test]
+fn test_worst_cases_dont_tunnel() {
+    for (name, preset) in [("thin_wall_bullet", thin_wall_bullet as fn(&mut World)), ("spinning_needle", spinning_needle)] {
+        let mut world = World::new();
+        preset(&mut world);
+        for frame in 0..240 {
+            world.step(1. / 60.).unwrap();
+            let pairs = world.interpenetrating_pairs();
+            assert!(pairs.is_empty(), "{name}: {pairs:?} overlap in frame {frame}");
+        }
+        assert!(world.objects.iter().any(|object| object.collided > 0), "{name}: nothing collided");
+    }
+}
+
+#[test]
+fn test_planet_settles_on_its_surface() {
+    let mut world = World::new();
+    planet(&mut world);
+    let moon = world.objects.len() - 1;
+    let orbit = world.objects[moon].position.length();
+    for _ in 0..300 {
+        world.step(1. / 60.).unwrap();
+        assert!((world.objects[moon].position.length() - orbit).abs() < orbit * 0.05);
+    }
+    // Everything dropped came to rest on the curved surface, right where it was let go
+    for (k, object) in world.objects[1..moon].iter().enumerate() {
+        let up = Vec2::from_angle(k as f32 * PI / 4.);
+        let height = object.position.length() - PLANET_RADIUS;
+        assert!((0.25..0.35).contains(&height), "{k}: resting {height} above the surface");
+        assert!(object.position.normalize().dot(up) > 0.99, "{k}: slid to {}", object.position);
+        assert!(object.velocity.length() < 0.1, "{k}: still moving at {}", object.velocity);
+    }
+}
+
+#[test]
+fn test_newtons_cradle_passes_the_swing() {
+    let mut world = World::new();
+    newtons_cradle(&mut world);
+    let rest = world.objects[1].position.y;
+    assert!(world.interpenetrating_pairs().is_empty());
+    // A quarter period of the pendulum to hit, as long again for the last ball to swing out
+    let mut highest = f32::MIN;
+    for _ in 0..240 {
+        world.step(1. / 120.).unwrap();
+        highest = highest.max(world.objects[CRADLE_BALLS - 1].position.y);
+    }
+    assert!(highest - rest > 0.5, "the last ball only rose {}", highest - rest);
+    for ball in &world.objects[1..CRADLE_BALLS - 1] {
+        assert!((ball.position.y - rest).abs() < 0.1, "{:?} swung along", ball.name);
+    }
+}
+
+#[test]
+fn test_billiard_break_scatters_the_rack() {
+    let mut world = World::new();
+    billiard_break(&mut world);
+    // Only the cushions touch, at the corners
+    let overlapping = |world: &World| {
+        let mut pairs = world.interpenetrating_pairs().into_iter();
+        !pairs.all(|(a, b)| world.objects[a].is_static() && world.objects[b].is_static())
+    };
+    assert!(!overlapping(&world));
+    let energy = world.total_energy();
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+        assert!(!overlapping(&world));
+    }
+    let moving = world.objects.iter().filter(|ball| ball.is_dynamic() && ball.velocity.length() > 0.1).count();
+    assert!(moving > 10, "only {moving} balls moving");
+    assert!(world.total_energy() < energy);
+}
+
+#[test]
+fn test_hail_of_triangles_lands() {
+    let mut world = World::new();
+    hail_of_triangles(&mut world);
+    assert!(world.interpenetrating_pairs().is_empty());
+    for frame in 0..300 {
+        world.step(1. / 60.).unwrap_or_else(|e| panic!("frame {frame}: {e}"));
+        for triangle in world.objects.iter().filter(|object| object.is_dynamic() && object.position.x.abs() < 9.) {
+            assert!(triangle.position.y > 0., "frame {frame}: a triangle sank to {}", triangle.position);
+        }
+    }
+    // Bounced off the ends at most, the rest came down and stayed on the floor
+    let on_floor = world.objects.iter().filter(|object| object.is_dynamic() && object.position.x.abs() < 9.);
+    assert!(on_floor.clone().count() > 20);
+    assert!(on_floor.clone().all(|triangle| triangle.position.y < 2.));
+}
+
+#[test]
+fn test_wall_bounce_keeps_speeds() {
+    let mut world = World::new();
+    wall_bounce(&mut world);
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+    }
+    for (object, speed) in world.objects[1..].iter().zip(WALL_BOUNCE_SPEEDS) {
+        assert!(object.collided > 0, "{:?} didn't reach the wall", object.name);
+        assert!((object.velocity.x + speed).abs() < speed * 1e-3, "{:?} came back at {}", object.name, object.velocity);
+    }
+}
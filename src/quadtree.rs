@@ -0,0 +1,113 @@
+//! Quadtree over the objects' bounding boxes for the viewer's own queries.
+//!
+//! Rebuilt after every step and used for view culling and picking, so the renderer never touches
+//! the solver's broad phase. Boxes are stored in the deepest node that fully contains them.
+
+use super::*;
+
+/// A node splits once it holds more boxes than this
+const MAX_ITEMS: usize = 8;
+const MAX_DEPTH: usize = 10;
+
+struct Node {
+    bounds: Aabb,
+    items: Vec<(usize, Aabb)>,
+    /// Indices of the four quadrants once the node has split
+    children: Option<[usize; 4]>,
+}
+
+pub struct QuadTree {
+    nodes: Vec<Node>,
+}
+
+impl QuadTree {
+    pub fn new() -> Self {
+        Self { nodes: vec![] }
+    }
+
+    /// Tree over the bounding circles of the objects, keyed by their indices
+    pub fn build(objects: &[Object]) -> Self {
+        let boxes = objects
+            .iter()
+            .map(|object| {
+                let center = object.to_world(object.bounding_circle.center);
+                Aabb::new(center, center).expanded_by(object.bounding_circle.radius)
+            })
+            .collect::<Vec<_>>();
+        let bounds = boxes.iter().fold(Aabb::EMPTY, |bounds, aabb| bounds.union(aabb));
+        let mut tree = Self {
+            nodes: vec![Node {
+                bounds,
+                items: vec![],
+                children: None,
+            }],
+        };
+        for (i, aabb) in boxes.into_iter().enumerate() {
+            tree.insert(0, i, aabb, 0);
+        }
+        tree
+    }
+
+    fn insert(&mut self, node: usize, index: usize, aabb: Aabb, depth: usize) {
+        if let Some(children) = self.nodes[node].children {
+            if let Some(&child) = children.iter().find(|&&child| encloses(&self.nodes[child].bounds, &aabb)) {
+                self.insert(child, index, aabb, depth + 1);
+                return;
+            }
+        }
+        self.nodes[node].items.push((index, aabb));
+        if self.nodes[node].children.is_none() && self.nodes[node].items.len() > MAX_ITEMS && depth < MAX_DEPTH {
+            self.split(node, depth);
+        }
+    }
+
+    fn split(&mut self, node: usize, depth: usize) {
+        let Aabb { min, max } = self.nodes[node].bounds;
+        let center = (min + max) / 2.;
+        let quadrants = [
+            Aabb::new(min, center),
+            Aabb::new(vec2(center.x, min.y), vec2(max.x, center.y)),
+            Aabb::new(vec2(min.x, center.y), vec2(center.x, max.y)),
+            Aabb::new(center, max),
+        ];
+        let first = self.nodes.len();
+        self.nodes.extend(quadrants.map(|bounds| Node {
+            bounds,
+            items: vec![],
+            children: None,
+        }));
+        self.nodes[node].children = Some([first, first + 1, first + 2, first + 3]);
+        for (index, aabb) in core::mem::take(&mut self.nodes[node].items) {
+            self.insert(node, index, aabb, depth);
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        memory::vec_bytes(&self.nodes) + self.nodes.iter().map(|node| memory::vec_bytes(&node.items)).sum::<usize>()
+    }
+
+    /// Indices of the objects whose boxes overlap `area`, in no particular order
+    pub fn query(&self, area: &Aabb) -> Vec<usize> {
+        let mut found = vec![];
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let Some(node) = self.nodes.get(node) else {
+                continue;
+            };
+            if !node.bounds.overlaps(area) {
+                continue;
+            }
+            found.extend(node.items.iter().filter(|(_, aabb)| aabb.overlaps(area)).map(|(i, _)| *i));
+            stack.extend(node.children.into_iter().flatten());
+        }
+        found
+    }
+
+    pub fn query_point(&self, point: Vec2) -> Vec<usize> {
+        self.query(&Aabb::new(point, point))
+    }
+}
+
+fn encloses(outer: &Aabb, inner: &Aabb) -> bool {
+    outer.contains(inner.min) && outer.contains(inner.max)
+}
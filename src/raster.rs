@@ -0,0 +1,246 @@
+//! Frames of a world as images, without a window or a GPU.
+//!
+//! The viewer draws on the GPU, batch runs on a server have neither a window nor a device to draw
+//! with. [`render`] puts the objects through the viewer's camera and drawing order, [`draw`], and
+//! fills them on the CPU into an [`Image`], which the `std` build writes as PNG, so a run can save
+//! its figures or the frames of a video as it steps.
+//!
+//! The GPU half of the viewer isn't run: ellipsoid only creates its graphics for a window and has
+//! no offscreen surface. Objects come out as flat fills, without the viewer's textures, outlines
+//! or overlays, but where and in which order they're drawn is what the viewer does.
+//!
+//! Rendering is deterministic, so frames of preset scenes double as golden images: the tests
+//! compare them with the ones under `tests/golden`, catching changes to the simulation, the camera
+//! and the drawing order. `UPDATE_GOLDEN=1 cargo test` rewrites them after an intended change.
+
+use super::*;
+use draw::{Depth, DrawQueue, View};
+
+/// Background of rendered frames, RGBA
+pub const BACKGROUND: [u8; 4] = [24, 24, 28, 255];
+/// Fill of static objects
+pub const STATIC_COLOR: [u8; 4] = [120, 120, 128, 255];
+/// Fills of dynamic objects, picked by index
+pub const PALETTE: [[u8; 4]; 6] = [
+    [231, 111, 81, 255],
+    [244, 162, 97, 255],
+    [233, 196, 106, 255],
+    [42, 157, 143, 255],
+    [86, 180, 233, 255],
+    [204, 121, 167, 255],
+];
+
+/// RGBA pixels, row by row from the top
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, color: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width * height],
+        }
+    }
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[y * self.width + x]
+    }
+    /// Number of pixels differing by more than `threshold` in any channel, `None` for images of
+    /// different sizes
+    pub fn diff(&self, other: &Self, threshold: u8) -> Option<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        let differs = |a: &[u8; 4], b: &[u8; 4]| a.iter().zip(b).any(|(a, b)| a.abs_diff(*b) > threshold);
+        Some(self.pixels.iter().zip(&other.pixels).filter(|(a, b)| differs(a, b)).count())
+    }
+    /// Fills the pixels whose centers lie inside the polygon given in pixel coordinates
+    pub fn fill_polygon(&mut self, points: &[Vec2], color: [u8; 4]) {
+        let bounds = Aabb::from_points(points);
+        if points.len() < 3 || !bounds.min.is_finite() || !bounds.max.is_finite() {
+            return;
+        }
+        let rows = bounds.min.y.max(0.) as usize..(bounds.max.y.ceil().max(0.) as usize).min(self.height);
+        for y in rows {
+            let center_y = y as f32 + 0.5;
+            // Crossings of the row with the edges, the polygon covers every other span between them
+            let mut crossings = points
+                .iter()
+                .zip(points.iter().cycle().skip(1))
+                .filter(|(a, b)| (a.y <= center_y) != (b.y <= center_y))
+                .map(|(a, b)| a.x + (center_y - a.y) / (b.y - a.y) * (b.x - a.x))
+                .collect::<Vec<_>>();
+            crossings.sort_by(f32::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let start = (span[0] - 0.5).ceil().max(0.) as usize;
+                let end = ((span[1] - 0.5).floor() + 1.).clamp(0., self.width as f32) as usize;
+                for x in start..end {
+                    self.pixels[y * self.width + x] = color;
+                }
+            }
+        }
+    }
+
+    /// The image as a PNG file, 8 bit RGBA
+    #[cfg(feature = "std")]
+    pub fn encode_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut png = vec![];
+        self.write_png(&mut png)?;
+        Ok(png)
+    }
+    /// Reads an 8 bit RGB or RGBA PNG, `None` for other color types or a broken file
+    #[cfg(feature = "std")]
+    pub fn decode_png(png: &[u8]) -> Option<Self> {
+        let mut decoder = png::Decoder::new(png);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().ok()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut data).ok()?;
+        let data = &data[..frame.buffer_size()];
+        let pixels = match frame.color_type {
+            png::ColorType::Rgba => data.chunks_exact(4).map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]).collect(),
+            png::ColorType::Rgb => data.chunks_exact(3).map(|pixel| [pixel[0], pixel[1], pixel[2], 255]).collect(),
+            _ => return None,
+        };
+        Some(Self {
+            width: frame.width as usize,
+            height: frame.height as usize,
+            pixels,
+        })
+    }
+    #[cfg(feature = "std")]
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        Ok(self.write_png(file)?)
+    }
+    #[cfg(feature = "std")]
+    fn write_png(&self, out: impl std::io::Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels.concat())?;
+        writer.finish()
+    }
+}
+
+/// Box around the outlines of every object
+pub fn bounds(world: &World) -> Aabb {
+    let points = world
+        .objects
+        .iter()
+        .flat_map(|object| object.shape.points.iter().map(|p| object.to_world(*p)))
+        .collect::<Vec<_>>();
+    Aabb::from_points(&points)
+}
+
+/// Frame of `world` as it is now, stacked like the viewer stacks it, see [`Depth`]
+pub fn render(world: &World, view: &View, width: usize, height: usize) -> Image {
+    let size = vec2(width as f32, height as f32);
+    let mut image = Image::new(width, height, BACKGROUND);
+    let mut queue = DrawQueue::default();
+    for (i, object) in world.objects.iter().enumerate() {
+        let points = object.shape.points.iter().map(|p| view.to_pixel(object.to_world(*p), size)).collect::<Vec<_>>();
+        let color = if object.is_dynamic() { PALETTE[i % PALETTE.len()] } else { STATIC_COLOR };
+        queue.add(Depth::of(object, false), (points, color));
+    }
+    queue.flush(|(points, color)| image.fill_polygon(&points, color));
+    image
+}
+
+#[test]
+fn test_render_to_png() {
+    let mut world = World::new();
+    world.objects.push(Object::new_static(vec2(0., -2.), 0., Polygon::rectangle(vec2(8., 1.))));
+    world.objects.push(Object::new(vec2(1., 1.), Vec2::ZERO, 0.3, Polygon::regular(3)));
+    // Added later but drawn below the triangle
+    let mut under = Object::new(vec2(1., 1.), Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 2.)));
+    under.z_index = -1;
+    world.objects.push(under);
+    // Ten pixels per world unit
+    let view = View {
+        focus: Vec2::ZERO,
+        zoom: 0.2,
+    };
+    let image = render(&world, &view, 100, 100);
+    assert_eq!(image.pixel(0, 0), BACKGROUND);
+    // The floor spans most of the width 20 pixels below the middle
+    assert_eq!(image.pixel(50, 70), STATIC_COLOR);
+    assert_eq!(image.pixel(5, 70), BACKGROUND);
+    assert_eq!(image.pixel(60, 40), PALETTE[1]);
+    assert_eq!(image.pixel(69, 31), PALETTE[2]);
+
+    // Fitting puts everything in the frame
+    let aabb = Aabb::from_points(&[vec2(-4., -2.5), vec2(4., 1.5)]);
+    let fitted = View::fit(&aabb);
+    for corner in [aabb.min, aabb.max] {
+        let pixel = fitted.to_pixel(corner, vec2(100., 100.));
+        assert!((0. ..=100.).contains(&pixel.x) && (0. ..=100.).contains(&pixel.y), "{pixel}");
+    }
+
+    #[cfg(feature = "std")]
+    {
+        let png = image.encode_png().unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(Image::decode_png(&png).as_ref(), Some(&image));
+        assert_eq!(Image::decode_png(&png[..png.len() / 2]), None);
+    }
+    assert_eq!(image.diff(&render(&world, &fitted, 100, 100), 0).map(|different| different > 0), Some(true));
+}
+
+/// Overlapping squares, each drawn over the one added after it
+#[cfg(all(test, feature = "std"))]
+fn stacked_scene(world: &mut World) {
+    for i in 0..4 {
+        let mut square = Object::new(Vec2::splat(i as f32 * 0.6 - 1.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1.5, 1.5)));
+        square.z_index = -i;
+        world.objects.push(square);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_golden_frames() {
+    const WIDTH: usize = 128;
+    const HEIGHT: usize = 96;
+    /// Pixels along the edges may flip with float noise between targets
+    const MAX_DIFFERENT: usize = WIDTH * HEIGHT / 200;
+    const CHANNEL_THRESHOLD: u8 = 8;
+
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    // Framed like the viewer's buttons frame them, the last one stacks objects against the order
+    // they were added in
+    let scenes = [
+        ("planet", presets::planet as fn(&mut World), presets::PLANET_VIEW, 90),
+        ("jointed_chain", presets::jointed_chain, presets::JOINTED_CHAIN_VIEW, 60),
+        ("stacking", stacked_scene, 3., 0),
+    ];
+    for (name, build, half_view, steps) in scenes {
+        let mut world = World::new();
+        build(&mut world);
+        let view = View::fit(&Aabb::new(Vec2::splat(-half_view), Vec2::splat(half_view)));
+        for _ in 0..steps {
+            world.step(1. / 60.).unwrap();
+        }
+        let image = render(&world, &view, WIDTH, HEIGHT);
+
+        let path = dir.join(format!("{name}.png"));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(&dir).unwrap();
+            image.save_png(&path).unwrap();
+            continue;
+        }
+        let golden = std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {e}, UPDATE_GOLDEN=1 records it", path.display()));
+        let golden = Image::decode_png(&golden).unwrap_or_else(|| panic!("{} isn't an RGBA PNG", path.display()));
+        let different = image.diff(&golden, CHANNEL_THRESHOLD).unwrap_or(usize::MAX);
+        if different > MAX_DIFFERENT {
+            let actual = std::env::temp_dir().join(format!("{name}.actual.png"));
+            let _ = image.save_png(&actual);
+            panic!("{name}: {different} pixels differ from {}, this run's frame is {}", path.display(), actual.display());
+        }
+    }
+}
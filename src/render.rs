@@ -0,0 +1,32 @@
+//! Object fill styles and outlines.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// On-screen thickness of outlines, in screen units (the screen spans -1..1)
+const OUTLINE_THICKNESS: f32 = 0.003;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, strum::Display, strum::EnumIter)]
+pub enum FillStyle {
+    #[default]
+    Solid,
+    /// Only the outline, the swept hull stays visible underneath
+    Wireframe,
+    Textured,
+}
+
+/// Border of the polygon `points` given in world space, as one thin rectangle per edge
+pub fn outline(points: &[Vec2], color: Color, camera: &Camera) -> Vec<Shape<Txts>> {
+    let thickness = OUTLINE_THICKNESS / camera.zoom();
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            let edge = b - a;
+            Shape::from_line(edge.length(), thickness)
+                .set_color(color)
+                .apply(GTransform::from_translation(a).rotate(edge.y.atan2(edge.x)))
+                .apply(camera.0)
+        })
+        .collect()
+}
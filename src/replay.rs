@@ -0,0 +1,293 @@
+//! Recording of simulation runs and replays checking they reproduce them exactly.
+//!
+//! A recording is the starting scene plus, for every step, its settings, the resulting state hash
+//! and object states. Changes made between steps (flicks, spawns, removals) aren't replayed as
+//! inputs, the edited scene is stored with the step instead, next to the commands issued. A
+//! replay redoes the steps and stops at the first frame whose hash doesn't match, with a diff of
+//! the object states.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use broad_phase::SweepAxis;
+use command::Command;
+use integrator::Integrator;
+use islands::SubstepPolicy;
+use scene::Scene;
+use world::ObjectState;
+
+/// World settings a step's outcome depends on
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StepConfig {
+    pub collision_margin: f32,
+    pub simplified: bool,
+    pub batch_tolerance: Option<f32>,
+    pub collision_limit: Option<usize>,
+    pub pair_cache: bool,
+    #[serde(default)]
+    pub gravitational_constant: Option<f32>,
+    #[serde(default)]
+    pub enforce_conservation: bool,
+    #[serde(default)]
+    pub integrator: Integrator,
+    #[serde(default)]
+    pub substeps: Option<SubstepPolicy>,
+    #[serde(default)]
+    pub gravity: Vec2,
+    #[serde(default)]
+    pub sweep_axis: SweepAxis,
+}
+
+impl StepConfig {
+    pub fn of(world: &World) -> Self {
+        Self {
+            collision_margin: world.collision_margin,
+            simplified: world.simplified,
+            batch_tolerance: world.batch_tolerance,
+            collision_limit: world.collision_limit,
+            pair_cache: world.pair_cache.enabled,
+            gravitational_constant: world.gravitational_constant,
+            enforce_conservation: world.enforce_conservation,
+            integrator: world.integrator,
+            substeps: world.substeps,
+            gravity: world.gravity,
+            sweep_axis: world.sweep_axis,
+        }
+    }
+    pub fn apply(&self, world: &mut World) {
+        world.collision_margin = self.collision_margin;
+        world.simplified = self.simplified;
+        world.batch_tolerance = self.batch_tolerance;
+        world.collision_limit = self.collision_limit;
+        world.pair_cache.enabled = self.pair_cache;
+        world.gravitational_constant = self.gravitational_constant;
+        world.enforce_conservation = self.enforce_conservation;
+        world.integrator = self.integrator;
+        world.substeps = self.substeps;
+        world.gravity = self.gravity;
+        world.sweep_axis = self.sweep_axis;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordedFrame {
+    pub dt: f32,
+    pub config: StepConfig,
+    /// Scene right before the step when it was changed since the previous one
+    #[serde(default)]
+    pub edited: Option<Scene>,
+    /// Commands issued since the previous step, what they changed is in `edited`
+    #[serde(default)]
+    pub commands: Vec<Command>,
+    /// [`World::state_hash`] after the step
+    pub hash: u64,
+    pub states: Vec<ObjectState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recording {
+    pub initial: Scene,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    /// Approximate heap bytes, see [`memory`]
+    pub fn heap_bytes(&self) -> usize {
+        memory::vec_bytes(&self.frames)
+            + self.initial.heap_bytes()
+            + self
+                .frames
+                .iter()
+                .map(|frame| {
+                    let names = frame.states.iter().filter_map(|state| state.name.as_ref()).map(String::capacity);
+                    memory::vec_bytes(&frame.states)
+                        + names.sum::<usize>()
+                        + frame.edited.as_ref().map_or(0, Scene::heap_bytes)
+                        + memory::vec_bytes(&frame.commands)
+                })
+                .sum::<usize>()
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = ron::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+pub struct Recorder {
+    pub recording: Recording,
+    /// Hash after the last recorded step, a different hash before the next one means an edit
+    last_hash: u64,
+    /// Commands issued since the last recorded step
+    commands: Vec<Command>,
+}
+
+impl Recorder {
+    /// Starts recording from the world's current state
+    pub fn start(world: &mut World) -> Self {
+        // The replay starts with an empty cache, its contents can decide borderline pairs
+        world.pair_cache.clear();
+        Self {
+            recording: Recording {
+                initial: Scene::from_world(world),
+                frames: vec![],
+            },
+            last_hash: world.state_hash(),
+            commands: vec![],
+        }
+    }
+
+    /// Notes a command issued before the next step
+    pub fn command(&mut self, command: &Command) {
+        self.commands.push(command.clone());
+    }
+
+    /// Steps `world` and records the step, edits since the last step are stored with it
+    pub fn step(&mut self, world: &mut World, dt: f32) -> Result<(), CollisionError> {
+        let edited = (world.state_hash() != self.last_hash).then(|| {
+            world.pair_cache.clear();
+            Scene::from_world(world)
+        });
+        let config = StepConfig::of(world);
+        let result = world.step(dt);
+        self.last_hash = world.state_hash();
+        self.recording.frames.push(RecordedFrame {
+            dt,
+            config,
+            edited,
+            commands: std::mem::take(&mut self.commands),
+            hash: self.last_hash,
+            states: world.snapshot_states(),
+        });
+        result
+    }
+}
+
+/// Object whose replayed state doesn't match the recording, `None` where it doesn't exist
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateDiff {
+    pub index: usize,
+    pub recorded: Option<ObjectState>,
+    pub replayed: Option<ObjectState>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    /// Index into [`Recording::frames`]
+    pub frame: usize,
+    pub diffs: Vec<StateDiff>,
+}
+
+pub struct Replay {
+    pub recording: Recording,
+    /// Next frame to replay
+    pub frame: usize,
+    pub divergence: Option<Divergence>,
+}
+
+impl Replay {
+    /// Loads the recording's starting scene into `world`
+    pub fn start(recording: Recording, world: &mut World) -> Self {
+        recording.initial.clone().load_into(world);
+        Self {
+            recording,
+            frame: 0,
+            divergence: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.frames.len() || self.divergence.is_some()
+    }
+
+    /// Replays the next frame, returns whether it matched the recording
+    pub fn step(&mut self, world: &mut World) -> bool {
+        let Some(recorded) = self.recording.frames.get(self.frame) else {
+            return true;
+        };
+        if let Some(scene) = &recorded.edited {
+            scene.clone().load_into(world);
+        }
+        recorded.config.apply(world);
+        // Errors are part of the recorded outcome, the hash tells whether they were the same
+        let _ = world.step(recorded.dt);
+
+        let matches = world.state_hash() == recorded.hash;
+        if !matches {
+            self.divergence = Some(Divergence {
+                frame: self.frame,
+                diffs: diff_states(&recorded.states, &world.snapshot_states()),
+            });
+        }
+        self.frame += 1;
+        matches
+    }
+}
+
+fn diff_states(recorded: &[ObjectState], replayed: &[ObjectState]) -> Vec<StateDiff> {
+    (0..recorded.len().max(replayed.len()))
+        .filter_map(|index| {
+            let (recorded, replayed) = (recorded.get(index), replayed.get(index));
+            (recorded != replayed).then(|| StateDiff {
+                index,
+                recorded: recorded.cloned(),
+                replayed: replayed.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_replay_detects_divergence() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., Polygon::regular(4)));
+    world.objects.push(Object::new(vec2(3., 0.3), vec2(-1., 0.), 0.5, Polygon::regular(5)));
+    world.objects.push(Object::new(vec2(0., 4.), vec2(0., -1.), 0., Polygon::regular(3)));
+
+    let mut recorder = Recorder::start(&mut world);
+    for frame in 0..60 {
+        if frame == 30 {
+            // An edit between steps, like a flick in the viewer
+            let flick = Command::ApplyImpulse {
+                object: 2,
+                impulse: vec2(0.5, 0.),
+                point: None,
+            };
+            recorder.command(&flick);
+            flick.run(&mut world).unwrap();
+        }
+        recorder.step(&mut world, 1. / 60.).unwrap();
+    }
+    // Goes through the file so the exactness of the stored floats is covered too
+    let path = std::env::temp_dir().join("perfect-collisions-test-recording.ron");
+    recorder.recording.save(&path).unwrap();
+    let recording = Recording::load(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    assert!(recording.frames[30].edited.is_some());
+    assert_eq!(recording.frames[30].commands.len(), 1);
+    assert_eq!(recording.frames.iter().filter(|frame| frame.edited.is_some()).count(), 1);
+
+    let mut replayed = World::new();
+    let mut replay = Replay::start(recording.clone(), &mut replayed);
+    while !replay.is_finished() {
+        assert!(replay.step(&mut replayed), "diverged at {:?}", replay.divergence);
+    }
+    assert_eq!(replayed.state_hash(), world.state_hash());
+
+    let mut tampered = recording;
+    tampered.frames[45].states[1].velocity.x += 1.;
+    tampered.frames[45].hash ^= 1;
+    let mut replay = Replay::start(tampered, &mut replayed);
+    while !replay.is_finished() {
+        replay.step(&mut replayed);
+    }
+    let divergence = replay.divergence.unwrap();
+    assert_eq!(divergence.frame, 45);
+    assert_eq!(divergence.diffs.len(), 1);
+    assert_eq!(divergence.diffs[0].index, 1);
+}
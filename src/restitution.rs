@@ -0,0 +1,101 @@
+//! Drop test measuring the effective coefficient of restitution, a self-test of the solver.
+//!
+//! A ball is dropped onto a static floor, the apex heights of its bounces give the coefficient:
+//! with no air drag `h_next / h = e²`.
+
+use super::*;
+use object::Kinematic;
+
+const GRAVITY: Vec2 = Vec2::new(0., -9.81);
+const BALL_RADIUS: f32 = 0.25;
+const BALL_SIDES: usize = 16;
+/// Bounces lower than this part of the drop height are the ball settling, they're dominated by
+/// the collision margin and end the measurement
+const SETTLED_FRACTION: f32 = 0.05;
+const FLOOR_SIZE: Vec2 = Vec2::new(4., 0.5);
+
+pub struct DropTest {
+    pub drop_height: f32,
+    /// Restitution of the ball and the floor, which the bounces should measure
+    pub configured: f32,
+    /// Index of the ball in the world
+    ball: usize,
+    /// Distance from the ball's center to its lowest point, the ball doesn't spin
+    bottom_offset: f32,
+    /// Gap between the ball and the floor at every apex after a bounce
+    pub apex_heights: Vec<f32>,
+    last_vertical_velocity: f32,
+    settled: bool,
+}
+
+impl DropTest {
+    /// Replaces the world's objects with a floor whose top is at `y = 0` and a ball whose lowest
+    /// point is `drop_height` above it, both with the given restitution
+    pub fn setup(world: &mut World, drop_height: f32, restitution: f32) -> Self {
+        let mut floor = Object::new_kinematic(vec2(0., -FLOOR_SIZE.y / 2.), 0., Polygon::rectangle(FLOOR_SIZE), Kinematic::Constant);
+        floor.restitution = restitution;
+        let mut shape = Polygon::regular(BALL_SIDES);
+        shape.points.iter_mut().for_each(|p| *p *= BALL_RADIUS);
+        let bottom_offset = -shape.points.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let mut ball = Object::new(vec2(0., drop_height + bottom_offset), Vec2::ZERO, 0., shape);
+        ball.acceleration = GRAVITY;
+        ball.restitution = restitution;
+
+        world.gravitational_constant = None;
+        world.load(vec![floor, ball], 0.);
+        Self {
+            drop_height,
+            configured: restitution,
+            ball: 1,
+            bottom_offset,
+            apex_heights: vec![],
+            last_vertical_velocity: 0.,
+            settled: false,
+        }
+    }
+
+    /// Call after every step, records an apex whenever the ball stops rising until it settles
+    pub fn observe(&mut self, world: &World) {
+        let Some(ball) = world.objects.get(self.ball) else {
+            return;
+        };
+        if self.last_vertical_velocity > 0. && ball.velocity.y <= 0. && !self.settled {
+            let height = ball.position.y - self.bottom_offset;
+            self.settled = height < self.drop_height * SETTLED_FRACTION;
+            if !self.settled {
+                self.apex_heights.push(height);
+            }
+        }
+        self.last_vertical_velocity = ball.velocity.y;
+    }
+
+    /// Coefficient of every bounce, starting with the drop itself
+    pub fn bounce_restitutions(&self) -> Vec<f32> {
+        core::iter::once(self.drop_height)
+            .chain(self.apex_heights.iter().copied())
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|heights| (heights[1] / heights[0]).max(0.).sqrt())
+            .collect()
+    }
+    /// Mean of the bounce coefficients, `None` before the first apex
+    pub fn restitution(&self) -> Option<f32> {
+        let bounces = self.bounce_restitutions();
+        (!bounces.is_empty()).then(|| bounces.iter().sum::<f32>() / bounces.len() as f32)
+    }
+}
+
+#[test]
+fn test_drop_test_matches_configured_restitution() {
+    for configured in [1., 0.5] {
+        let mut world = World::new();
+        let mut drop_test = DropTest::setup(&mut world, 2., configured);
+        for _ in 0..600 {
+            world.step(1. / 120.).unwrap();
+            drop_test.observe(&world);
+        }
+        assert!(drop_test.apex_heights.len() >= 2);
+        let restitution = drop_test.restitution().unwrap();
+        assert!((restitution - configured).abs() < 0.02, "{restitution} instead of {configured}");
+    }
+}
@@ -0,0 +1,159 @@
+//! Scrubbing back through the last seconds of a simulation.
+//!
+//! A [`RewindBuffer`] keeps a [`Snapshot`] of the world after every step in a ring, dropping the
+//! oldest once they span more than its length. Restoring one puts the objects, joints, teams and
+//! random streams back as they were after that step, stepping on from there at the same rate takes
+//! the same path again. Snapshots are whole copies of the objects, the buffer grows with the
+//! object count times the steps it spans.
+
+use alloc::collections::VecDeque;
+
+use super::*;
+use joint::Joint;
+use rng::RngService;
+use team::Team;
+
+/// Seconds of simulation a buffer keeps by default
+pub const DEFAULT_SECONDS: f32 = 5.;
+
+/// State of a world after a step
+#[derive(Clone)]
+pub struct Snapshot {
+    pub time: f32,
+    pub frame: usize,
+    objects: Vec<Object>,
+    joints: Vec<Joint>,
+    teams: Vec<Team>,
+    rng: RngService,
+}
+
+impl Snapshot {
+    pub fn capture(world: &World) -> Self {
+        Self {
+            time: world.time_elapsed,
+            frame: world.frame,
+            objects: world.objects.clone(),
+            joints: world.joints.clone(),
+            teams: world.teams.clone(),
+            rng: world.rng.clone(),
+        }
+    }
+    /// Puts the world back into the captured state, the settings stay as they are
+    pub fn restore(&self, world: &mut World) {
+        world.objects = self.objects.clone();
+        world.joints = self.joints.clone();
+        world.teams = self.teams.clone();
+        world.rng = self.rng.clone();
+        world.time_elapsed = self.time;
+        world.frame = self.frame;
+        // Results of the steps after the snapshot
+        world.pair_cache.clear();
+        world.collision_queue.clear();
+        world.recent_collisions.clear();
+        world.resolved.clear();
+        world.scored.clear();
+        world.pending_spawns.clear();
+    }
+    /// Approximate heap bytes, see [`memory`]
+    pub fn heap_bytes(&self) -> usize {
+        memory::vec_bytes(&self.objects)
+            + self.objects.iter().map(|object| memory::vec_bytes(&object.shape.points)).sum::<usize>()
+            + memory::vec_bytes(&self.joints)
+            + memory::vec_bytes(&self.teams)
+    }
+}
+
+pub struct RewindBuffer {
+    /// Simulated seconds kept
+    pub seconds: f32,
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl RewindBuffer {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            seconds,
+            snapshots: VecDeque::new(),
+        }
+    }
+    /// Keeps the world's state if it stepped since the last call
+    ///
+    /// A world that went back in time, e.g. restored from an earlier snapshot or loaded from a
+    /// scene, drops the snapshots from then on, they belong to another timeline.
+    pub fn record(&mut self, world: &World) {
+        if self.snapshots.back().is_some_and(|last| last.frame == world.frame && last.time == world.time_elapsed) {
+            return;
+        }
+        while self.snapshots.back().is_some_and(|last| last.time >= world.time_elapsed) {
+            self.snapshots.pop_back();
+        }
+        self.snapshots.push_back(Snapshot::capture(world));
+        while self.snapshots.front().is_some_and(|first| first.time < world.time_elapsed - self.seconds) {
+            self.snapshots.pop_front();
+        }
+    }
+    /// Snapshot `index` steps after the oldest one kept
+    pub fn get(&self, index: usize) -> Option<&Snapshot> {
+        self.snapshots.get(index)
+    }
+    /// Index of the snapshot of `frame`
+    pub fn position(&self, frame: usize) -> Option<usize> {
+        self.snapshots.iter().position(|snapshot| snapshot.frame == frame)
+    }
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+    /// Approximate heap bytes, see [`memory`]
+    pub fn heap_bytes(&self) -> usize {
+        memory::deque_bytes(&self.snapshots) + self.snapshots.iter().map(Snapshot::heap_bytes).sum::<usize>()
+    }
+}
+
+#[test]
+fn test_rewind_repeats_the_steps() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    world.objects.push(Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(10., 1.))));
+    for i in 0..4 {
+        let mut object = Object::new(vec2(i as f32 - 1.5, 1. + i as f32), vec2(0.5, 0.), i as f32, Polygon::regular(3 + i));
+        object.rot_velocity = 2.;
+        world.objects.push(object);
+    }
+
+    let mut rewind = RewindBuffer::new(1.);
+    let mut hashes = vec![];
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+        rewind.record(&world);
+        rewind.record(&world);
+        hashes.push((world.frame, world.state_hash()));
+    }
+    // Only the last second is kept, one snapshot per step
+    assert!((60..=61).contains(&rewind.len()), "{}", rewind.len());
+
+    // Back half a second and on again, through the same states
+    let index = rewind.len() - 31;
+    rewind.get(index).unwrap().restore(&mut world);
+    let frame = world.frame;
+    assert_eq!(rewind.position(frame), Some(index));
+    for _ in 0..30 {
+        world.step(1. / 60.).unwrap();
+        rewind.record(&world);
+        let (recorded_frame, hash) = hashes.iter().find(|(recorded, _)| *recorded == world.frame).unwrap();
+        assert_eq!((world.frame, world.state_hash()), (*recorded_frame, *hash));
+    }
+    assert_eq!(rewind.get(rewind.len() - 1).unwrap().frame, world.frame);
+
+    // Going back and stepping differently drops the snapshots of the old timeline
+    rewind.get(index).unwrap().restore(&mut world);
+    world.objects[1].velocity.x += 1.;
+    world.step(1. / 60.).unwrap();
+    rewind.record(&world);
+    assert_eq!(rewind.len(), index + 2);
+}
@@ -0,0 +1,152 @@
+//! Keyboard-flown rocket in the asteroid field.
+//!
+//! The rocket is an ordinary object driven by a [`Controller`], the arrow keys feed it through a
+//! state shared with the viewer, which the controller also writes the rocket's pose to for the
+//! exhaust. Exhaust particles are only drawn, they don't collide.
+
+use std::sync::{Arc, Mutex};
+
+use perfect_collisions::{
+    controller::{Controller, ObjectHandle},
+    rng::{RngService, Stream},
+};
+
+use super::*;
+
+const THRUST: f32 = 6.;
+const TURN_RATE: f32 = 3.;
+const ROCKET_MASS: f32 = 1.;
+/// Thrust direction in object space
+const NOSE: Vec2 = Vec2::new(0., 1.);
+/// Where the exhaust leaves, in object space
+const NOZZLE: Vec2 = Vec2::new(0., -0.35);
+const EXHAUST_SPEED: f32 = 4.;
+const EXHAUST_SPREAD: f32 = 1.;
+const PARTICLES_PER_FRAME: usize = 3;
+const PARTICLE_LIFETIME: f32 = 0.6;
+const PARTICLE_SIZE: f32 = 0.06;
+
+#[derive(Clone, Copy, Default)]
+struct Shared {
+    thrust: bool,
+    /// 1 turns left, -1 right
+    turn: f32,
+    position: Vec2,
+    velocity: Vec2,
+    rotation: f32,
+}
+
+#[derive(Clone)]
+struct RocketController(Arc<Mutex<Shared>>);
+
+impl Controller for RocketController {
+    fn control(&mut self, object: &mut ObjectHandle, _dt: f32) {
+        let mut shared = self.0.lock().unwrap();
+        if shared.thrust {
+            let nose = object.transform.rotation.apply(NOSE);
+            object.apply_force(nose * THRUST);
+        }
+        object.set_rot_velocity(shared.turn * TURN_RATE);
+        shared.position = object.position;
+        shared.velocity = object.velocity;
+        shared.rotation = object.rotation;
+    }
+}
+
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    remaining: f32,
+}
+
+pub struct Rocket {
+    shared: Arc<Mutex<Shared>>,
+    particles: Vec<Particle>,
+}
+
+impl Rocket {
+    /// Replaces the scene with the asteroid field and puts the rocket in its middle
+    pub fn spawn(world: &mut World) -> Self {
+        presets::asteroid_field(world);
+        let shape = Polygon::new(vec![vec2(0., 2. / 3.), vec2(-0.35, -1. / 3.), vec2(0.35, -1. / 3.)]);
+        let mut rocket = Object::new(Vec2::ZERO, Vec2::ZERO, 0., shape);
+        rocket.mass = ROCKET_MASS;
+        rocket.name = Some("rocket".to_string());
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        rocket.controller = Some(Box::new(RocketController(shared.clone())));
+        world.objects.push(rocket);
+        Self {
+            shared,
+            particles: vec![],
+        }
+    }
+
+    /// Whether the rocket is still in the world, the controller's handle goes with the object
+    pub fn is_alive(&self) -> bool {
+        Arc::strong_count(&self.shared) > 1
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.is_alive() && self.particles.is_empty()
+    }
+
+    /// Arrow up thrusts, left and right turn
+    pub fn key(&mut self, key: winit::event::VirtualKeyCode, pressed: bool) {
+        let mut shared = self.shared.lock().unwrap();
+        let direction = match key {
+            winit::event::VirtualKeyCode::Up => {
+                shared.thrust = pressed;
+                return;
+            }
+            winit::event::VirtualKeyCode::Left => 1.,
+            winit::event::VirtualKeyCode::Right => -1.,
+            _ => return,
+        };
+        if pressed {
+            shared.turn = direction;
+        } else if shared.turn == direction {
+            shared.turn = 0.;
+        }
+    }
+
+    /// Moves and ages the exhaust, emits more while thrusting
+    pub fn update(&mut self, dt: f32, rng: &mut RngService) {
+        self.particles.retain_mut(|particle| {
+            particle.position += particle.velocity * dt;
+            particle.remaining -= dt;
+            particle.remaining > 0.
+        });
+        let shared = *self.shared.lock().unwrap();
+        if !shared.thrust || !self.is_alive() {
+            return;
+        }
+        let transform = Transform2::new(shared.position, shared.rotation);
+        let nozzle = transform.apply(NOZZLE);
+        let backwards = -transform.rotation.apply(NOSE);
+        for _ in 0..PARTICLES_PER_FRAME {
+            let jitter = vec2(rng.range(Stream::Scripts, -0.5..0.5), rng.range(Stream::Scripts, -0.5..0.5)) * EXHAUST_SPREAD;
+            self.particles.push(Particle {
+                position: nozzle,
+                velocity: shared.velocity + backwards * EXHAUST_SPEED + jitter,
+                remaining: PARTICLE_LIFETIME * rng.next_f32(Stream::Scripts),
+            });
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        memory::vec_bytes(&self.particles)
+    }
+
+    pub fn draw(&self, queue: &mut DrawQueue, camera: &Camera) {
+        for particle in &self.particles {
+            let life = particle.remaining / PARTICLE_LIFETIME;
+            // Yellow when fresh, fading to transparent red
+            let color = Color::from_rgb(1., 0.3 + 0.7 * life, 0.).set_alpha(life);
+            let circle = Shape::from_circle(8)
+                .set_color(color)
+                .apply(GTransform::from_translation(particle.position).inflate(PARTICLE_SIZE))
+                .apply(camera.0);
+            queue.add(Depth::Particles, circle);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Assertions embedded in scenes, turning them into acceptance tests for the solver.
+//!
+//! Every [`Assertion`] is checked once the world reaches its time. [`run`] steps the world there,
+//! evening out the steps before each assertion so they land on its time, and returns the outcome
+//! of each. `cargo run --example scenario <scene.ron>` runs the ones in a scene file.
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// Part of a step the assertions may come early by instead of taking a step that short
+const IGNORED_LEFTOVER: f32 = 1e-3;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Assertion {
+    /// World time the check runs at, in seconds
+    pub time: f32,
+    pub check: Check,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Check {
+    /// The named object's center lies in the region
+    Inside { object: String, region: Aabb },
+    /// The kinetic energy differs from the one at the start of the run by at most this fraction
+    EnergyDrift { max: f32 },
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {} s, ", self.time)?;
+        match &self.check {
+            Check::Inside { object, region } => write!(f, "'{object}' inside {} to {}", region.min, region.max),
+            Check::EnergyDrift { max } => write!(f, "energy drift below {}%", max * 100.),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Failure {
+    MissingObject { name: String },
+    Outside { name: String, position: Vec2 },
+    EnergyDrift { drift: f32 },
+    /// A step on the way failed, the assertions after it aren't checked
+    Step(CollisionError),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingObject { name } => write!(f, "no object is named '{name}'"),
+            Self::Outside { name, position } => write!(f, "'{name}' is at {position}"),
+            Self::EnergyDrift { drift } => write!(f, "the energy drifted by {}%", drift * 100.),
+            Self::Step(e) => write!(f, "step failed: {e}"),
+        }
+    }
+}
+
+/// Steps `world` through the assertions' times with steps of at most `dt` and checks each,
+/// the outcomes are in the order of `assertions`
+pub fn run(world: &mut World, assertions: &[Assertion], dt: f32) -> Vec<Result<(), Failure>> {
+    let initial_energy = world.total_energy();
+    let mut order = (0..assertions.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| assertions[a].time.total_cmp(&assertions[b].time));
+
+    let mut outcomes = vec![Ok(()); assertions.len()];
+    let mut step_error = None;
+    for i in order {
+        let assertion = &assertions[i];
+        // Equal steps, a tiny one for a rounding leftover could be shorter than the objects' lead
+        let remaining = assertion.time - world.time_elapsed;
+        let steps = (remaining / dt - IGNORED_LEFTOVER).ceil().max(0.) as usize;
+        for _ in 0..steps {
+            if step_error.is_some() {
+                break;
+            }
+            if let Err(e) = world.step(remaining / steps as f32) {
+                step_error = Some(e);
+            }
+        }
+        outcomes[i] = match step_error {
+            Some(e) => Err(Failure::Step(e)),
+            None => check(world, &assertion.check, initial_energy),
+        };
+    }
+    outcomes
+}
+
+fn check(world: &World, check: &Check, initial_energy: f32) -> Result<(), Failure> {
+    match check {
+        Check::Inside { object, region } => {
+            let found = world.objects.iter().find(|candidate| candidate.name.as_ref() == Some(object));
+            let Some(found) = found else {
+                return Err(Failure::MissingObject { name: object.clone() });
+            };
+            if !region.contains(found.position) {
+                return Err(Failure::Outside {
+                    name: object.clone(),
+                    position: found.position,
+                });
+            }
+        }
+        Check::EnergyDrift { max } => {
+            let change = (world.total_energy() - initial_energy).abs();
+            // Relative to the start unless there was nothing to be relative to
+            let drift = if initial_energy > 0. { change / initial_energy } else { change };
+            if drift > *max {
+                return Err(Failure::EnergyDrift { drift });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_scene_assertions() {
+    let mut ball = Object::new(vec2(0., 0.), vec2(2., 0.), 0., Polygon::rectangle(vec2(1., 1.)));
+    ball.name = Some("ball".into());
+    let wall = Object::new_kinematic(vec2(4., 0.), 0., Polygon::rectangle(vec2(1., 4.)), object::Kinematic::Constant);
+    let mut scene = scene::Scene::from_objects(&[ball, wall], 0.);
+    // Bounces off the wall at 1.5 s and is back at the start at 3 s
+    scene.assertions = vec![
+        Assertion {
+            time: 3.,
+            check: Check::Inside {
+                object: "ball".into(),
+                region: Aabb::new(vec2(-0.5, -0.5), vec2(0.5, 0.5)),
+            },
+        },
+        Assertion {
+            time: 1.,
+            check: Check::Inside {
+                object: "ball".into(),
+                region: Aabb::new(vec2(-1., -1.), vec2(1., 1.)),
+            },
+        },
+        Assertion {
+            time: 3.,
+            check: Check::EnergyDrift { max: 0.01 },
+        },
+        Assertion {
+            time: 2.,
+            check: Check::Inside {
+                object: "missing".into(),
+                region: Aabb::new(Vec2::ZERO, Vec2::ONE),
+            },
+        },
+    ];
+    let scene = ron::from_str::<scene::Scene>(&ron::to_string(&scene).unwrap()).unwrap();
+
+    let mut world = World::new();
+    let assertions = scene.assertions.clone();
+    scene.load_into(&mut world);
+    let outcomes = run(&mut world, &assertions, 0.3);
+    assert!((world.time_elapsed - 3.).abs() < 1e-5);
+    assert_eq!(outcomes[0], Ok(()));
+    assert!(matches!(&outcomes[1], Err(Failure::Outside { position, .. }) if (position.x - 2.).abs() < 1e-2));
+    assert_eq!(outcomes[2], Ok(()));
+    assert_eq!(outcomes[3], Err(Failure::MissingObject { name: "missing".into() }));
+}
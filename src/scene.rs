@@ -0,0 +1,422 @@
+//! On-disk world representation, stored as RON or, for files ending in `.json`, as JSON.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use gravity::GravitySource;
+use joint::Joint;
+use dilation::TimeRegion;
+use layer::Layer;
+use object::{BodyType, CcdMode, Kinematic, Rounded};
+use replay::StepConfig;
+use rng::RngService;
+use scenario::Assertion;
+use team::Team;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SceneObject {
+    /// Infinite for static objects, which JSON writes as `null`
+    #[serde(deserialize_with = "infinite_if_null")]
+    pub mass: f32,
+    #[serde(deserialize_with = "infinite_if_null")]
+    pub inertia: f32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub acceleration: Vec2,
+    #[serde(default = "default_gravity_scale")]
+    pub gravity_scale: f32,
+    pub rotation: f32,
+    pub rot_velocity: f32,
+    #[serde(default = "default_restitution")]
+    pub restitution: f32,
+    #[serde(default)]
+    pub static_friction: f32,
+    #[serde(default)]
+    pub dynamic_friction: f32,
+    /// Vertices in object space, anti-clockwise
+    pub points: Vec<Vec2>,
+    /// Overrides of single edges' materials, see [`Polygon::edge_materials`]
+    #[serde(default)]
+    pub edge_materials: Vec<Option<Material>>,
+    /// Exact curved outline, `points` only enclose it
+    #[serde(default)]
+    pub rounded: Option<Rounded>,
+    pub cur_time: f32,
+    pub collided: usize,
+    #[serde(default)]
+    pub impacts: usize,
+    #[serde(default)]
+    pub body_type: BodyType,
+    /// Motion of kinematic objects in scenes from before body types, see [`Self::body_type_mut`]
+    #[serde(default)]
+    pub kinematic: Option<Kinematic>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub layer: usize,
+    #[serde(default)]
+    pub team: Option<usize>,
+    #[serde(default)]
+    pub goal: bool,
+    #[serde(default)]
+    pub z_index: i32,
+    #[serde(default)]
+    pub ccd: CcdMode,
+    /// Time stands still for the object, `velocity` and `rot_velocity` are what it resumes with
+    #[serde(default)]
+    pub paused: bool,
+}
+
+fn default_restitution() -> f32 {
+    1.
+}
+
+fn default_gravity_scale() -> f32 {
+    1.
+}
+
+/// A number, or infinity for the `null` JSON writes instead
+fn infinite_if_null<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    struct NumberOrNull;
+    impl serde::de::Visitor<'_> for NumberOrNull {
+        type Value = f32;
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("a number or null")
+        }
+        fn visit_f32<E>(self, value: f32) -> Result<f32, E> {
+            Ok(value)
+        }
+        fn visit_f64<E>(self, value: f64) -> Result<f32, E> {
+            Ok(value as f32)
+        }
+        fn visit_i64<E>(self, value: i64) -> Result<f32, E> {
+            Ok(value as f32)
+        }
+        fn visit_u64<E>(self, value: u64) -> Result<f32, E> {
+            Ok(value as f32)
+        }
+        fn visit_unit<E>(self) -> Result<f32, E> {
+            Ok(f32::INFINITY)
+        }
+    }
+    deserializer.deserialize_any(NumberOrNull)
+}
+
+impl From<&Object> for SceneObject {
+    fn from(object: &Object) -> Self {
+        let (velocity, rot_velocity) = object.motion();
+        Self {
+            mass: object.mass,
+            inertia: object.inertia,
+            position: object.position,
+            velocity,
+            acceleration: object.acceleration,
+            gravity_scale: object.gravity_scale,
+            rotation: object.rotation,
+            rot_velocity,
+            restitution: object.restitution,
+            static_friction: object.static_friction,
+            dynamic_friction: object.dynamic_friction,
+            points: object.shape.points.clone(),
+            edge_materials: object.shape.edge_materials.clone(),
+            rounded: object.rounded,
+            cur_time: object.cur_time,
+            collided: object.collided,
+            impacts: object.impacts,
+            body_type: object.body_type,
+            kinematic: None,
+            name: object.name.clone(),
+            layer: object.layer,
+            team: object.team,
+            goal: object.goal,
+            z_index: object.z_index,
+            ccd: object.ccd,
+            paused: object.is_paused(),
+        }
+    }
+}
+
+impl SceneObject {
+    /// The body type, a motion from before body types makes the object kinematic
+    pub fn body_type_mut(&mut self) -> &mut BodyType {
+        if let Some(motion) = self.kinematic.take() {
+            self.body_type = BodyType::Kinematic(motion);
+        }
+        &mut self.body_type
+    }
+}
+
+impl From<SceneObject> for Object {
+    fn from(mut scene_object: SceneObject) -> Self {
+        let body_type = *scene_object.body_type_mut();
+        let mut object = match scene_object.rounded {
+            Some(rounded) => {
+                Object::new_rounded(scene_object.position, scene_object.velocity, scene_object.rotation, rounded)
+            }
+            None => Object::new(
+                scene_object.position,
+                scene_object.velocity,
+                scene_object.rotation,
+                Polygon {
+                    points: scene_object.points,
+                    edge_materials: scene_object.edge_materials,
+                },
+            ),
+        };
+        object.mass = scene_object.mass;
+        object.inertia = scene_object.inertia;
+        object.acceleration = scene_object.acceleration;
+        object.gravity_scale = scene_object.gravity_scale;
+        object.rot_velocity = scene_object.rot_velocity;
+        object.restitution = scene_object.restitution;
+        object.static_friction = scene_object.static_friction;
+        object.dynamic_friction = scene_object.dynamic_friction;
+        object.cur_time = scene_object.cur_time;
+        object.collided = scene_object.collided;
+        object.impacts = scene_object.impacts;
+        object.body_type = body_type;
+        object.name = scene_object.name;
+        object.layer = scene_object.layer;
+        object.team = scene_object.team;
+        object.goal = scene_object.goal;
+        object.z_index = scene_object.z_index;
+        object.ccd = scene_object.ccd;
+        if scene_object.paused {
+            object.pause();
+        }
+        object
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Scene {
+    pub time_elapsed: f32,
+    pub objects: Vec<SceneObject>,
+    /// Empty in scenes saved before layers existed, the world's default layers are used then
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+    /// `None` in scenes saved before it existed or built from bare objects, loading keeps the
+    /// world's then
+    #[serde(default)]
+    pub rng: Option<RngService>,
+    /// The world's gravity, `None` keeps the world's like `rng`
+    #[serde(default)]
+    pub gravity: Option<Vec2>,
+    #[serde(default)]
+    pub teams: Vec<Team>,
+    #[serde(default)]
+    pub joints: Vec<Joint>,
+    #[serde(default)]
+    pub gravity_sources: Vec<GravitySource>,
+    #[serde(default)]
+    pub time_regions: Vec<TimeRegion>,
+    /// Solver settings, `None` in scenes saved before they were stored keeps the world's
+    #[serde(default)]
+    pub settings: Option<StepConfig>,
+    /// Checks for the headless runner, see [`scenario`], the world doesn't keep them
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+}
+
+impl Scene {
+    /// Scene of just the objects, they all end up on the default layers when loaded
+    pub fn from_objects(objects: &[Object], time_elapsed: f32) -> Self {
+        Self {
+            time_elapsed,
+            objects: objects.iter().map(SceneObject::from).collect(),
+            layers: vec![],
+            rng: None,
+            gravity: None,
+            teams: vec![],
+            joints: vec![],
+            gravity_sources: vec![],
+            time_regions: vec![],
+            settings: None,
+            assertions: vec![],
+        }
+    }
+    pub fn from_world(world: &World) -> Self {
+        Self {
+            layers: world.layers.clone(),
+            rng: Some(world.rng.clone()),
+            gravity: Some(world.gravity),
+            teams: world.teams.clone(),
+            joints: world.joints.clone(),
+            gravity_sources: world.gravity_sources.clone(),
+            time_regions: world.time_regions.clone(),
+            settings: Some(StepConfig::of(world)),
+            ..Self::from_objects(&world.objects, world.time_elapsed)
+        }
+    }
+    /// Replaces the world's objects, layers, teams, joints, random streams, settings, gravity,
+    /// gravity sources and time regions with the scene's
+    pub fn load_into(self, world: &mut World) {
+        let (time_elapsed, layers, rng) = (self.time_elapsed, self.layers.clone(), self.rng.clone());
+        world.time_regions = self.time_regions.clone();
+        let (teams, joints, gravity_sources) = (self.teams.clone(), self.joints.clone(), self.gravity_sources.clone());
+        if let Some(settings) = self.settings {
+            settings.apply(world);
+        }
+        if let Some(gravity) = self.gravity {
+            world.gravity = gravity;
+        }
+        world.load(self.into_objects(), time_elapsed);
+        if !layers.is_empty() {
+            world.layers = layers;
+        }
+        world.teams = teams;
+        world.joints = joints;
+        world.gravity_sources = gravity_sources;
+        if let Some(rng) = rng {
+            world.rng = rng;
+        }
+    }
+    /// Approximate heap bytes, see [`memory`]
+    pub fn heap_bytes(&self) -> usize {
+        memory::vec_bytes(&self.objects)
+            + self
+                .objects
+                .iter()
+                .map(|object| memory::vec_bytes(&object.points) + object.name.as_ref().map_or(0, String::capacity))
+                .sum::<usize>()
+    }
+    pub fn into_objects(self) -> Vec<Object> {
+        self.objects.into_iter().map(Object::from).collect()
+    }
+
+    /// Writes JSON for paths ending in `.json`, RON otherwise
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if !is_json(path) {
+            return write_ron(self, path);
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, text)?;
+        fs::rename(tmp_path, path)
+    }
+    /// Reads JSON for paths ending in `.json`, RON otherwise
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        if !is_json(path) {
+            return read_ron(path);
+        }
+        let text = fs::read_to_string(path)?;
+        serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+pub(crate) fn write_ron(value: &impl Serialize, path: &Path) -> io::Result<()> {
+    let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    // Write to a sibling file first so a crash mid-write never leaves a truncated file
+    let tmp_path = path.with_extension("ron.tmp");
+    fs::write(&tmp_path, text)?;
+    fs::rename(tmp_path, path)
+}
+
+pub fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+pub(crate) fn read_ron<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<T> {
+    let text = fs::read_to_string(path)?;
+    ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[test]
+fn test_scene_keeps_layers() {
+    let mut world = World::new();
+    world.layers.push(Layer {
+        simulate: false,
+        ..Layer::new("walls")
+    });
+    let mut wall = Object::new(Vec2::ZERO, vec2(1., 0.), 0., Polygon::regular(4));
+    wall.layer = 1;
+    world.objects.push(wall);
+    world.rng = RngService::new(3);
+    world.rng.next_u64(rng::Stream::Spawn);
+
+    let text = ron::to_string(&Scene::from_world(&world)).unwrap();
+    let mut loaded = World::new();
+    ron::from_str::<Scene>(&text).unwrap().load_into(&mut loaded);
+    assert_eq!(loaded.layers, world.layers);
+    assert_eq!(loaded.objects[0].layer, 1);
+    assert_eq!(loaded.rng, world.rng);
+
+    // The wall's layer doesn't simulate, it holds still and keeps its velocity
+    loaded.step(0.5).unwrap();
+    assert_eq!(loaded.objects[0].position, Vec2::ZERO);
+    assert_eq!(loaded.objects[0].velocity, vec2(1., 0.));
+    loaded.layers[1].simulate = true;
+    loaded.step(0.5).unwrap();
+    assert!(loaded.objects[0].position.x > 0.4);
+
+    // A paused object loads paused, keeping the velocity it resumes with
+    loaded.objects[0].pause();
+    let text = ron::to_string(&Scene::from_world(&loaded)).unwrap();
+    let mut paused = ron::from_str::<Scene>(&text).unwrap().into_objects().remove(0);
+    assert!(paused.is_paused() && paused.velocity == Vec2::ZERO);
+    paused.resume();
+    assert_eq!(paused.velocity, vec2(1., 0.));
+
+    // Scenes from before layers keep the defaults
+    let old = ron::from_str::<Scene>("(time_elapsed: 0., objects: [])").unwrap();
+    old.load_into(&mut loaded);
+    assert_eq!(loaded.layers, layer::default_layers());
+}
+
+#[test]
+fn test_scene_keeps_names() {
+    let material = Material {
+        restitution: 0.5,
+        static_friction: 1.,
+        dynamic_friction: 0.5,
+    };
+    let shape = Polygon::regular(3).with_edge_material(1, material);
+    let mut named = Object::new(vec2(1., 2.), Vec2::ZERO, 0., shape.clone());
+    named.name = Some("ball_A".into());
+    let unnamed = Object::circle(Vec2::ZERO, Vec2::ZERO, 0.5);
+
+    let text = ron::to_string(&Scene::from_objects(&[named, unnamed], 0.)).unwrap();
+    let objects = ron::from_str::<Scene>(&text).unwrap().into_objects();
+    assert_eq!(objects[0].name.as_deref(), Some("ball_A"));
+    assert_eq!(objects[1].name, None);
+    assert_eq!(objects[0].shape, shape);
+    // Circles stay exact
+    assert_eq!((objects[0].rounded, objects[1].rounded), (None, Some(Rounded::Circle { radius: 0.5 })));
+}
+
+#[test]
+fn test_scene_json_round_trip() {
+    let mut world = World::new();
+    presets::planet(&mut world);
+    world.objects[1].name = Some("crate".into());
+    world.collision_margin = 0.02;
+    world.integrator = integrator::Integrator::Leapfrog;
+    world.collision_limit = None;
+    world.step(1. / 60.).unwrap();
+
+    let path = std::env::temp_dir().join(format!("perfect-collisions-test-{}.json", std::process::id()));
+    Scene::from_world(&world).save(&path).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    // The planet is static, JSON has no infinity
+    assert!(text.trim_start().starts_with('{') && text.contains("\"mass\": null"), "{text}");
+    let loaded_scene = Scene::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut loaded = World::new();
+    loaded_scene.load_into(&mut loaded);
+    assert_eq!(loaded.state_hash(), world.state_hash());
+    assert_eq!(loaded.objects[0].mass, f32::INFINITY);
+    assert_eq!((loaded.collision_margin, loaded.integrator), (0.02, integrator::Integrator::Leapfrog));
+    assert_eq!(loaded.collision_limit, None);
+    assert_eq!(loaded.gravity_sources, world.gravity_sources);
+    for _ in 0..30 {
+        world.step(1. / 60.).unwrap();
+        loaded.step(1. / 60.).unwrap();
+    }
+    assert_eq!(loaded.state_hash(), world.state_hash());
+}
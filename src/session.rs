@@ -0,0 +1,248 @@
+//! Recording of whole interactive sessions as inputs, replayed end to end.
+//!
+//! Unlike a [`replay::Recording`], which stores the edited scene with every step, a [`Session`]
+//! keeps only the starting scene, the length of every step and the inputs issued between them:
+//! commands, camera moves and scene loads. Each input is stamped with the step it preceded and
+//! the simulation time, not the wall clock, so the replay runs at any rate, e.g. headless as fast
+//! as possible, and still feeds every input in before the same step. The state hash after every
+//! step is stored too, a replay reaching different hashes means some edit bypassed the input
+//! layer. The viewer records its edits outside of commands, like presets or the layer panel, as
+//! scene loads. Dragging the orbit probe and flying the rocket still bypass it.
+
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use command::Command;
+use replay::StepConfig;
+use scene::Scene;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum Input {
+    Command(Command),
+    /// The view moved, `focus` is the world point in the middle of the screen
+    Camera { focus: Vec2, zoom: f32 },
+    /// A scene was loaded, it's stored whole since the file may change
+    Load(Box<Scene>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimedInput {
+    /// Index of the step the input came before
+    pub step: usize,
+    /// [`World::time_elapsed`] when it came
+    pub time: f32,
+    pub input: Input,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SessionStep {
+    pub dt: f32,
+    /// Settings changed outside of commands, e.g. the integrator, are replayed from here
+    pub config: StepConfig,
+    /// [`World::state_hash`] after the step
+    pub hash: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Session {
+    pub initial: Scene,
+    pub steps: Vec<SessionStep>,
+    /// In the order they came
+    pub inputs: Vec<TimedInput>,
+}
+
+impl Session {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = ron::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+pub struct SessionRecorder {
+    pub session: Session,
+    /// Last view noted, the camera is only recorded when it moves
+    camera: Option<(Vec2, f32)>,
+}
+
+impl SessionRecorder {
+    /// Starts recording from the world's current state
+    pub fn start(world: &mut World) -> Self {
+        // The replay starts with an empty cache, its contents can decide borderline pairs
+        world.pair_cache.clear();
+        Self {
+            session: Session {
+                initial: Scene::from_world(world),
+                ..Session::default()
+            },
+            camera: None,
+        }
+    }
+
+    /// Notes an input issued before the next step
+    pub fn input(&mut self, world: &World, input: Input) {
+        self.session.inputs.push(TimedInput {
+            step: self.session.steps.len(),
+            time: world.time_elapsed,
+            input,
+        });
+    }
+
+    /// Notes the view when it differs from the last one noted
+    pub fn camera(&mut self, world: &World, focus: Vec2, zoom: f32) {
+        if self.camera != Some((focus, zoom)) {
+            self.camera = Some((focus, zoom));
+            self.input(world, Input::Camera { focus, zoom });
+        }
+    }
+
+    /// Steps `world` and records the step
+    pub fn step(&mut self, world: &mut World, dt: f32) -> Result<(), CollisionError> {
+        let config = StepConfig::of(world);
+        let result = world.step(dt);
+        self.session.steps.push(SessionStep {
+            dt,
+            config,
+            hash: world.state_hash(),
+        });
+        result
+    }
+}
+
+pub struct SessionPlayer {
+    pub session: Session,
+    /// Next step to replay
+    pub step: usize,
+    next_input: usize,
+    /// First step whose hash didn't match the recording
+    pub diverged_at: Option<usize>,
+}
+
+impl SessionPlayer {
+    /// Loads the session's starting scene into `world`
+    pub fn start(session: Session, world: &mut World) -> Self {
+        session.initial.clone().load_into(world);
+        world.pair_cache.clear();
+        Self {
+            session,
+            step: 0,
+            next_input: 0,
+            diverged_at: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.session.steps.len() && self.next_input >= self.session.inputs.len()
+    }
+
+    /// Feeds `world` the inputs that came before the next step and replays it, the inputs only the
+    /// front end can apply (camera moves, pausing, files) are returned
+    ///
+    /// Once the steps run out the trailing inputs are fed without stepping.
+    pub fn step(&mut self, world: &mut World) -> Vec<Input> {
+        let mut front_end = vec![];
+        while let Some(timed) = self.session.inputs.get(self.next_input).filter(|timed| timed.step <= self.step) {
+            match &timed.input {
+                // Failures repeat the recorded ones
+                Input::Command(command) => {
+                    if let Ok(Some(command)) = command.clone().run(world) {
+                        front_end.push(Input::Command(command));
+                    }
+                }
+                Input::Load(scene) => (**scene).clone().load_into(world),
+                Input::Camera { .. } => front_end.push(timed.input.clone()),
+            }
+            self.next_input += 1;
+        }
+        if let Some(recorded) = self.session.steps.get(self.step) {
+            recorded.config.apply(world);
+            // Errors are part of the recorded outcome, the hash tells whether they were the same
+            let _ = world.step(recorded.dt);
+            if world.state_hash() != recorded.hash && self.diverged_at.is_none() {
+                self.diverged_at = Some(self.step);
+            }
+            self.step += 1;
+        }
+        front_end
+    }
+}
+
+#[test]
+fn test_session_replays_inputs() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., Polygon::regular(4)));
+    world.objects.push(Object::new(vec2(3., 0.3), vec2(-1., 0.), 0.5, Polygon::regular(5)));
+
+    let mut recorder = SessionRecorder::start(&mut world);
+    // Uneven steps like a real frame rate
+    for frame in 0..60 {
+        let issued = match frame {
+            10 => vec![Input::Command(Command::parse("spawn triangle 0 4 vel 0 -2").unwrap())],
+            20 => vec![
+                Input::Command(Command::parse("impulse 1 0.5 0 at 3 0").unwrap()),
+                Input::Command(Command::Pause),
+            ],
+            40 => vec![Input::Command(Command::parse("set 0 restitution 0.5").unwrap())],
+            _ => vec![],
+        };
+        // Only moves are noted
+        recorder.camera(&world, vec2(1., 0.), if frame < 20 { 1. } else { 0.5 });
+        for input in issued {
+            if let Input::Command(command) = &input {
+                command.clone().run(&mut world).unwrap();
+            }
+            recorder.input(&world, input);
+        }
+        if frame == 30 {
+            // Edited around the commands, noted as a snapshot like the viewer does
+            world.time_regions.push(dilation::TimeRegion {
+                area: Aabb::new(vec2(-10., -10.), vec2(1., 10.)),
+                scale: 0.5,
+            });
+            world.sweep_axis = broad_phase::SweepAxis::Y;
+            world.pair_cache.clear();
+            recorder.input(&world, Input::Load(Box::new(Scene::from_world(&world))));
+        }
+        recorder.step(&mut world, if frame % 2 == 0 { 1. / 60. } else { 1. / 45. }).unwrap();
+    }
+    // Goes through the file so the exactness of the stored floats is covered too
+    let path = std::env::temp_dir().join("perfect-collisions-test-session.ron");
+    recorder.session.save(&path).unwrap();
+    let session = Session::load(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    assert_eq!(session.inputs.len(), 7);
+    assert_eq!(session.inputs[2].step, 20);
+
+    let mut replayed = World::new();
+    let mut player = SessionPlayer::start(session.clone(), &mut replayed);
+    let mut front_end = vec![];
+    while !player.is_finished() {
+        front_end.extend(player.step(&mut replayed));
+    }
+    assert_eq!(player.diverged_at, None);
+    assert_eq!(replayed.state_hash(), world.state_hash());
+    let zooms: Vec<_> = front_end
+        .iter()
+        .filter_map(|input| match input {
+            Input::Camera { zoom, .. } => Some(*zoom),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(zooms, [1., 0.5]);
+    assert!(matches!(front_end.last(), Some(Input::Command(Command::Pause))));
+
+    // An edit around the input layer shows up as a divergence
+    let mut player = SessionPlayer::start(session, &mut replayed);
+    while !player.is_finished() {
+        if player.step == 35 {
+            replayed.objects[0].velocity.y += 0.1;
+        }
+        player.step(&mut replayed);
+    }
+    assert_eq!(player.diverged_at, Some(35));
+}
@@ -0,0 +1,65 @@
+//! Viewer preferences kept between sessions.
+//!
+//! Stored as RON in `$XDG_CONFIG_HOME/perfect-collisions` (`~/.config` when unset), missing or
+//! unreadable settings fall back to the defaults.
+
+use std::{fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::*;
+use theme::Theme;
+
+const SETTINGS_FILE_NAME: &str = "settings.ron";
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let Some(path) = settings_path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|e| {
+                warn!(path = %path.display(), error = %e, "ignoring unreadable settings");
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "couldn't read settings");
+                Self::default()
+            }
+        }
+    }
+    pub fn save(&self) {
+        let Some(path) = settings_path() else {
+            return;
+        };
+        let written = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|text| {
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(&path, text)
+            });
+        if let Err(e) = written {
+            warn!(path = %path.display(), error = %e, "couldn't save settings");
+        }
+    }
+}
+
+/// Directory the app keeps its own files in, `None` without a home directory
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("perfect-collisions"))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    Some(config_dir()?.join(SETTINGS_FILE_NAME))
+}
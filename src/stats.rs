@@ -0,0 +1,70 @@
+//! Histograms of collision impulses and per-frame TOI counts.
+//!
+//! Helps tune restitution, the collision margin and the resolution caps: the impulse spread shows
+//! how hard contacts are, the TOI counts how much work each frame takes.
+
+use std::collections::VecDeque;
+
+use egui::plot::{Bar, BarChart, Plot};
+
+use super::*;
+
+/// Frames the histograms cover
+const HISTORY_FRAMES: usize = 300;
+const HISTOGRAM_BINS: usize = 20;
+const PLOT_HEIGHT: f32 = 120.;
+
+pub struct CollisionStats {
+    /// Impulse magnitudes of every resolved collision, per frame, oldest first
+    frames: VecDeque<Vec<f32>>,
+}
+
+impl CollisionStats {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::with_capacity(HISTORY_FRAMES),
+        }
+    }
+
+    pub fn record(&mut self, resolved: &[Toi]) {
+        if self.frames.len() == HISTORY_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(resolved.iter().map(|toi| toi.impulse.abs()).collect());
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        memory::deque_bytes(&self.frames) + self.frames.iter().map(memory::vec_bytes).sum::<usize>()
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) {
+        ui.label(format!("Impulse magnitudes, last {} frames", self.frames.len()));
+        let impulses = self.frames.iter().flatten().copied();
+        Plot::new("impulse_histogram")
+            .height(PLOT_HEIGHT)
+            .show(ui, |plot_ui| plot_ui.bar_chart(histogram(impulses)));
+
+        ui.label("Collisions resolved per frame");
+        let counts = self.frames.iter().map(|frame| frame.len() as f32);
+        Plot::new("toi_histogram")
+            .height(PLOT_HEIGHT)
+            .show(ui, |plot_ui| plot_ui.bar_chart(histogram(counts)));
+    }
+}
+
+/// Evenly spaced bins from zero to the largest value
+fn histogram(values: impl Iterator<Item = f32> + Clone) -> BarChart {
+    let max = values.clone().fold(0f32, f32::max);
+    let bin_width = if max > 0. { max / HISTOGRAM_BINS as f32 } else { 1. };
+    let mut counts = [0u32; HISTOGRAM_BINS];
+    for value in values {
+        let bin = ((value / bin_width) as usize).min(HISTOGRAM_BINS - 1);
+        counts[bin] += 1;
+    }
+    let bars = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| Bar::new((i as f64 + 0.5) * bin_width as f64, count as f64))
+        .collect();
+    BarChart::new(bars).width(bin_width as f64)
+}
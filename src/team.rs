@@ -0,0 +1,114 @@
+//! Teams of objects for physics mini-games, e.g. two sides pushing into each other's goal.
+//!
+//! An object joins a team by index into [`World::teams`], objects without one are neutral and
+//! objects pointing past the end behave like neutral ones. Teammates pass through each other
+//! unless their team has friendly collisions, and a teammate touching a goal of another team
+//! scores a point for its own. Goals are found in the collisions every step resolves,
+//! [`World::scored`] lists the ones of the last step.
+
+use alloc::string::String;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Team {
+    pub name: String,
+    /// RGB in 0..1, the viewer draws the team's objects in it
+    pub color: [f32; 3],
+    /// Whether teammates collide with each other, enemies and neutral objects always do
+    pub friendly_collisions: bool,
+    pub score: u32,
+}
+
+impl Team {
+    pub fn new(name: impl Into<String>, color: [f32; 3]) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            friendly_collisions: false,
+            score: 0,
+        }
+    }
+}
+
+/// A point scored during a step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Score {
+    /// Team the point went to
+    pub team: usize,
+    /// The object that touched the goal
+    pub scorer: usize,
+    pub goal: usize,
+    pub time: f32,
+}
+
+/// Whether objects of teams `a` and `b` collide under the rules of `teams`
+pub fn collide(teams: &[Team], a: Option<usize>, b: Option<usize>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) if a == b => teams.get(a).is_none_or(|team| team.friendly_collisions),
+        _ => true,
+    }
+}
+
+/// Points the `resolved` collisions scored, one per scorer and goal however often they touched
+pub fn scores(teams: &[Team], objects: &[Object], resolved: &[Toi]) -> Vec<Score> {
+    let mut scores: Vec<Score> = vec![];
+    for toi in resolved {
+        for (scorer, goal) in [(toi.object_1, toi.object_2), (toi.object_2, toi.object_1)] {
+            let (Some(scorer_object), Some(goal_object)) = (objects.get(scorer), objects.get(goal)) else {
+                continue;
+            };
+            let (Some(team), Some(goal_team)) = (scorer_object.team, goal_object.team) else {
+                continue;
+            };
+            let scored = goal_object.goal && team != goal_team && team < teams.len();
+            if scored && !scores.iter().any(|score| score.scorer == scorer && score.goal == goal) {
+                scores.push(Score { team, scorer, goal, time: toi.time });
+            }
+        }
+    }
+    scores
+}
+
+#[test]
+fn test_teams_collide_with_enemies_and_score() {
+    let square = || Polygon::rectangle(vec2(1., 1.));
+    let mut world = World::new();
+    world.teams = vec![Team::new("red", [1., 0., 0.]), Team::new("blue", [0., 0., 1.])];
+    let mut goal = Object::new_kinematic(vec2(6., 0.), 0., Polygon::rectangle(vec2(1., 4.)), object::Kinematic::Constant);
+    goal.team = Some(1);
+    goal.goal = true;
+    world.objects.push(goal);
+    // Two reds in a row, the back one passes through the front one and both reach the goal
+    for (x, speed) in [(0., 4.), (-2., 8.)] {
+        let mut red = Object::new(vec2(x, 0.), vec2(speed, 0.), 0., square());
+        red.team = Some(0);
+        world.objects.push(red);
+    }
+    // A blue defender further down is in an enemy's way
+    let mut blue = Object::new(vec2(0., 3.), Vec2::ZERO, 0., square());
+    blue.team = Some(1);
+    world.objects.push(blue);
+    let mut red = Object::new(vec2(-2., 3.), vec2(4., 0.), 0., square());
+    red.team = Some(0);
+    world.objects.push(red);
+
+    world.step(0.9).unwrap();
+    // The reds only met the goal and the blue
+    assert!(world.resolved.iter().all(|toi| toi.object_1.min(toi.object_2) == 0 || toi.object_1.max(toi.object_2) == 4));
+    assert_eq!(world.scored.iter().map(|score| score.scorer).collect::<Vec<_>>(), [2]);
+    world.step(0.5).unwrap();
+    // The first red bounced back off the goal, it doesn't score again this step
+    let scorers = world.scored.iter().map(|score| (score.team, score.scorer)).collect::<Vec<_>>();
+    assert_eq!(scorers, [(0, 1)]);
+    assert_eq!((world.teams[0].score, world.teams[1].score), (2, 0));
+    // The blue was pushed, the defender doesn't score on its own goal
+    assert!(world.objects[3].velocity.x > 0.);
+
+    // Friendly collisions turn teammates solid again
+    assert!(!collide(&world.teams, Some(0), Some(0)));
+    world.teams[0].friendly_collisions = true;
+    assert!(collide(&world.teams, Some(0), Some(0)) && collide(&world.teams, Some(0), None));
+}
@@ -0,0 +1,52 @@
+//! Semantic colors of the viewer, grouped in palettes switchable at runtime.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, strum::Display, strum::EnumIter)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+pub struct Palette {
+    pub background: Color,
+    pub object: Color,
+    /// Hull of an object's start and end poses of the frame
+    pub swept_volume: Color,
+    pub outline: Color,
+    /// Contact points and impulse arrows
+    pub debug: Color,
+    pub selection: Color,
+}
+
+impl Theme {
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Dark => Palette {
+                background: Color::from_rgb(0.1, 0.1, 0.1),
+                object: Color::WHITE,
+                swept_volume: Color::from_rgb(0.2, 0.35, 0.9),
+                outline: Color::BLACK,
+                debug: Color::from_rgb(0.3, 0.6, 1.),
+                selection: Color::GREEN,
+            },
+            Theme::Light => Palette {
+                background: Color::from_rgb(0.95, 0.95, 0.93),
+                object: Color::from_rgb(0.35, 0.35, 0.4),
+                swept_volume: Color::from_rgb(0.7, 0.8, 1.),
+                outline: Color::from_rgb(0.05, 0.05, 0.1),
+                debug: Color::from_rgb(0.85, 0.3, 0.1),
+                selection: Color::from_rgb(0.1, 0.6, 0.2),
+            },
+        }
+    }
+    pub fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Stepping at a fixed rate whatever the frame rate.
+//!
+//! Frames take as long as they take, [`FixedTimestep`] turns their durations into whole steps of
+//! one length. A world stepped by it goes through the same states at 30 or 240 frames per second,
+//! so the same seed and inputs reproduce the same trajectories. The time left over is how far the
+//! frame is into the next step, drawing can follow the motion that far with
+//! [`interpolate_pose`](integrator::interpolate_pose).
+
+#[cfg(test)]
+use super::*;
+
+/// Steps a frame catches up on at most by default, the rest of a long frame is dropped
+pub const DEFAULT_MAX_STEPS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedTimestep {
+    /// Steps per second
+    pub rate: f32,
+    /// Steps a single frame catches up on at most, a slow frame would otherwise need more steps
+    /// for the next one, slowing it down in turn
+    pub max_steps: usize,
+    /// Time not stepped yet, less than a step
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate,
+            max_steps: DEFAULT_MAX_STEPS,
+            accumulator: 0.,
+        }
+    }
+    /// Length of a step
+    pub fn dt(&self) -> f32 {
+        1. / self.rate
+    }
+    /// Number of steps of [`Self::dt`] due after a frame of `frame_dt`
+    pub fn advance(&mut self, frame_dt: f32) -> usize {
+        let dt = self.dt();
+        self.accumulator += frame_dt;
+        let steps = ((self.accumulator / dt) as usize).min(self.max_steps);
+        self.accumulator = (self.accumulator - steps as f32 * dt).clamp(0., dt);
+        steps
+    }
+    /// Fraction of the next step the time left over covers, in `0..=1`
+    pub fn alpha(&self) -> f32 {
+        self.accumulator * self.rate
+    }
+    /// Drops the time left over, e.g. after a pause or changing the rate
+    pub fn reset(&mut self) {
+        self.accumulator = 0.;
+    }
+}
+
+#[test]
+fn test_frame_rate_independence() {
+    let mut timestep = FixedTimestep::new(120.);
+    assert_eq!(timestep.advance(1. / 60.), 2);
+    assert!(timestep.alpha() < 1e-3);
+    assert_eq!(timestep.advance(1. / 240.), 0);
+    assert!((timestep.alpha() - 0.5).abs() < 1e-3);
+    // A hitch catches up on a few steps only
+    assert_eq!(timestep.advance(1.), DEFAULT_MAX_STEPS);
+    assert!(timestep.alpha() <= 1.);
+
+    // Frames at 30 Hz and uneven ones around 144 Hz take the world through the same states
+    let run = |frames: &mut dyn Iterator<Item = f32>| {
+        let mut world = World::new();
+        world.gravity = vec2(0., -9.81);
+        world.objects.push(Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(10., 1.))));
+        for i in 0..5 {
+            let mut object = Object::new(vec2(i as f32 - 2., 1. + i as f32), vec2(1., 0.), i as f32, Polygon::regular(3 + i));
+            object.rot_velocity = 1.;
+            world.objects.push(object);
+        }
+        let mut timestep = FixedTimestep::new(120.);
+        let mut steps = 0;
+        while steps < 240 {
+            for _ in 0..timestep.advance(frames.next().unwrap()).min(240 - steps) {
+                world.step(timestep.dt()).unwrap();
+                steps += 1;
+            }
+        }
+        world.state_hash()
+    };
+    let slow = run(&mut core::iter::repeat(1. / 30.));
+    let uneven = run(&mut [1. / 144., 1. / 100., 1. / 200.].into_iter().cycle());
+    assert_eq!(slow, uneven);
+}
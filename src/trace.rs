@@ -0,0 +1,132 @@
+//! Event timeline export in the Chrome tracing (JSON array) format.
+//!
+//! With the `chrome-trace` feature enabled every frame phase is written as a complete (`"X"`)
+//! event and every resolved collision as an instant (`"i"`) event. The file can be opened in
+//! `chrome://tracing` or Perfetto. Only a tracer made with [`Tracer::to_file`] writes, the one a
+//! [`World`](crate::World) starts with stays silent. Without the feature all methods compile
+//! down to nothing.
+
+#[cfg(feature = "chrome-trace")]
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+#[cfg(feature = "std")]
+pub use std::time::Instant;
+
+/// Stand-in for `std::time::Instant` without a clock, every measured duration is zero
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Copy, Debug)]
+pub struct Instant;
+
+#[cfg(not(feature = "std"))]
+impl Instant {
+    pub fn now() -> Self {
+        Instant
+    }
+    pub fn elapsed(&self) -> core::time::Duration {
+        core::time::Duration::ZERO
+    }
+}
+
+/// Path used when `PERFECT_COLLISIONS_TRACE` isn't set.
+#[cfg(feature = "chrome-trace")]
+const DEFAULT_TRACE_PATH: &str = "trace.json";
+
+pub struct Tracer {
+    #[cfg(feature = "chrome-trace")]
+    start: Instant,
+    #[cfg(feature = "chrome-trace")]
+    writer: Option<BufWriter<File>>,
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tracer {
+    /// Tracer writing nothing, the one every [`World`](crate::World) starts with. Worlds made up
+    /// on the fly, like islands and path predictions, keep it and never touch the disk.
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "chrome-trace")]
+            start: Instant::now(),
+            #[cfg(feature = "chrome-trace")]
+            writer: None,
+        }
+    }
+
+    /// Tracer writing to `PERFECT_COLLISIONS_TRACE`, `trace.json` by default, the file is created
+    /// right away. Without the `chrome-trace` feature it's the same as [`Self::new`].
+    pub fn to_file() -> Self {
+        #[cfg(feature = "chrome-trace")]
+        {
+            let path = std::env::var("PERFECT_COLLISIONS_TRACE")
+                .unwrap_or_else(|_| DEFAULT_TRACE_PATH.to_string());
+            // The array is intentionally never closed, trace viewers accept that and it means
+            // the file stays valid no matter when the process gets killed.
+            let writer = File::create(&path).ok().map(BufWriter::new).and_then(|mut writer| {
+                writer.write_all(b"[\n").ok()?;
+                Some(writer)
+            });
+            Self {
+                start: Instant::now(),
+                writer,
+            }
+        }
+        #[cfg(not(feature = "chrome-trace"))]
+        Self::new()
+    }
+
+    /// Records a phase which started at `start` and ends now
+    #[allow(unused_variables)]
+    pub fn span(&mut self, name: &str, start: Instant) {
+        #[cfg(feature = "chrome-trace")]
+        {
+            let ts = start.duration_since(self.start).as_micros();
+            let dur = start.elapsed().as_micros();
+            self.write(format!(
+                r#"{{"name":"{name}","cat":"phase","ph":"X","ts":{ts},"dur":{dur},"pid":0,"tid":0}}"#
+            ));
+        }
+    }
+
+    /// Records a point event, `args` are written as numeric arguments
+    #[allow(unused_variables)]
+    pub fn instant(&mut self, name: &str, args: &[(&str, f64)]) {
+        #[cfg(feature = "chrome-trace")]
+        {
+            let ts = self.start.elapsed().as_micros();
+            let args = args
+                .iter()
+                .map(|(key, value)| format!(r#""{key}":{value}"#))
+                .collect::<Vec<_>>()
+                .join(",");
+            self.write(format!(
+                r#"{{"name":"{name}","cat":"event","ph":"i","s":"t","ts":{ts},"pid":0,"tid":0,"args":{{{args}}}}}"#
+            ));
+        }
+    }
+
+    /// Flushes buffered events, called once per frame
+    pub fn flush(&mut self) {
+        #[cfg(feature = "chrome-trace")]
+        if let Some(writer) = &mut self.writer {
+            if writer.flush().is_err() {
+                self.writer = None;
+            }
+        }
+    }
+
+    #[cfg(feature = "chrome-trace")]
+    fn write(&mut self, event: String) {
+        if let Some(writer) = &mut self.writer {
+            if writeln!(writer, "{event},").is_err() {
+                self.writer = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Named snapshots of a whole viewer session.
+//!
+//! A workspace keeps the scene together with everything around it, the camera, the stepping
+//! settings, the open panels and the selection, so an experiment can be put away and picked up
+//! again exactly where it was left. Workspaces are RON files in a `workspaces` directory next to
+//! the [settings](settings).
+
+use std::{fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::*;
+use render::FillStyle;
+use scene::Scene;
+
+const WORKSPACE_DIR_NAME: &str = "workspaces";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Workspace {
+    pub scene: Scene,
+    /// World point in the middle of the screen
+    pub camera_focus: Vec2,
+    pub camera_zoom: f32,
+    pub paused: bool,
+    /// Steps per second, `None` steps once per frame
+    pub fixed_rate: Option<f32>,
+    pub spawn_restitution: f32,
+    pub fill_style: FillStyle,
+    pub panels: Panels,
+    pub selection: Vec<usize>,
+}
+
+/// Which panels and overlays are shown
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct Panels {
+    pub log: bool,
+    pub stats: bool,
+    pub lint: bool,
+    pub camera_path: bool,
+    pub phase_plot: bool,
+    pub restitution_wizard: bool,
+    pub impulses: bool,
+    pub labels: bool,
+    pub labels_selection_only: bool,
+    pub heat_map: bool,
+    pub contact_graph: bool,
+    pub outlines: bool,
+}
+
+impl Workspace {
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let path = workspace_path(name)?;
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, text)
+    }
+    pub fn load(name: &str) -> io::Result<Self> {
+        let text = fs::read_to_string(workspace_path(name)?)?;
+        ron::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Names of the saved workspaces, sorted
+pub fn list() -> Vec<String> {
+    let Some(dir) = settings::config_dir().map(|dir| dir.join(WORKSPACE_DIR_NAME)) else {
+        return vec![];
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return vec![],
+        Err(e) => {
+            warn!(path = %dir.display(), error = %e, "couldn't list workspaces");
+            return vec![];
+        }
+    };
+    let mut names = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "ron" {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_owned)
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+pub fn delete(name: &str) -> io::Result<()> {
+    fs::remove_file(workspace_path(name)?)
+}
+
+/// Path of the workspace `name`, names have to be plain file names
+fn workspace_path(name: &str) -> io::Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a valid workspace name"));
+    }
+    let dir = settings::config_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    Ok(dir.join(WORKSPACE_DIR_NAME).join(format!("{name}.ron")))
+}
@@ -0,0 +1,2315 @@
+//! Simulation state and the continuous collision pipeline.
+//!
+//! Every step the objects' swept volumes are sorted along x to find candidate pairs, the exact
+//! times of impact of those pairs are queued and resolved in chronological order.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque},
+    string::String,
+};
+use core::{cmp::Reverse, ops::Range};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, debug_span, trace};
+
+use super::*;
+use broad_phase::{BroadPhaseStats, SweepAxis};
+use certificate::{Certificate, Violation, ViolationKind};
+use conservation::{Correction, Totals};
+use controller::ObjectHandle;
+use dilation::TimeRegion;
+use gravity::GravitySource;
+use energy_audit::{EnergyAudit, Snapshot};
+use energy_budget::{local_energy, EnergyBudget};
+use integrator::Integrator;
+use islands::SubstepPolicy;
+use joint::{Joint, DEFAULT_JOINT_ITERATIONS};
+use layer::Layer;
+use object::{BodyType, Kinematic};
+use pair_cache::{PairCache, PairConfig};
+use rng::RngService;
+use team::{Score, Team};
+use trace::{Instant, Tracer};
+
+/// Seconds before the impact the objects of a collision are rolled back to, a little room for the
+/// rounding of the impact time
+const TOI_BACKOFF: f32 = 1e-5;
+/// Corners starting this close outside the skin count as on it, e.g. right after bouncing off it,
+/// rounding could otherwise find them crossing it again straight away
+const SKIN_TOLERANCE: f32 = 1e-5;
+/// Seconds at least between a bounce and the next impact of objects their accelerations press
+/// together, pairs bouncing slower rest until the end of the step instead of bouncing ever smaller
+/// and infinitely often
+const MIN_HOP: f32 = 0.01;
+/// Vertices this much further than the skin from the other outline at an impact are still part of
+/// the contact, e.g. the second corner of a face hitting flat on
+const CONTACT_TOLERANCE: f32 = 1e-3;
+pub const DEFAULT_COLLISION_MARGIN: f32 = 0.002;
+/// Simplified colliders are switched off again once frames take less than this part of the budget
+const RESTORE_FRACTION: f32 = 0.6;
+/// Number of resolved collisions kept in [`World::recent_collisions`]
+pub const RECENT_COLLISIONS: usize = 64;
+/// A step needing more resolutions than this per object is considered stuck
+const RESOLUTIONS_PER_OBJECT: usize = 100;
+pub const DEFAULT_COLLISION_LIMIT: usize = 100;
+/// Approach speed an impact needs to count towards [`World::collision_limit`], slower ones are
+/// objects settling or rocking on each other
+const MIN_IMPACT_SPEED: f32 = 0.5;
+/// Distance between spawn candidates relative to the spawned object's bounding radius
+const SPAWN_SPACING: f32 = 0.25;
+const MAX_SPAWN_CANDIDATES: usize = 1024;
+
+/// Time of impact of a vertex of one object with an edge or, head on, a vertex of another
+#[derive(PartialEq, Debug, Clone, Copy, Serialize)]
+pub struct Toi {
+    pub time: f32,
+    /// Object hitting with one of its vertices
+    pub object_1: usize,
+    pub object_1_col_stamp: usize,
+    /// Index of the hitting vertex in the collider, see [`Object::collider`]
+    pub point_1: usize,
+    pub object_2: usize,
+    pub object_2_col_stamp: usize,
+    /// Index of the hit edge, it starts at the vertex with the same index, which is the one hit
+    /// in vertex-vertex impacts
+    pub line_2: usize,
+    /// Contact point in world space at `time`
+    pub point: Vec2,
+    /// Unit normal of the hit edge pointing out of `object_2`, halfway between its two edges'
+    /// normals in vertex-vertex impacts
+    pub normal: Vec2,
+    /// Impulse applied to `object_1` along `normal`, `object_2` gets the opposite, zero until the
+    /// collision is resolved
+    pub impulse: f32,
+}
+
+impl Toi {
+    fn features(&self) -> (usize, usize, usize, usize, usize, usize) {
+        (
+            self.object_1,
+            self.object_1_col_stamp,
+            self.point_1,
+            self.object_2,
+            self.object_2_col_stamp,
+            self.line_2,
+        )
+    }
+}
+
+impl Eq for Toi {}
+
+impl PartialOrd for Toi {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Toi {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.time
+            .total_cmp(&other.time)
+            .then_with(|| self.features().cmp(&other.features()))
+    }
+}
+
+/// Kinematic state of one object, see [`World::snapshot_states`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectState {
+    pub index: usize,
+    pub name: Option<String>,
+    /// Time the state is valid at, [`World::time_elapsed`] after a step
+    pub time: f32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub rotation: f32,
+    pub rot_velocity: f32,
+    pub mass: f32,
+}
+
+impl ObjectState {
+    pub fn new(index: usize, object: &Object) -> Self {
+        Self {
+            index,
+            name: object.name.clone(),
+            time: object.cur_time,
+            position: object.position,
+            velocity: object.velocity,
+            rotation: object.rotation,
+            rot_velocity: object.rot_velocity,
+            mass: object.mass,
+        }
+    }
+}
+
+/// Broad-phase bounds of an object over the rest of the frame
+#[derive(Clone, Copy, Debug)]
+struct SweptBounds {
+    aabb: Aabb,
+    /// Path of the bounding circle's center
+    center_path: Segment,
+    radius: f32,
+    /// Tight box around the traversed volume, `None` for spinning objects whose volume isn't exact
+    obb: Option<Obb>,
+}
+
+impl SweptBounds {
+    /// `acceleration` bends the path, see [`TraversedVolume::new`]
+    fn from_object(object: &Object, acceleration: Vec2, target_time: f32, margin: f32, simplified: bool) -> Self {
+        let dt = target_time - object.cur_time;
+        let end = Transform2::new(
+            object.position + object.velocity * dt + acceleration * (dt * dt / 2.),
+            object.rotation + object.rot_velocity * dt,
+        );
+        let center = object.to_world(object.bounding_circle.center);
+        let center_path = Segment::new(center, end.apply(object.bounding_circle.center));
+        // The parabola strays at most this far from its chord, halfway along
+        let radius = object.bounding_circle.radius + margin + acceleration.length() * dt * dt / 8.;
+
+        let traversed_volume = TraversedVolume::new(object, object.collider(simplified), acceleration, target_time);
+        let mut aabb = Aabb::from_points(&traversed_volume.points).expanded_by(margin);
+        let tight_obb = min_area_obb(&traversed_volume.points);
+        let mut obb = Some(Obb {
+            half_extents: tight_obb.half_extents + Vec2::splat(margin),
+            ..tight_obb
+        });
+        if object.rot_velocity != 0. {
+            // Mid-sweep the rotated vertices can leave the hull of the end poses, they never leave
+            // the bounding circle though
+            let control = center + object.velocity * dt / 2.;
+            aabb = aabb.union(&Aabb::from_points(&[center_path.a, control, center_path.b]).expanded_by(radius));
+            obb = None;
+        }
+
+        Self { aabb, center_path, radius, obb }
+    }
+    /// Cheap rejection, the swept bounding circles (capsules) have to touch for a collision
+    fn may_collide(&self, other: &SweptBounds) -> bool {
+        if !self.aabb.overlaps(&other.aabb) {
+            return false;
+        }
+        let (a, b) = self.center_path.closest_points(&other.center_path);
+        if a.distance(b) > self.radius + other.radius {
+            return false;
+        }
+        // Elongated fast movers have loose circles and boxes, the oriented boxes stay tight
+        match (self.obb, other.obb) {
+            (Some(obb), Some(other_obb)) => sat_collision_detect(&obb.corners(), &other_obb.corners()),
+            _ => true,
+        }
+    }
+}
+
+/// Convex hull of an object's start and end poses, and with an acceleration of the pose at the
+/// parabola's control point
+///
+/// A path `p + v·t + a·t²/2` over `dt` is the quadratic Bézier curve through `p` and the end
+/// position with the control point `p + v·dt/2`, so it never leaves the triangle of the three.
+/// Exact for objects that don't spin.
+pub struct TraversedVolume {
+    pub points: Vec<Vec2>,
+}
+
+impl TraversedVolume {
+    /// Volume of the object's own motion, [`Object::update`]
+    pub fn from_object(object: Object, target_time: f32) -> Self {
+        Self::new(&object, &object.shape, object.total_acceleration(), target_time)
+    }
+    /// Volume swept by `outline`, given in `object`'s space, moving with `acceleration`
+    pub fn new(object: &Object, outline: &Polygon, acceleration: Vec2, target_time: f32) -> Self {
+        let dt = target_time - object.cur_time;
+        let rotation = object.rotation + object.rot_velocity * dt;
+        let end = Transform2::new(object.position + object.velocity * dt + acceleration * (dt * dt / 2.), rotation);
+        let mut poses = vec![object.transform, end];
+        if acceleration != Vec2::ZERO {
+            poses.push(Transform2::new(object.position + object.velocity * dt / 2., (object.rotation + rotation) / 2.));
+        }
+        let points = convex_hull(
+            poses
+                .iter()
+                .flat_map(|pose| outline.points.iter().map(|p| pose.apply(*p)))
+                .collect::<Vec<_>>(),
+        );
+        Self { points }
+    }
+}
+
+pub struct World {
+    pub objects: Vec<Object>,
+    pub time_elapsed: f32,
+    /// Number of steps taken
+    pub frame: usize,
+    /// Skin around every shape, collisions are detected when the skins touch
+    pub collision_margin: f32,
+    pub pair_cache: PairCache,
+    pub collision_queue: BinaryHeap<Reverse<Toi>>,
+    /// Last [`RECENT_COLLISIONS`] resolved collisions, oldest first
+    pub recent_collisions: VecDeque<Toi>,
+    /// Collisions resolved during the last step
+    pub resolved: Vec<Toi>,
+    pub tracer: Tracer,
+    /// Frame time in seconds above which the narrow phase switches to the objects' proxies,
+    /// `None` keeps full detail no matter the load
+    pub frame_budget: Option<f32>,
+    /// Whether the proxies are collided instead of the full shapes
+    pub simplified: bool,
+    /// Collisions closer in time than this which involve different objects are resolved as one
+    /// batch, `None` resolves strictly one by one
+    pub batch_tolerance: Option<f32>,
+    /// Dynamic objects are removed after this many [`Object::impacts`], `None` keeps them forever
+    pub collision_limit: Option<usize>,
+    /// Uniform acceleration every dynamic object falls with, scaled by its
+    /// [`Object::gravity_scale`]
+    pub gravity: Vec2,
+    /// Strength of the mutual attraction between objects, `None` turns it off, see [`gravity`]
+    pub gravitational_constant: Option<f32>,
+    /// Centers pulling dynamic objects towards them on top of `gravity`, also scaled by the
+    /// objects' [`Object::gravity_scale`]
+    pub gravity_sources: Vec<GravitySource>,
+    pub integrator: Integrator,
+    /// Whether every step ends by restoring the momentum and energy it started with, see
+    /// [`conservation`]
+    pub enforce_conservation: bool,
+    /// Change the conservation projection made in the last step
+    pub last_correction: Correction,
+    /// Validation mode, every step also checks that nothing tunneled, see [`certificate`]
+    ///
+    /// Checks every pair of objects, island substeps are skipped while it's set.
+    pub certify: bool,
+    /// Outcome of the checks of the last step while [`Self::certify`] is set
+    pub certificate: Option<Certificate>,
+    /// Checks the energy every collision leaves while set, see [`energy_audit`]
+    pub energy_audit: Option<EnergyAudit>,
+    /// Where the kinetic energy changed during the last step while set, see [`energy_budget`]
+    pub energy_budget: Option<EnergyBudget>,
+    /// Axis the broad phase sweeps along, see [`broad_phase`]
+    pub sweep_axis: SweepAxis,
+    /// Broad phase counts of the last step
+    pub broad_phase: BroadPhaseStats,
+    /// Layers the objects are grouped into, see [`layer`]
+    pub layers: Vec<Layer>,
+    /// Teams the objects play for, see [`team`]
+    pub teams: Vec<Team>,
+    /// Points of objects held together, see [`joint`]
+    pub joints: Vec<Joint>,
+    /// Sweeps over the joints per step
+    pub joint_iterations: usize,
+    /// Points scored during the last step
+    pub scored: Vec<Score>,
+    /// Islands of interacting objects substep on their own when set, see [`islands`]
+    ///
+    /// Ignored while mutual gravity or joints couple objects, spawns are pending, steps are
+    /// certified, collision energies audited or energies budgeted.
+    pub substeps: Option<SubstepPolicy>,
+    /// Regions where time runs at a different rate, see [`dilation`]
+    pub time_regions: Vec<TimeRegion>,
+    /// Objects queued by [`Self::spawn_mid_step`], latest `cur_time` first
+    pub pending_spawns: Vec<Object>,
+    /// Pairs resting on each other during the step with their contact normals, pointing towards
+    /// the first object. A bounce too slow to part them for long leaves them resting, their
+    /// impacts aren't looked for again until the step's end resolves their overlap.
+    pub resting_pairs: BTreeMap<(usize, usize), Vec2>,
+    /// Source of every random draw, see [`rng`]
+    pub rng: RngService,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            objects: vec![],
+            time_elapsed: 0.,
+            frame: 0,
+            collision_margin: DEFAULT_COLLISION_MARGIN,
+            pair_cache: PairCache::new(),
+            collision_queue: BinaryHeap::new(),
+            recent_collisions: VecDeque::with_capacity(RECENT_COLLISIONS),
+            resolved: vec![],
+            tracer: Tracer::new(),
+            frame_budget: None,
+            simplified: false,
+            batch_tolerance: None,
+            collision_limit: Some(DEFAULT_COLLISION_LIMIT),
+            gravity: Vec2::ZERO,
+            gravitational_constant: None,
+            gravity_sources: vec![],
+            integrator: Integrator::Euler,
+            enforce_conservation: false,
+            last_correction: Correction::default(),
+            certify: false,
+            certificate: None,
+            energy_audit: None,
+            energy_budget: None,
+            sweep_axis: SweepAxis::default(),
+            broad_phase: BroadPhaseStats::default(),
+            layers: layer::default_layers(),
+            teams: vec![],
+            joints: vec![],
+            joint_iterations: DEFAULT_JOINT_ITERATIONS,
+            scored: vec![],
+            substeps: None,
+            time_regions: vec![],
+            pending_spawns: vec![],
+            resting_pairs: BTreeMap::new(),
+            rng: RngService::default(),
+        }
+    }
+
+    /// Replaces the objects, e.g. with a loaded scene or a preset, and drops every cached result
+    ///
+    /// The layers are reset to the default ones and the teams, joints and gravity sources dropped,
+    /// scenes bring theirs back afterwards.
+    pub fn load(&mut self, objects: Vec<Object>, time_elapsed: f32) {
+        self.objects = objects;
+        self.layers = layer::default_layers();
+        self.teams.clear();
+        self.joints.clear();
+        self.gravity_sources.clear();
+        self.scored.clear();
+        self.pending_spawns.clear();
+        self.time_elapsed = time_elapsed;
+        self.pair_cache.clear();
+        self.collision_queue.clear();
+        self.resolved.clear();
+    }
+
+    /// Removes the object at `index` between steps along with its joints, the ones after it shift
+    /// down
+    pub fn remove_object(&mut self, index: usize) -> Object {
+        // The cache is keyed by indices
+        self.pair_cache.clear();
+        self.joints = self.joints.iter().filter_map(|joint| joint.without(index)).collect();
+        self.objects.remove(index)
+    }
+    /// Replaces object `index` by its fragments between steps, see [`fracture::shatter`]. The
+    /// objects after it shift down, the fragments are appended at the returned indices.
+    pub fn fracture(&mut self, index: usize, pieces: usize, energy: f32) -> Range<usize> {
+        let object = self.remove_object(index);
+        let fragments = fracture::shatter(&object, pieces, energy, &mut self.rng);
+        let start = self.objects.len();
+        self.objects.extend(fragments);
+        start..self.objects.len()
+    }
+
+    /// Feeds the time the last frame took to the adaptive quality, collisions switch to the
+    /// simplified proxies over budget and back to full detail once the load drops
+    pub fn report_frame_time(&mut self, frame_time: f32) {
+        let Some(budget) = self.frame_budget else {
+            self.set_simplified(false);
+            return;
+        };
+        if frame_time > budget {
+            self.set_simplified(true);
+        } else if frame_time < budget * RESTORE_FRACTION {
+            self.set_simplified(false);
+        }
+    }
+    fn set_simplified(&mut self, simplified: bool) {
+        if self.simplified != simplified {
+            debug!(simplified, "switching collider detail");
+            self.simplified = simplified;
+            // Cached separations were checked against the other outlines
+            self.pair_cache.clear();
+        }
+    }
+
+    /// Advances the simulation by `dt`, resolving every collision on the way
+    ///
+    /// Objects with a non-finite state are reported before anything is advanced. When resolution
+    /// fails mid-step the remaining collisions are dropped, the objects are still advanced. A
+    /// state turning non-finite mid-step ends it right away instead, nothing is removed so the
+    /// reported index still points at the object.
+    pub fn step(&mut self, dt: f32) -> Result<(), CollisionError> {
+        let _step_span = debug_span!("step", frame = self.frame).entered();
+        if let Some(object) = self.objects.iter().position(|object| !object.is_finite()) {
+            return Err(CollisionError::NaNDetected { object });
+        }
+        self.sample_gravity();
+        for object in &mut self.objects {
+            if object.is_static() {
+                // Whatever velocity it was given, a static object stays put
+                object.velocity = Vec2::ZERO;
+                object.rot_velocity = 0.;
+            }
+        }
+        if let Some(policy) = self.substeps {
+            if self.gravitational_constant.is_none()
+                && self.joints.is_empty()
+                && self.pending_spawns.is_empty()
+                && !self.certify
+                && self.energy_audit.is_none()
+                && self.energy_budget.is_none()
+            {
+                let result = islands::step_islands(self, dt, &policy);
+                self.score_goals();
+                return result;
+            }
+        }
+        for object in &mut self.objects {
+            object.frozen = self.layers.get(object.layer).is_some_and(|layer| !layer.simulate);
+        }
+        let totals = self.enforce_conservation.then(|| Totals::of(&self.objects));
+        if self.energy_budget.is_some() {
+            self.energy_budget = Some(EnergyBudget::start(self.total_energy()));
+        }
+        self.time_elapsed += dt;
+        self.frame += 1;
+        self.drive_kinematic();
+        match self.integrator {
+            Integrator::Euler => {
+                if let Some(constant) = self.gravitational_constant {
+                    gravity::mutual_gravity_kick(&mut self.objects, constant, dt);
+                }
+            }
+            Integrator::Leapfrog => self.kick(dt / 2.),
+        }
+        self.book_energy(|budget| &mut budget.fields);
+        self.run_controllers(dt);
+        self.book_energy(|budget| &mut budget.controllers);
+        if !self.joints.is_empty() {
+            let accelerations = self.objects.iter().map(|object| self.swept_acceleration(object)).collect::<Vec<_>>();
+            joint::solve(&self.joints, &mut self.objects, &accelerations, dt, self.joint_iterations);
+            self.book_energy(|budget| &mut budget.joints);
+        }
+        let dilated = !self.time_regions.is_empty();
+        if dilated {
+            dilation::dilate(&mut self.objects, &self.time_regions);
+        }
+        let resolution = self.update_collisions();
+        let non_finite = matches!(resolution, Err(CollisionError::NaNDetected { .. }));
+        if !non_finite {
+            self.update_objects();
+        }
+        if dilated {
+            dilation::contract(&mut self.objects);
+        }
+        if non_finite {
+            return resolution;
+        }
+        if self.integrator == Integrator::Leapfrog {
+            if !self.gravity_sources.is_empty() {
+                self.sample_gravity();
+            }
+            self.kick(dt / 2.);
+        }
+        self.book_energy(|budget| &mut budget.fields);
+        self.last_correction = match totals {
+            Some(totals) => conservation::project(&mut self.objects, totals),
+            None => Correction::default(),
+        };
+        self.book_energy(|budget| &mut budget.corrections);
+        self.score_goals();
+        resolution
+    }
+
+    /// Layer the object is on, `None` when its index is past the layer list
+    pub fn layer_of(&self, object: &Object) -> Option<&Layer> {
+        self.layers.get(object.layer)
+    }
+
+    /// Pairs of objects whose shapes overlap, empty as long as nothing tunneled
+    pub fn interpenetrating_pairs(&self) -> Vec<(usize, usize)> {
+        let cores = self.objects.iter().map(|object| object.world_core(false)).collect::<Vec<_>>();
+        let mut pairs = vec![];
+        for i in 0..cores.len() {
+            for j in i + 1..cores.len() {
+                let ((a, radius_a), (b, radius_b)) = (&cores[i], &cores[j]);
+                if rounded_overlap(a, *radius_a, b, *radius_b) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Position closest to `object`'s own at which it overlaps nothing, `None` if there's no room
+    /// within `max_nudge`
+    ///
+    /// Candidates spiral outwards from the object's position, a fraction of its size apart, and
+    /// are checked against the convex hulls of the objects in reach, the ones still waiting in
+    /// [`Self::spawn_mid_step`] included.
+    pub fn free_position(&self, object: &Object, max_nudge: f32) -> Option<Vec2> {
+        let radius = object.bounding_circle.radius;
+        let center = object.to_world(object.bounding_circle.center);
+        let neighbours = self
+            .objects
+            .iter()
+            .chain(&self.pending_spawns)
+            .map(|other| (other.to_world(other.bounding_circle.center), other.bounding_circle.radius, other))
+            .filter(|(other_center, other_radius, _)| other_center.distance(center) < max_nudge + radius + other_radius)
+            .map(|(other_center, other_radius, other)| {
+                let outline = convex_hull(other.shape.points.iter().map(|p| other.to_world(*p)).collect());
+                (Circle::new(other_center, other_radius), outline)
+            })
+            .collect::<Vec<_>>();
+        let outline = convex_hull(object.shape.points.clone());
+        let spacing = radius * SPAWN_SPACING;
+        let candidates = ((max_nudge / spacing) * (max_nudge / spacing)).ceil() as usize + 1;
+        let golden_angle = PI * (3. - 5f32.sqrt());
+        (0..candidates.min(MAX_SPAWN_CANDIDATES))
+            .map(|k| object.position + Vec2::from_angle(k as f32 * golden_angle) * spacing * (k as f32).sqrt())
+            .find(|&position| {
+                let transform = Transform2::new(position, object.rotation);
+                let points = outline.iter().map(|p| transform.apply(*p)).collect::<Vec<_>>();
+                let center = transform.apply(object.bounding_circle.center);
+                neighbours.iter().all(|(circle, other)| {
+                    circle.center.distance(center) > circle.radius + radius || !sat_collision_detect(&points, other)
+                })
+            })
+    }
+    /// Adds `object` at the nearest free position within `max_nudge`, see [`Self::free_position`]
+    ///
+    /// Returns the new object's index, or `None` and leaves the world as it is if there's no room.
+    pub fn spawn(&mut self, mut object: Object, max_nudge: f32) -> Option<usize> {
+        let position = self.free_position(&object, max_nudge)?;
+        if position != object.position {
+            if let BodyType::Kinematic(Kinematic::Oscillate { anchor, .. }) = &mut object.body_type {
+                *anchor += position - object.position;
+            }
+            object.position = position;
+            object.refresh_transform();
+        }
+        object.cur_time = self.time_elapsed;
+        self.objects.push(object);
+        Some(self.objects.len() - 1)
+    }
+
+    /// Adds `object` at `time` during the step that reaches it, its state is taken to be the one at
+    /// `time`
+    ///
+    /// Its bounds and times of impact against the rest of that step are computed the moment it
+    /// appears, unlike objects pushed between steps it can collide before the step ends.
+    pub fn spawn_mid_step(&mut self, mut object: Object, time: f32) {
+        object.cur_time = time;
+        let i = self.pending_spawns.partition_point(|pending| pending.cur_time > time);
+        self.pending_spawns.insert(i, object);
+    }
+
+    /// FNV-1a hash of the exact bits of every object's state, equal hashes on two machines mean
+    /// the simulations haven't diverged
+    pub fn state_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+        let mut hash = FNV_OFFSET;
+        let mut feed = |bits: u32| {
+            for byte in bits.to_le_bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+            }
+        };
+        feed(self.objects.len() as u32);
+        feed(self.time_elapsed.to_bits());
+        for object in &self.objects {
+            for value in [
+                object.position.x,
+                object.position.y,
+                object.velocity.x,
+                object.velocity.y,
+                object.rotation,
+                object.rot_velocity,
+                object.cur_time,
+            ] {
+                feed(value.to_bits());
+            }
+        }
+        hash
+    }
+
+    /// Current state of every object, for analysis outside the simulation
+    pub fn snapshot_states(&self) -> Vec<ObjectState> {
+        self.objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| ObjectState::new(index, object))
+            .collect()
+    }
+
+    /// [`Self::total_energy`] plus the potential energy of the dynamic objects in the world's
+    /// gravity and their own accelerations, zero at the origin, and in the gravity sources' fields,
+    /// zero far away
+    pub fn mechanical_energy(&self) -> f32 {
+        let potential = self
+            .objects
+            .iter()
+            .filter(|object| object.is_dynamic() && object.mass.is_finite())
+            .map(|object| {
+                let sources = self.gravity_sources.iter().map(|source| source.potential_at(object.position)).sum::<f32>();
+                -object.mass * (object.acceleration + gravity_on(object, self.gravity)).dot(object.position)
+                    + object.mass * object.gravity_scale * sources
+            })
+            .sum::<f32>();
+        self.total_energy() + potential
+    }
+    /// Acceleration of gravity at `point` before the objects' scales, the uniform `gravity` plus
+    /// the pull of every source
+    pub fn gravity_at(&self, point: Vec2) -> Vec2 {
+        self.gravity_sources.iter().fold(self.gravity, |field, source| field + source.acceleration_at(point))
+    }
+    /// Kinetic energy of the dynamic objects, translational and rotational
+    pub fn total_energy(&self) -> f32 {
+        let mut total_energy = 0.;
+        for object in self.objects.iter().filter(|object| object.is_dynamic()) {
+            total_energy += 0.5 * object.mass * object.velocity.length_squared()
+                + 0.5 * object.inertia * object.rot_velocity * object.rot_velocity;
+        }
+        total_energy
+    }
+
+    /// Books the change of the kinetic energy since the budget's last measurement to `part` of it,
+    /// if a budget is kept
+    fn book_energy(&mut self, part: fn(&mut EnergyBudget) -> &mut f32) {
+        let Some(energy) = self.energy_budget.is_some().then(|| self.total_energy()) else {
+            return;
+        };
+        if let Some(budget) = &mut self.energy_budget {
+            budget.settle(energy, part);
+        }
+    }
+    /// Kinetic energy of objects `i` and `j` while a budget is kept, see [`Self::book_collision`]
+    fn pair_energy(&self, i: usize, j: usize) -> Option<f32> {
+        self.energy_budget.is_some().then(|| local_energy(&self.objects[i]) + local_energy(&self.objects[j]))
+    }
+    /// Books what a collision of objects `i` and `j` changed since their [`Self::pair_energy`] was
+    /// `before`
+    fn book_collision(&mut self, i: usize, j: usize, before: Option<f32>) {
+        let Some(change) = before.map(|before| local_energy(&self.objects[i]) + local_energy(&self.objects[j]) - before) else {
+            return;
+        };
+        if let Some(budget) = &mut self.energy_budget {
+            budget.add(change, |budget| &mut budget.collisions);
+        }
+    }
+    /// Sets the velocity of scripted objects so they reach their prescribed position at the end of
+    /// the step, within a step they move linearly like everything else
+    fn drive_kinematic(&mut self) {
+        for object in self.objects.iter_mut().filter(|object| !object.frozen && !object.is_paused()) {
+            let Some(target) = object.kinematic().and_then(|motion| motion.position_at(self.time_elapsed)) else {
+                continue;
+            };
+            let remaining = self.time_elapsed - object.cur_time;
+            if remaining > 0. {
+                object.velocity = (target - object.position) / remaining;
+            }
+        }
+    }
+    /// Finds the points the last step's collisions scored and adds them to the teams
+    fn score_goals(&mut self) {
+        self.scored = team::scores(&self.teams, &self.objects, &self.resolved);
+        for score in &self.scored {
+            self.teams[score.team].score += 1;
+        }
+    }
+    /// Acceleration bending `object`'s path between collisions, the leapfrog integrator moves
+    /// objects in straight lines and applies it as kicks
+    fn swept_acceleration(&self, object: &Object) -> Vec2 {
+        match self.integrator {
+            Integrator::Euler if !object.is_static() => object.total_acceleration(),
+            _ => Vec2::ZERO,
+        }
+    }
+    /// Gravity on every object at its current position
+    fn sample_gravity(&mut self) {
+        for i in 0..self.objects.len() {
+            let field = self.gravity_at(self.objects[i].position);
+            self.objects[i].gravity = gravity_on(&self.objects[i], field);
+        }
+    }
+    /// Velocity change from the accelerations and mutual gravity over `dt`
+    fn kick(&mut self, dt: f32) {
+        for object in self.objects.iter_mut().filter(|object| object.is_dynamic() && !object.frozen) {
+            object.velocity += object.total_acceleration() * dt;
+        }
+        if let Some(constant) = self.gravitational_constant {
+            gravity::mutual_gravity_kick(&mut self.objects, constant, dt);
+        }
+    }
+    /// Moves object `i` to `time`, see [`Self::move_object`]
+    fn advance(&mut self, i: usize, time: f32) {
+        Self::move_object(self.integrator, &mut self.objects[i], time);
+    }
+    /// Moves `object` to `time`, the leapfrog integrator applies the acceleration as kicks instead
+    ///
+    /// Frozen and static objects stay where they are, only their clock follows. Their collision
+    /// stamp doesn't change either, the impacts queued against them stay valid.
+    fn move_object(integrator: Integrator, object: &mut Object, time: f32) {
+        if object.frozen || object.is_static() {
+            object.cur_time = time;
+            return;
+        }
+        match integrator {
+            Integrator::Euler => object.update(time),
+            Integrator::Leapfrog => object.drift(time),
+        }
+    }
+    fn run_controllers(&mut self, dt: f32) {
+        for (index, object) in self.objects.iter_mut().enumerate().filter(|(_, object)| !object.frozen && !object.is_paused()) {
+            // Taken out for the call, the handle borrows the object
+            let Some(mut controller) = object.controller.take() else {
+                continue;
+            };
+            controller.control(&mut ObjectHandle::new(index, object, dt), dt);
+            object.controller = Some(controller);
+        }
+    }
+    fn update_objects(&mut self) {
+        self.remove_worn_out();
+        for i in 0..self.objects.len() {
+            self.advance(i, self.time_elapsed);
+        }
+    }
+    /// Removes the dynamic objects over the collision limit
+    pub(crate) fn remove_worn_out(&mut self) {
+        let object_count = self.objects.len();
+        let mut active_objects = vec![];
+
+        for object in core::mem::take(&mut self.objects) {
+            let over_limit = self.collision_limit.is_some_and(|limit| object.impacts > limit);
+            if over_limit && object.is_dynamic() {
+                if let Some(budget) = &mut self.energy_budget {
+                    budget.add(-local_energy(&object), |budget| &mut budget.population);
+                }
+                // The joints already follow the earlier removals, the kept objects come before it
+                let removed = active_objects.len();
+                self.joints = self.joints.iter().filter_map(|joint| joint.without(removed)).collect();
+                continue;
+            }
+            active_objects.push(object);
+        }
+        self.objects = active_objects;
+        if self.objects.len() != object_count {
+            // Removal shifted the indices the cache is keyed by
+            self.pair_cache.clear();
+        }
+    }
+    fn update_collisions(&mut self) -> Result<(), CollisionError> {
+        debug!(objects = self.objects.len(), "updating collisions");
+
+        let broad_span = debug_span!("broad_phase").entered();
+        let mut time_measure = Instant::now();
+        self.collision_queue.clear();
+        self.resolved.clear();
+        self.resting_pairs.clear();
+        if let Some(audit) = &mut self.energy_audit {
+            audit.records.clear();
+        }
+        self.pair_cache.start_frame(self.frame);
+        let start = self.certify.then(|| self.objects.clone());
+        let mut stats = BroadPhaseStats {
+            candidates: vec![0; self.objects.len()],
+            ..BroadPhaseStats::default()
+        };
+
+        // The sweep runs along the sweep axis, the trees are keyed by (min, max, id) and (max, min, id)
+        let axis = self.sweep_axis;
+        let left_key = |aabb: &Aabb, i: usize| {
+            let (min, max) = axis.span(aabb);
+            (F32Ord(min), F32Ord(max), i)
+        };
+        let right_key = |aabb: &Aabb, i: usize| {
+            let (min, max) = axis.span(aabb);
+            (F32Ord(max), F32Ord(min), i)
+        };
+
+        let mut bounds = self
+            .objects
+            .iter()
+            .map(|object| {
+                SweptBounds::from_object(object, self.swept_acceleration(object), self.time_elapsed, self.collision_margin, self.simplified)
+            })
+            .collect::<Vec<_>>();
+
+        debug!(elapsed = ?time_measure.elapsed(), "computed bounds");
+        self.tracer.span("bounds", time_measure);
+        stats.overlaps = broad_phase::axis_overlaps(&bounds.iter().map(|b| b.aabb).collect::<Vec<_>>());
+        time_measure = Instant::now();
+
+        // Frozen objects are left out of the trees, nothing ever finds them as a candidate
+        let simulated = |&(i, _): &(usize, &SweptBounds)| !self.objects[i].frozen && !self.objects[i].is_static();
+        let mut bounds_left_bt =
+            BTreeSet::from_iter(bounds.iter().enumerate().filter(simulated).map(|(i, b)| left_key(&b.aabb, i)));
+        let mut bounds_right_bt =
+            BTreeSet::from_iter(bounds.iter().enumerate().filter(simulated).map(|(i, b)| right_key(&b.aabb, i)));
+        // Static objects never move, they're found by the others in a list of their own sorted by
+        // the left edge, long floors enclosing a box included
+        let mut statics = (0..self.objects.len())
+            .filter(|&i| self.objects[i].is_static() && !self.objects[i].frozen)
+            .collect::<Vec<_>>();
+        statics.sort_unstable_by_key(|&i| left_key(&bounds[i].aabb, i));
+
+        // might contain duplicates (segments that are entirely inside) but we don't care, doesn't change anything
+        macro_rules! find_candidates {
+            ($i: expr) => {
+                {
+                    let swept = bounds[$i];
+                    let max = axis.span(&swept.aabb).1;
+                    let range = left_key(&swept.aabb, $i)..(F32Ord(max), F32Ord(0.), 0);
+                    let reached_statics = statics.iter().copied().take_while(|&s| axis.span(&bounds[s].aabb).0 <= max);
+                    let candidates = bounds_left_bt
+                        .range(range.clone())
+                        .chain(bounds_right_bt.range(range))
+                        .map(|bound| bound.2)
+                        .chain(reached_statics)
+                        .filter(|&candidate| candidate != $i && bounds[candidate].may_collide(&swept))
+                        .collect::<Vec<_>>();
+                    stats.candidates[$i] += candidates.len();
+                    candidates
+                }
+            }
+        }
+
+        debug!(elapsed = ?time_measure.elapsed(), "constructed trees");
+        stats.build_time = time_measure.elapsed().as_secs_f32();
+        self.tracer.span("tree_construction", time_measure);
+        drop(broad_span);
+        let narrow_span = debug_span!("narrow_phase").entered();
+        time_measure = Instant::now();
+
+        // Pairs neither of which needs continuous detection and ones already overlapping, checked
+        // once everything is resolved
+        let mut discrete_pairs = vec![];
+        for i in 0..self.objects.len() {
+            if self.objects[i].frozen || self.objects[i].is_static() {
+                continue;
+            }
+            for candidate in find_candidates!(i) {
+                let pair = (i.min(candidate), i.max(candidate));
+                if !self.objects[i].needs_ccd() && !self.objects[candidate].needs_ccd() {
+                    discrete_pairs.push(pair);
+                    stats.discrete += 1;
+                    continue;
+                }
+                let config = PairConfig::new(&self.objects[pair.0], &self.objects[pair.1], self.time_elapsed);
+                if self.pair_cache.is_separated(pair, &config, self.frame) {
+                    stats.cached += 1;
+                    continue;
+                }
+
+                let toi = self.check_collision_pair(i, candidate, self.time_elapsed)?;
+                stats.narrow_checks += 1;
+                if let Some(toi) = toi {
+                    stats.impacts += 1;
+                    self.collision_queue.push(Reverse(toi));
+                }
+                // Already sunk into each other there's no impact left to find, the pair is pushed
+                // apart at the end of the step instead of sinking on through
+                let sunk = toi.is_none() && self.penetration(&self.objects[pair.0], &self.objects[pair.1]).is_some();
+                if sunk {
+                    discrete_pairs.push(pair);
+                }
+                self.pair_cache.record(pair, config, toi.is_some() || sunk, self.frame);
+            }
+        }
+
+        debug!(
+            elapsed = ?time_measure.elapsed(),
+            queued = self.collision_queue.len(),
+            cache_hits = self.pair_cache.hits,
+            "detected initial collisions"
+        );
+        self.tracer.span("initial_detection", time_measure);
+        drop(narrow_span);
+        let _resolve_span = debug_span!("resolve").entered();
+        time_measure = Instant::now();
+
+        let max_resolutions = RESOLUTIONS_PER_OBJECT * (self.objects.len() + self.pending_spawns.len());
+        loop {
+            let next_collision = self.collision_queue.peek().map(|Reverse(toi)| toi.time);
+            let spawn_due = self.pending_spawns.last().is_some_and(|pending| {
+                pending.cur_time <= self.time_elapsed && next_collision.is_none_or(|time| pending.cur_time <= time)
+            });
+            if spawn_due {
+                let mut object = self.pending_spawns.pop().unwrap();
+                object.frozen = self.layers.get(object.layer).is_some_and(|layer| !layer.simulate);
+                object.gravity = gravity_on(&object, self.gravity_at(object.position));
+                if let Some(budget) = &mut self.energy_budget {
+                    budget.add(local_energy(&object), |budget| &mut budget.population);
+                }
+                let i = self.objects.len();
+                stats.candidates.push(0);
+                bounds.push(SweptBounds::from_object(&object, self.swept_acceleration(&object), self.time_elapsed, self.collision_margin, self.simplified));
+                self.objects.push(object);
+                if self.objects[i].frozen {
+                    continue;
+                }
+                if self.objects[i].is_static() {
+                    let at = statics.partition_point(|&s| left_key(&bounds[s].aabb, s) < left_key(&bounds[i].aabb, i));
+                    statics.insert(at, i);
+                } else {
+                    bounds_left_bt.insert(left_key(&bounds[i].aabb, i));
+                    bounds_right_bt.insert(right_key(&bounds[i].aabb, i));
+                }
+                // The trees only find boxes with an end inside the new one, long sweeps enclosing
+                // it would be missed
+                let candidates = (0..i)
+                    .filter(|&candidate| !self.objects[candidate].frozen && bounds[candidate].may_collide(&bounds[i]))
+                    .collect::<Vec<_>>();
+                stats.candidates[i] += candidates.len();
+                for candidate in candidates {
+                    if !self.objects[i].needs_ccd() && !self.objects[candidate].needs_ccd() {
+                        discrete_pairs.push((candidate, i));
+                        stats.discrete += 1;
+                        continue;
+                    }
+                    stats.narrow_checks += 1;
+                    if let Some(toi) = self.check_collision_pair(i, candidate, self.time_elapsed)? {
+                        stats.impacts += 1;
+                        self.collision_queue.push(Reverse(toi));
+                    }
+                }
+                continue;
+            }
+            let Some(Reverse(first)) = self.collision_queue.pop() else {
+                break;
+            };
+            if self.resolved.len() >= max_resolutions {
+                self.collision_queue.clear();
+                self.broad_phase = stats;
+                return Err(CollisionError::NoConvergence { resolved: self.resolved.len() });
+            }
+            let mut touched = vec![];
+            for toi in self.pop_batch(first) {
+                if self.handle_collision(toi)? {
+                    touched.extend([toi.object_1, toi.object_2]);
+                }
+            }
+            // Static objects keep their bounds and stamps, the impacts queued against them stay valid
+            for i in touched.into_iter().filter(|&i| !self.objects[i].is_static()) {
+                let update_start = Instant::now();
+                let new_bounds = SweptBounds::from_object(
+                    &self.objects[i],
+                    self.swept_acceleration(&self.objects[i]),
+                    self.time_elapsed,
+                    self.collision_margin,
+                    self.simplified,
+                );
+                let old_bounds = core::mem::replace(&mut bounds[i], new_bounds);
+
+                bounds_left_bt.remove(&left_key(&old_bounds.aabb, i));
+                bounds_right_bt.remove(&right_key(&old_bounds.aabb, i));
+
+                bounds_left_bt.insert(left_key(&new_bounds.aabb, i));
+                bounds_right_bt.insert(right_key(&new_bounds.aabb, i));
+                stats.updates += 1;
+                stats.update_time += update_start.elapsed().as_secs_f32();
+
+                // The trees only find boxes with an end inside this one, the initial detection
+                // caught the enclosing ones from their side, here they're looked for directly
+                let (min, max) = axis.span(&bounds[i].aabb);
+                let enclosing = bounds.iter().enumerate().filter(|&(candidate, other)| {
+                    let (other_min, other_max) = axis.span(&other.aabb);
+                    other_min < min
+                        && other_max > max
+                        && !self.objects[candidate].frozen
+                        && !self.objects[candidate].is_static()
+                        && other.may_collide(&bounds[i])
+                });
+                let enclosing = enclosing.map(|(candidate, _)| candidate).collect::<Vec<_>>();
+                stats.candidates[i] += enclosing.len();
+                for candidate in find_candidates!(i).into_iter().chain(enclosing) {
+                    if self.resting_pairs.contains_key(&(i.min(candidate), i.max(candidate))) {
+                        continue;
+                    }
+                    if !self.objects[i].needs_ccd() && !self.objects[candidate].needs_ccd() {
+                        discrete_pairs.push((i.min(candidate), i.max(candidate)));
+                        stats.discrete += 1;
+                        continue;
+                    }
+                    stats.narrow_checks += 1;
+                    if let Some(toi) = self.check_collision_pair(i, candidate, self.time_elapsed)? {
+                        stats.impacts += 1;
+                        self.collision_queue.push(Reverse(toi));
+                    }
+                }
+            }
+        }
+        debug!(elapsed = ?time_measure.elapsed(), "resolved collisions");
+        self.tracer.span("resolution", time_measure);
+        self.broad_phase = stats;
+
+        let resting_pairs = core::mem::take(&mut self.resting_pairs);
+        discrete_pairs.sort_unstable();
+        discrete_pairs.dedup();
+        discrete_pairs.retain(|pair| !resting_pairs.contains_key(pair));
+        for (i, j) in discrete_pairs {
+            self.resolve_overlap(i, j, None);
+        }
+        for ((i, j), normal) in resting_pairs {
+            self.resolve_overlap(i, j, Some(normal));
+        }
+        if let Some(start) = start {
+            self.certificate = Some(self.certify_step(start)?);
+        }
+        Ok(())
+    }
+    /// Checks every pair of the objects the step started with, see [`certificate`]
+    fn certify_step(&mut self, start: Vec<Object>) -> Result<Certificate, CollisionError> {
+        let end_time = self.time_elapsed;
+        let mut certificate = Certificate {
+            frame: self.frame,
+            time: end_time,
+            ..Certificate::default()
+        };
+        let bounds = start
+            .iter()
+            .map(|object| SweptBounds::from_object(object, self.swept_acceleration(object), end_time, self.collision_margin, self.simplified))
+            .collect::<Vec<_>>();
+        let end_cores = self.objects[..start.len()]
+            .iter()
+            .map(|object| {
+                let mut object = object.clone();
+                if object.cur_time < end_time {
+                    Self::move_object(self.integrator, &mut object, end_time);
+                }
+                object.world_core(self.simplified)
+            })
+            .collect::<Vec<_>>();
+        let collided_pairs = self
+            .resolved
+            .iter()
+            .map(|toi| (toi.object_1.min(toi.object_2), toi.object_1.max(toi.object_2)))
+            .collect::<BTreeSet<_>>();
+        // A collision with anything, discrete ones included, leaves the swept path
+        let free = (0..start.len()).map(|i| self.objects[i].collided == start[i].collided).collect::<Vec<_>>();
+        let mut missed = vec![];
+        for i in 0..start.len() {
+            for j in i + 1..start.len() {
+                let (a, b) = (&start[i], &start[j]);
+                let certifiable = !a.frozen && !b.frozen && (a.is_dynamic() || b.is_dynamic());
+                if !certifiable || !team::collide(&self.teams, a.team, b.team) {
+                    continue;
+                }
+                certificate.pairs += 1;
+                let ((core_a, radius_a), (core_b, radius_b)) = (&end_cores[i], &end_cores[j]);
+                let overlap = rounded_overlap(core_a, *radius_a, core_b, *radius_b);
+                let kind = if collided_pairs.contains(&(i, j)) {
+                    certificate.collided += 1;
+                    overlap.then_some(ViolationKind::Overlap)
+                } else if free[i] && free[j] && !bounds[i].may_collide(&bounds[j]) {
+                    certificate.disjoint += 1;
+                    None
+                } else if free[i] && free[j] {
+                    // Looked up once all pairs are through, the query needs the starting state
+                    missed.push((i, j));
+                    None
+                } else if overlap {
+                    Some(ViolationKind::Overlap)
+                } else {
+                    certificate.separated += 1;
+                    None
+                };
+                if let Some(kind) = kind {
+                    certificate.violations.push(Violation {
+                        objects: (i, j),
+                        kind,
+                        start: [ObjectState::new(i, a), ObjectState::new(j, b)],
+                    });
+                }
+            }
+        }
+        // The exact query runs on the starting state, swapped in for the moment
+        let end = core::mem::replace(&mut self.objects, start);
+        let queries = missed.iter().map(|&(i, j)| self.check_collision_pair(i, j, end_time)).collect::<Vec<_>>();
+        let start = core::mem::replace(&mut self.objects, end);
+        for (&(i, j), toi) in missed.iter().zip(queries) {
+            match toi? {
+                Some(toi) => certificate.violations.push(Violation {
+                    objects: (i, j),
+                    kind: ViolationKind::MissedImpact { time: toi.time },
+                    start: [ObjectState::new(i, &start[i]), ObjectState::new(j, &start[j])],
+                }),
+                None => certificate.cleared += 1,
+            }
+        }
+        certificate.violations.sort_by_key(|violation| violation.objects);
+        Ok(certificate)
+    }
+    /// Discrete handling of a pair at the end of the step, an overlap is pushed apart along the
+    /// axis of least penetration and approaching objects bounce like in [`Self::handle_collision`]
+    ///
+    /// Pairs `resting` on each other along the given normal, pointing towards `i`, only stop
+    /// approaching, overlapping or not. Bouncing them would add the speed they gained sinking
+    /// into each other.
+    fn resolve_overlap(&mut self, i: usize, j: usize, resting: Option<Vec2>) {
+        if !team::collide(&self.teams, self.objects[i].team, self.objects[j].team) {
+            return;
+        }
+        self.advance(i, self.time_elapsed);
+        self.advance(j, self.time_elapsed);
+        let (a, b) = (&self.objects[i], &self.objects[j]);
+        let inverse_masses = 1. / a.effective_mass() + 1. / b.effective_mass();
+        if inverse_masses == 0. {
+            return;
+        }
+        let penetration = self.penetration(a, b);
+        let Some(normal) = penetration.map(|(normal, _)| normal).or(resting) else {
+            return;
+        };
+        let approach = (a.velocity - b.velocity).dot(normal);
+        let share = (1. / a.effective_mass()) / inverse_masses;
+        let before = self.pair_energy(i, j);
+        if approach < 0. {
+            let impulse = if resting.is_some() { -approach } else { -2. * approach } / inverse_masses;
+            self.objects[i].apply_impulse(impulse * normal);
+            self.objects[j].apply_impulse(-impulse * normal);
+        }
+        if let Some((_, depth)) = penetration {
+            let separation = depth + self.collision_margin;
+            self.objects[i].position += normal * separation * share;
+            self.objects[j].position -= normal * separation * (1. - share);
+            for k in [i, j] {
+                self.objects[k].collided += 1;
+                self.objects[k].refresh_transform();
+            }
+        }
+        self.book_collision(i, j, before);
+    }
+    /// Direction to push `a` out of `b` along and how deep the outlines overlap, `None` while
+    /// they don't, skins aside
+    fn penetration(&self, a: &Object, b: &Object) -> Option<(Vec2, f32)> {
+        let outline = |object: &Object| {
+            convex_hull(object.collider(self.simplified).points.iter().map(|p| object.to_world(*p)).collect())
+        };
+        if a.rounded.is_none() && b.rounded.is_none() {
+            sat_penetration(&outline(a), &outline(b))
+        } else {
+            let ((core_a, radius_a), (core_b, radius_b)) = (a.world_core(self.simplified), b.world_core(self.simplified));
+            match convex_closest_points(&core_a, &core_b) {
+                // Rounded outlines overlap by how much closer the cores are than the radii
+                Some((p, q)) => Some(((p - q).normalize(), radius_a + radius_b - p.distance(q))).filter(|&(_, depth)| depth > 0.),
+                // Overlapping cores are pushed apart by the polygons enclosing the outlines
+                None => sat_penetration(&outline(a), &outline(b)),
+            }
+        }
+    }
+    /// Pushes objects `i` and `j` apart by as far as they genuinely overlap, e.g. after an impact
+    /// found too late by rounding, in inverse proportion to their masses
+    ///
+    /// Static and scripted objects stay on their path, touching skins are left alone.
+    fn correct_overlap(&mut self, i: usize, j: usize) {
+        let (a, b) = (&self.objects[i], &self.objects[j]);
+        let inverse_masses = 1. / a.effective_mass() + 1. / b.effective_mass();
+        if inverse_masses == 0. {
+            return;
+        }
+        let Some((normal, depth)) = self.penetration(a, b) else {
+            return;
+        };
+        let share = (1. / a.effective_mass()) / inverse_masses;
+        trace!(object_1 = i, object_2 = j, depth, "correcting overlap");
+        self.objects[i].position += normal * depth * share;
+        self.objects[j].position -= normal * depth * (1. - share);
+        for k in [i, j] {
+            self.objects[k].refresh_transform();
+        }
+    }
+    /// Middle of the vertices of either object touching the other one, a face hitting flat on
+    /// pushes through its middle instead of the corner the impact was found for
+    ///
+    /// Curved outlines touch in a single point, the impact itself.
+    fn contact_center(&self, a: &Object, b: &Object, impact: Vec2) -> Vec2 {
+        if a.rounded.is_some() || b.rounded.is_some() {
+            return impact;
+        }
+        let reach = self.collision_margin + CONTACT_TOLERANCE;
+        let outline = |object: &Object| {
+            object.collider(self.simplified).points.iter().map(|p| object.to_world(*p)).collect::<Vec<_>>()
+        };
+        let (outline_a, outline_b) = (outline(a), outline(b));
+        let touching = |points: &[Vec2], other: &[Vec2]| {
+            points
+                .iter()
+                .copied()
+                .filter(|&p| closest_point_on_polygon(other, p).is_some_and(|(closest, _)| closest.distance(p) <= reach))
+                .collect::<Vec<_>>()
+        };
+        let mut points = touching(&outline_a, &outline_b);
+        points.extend(touching(&outline_b, &outline_a));
+        if points.is_empty() {
+            return impact;
+        }
+        points.iter().sum::<Vec2>() / points.len() as f32
+    }
+    /// `first` together with the queued collisions within `batch_tolerance` of it which share no
+    /// object with the batch. The batch doesn't depend on the order it's resolved in, new
+    /// collisions are only looked for once all of it is resolved.
+    fn pop_batch(&mut self, first: Toi) -> Vec<Toi> {
+        let mut batch = vec![first];
+        let Some(tolerance) = self.batch_tolerance else {
+            return batch;
+        };
+        let mut deferred = vec![];
+        while let Some(&Reverse(toi)) = self.collision_queue.peek() {
+            if toi.time - first.time > tolerance {
+                break;
+            }
+            self.collision_queue.pop();
+            let shares_object = batch.iter().any(|other| {
+                [other.object_1, other.object_2]
+                    .iter()
+                    .any(|i| *i == toi.object_1 || *i == toi.object_2)
+            });
+            if shares_object {
+                deferred.push(Reverse(toi));
+            } else {
+                batch.push(toi);
+            }
+        }
+        self.collision_queue.extend(deferred);
+        batch
+    }
+    /// Material the impact of `toi` collides with, that of the hit edge combined with that of the
+    /// hitting vertex's edge facing it
+    fn contact_surface(&self, toi: &Toi) -> Material {
+        let (sharp_obj, other_obj) = (&self.objects[toi.object_1], &self.objects[toi.object_2]);
+        let mut edge_1 = toi.point_1;
+        if sharp_obj.rounded.is_none() {
+            let points = &sharp_obj.collider(self.simplified).points;
+            let n = points.len();
+            let previous = (toi.point_1 + n - 1) % n;
+            // Edges of anti-clockwise outlines face out to their right, against the normal when
+            // facing the hit edge
+            let facing = |edge: usize| {
+                let direction = sharp_obj.transform.rotation.apply(points[(edge + 1) % n] - points[edge]);
+                direction.normalize_or_zero().perp().dot(toi.normal)
+            };
+            if facing(previous) > facing(edge_1) {
+                edge_1 = previous;
+            }
+        }
+        let surface_1 = sharp_obj.surface(edge_1, self.simplified);
+        surface_1.combine(other_obj.surface(toi.line_2, self.simplified))
+    }
+    fn handle_collision(&mut self, toi: Toi) -> Result<bool, CollisionError> {
+        let sharp_obj = &self.objects[toi.object_1];
+        let other_obj = &self.objects[toi.object_2];
+
+        if toi.object_1_col_stamp != sharp_obj.updated || toi.object_2_col_stamp != other_obj.updated {
+            trace!(object_1 = toi.object_1, object_2 = toi.object_2, "skipping stale collision");
+            return Ok(false);
+        }
+
+        // Just short of the impact along their paths, the bounce alone keeps the objects apart from
+        // there. Unlike pushing them apart, stopping short keeps the energy.
+        let time = (toi.time - TOI_BACKOFF).max(sharp_obj.cur_time).max(other_obj.cur_time);
+        self.advance(toi.object_1, time);
+        self.advance(toi.object_2, time);
+        let (sharp_obj, other_obj) = (&self.objects[toi.object_1], &self.objects[toi.object_2]);
+        let point = self.contact_center(sharp_obj, other_obj, toi.point);
+        let surface = self.contact_surface(&toi);
+        let snapshot = self.energy_audit.is_some().then(|| Snapshot::take(sharp_obj, other_obj, point, toi.normal, surface));
+        let before = self.pair_energy(toi.object_1, toi.object_2);
+        let approach = (other_obj.velocity_at_point(point) - sharp_obj.velocity_at_point(point)).dot(toi.normal);
+        let impulse = contact_impulse_with(sharp_obj, other_obj, point, toi.normal, surface);
+
+        let normal = toi.normal;
+        self.objects[toi.object_1].apply_impulse_at_point(impulse * normal, point);
+        self.objects[toi.object_2].apply_impulse_at_point(-impulse * normal, point);
+        // Against the sliding left after the bounce
+        let (sharp_obj, other_obj) = (&self.objects[toi.object_1], &self.objects[toi.object_2]);
+        let friction = friction_impulse_with(sharp_obj, other_obj, point, normal, impulse, surface);
+        let tangent = normal.perp();
+        self.objects[toi.object_1].apply_impulse_at_point(friction * tangent, point);
+        self.objects[toi.object_2].apply_impulse_at_point(-friction * tangent, point);
+        // Too slow a separation to hop for `MIN_HOP` against the accelerations leaves the pair
+        // resting, at the hitting vertex as well in case the push through the middle leaves it
+        // rocking down. Nothing is added to the bounce, the overlap it sinks into is resolved at
+        // the end of the step.
+        let resting = [point, toi.point].into_iter().any(|point| {
+            let (sharp_obj, other_obj) = (&self.objects[toi.object_1], &self.objects[toi.object_2]);
+            let pressing = (self.swept_acceleration(other_obj) - self.swept_acceleration(sharp_obj)).dot(normal);
+            let separation = (sharp_obj.velocity_at_point(point) - other_obj.velocity_at_point(point)).dot(normal);
+            separation < pressing * MIN_HOP / 2.
+        });
+        if resting {
+            let pair = (toi.object_1.min(toi.object_2), toi.object_1.max(toi.object_2));
+            self.resting_pairs.insert(pair, if pair.0 == toi.object_1 { normal } else { -normal });
+        }
+
+        self.objects[toi.object_1].collided += 1;
+        self.objects[toi.object_2].collided += 1;
+        if !resting && approach > MIN_IMPACT_SPEED {
+            self.objects[toi.object_1].impacts += 1;
+            self.objects[toi.object_2].impacts += 1;
+        }
+
+        self.correct_overlap(toi.object_1, toi.object_2);
+        self.book_collision(toi.object_1, toi.object_2, before);
+
+        let toi = Toi { impulse, ..toi };
+        if let (Some(audit), Some(snapshot)) = (&mut self.energy_audit, snapshot) {
+            audit.record(toi, snapshot, &self.objects[toi.object_1], &self.objects[toi.object_2]);
+        }
+        if self.recent_collisions.len() == RECENT_COLLISIONS {
+            self.recent_collisions.pop_front();
+        }
+        self.recent_collisions.push_back(toi);
+        self.resolved.push(toi);
+
+        trace!(
+            time = toi.time,
+            object_1 = toi.object_1,
+            object_2 = toi.object_2,
+            impulse,
+            "collision resolved"
+        );
+        self.tracer.instant(
+            "collision",
+            &[
+                ("time", toi.time as f64),
+                ("object_1", toi.object_1 as f64),
+                ("object_2", toi.object_2 as f64),
+                ("impulse", impulse as f64),
+            ],
+        );
+
+        for object in [toi.object_1, toi.object_2] {
+            if !self.objects[object].is_finite() {
+                return Err(CollisionError::NaNDetected { object });
+            }
+        }
+        Ok(true)
+    }
+
+    /// Earliest impact of one of `sharp_obj_id`'s corners with an edge of `other_obj_id` before
+    /// `horizon`, the reverse (corners of the other object) isn't checked
+    ///
+    /// The corners are followed in the other object's space, where spin and the accelerations bend
+    /// their paths. The impact is the first time a corner's distance to an edge's line drops to
+    /// zero within the edge, found by [`first_crossing`] with the spins and the relative
+    /// acceleration bounding how sharply the distance can turn, so fast-spinning corners can't
+    /// sweep past an edge between two samples. Without spin the distance is a parabola and solved
+    /// for directly by [`first_quadratic_crossing`].
+    pub fn compute_toi(
+        &self,
+        sharp_obj_id: usize,
+        other_obj_id: usize,
+        horizon: f32,
+    ) -> Result<Option<Toi>, CollisionError> {
+        for object in [sharp_obj_id, other_obj_id] {
+            if !self.objects[object].is_finite() {
+                return Err(CollisionError::NaNDetected { object });
+            }
+        }
+        // Two infinite masses can't exchange an impulse
+        if !self.objects[sharp_obj_id].is_dynamic() && !self.objects[other_obj_id].is_dynamic() {
+            return Ok(None);
+        }
+        let mut sharp_obj = self.objects[sharp_obj_id].clone();
+        let mut other_obj = self.objects[other_obj_id].clone();
+
+        let cur_time = sharp_obj.cur_time.max(other_obj.cur_time);
+
+        for object in [&mut sharp_obj, &mut other_obj] {
+            Self::move_object(self.integrator, object, cur_time);
+        }
+
+        if horizon <= cur_time {
+            return Ok(None);
+        }
+        let (sharp_acceleration, other_acceleration) =
+            (self.swept_acceleration(&sharp_obj), self.swept_acceleration(&other_obj));
+        // Pose at `time` assuming constant accelerations from `cur_time` on
+        let pose_at = |object: &Object, acceleration: Vec2, time: f32| {
+            let dt = time - cur_time;
+            let position = object.position + object.velocity * dt + acceleration * (dt * dt / 2.);
+            Transform2::new(position, object.rotation + object.rot_velocity * dt)
+        };
+        let sharp_at = |time| pose_at(&sharp_obj, sharp_acceleration, time);
+        let other_at = |time| pose_at(&other_obj, other_acceleration, time);
+
+        // Rounded objects collide by their cores, the radii add to the distance they meet at
+        let (sharp_obj_points, sharp_radius) = sharp_obj.core(self.simplified);
+        let (other_obj_points, other_radius) = other_obj.core(self.simplified);
+        let rounding = sharp_radius + other_radius;
+        // The skin: edges pushed outwards by the margin, joined around each vertex by a bevel
+        // edge so a vertex heading straight at another one can't slip between them. Shapes are
+        // anti-clockwise, every skin edge keeps the index of the vertex it starts at. Rounded
+        // pairs push the edges out by the radii as well and instead of bevels meet the vertices
+        // at that distance, a single point core has no edges at all.
+        let margin = self.collision_margin;
+        let skin_offset = margin + rounding;
+        let edge_count = if other_obj_points.len() > 1 { other_obj_points.len() } else { 0 };
+        let normals = (0..edge_count)
+            .map(|j| -(other_obj_points[(j + 1) % other_obj_points.len()] - other_obj_points[j]).perp().normalize_or_zero())
+            .collect::<Vec<_>>();
+        let mut skin_edges = vec![];
+        for (j, (&a, &normal)) in other_obj_points.iter().zip(&normals).enumerate() {
+            let previous = normals[(j + normals.len() - 1) % normals.len()];
+            if margin > 0. && rounding == 0. {
+                let bevel = Segment::new(a + previous * margin, a + normal * margin);
+                skin_edges.push((bevel, (previous + normal).normalize_or_zero(), j));
+            }
+            let b = other_obj_points[(j + 1) % other_obj_points.len()];
+            skin_edges.push((Segment::new(a + normal * skin_offset, b + normal * skin_offset), normal, j));
+        }
+        let skin_vertices = if rounding > 0. { &other_obj_points[..] } else { &[] };
+
+        // The centers move along a parabola relative to each other, it's farthest from the origin
+        // at an end or bulging out by at most its sag in between
+        let span = horizon - cur_time;
+        let (relative_velocity, relative_acceleration) =
+            (sharp_obj.velocity - other_obj.velocity, sharp_acceleration - other_acceleration);
+        let offset = sharp_obj.position - other_obj.position;
+        let end_offset = offset + relative_velocity * span + relative_acceleration * (span * span / 2.);
+        let relative_acceleration = relative_acceleration.length();
+        let relative_speed = relative_velocity.length() + relative_acceleration * span;
+        let farthest = offset.length().max(end_offset.length()) + relative_acceleration * span * span / 8.;
+        let (spin_sharp, spin_other) = (sharp_obj.rot_velocity.abs(), other_obj.rot_velocity.abs());
+        // Most searches end after looking at the ends of the interval
+        let start_poses = (sharp_at(cur_time), other_at(cur_time));
+        let end_poses = (sharp_at(horizon), other_at(horizon));
+
+        let mut collision: Option<Toi> = None;
+        for (i, p) in sharp_obj_points.iter().enumerate() {
+            let corner_at = |time| {
+                let (sharp, other) = match time {
+                    time if time == cur_time => start_poses,
+                    time if time == horizon => end_poses,
+                    time => (sharp_at(time), other_at(time)),
+                };
+                other.apply_inverse(sharp.apply(*p))
+            };
+            // Bound on the corner's acceleration in the other object's spinning space: the relative
+            // acceleration, its own spin, the Coriolis term and the centrifugal term of the
+            // other's spin
+            let reach = p.length();
+            let curvature = relative_acceleration
+                + spin_sharp * spin_sharp * reach
+                + 2. * spin_other * (relative_speed + spin_sharp * reach)
+                + spin_other * spin_other * (farthest + reach);
+
+            for &(edge, normal, j) in &skin_edges {
+                // Edges found later only count if they're hit earlier
+                let until = collision.map_or(horizon, |collision| collision.time);
+                // Corners starting within the skin, e.g. right after a bounce off something else,
+                // can't cross it from outside, the real edge is their impact instead
+                let start = normal.dot(corner_at(cur_time) - edge.a);
+                let skin = if start <= SKIN_TOLERANCE && start > -margin { margin } else { 0. };
+                let distance = |time| normal.dot(corner_at(time) - edge.a) + skin;
+                let within_edge = |time| {
+                    let along = (corner_at(time) - edge.a).dot(edge.direction()) / edge.direction().length_squared();
+                    (0. ..=1.).contains(&along)
+                };
+                // Without spin the distance is a quadratic of time
+                let crossing = if spin_sharp == 0. && spin_other == 0. && relative_acceleration > 0. {
+                    first_quadratic_crossing(distance, within_edge, cur_time..until)
+                } else {
+                    first_crossing(distance, within_edge, cur_time..until, curvature)
+                };
+                let Some(time) = crossing else {
+                    continue;
+                };
+                if time <= cur_time || time >= until {
+                    continue;
+                }
+                let normal = Vec2::from_angle(other_obj.rotation + other_obj.rot_velocity * (time - cur_time)).rotate(normal);
+                collision = Some(Toi {
+                    time,
+                    object_1: sharp_obj_id,
+                    object_1_col_stamp: self.objects[sharp_obj_id].updated,
+                    point_1: i,
+                    object_2: other_obj_id,
+                    object_2_col_stamp: self.objects[other_obj_id].updated,
+                    line_2: j,
+                    point: sharp_at(time).apply(*p) - normal * sharp_radius,
+                    normal,
+                    impulse: 0.,
+                });
+            }
+            // Rounded pairs meet around the vertices where the squared distance drops to the
+            // square of the skin offset, found from both sides, whichever comes first
+            for (j, &vertex) in skin_vertices.iter().enumerate() {
+                let until = collision.map_or(horizon, |collision| collision.time);
+                let start = corner_at(cur_time).distance(vertex) - skin_offset;
+                let skin = if start <= 0. && start > -margin { margin } else { 0. };
+                let contact_distance = skin_offset - skin;
+                let distance = |time| corner_at(time).distance_squared(vertex) - contact_distance * contact_distance;
+                // Points at their object's origin don't move with its spin, with neither moving
+                // with a spin nor an acceleration the squared distance is a quadratic of time
+                let unturned = (spin_sharp == 0. || *p == Vec2::ZERO) && (spin_other == 0. || vertex == Vec2::ZERO);
+                let crossing = if unturned && relative_acceleration == 0. {
+                    first_quadratic_crossing(distance, |_| true, cur_time..until)
+                } else {
+                    // The squared distance bends with twice the squared speed of the corner and
+                    // its distance times its acceleration
+                    let speed = relative_speed + spin_sharp * reach + spin_other * (farthest + reach);
+                    let span = farthest + reach + vertex.length();
+                    first_crossing(distance, |_| true, cur_time..until, 2. * (speed * speed + span * curvature))
+                };
+                let Some(time) = crossing else {
+                    continue;
+                };
+                if time <= cur_time || time >= until {
+                    continue;
+                }
+                let direction = (corner_at(time) - vertex).normalize_or_zero();
+                let normal = Vec2::from_angle(other_obj.rotation + other_obj.rot_velocity * (time - cur_time)).rotate(direction);
+                collision = Some(Toi {
+                    time,
+                    object_1: sharp_obj_id,
+                    object_1_col_stamp: self.objects[sharp_obj_id].updated,
+                    point_1: i,
+                    object_2: other_obj_id,
+                    object_2_col_stamp: self.objects[other_obj_id].updated,
+                    line_2: j,
+                    point: sharp_at(time).apply(*p) - normal * sharp_radius,
+                    normal,
+                    impulse: 0.,
+                });
+            }
+        }
+
+        Ok(collision)
+    }
+
+    /// Earliest impact between `a` and `b` before `horizon`, the vertices of either hitting the
+    /// other, see [`Self::compute_toi`]
+    pub fn check_collision_pair(&self, a: usize, b: usize, horizon: f32) -> Result<Option<Toi>, CollisionError> {
+        if !team::collide(&self.teams, self.objects[a].team, self.objects[b].team) {
+            return Ok(None);
+        }
+        let forward = self.compute_toi(a, b, horizon)?;
+        let backward = self.compute_toi(b, a, horizon)?;
+        Ok(match (forward, backward) {
+            (Some(forward), Some(backward)) => Some(forward.min(backward)),
+            (forward, backward) => forward.or(backward),
+        })
+    }
+}
+
+/// Gravity `object` falls with in a world with `gravity`
+fn gravity_on(object: &Object, gravity: Vec2) -> Vec2 {
+    if !object.is_dynamic() || object.mass.is_infinite() {
+        return Vec2::ZERO;
+    }
+    gravity * object.gravity_scale
+}
+
+/// Impulse along `normal` (pointing out of `b`) for a contact at `point`, the spin of both
+/// objects adds to the approach speed and the lever arms to the effective mass
+///
+/// `a` gets the impulse times `normal`, `b` the opposite. The pair's restitution is the geometric
+/// mean of the objects', so an inelastic object stays inelastic against anything. A contact
+/// already separating at `point` gets none, the impulse only ever pushes apart.
+pub fn contact_impulse(a: &Object, b: &Object, point: Vec2, normal: Vec2) -> f32 {
+    contact_impulse_with(a, b, point, normal, a.material().combine(b.material()))
+}
+
+/// [`contact_impulse`] bouncing with the restitution of `surface`, e.g. that of the edges touching
+pub fn contact_impulse_with(a: &Object, b: &Object, point: Vec2, normal: Vec2, surface: Material) -> f32 {
+    let approach = (a.velocity_at_point(point) - b.velocity_at_point(point)).dot(normal);
+    (-(1. + surface.restitution) * approach / effective_mass_inverse(a, b, point, normal)).max(0.)
+}
+
+/// Change of the objects' relative velocity at `point` along `direction` per unit of impulse
+/// along it, the lever arms turn part of the impulse into spin
+pub(crate) fn effective_mass_inverse(a: &Object, b: &Object, point: Vec2, direction: Vec2) -> f32 {
+    let (arm_a, arm_b) = (point - a.position, point - b.position);
+    let (turn_a, turn_b) = (arm_a.perp_dot(direction), arm_b.perp_dot(direction));
+    1. / a.effective_mass() + 1. / b.effective_mass() + turn_a * turn_a / a.effective_inertia() + turn_b * turn_b / b.effective_inertia()
+}
+
+/// Coulomb friction impulse along `normal.perp()` for a contact that took `normal_impulse`
+///
+/// The impulse stopping the sliding at `point` is used while it stays within the static friction
+/// cone, otherwise the contact slides and gets the dynamic friction. Coefficients combine like
+/// the restitution, as the geometric mean of the objects'.
+pub fn friction_impulse(a: &Object, b: &Object, point: Vec2, normal: Vec2, normal_impulse: f32) -> f32 {
+    friction_impulse_with(a, b, point, normal, normal_impulse, a.material().combine(b.material()))
+}
+
+/// [`friction_impulse`] with the friction coefficients of `surface`
+pub fn friction_impulse_with(a: &Object, b: &Object, point: Vec2, normal: Vec2, normal_impulse: f32, surface: Material) -> f32 {
+    let tangent = normal.perp();
+    let sliding = (a.velocity_at_point(point) - b.velocity_at_point(point)).dot(tangent);
+    let sticking = -sliding / effective_mass_inverse(a, b, point, tangent);
+    let static_limit = surface.static_friction * normal_impulse.abs();
+    let dynamic = surface.dynamic_friction * normal_impulse.abs();
+    if sticking.abs() <= static_limit {
+        sticking
+    } else {
+        // Never so strong it reverses the sliding
+        sticking.signum() * dynamic.min(sticking.abs())
+    }
+}
+
+#[test]
+fn test_compute_toi() {
+    let mut world = World::new();
+    world.collision_margin = 0.;
+    // Squares with a gap of 2, closing at speed 2
+    let square = Polygon::new(vec![vec2(-1., -1.), vec2(1., -1.), vec2(1., 1.), vec2(-1., 1.)]);
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., square.clone()));
+    world.objects.push(Object::new(vec2(4., 0.5), Vec2::ZERO, 0., square));
+
+    let toi = world.compute_toi(0, 1, 10.).unwrap().unwrap();
+    assert!((toi.time - 1.).abs() < 1e-5);
+    assert_eq!((toi.object_1, toi.object_2), (0, 1));
+    // The top right corner of the moving square hits the left edge of the resting one
+    assert_eq!(toi.point_1, 2);
+    assert_eq!(toi.line_2, 3);
+    assert!(toi.point.abs_diff_eq(vec2(3., 1.), 1e-5));
+    assert!(toi.normal.abs_diff_eq(vec2(-1., 0.), 1e-5));
+
+    assert_eq!(world.compute_toi(0, 1, 0.5), Ok(None));
+}
+
+#[test]
+fn test_axis_aligned_impacts() {
+    let mut world = World::new();
+    world.collision_margin = 0.;
+    let square = || Polygon::rectangle(vec2(2., 2.));
+    // A square falling straight down onto a floor, an infinite slope for `dy/dx`
+    world.objects.push(Object::new(vec2(0., 3.), vec2(0., -2.), 0., square()));
+    world.objects.push(Object::new_static(Vec2::ZERO, 0., Polygon::rectangle(vec2(6., 2.))));
+    let toi = world.compute_toi(0, 1, 2.).unwrap().unwrap();
+    assert!((toi.time - 0.5).abs() < 1e-5);
+    let up = if toi.object_1 == 0 { vec2(0., 1.) } else { vec2(0., -1.) };
+    assert!(toi.normal.abs_diff_eq(up, 1e-5));
+
+    // Into the vertical edge of a wall, and sliding past it along the edge
+    world.objects.clear();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(4., 0.), 0., square()));
+    world.objects.push(Object::new(vec2(0.5, 3.), vec2(0., -4.), 0., square()));
+    world.objects.push(Object::new_static(vec2(3., 0.), 0., Polygon::rectangle(vec2(2., 14.))));
+    let toi = world.compute_toi(0, 2, 2.).unwrap().unwrap();
+    assert!((toi.time - 0.25).abs() < 1e-5);
+    assert!(toi.normal.x.abs() > 1. - 1e-5);
+    assert_eq!(world.compute_toi(1, 2, 2.), Ok(None));
+
+    // Stepped with the skin, the square hits the wall face on and bounces straight back
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(4., 0.), 0., square()));
+    world.objects.push(Object::new_static(vec2(3., 0.), 0., Polygon::rectangle(vec2(2., 14.))));
+    world.step(1.).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    assert!(world.objects[0].velocity.abs_diff_eq(vec2(-4., 0.), 1e-4));
+    assert!(world.objects[0].rot_velocity.abs() < 1e-4);
+}
+
+#[test]
+fn test_restitution_combines() {
+    let mut world = World::new();
+    let square = Polygon::rectangle(vec2(1., 1.));
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., square.clone()));
+    world.objects.push(Object::new(vec2(3., 0.), Vec2::ZERO, 0., square));
+    // The pair bounces with the geometric mean, 0.5
+    world.objects[0].restitution = 0.25;
+    world.step(2.).unwrap();
+    assert!(world.objects[0].velocity.abs_diff_eq(vec2(0.5, 0.), 1e-4));
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(1.5, 0.), 1e-4));
+}
+
+#[test]
+fn test_friction_impulse() {
+    // A square sliding right on a floor while bouncing off it, hit at its bottom center
+    let mut square = Object::new(vec2(0., 0.), vec2(1., -1.), 0., Polygon::rectangle(vec2(2., 2.)));
+    let mut floor = Object::new_kinematic(vec2(0., -2.), 0., Polygon::rectangle(vec2(10., 2.)), object::Kinematic::Constant);
+    let (point, normal) = (vec2(0., -1.), vec2(0., 1.));
+    let normal_impulse = contact_impulse(&square, &floor, point, normal);
+    square.apply_impulse_at_point(normal_impulse * normal, point);
+    assert_eq!(friction_impulse(&square, &floor, point, normal, normal_impulse), 0.);
+
+    // Within the static cone the contact point stops sliding, the square starts rolling clockwise
+    for object in [&mut square, &mut floor] {
+        object.static_friction = 1.;
+        object.dynamic_friction = 0.1;
+    }
+    let sticking = friction_impulse(&square, &floor, point, normal, normal_impulse);
+    let mut stuck = square.clone();
+    stuck.apply_impulse_at_point(sticking * normal.perp(), point);
+    assert!(stuck.velocity_at_point(point).x.abs() < 1e-5);
+    assert!(stuck.rot_velocity < 0.);
+
+    // Outside of it the contact slides with the dynamic friction
+    square.velocity.x = 10.;
+    let sliding = friction_impulse(&square, &floor, point, normal, normal_impulse);
+    assert!((sliding - 0.1 * normal_impulse).abs() < 1e-5);
+}
+
+#[test]
+fn test_collisions_stop_short_of_the_impact() {
+    // A square bouncing off a wall turns back where its skin touched it, nothing pushes it further
+    let mut world = World::new();
+    let margin = world.collision_margin;
+    world.objects.push(Object::new(Vec2::ZERO, vec2(1., 0.), 0., Polygon::rectangle(vec2(1., 1.))));
+    world.objects.push(Object::new_static(vec2(2.5, 0.), 0., Polygon::rectangle(vec2(1., 4.))));
+    world.step(2.).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    let turn = 1.5 - margin;
+    let square = &world.objects[0];
+    assert!((square.position.x - (turn - (square.cur_time - turn))).abs() < 1e-4);
+
+    // Dropped on a floor it settles on its skin instead of hopping about
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    world.objects.push(Object::new(vec2(0., 1.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1., 1.))));
+    world.objects.push(Object::new_static(vec2(0., -0.5), 0., Polygon::rectangle(vec2(4., 1.))));
+    world.objects[0].restitution = 0.5;
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+    }
+    let gap = world.objects[0].position.y - 0.5;
+    assert!(gap > 0. && gap < margin + 1e-3, "{gap}");
+    assert!(world.objects[0].velocity.length() < 0.1);
+}
+
+#[test]
+fn test_edge_materials() {
+    // A trampoline with a springy top and dead sides, elastic boxes bounce off the edge they hit
+    let springy = Material {
+        restitution: 1.,
+        static_friction: 0.,
+        dynamic_friction: 0.,
+    };
+    let trampoline = Polygon::rectangle(vec2(2., 1.)).with_edge_material(2, springy);
+    let bounce = |position: Vec2, velocity: Vec2, size: Vec2| {
+        let mut world = World::new();
+        world.objects.push(Object::new_static(Vec2::ZERO, 0., trampoline.clone()));
+        world.objects.push(Object::new(position, velocity, 0., Polygon::rectangle(size)));
+        world.objects[0].restitution = 0.;
+        world.step(1.).unwrap();
+        assert_eq!(world.resolved.len(), 1);
+        world.objects[1].velocity
+    };
+    // Hit on the top edge by a small box's corners
+    assert!(bounce(vec2(0., 1.), vec2(0., -1.), vec2(0.5, 0.5)).abs_diff_eq(vec2(0., 1.), 1e-4));
+    // Hitting a wide plank with the top corners, the edges facing it are the top's
+    assert!(bounce(vec2(0., 1.), vec2(0., -1.), vec2(4., 0.5)).abs_diff_eq(vec2(0., 1.), 1e-4));
+    // The sides keep the object's material
+    assert!(bounce(vec2(-2., 0.), vec2(1., 0.), vec2(0.5, 0.5)).abs_diff_eq(Vec2::ZERO, 1e-4));
+}
+
+#[test]
+fn test_check_collision_pair() {
+    let mut world = World::new();
+    // Diamonds tip to tip, each tip heads straight at the other's, between its edges' skins
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., Polygon::regular(4)));
+    world.objects.push(Object::new(vec2(4., 0.), Vec2::ZERO, 0., Polygon::regular(4)));
+
+    // The bevel across the hit tip is the margin over root two out
+    let skin = DEFAULT_COLLISION_MARGIN * core::f32::consts::FRAC_1_SQRT_2;
+    let toi = world.check_collision_pair(0, 1, 3.).unwrap().unwrap();
+    assert!((toi.time - (1. - skin / 2.)).abs() < 1e-5);
+    let tip = if toi.object_1 == 0 { vec2(3. - skin, 0.) } else { vec2(3., 0.) };
+    assert!(toi.point.abs_diff_eq(tip, 1e-4));
+    // Either order finds the same impact
+    assert_eq!(world.check_collision_pair(1, 0, 3.).unwrap().unwrap().time, toi.time);
+    let normal = if toi.object_1 == 0 { vec2(-1., 0.) } else { vec2(1., 0.) };
+    assert!(toi.normal.abs_diff_eq(normal, 1e-4));
+
+    // Equal masses swap velocities
+    world.step(2.).unwrap();
+    assert!(world.objects[0].velocity.abs_diff_eq(Vec2::ZERO, 1e-4));
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(2., 0.), 1e-4));
+}
+
+#[test]
+fn test_compute_toi_follows_spin() {
+    let mut world = World::new();
+    world.collision_margin = 0.;
+    // A needle spinning through almost a radian per hundredth of a second, its tip only dips
+    // slightly past the box's face
+    let needle = Polygon::rectangle(vec2(4., 0.1));
+    let mut spinner = Object::new(Vec2::ZERO, Vec2::ZERO, -0.47, needle);
+    spinner.rot_velocity = 60.;
+    world.objects.push(spinner);
+    world.objects.push(Object::new(vec2(2.9, 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 2.))));
+
+    let toi = world.compute_toi(0, 1, 1.).unwrap().unwrap();
+    // Hit on the way in, before the needle points straight at the box
+    assert!(toi.time < 0.47 / 60.);
+    assert!((toi.point.x - 1.9).abs() < 1e-3);
+    assert!(toi.normal.abs_diff_eq(vec2(-1., 0.), 1e-5));
+}
+
+#[test]
+fn test_circle_collisions_are_exact() {
+    let mut world = World::new();
+    let contact = 2. + world.collision_margin;
+    // Off-center impact of two unit circles, their centers meet `contact` apart
+    world.objects.push(Object::circle(vec2(0., 1.5), vec2(2., 0.), 1.));
+    world.objects.push(Object::circle(vec2(5., 0.), Vec2::ZERO, 1.));
+    let toi = world.check_collision_pair(0, 1, 10.).unwrap().unwrap();
+    let expected = (5. - (contact * contact - 1.5 * 1.5).sqrt()) / 2.;
+    assert!((toi.time - expected).abs() < 1e-5);
+    let center = vec2(2. * toi.time, 1.5);
+    assert!(toi.normal.abs_diff_eq((center - vec2(5., 0.)).normalize() * if toi.object_1 == 0 { 1. } else { -1. }, 1e-5));
+    // The contact lies on the line of centers, a spinning circle hits at the same time
+    assert!((toi.point - vec2(5., 0.)).perp_dot(center - vec2(5., 0.)).abs() < 1e-4);
+    world.objects[0].rot_velocity = 7.;
+    assert!((world.check_collision_pair(0, 1, 10.).unwrap().unwrap().time - expected).abs() < 1e-5);
+
+    // Against a box a circle meets the flat face or, past the edge, the corner at its radius
+    let mut world = World::new();
+    world.objects.push(Object::new_static(Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 2.))));
+    world.objects.push(Object::circle(vec2(0., 5.), vec2(0., -1.), 1.));
+    world.objects.push(Object::circle(vec2(1.5, 5.), vec2(0., -1.), 1.));
+    let face = world.check_collision_pair(0, 1, 10.).unwrap().unwrap();
+    assert!((face.time - (3. - world.collision_margin)).abs() < 1e-5);
+    assert!(face.point.abs_diff_eq(vec2(0., 1. + world.collision_margin), 1e-4));
+    let corner = world.check_collision_pair(0, 2, 10.).unwrap().unwrap();
+    let reach = 1. + world.collision_margin;
+    assert!((corner.time - (4. - (reach * reach - 0.25).sqrt())).abs() < 1e-5);
+
+    // Head on, equal masses swap their velocities and pick up no spin
+    let mut world = World::new();
+    world.objects.push(Object::circle(vec2(0., 0.), vec2(1., 0.), 1.));
+    world.objects.push(Object::circle(vec2(5., 0.), vec2(-1., 0.), 1.));
+    world.step(2.).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    assert!((world.resolved[0].time - (3. - world.collision_margin) / 2.).abs() < 1e-5);
+    assert!(world.objects[0].velocity.abs_diff_eq(vec2(-1., 0.), 1e-5));
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(1., 0.), 1e-5));
+    assert_eq!((world.objects[0].rot_velocity, world.objects[1].rot_velocity), (0., 0.));
+    assert!(world.interpenetrating_pairs().is_empty());
+}
+
+#[test]
+fn test_capsule_collisions_are_exact() {
+    let margin = World::new().collision_margin;
+    // Lying flat a capsule lands on its side, standing up on its cap
+    let mut world = World::new();
+    world.objects.push(Object::new_static(Vec2::ZERO, 0., Polygon::rectangle(vec2(6., 2.))));
+    world.objects.push(Object::capsule(vec2(0., 4.), vec2(0., -1.), 0., 3., 0.5));
+    world.objects.push(Object::capsule(vec2(2., 5.), vec2(0., -1.), PI / 2., 3., 0.5));
+    let side = world.check_collision_pair(0, 1, 10.).unwrap().unwrap();
+    assert!((side.time - (2.5 - margin)).abs() < 1e-5);
+    assert!(side.normal.abs_diff_eq(vec2(0., 1.), 1e-5));
+    let cap = world.check_collision_pair(0, 2, 10.).unwrap().unwrap();
+    assert!((cap.time - (2.5 - margin)).abs() < 1e-5);
+    assert!(cap.point.abs_diff_eq(vec2(2., 1. + margin), 1e-4));
+
+    // A box corner meets the capsule's side and a circle its rounded end
+    let mut world = World::new();
+    world.objects.push(Object::capsule(Vec2::ZERO, Vec2::ZERO, 0., 3., 0.5));
+    world.objects.push(Object::new(vec2(0.5, 3.), vec2(0., -1.), PI / 4., Polygon::rectangle(vec2(1., 1.))));
+    world.objects.push(Object::circle(vec2(5., 0.5), vec2(-1., 0.), 0.5));
+    let corner = world.check_collision_pair(0, 1, 10.).unwrap().unwrap();
+    assert!((corner.time - (3. - 0.5_f32.sqrt() - 0.5 - margin)).abs() < 1e-5);
+    let end = world.check_collision_pair(0, 2, 10.).unwrap().unwrap();
+    let reach = 1. + margin;
+    assert!((end.time - (4. - (reach * reach - 0.25).sqrt())).abs() < 1e-5);
+
+    // A spinning capsule sweeps its end into a wall the still one never reaches
+    let mut world = World::new();
+    world.objects.push(Object::capsule(Vec2::ZERO, Vec2::ZERO, 0., 3., 0.5));
+    world.objects.push(Object::new_static(vec2(0., 2.), 0., Polygon::rectangle(vec2(4., 1.))));
+    assert_eq!(world.check_collision_pair(0, 1, 1.), Ok(None));
+    world.objects[0].rot_velocity = 2.;
+    let toi = world.check_collision_pair(0, 1, 1.).unwrap().unwrap();
+    // The end's rounded tip, 1 from the center and 0.5 around it, touches y = 1.5 - margin
+    let tip = 1.5 - margin - 0.5;
+    assert!((toi.time - tip.asin() / 2.).abs() < 1e-4);
+}
+
+#[test]
+fn test_step_reports_nan() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(1., 0.), 0., Polygon::regular(4)));
+    world.objects.push(Object::new(vec2(5., 0.), vec2(f32::NAN, 0.), 0., Polygon::regular(4)));
+
+    assert_eq!(world.step(0.1), Err(CollisionError::NaNDetected { object: 1 }));
+    assert_eq!(world.time_elapsed, 0.);
+    assert_eq!(world.compute_toi(0, 1, 1.), Err(CollisionError::NaNDetected { object: 1 }));
+
+    world.objects.pop();
+    assert_eq!(world.step(0.1), Ok(()));
+}
+
+#[test]
+fn test_resting_objects_outlive_collision_limit() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    assert_eq!(world.collision_limit, Some(DEFAULT_COLLISION_LIMIT));
+    world.objects.push(Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(20., 1.))));
+    for (x, restitution) in [(-6., 0.), (-2., 0.5)] {
+        for mut object in [Object::circle(vec2(x, 2.), Vec2::ZERO, 0.5), Object::new(vec2(x + 2., 2.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1., 1.)))] {
+            object.restitution = restitution;
+            world.objects.push(object);
+        }
+    }
+
+    // Ten seconds on the floor, resting contacts every step
+    for _ in 0..1200 {
+        world.step(1. / 120.).unwrap();
+    }
+    assert_eq!(world.objects.len(), 5);
+    for object in &world.objects[1..] {
+        assert!(object.collided > DEFAULT_COLLISION_LIMIT, "{object:?}");
+        assert!(object.impacts <= DEFAULT_COLLISION_LIMIT, "{object:?}");
+        // On the floor's top at -0.5
+        assert!(object.position.y.abs() < 0.01, "{object:?}");
+    }
+}
+
+#[test]
+fn test_step_ends_on_mid_step_nan() {
+    let mut world = World::new();
+    world.collision_limit = Some(1);
+    // Worn out, removing it would shift the index of the object going non-finite
+    let mut worn = Object::new(vec2(-5., 0.), Vec2::ZERO, 0., Polygon::regular(4));
+    worn.impacts = 2;
+    world.objects.push(worn);
+    world.objects.push(Object::new(vec2(0., 0.), vec2(10., 0.), 0., Polygon::regular(4)));
+    // Massless, the bounce divides zero by zero
+    let mut massless = Object::new(vec2(3., 0.), Vec2::ZERO, 0., Polygon::regular(4));
+    massless.mass = 0.;
+    world.objects.push(massless);
+
+    let Err(CollisionError::NaNDetected { object }) = world.step(0.1) else {
+        panic!("the bounce should go non-finite");
+    };
+    assert_eq!(world.objects.len(), 3);
+    assert!(!world.objects[object].is_finite());
+}
+
+#[test]
+fn test_adaptive_quality() {
+    let mut world = World::new();
+    world.report_frame_time(1.);
+    assert!(!world.simplified);
+
+    world.frame_budget = Some(0.01);
+    world.report_frame_time(0.02);
+    assert!(world.simplified);
+    // Between the restore threshold and the budget nothing changes
+    world.report_frame_time(0.008);
+    assert!(world.simplified);
+    world.report_frame_time(0.005);
+    assert!(!world.simplified);
+}
+
+#[test]
+fn test_batched_resolution() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let run = |batch_tolerance: Option<f32>| {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut world = World { batch_tolerance, ..World::new() };
+        for x in 0..8 {
+            for y in 0..8 {
+                let velocity = vec2(rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0));
+                let rotation = rng.gen_range(0.0..PI);
+                world.objects.push(Object::new(vec2(x as f32, y as f32) * 2.5, velocity, rotation, Polygon::regular(4)));
+            }
+        }
+        let initial_energy = world.total_energy();
+        for _ in 0..60 {
+            world.step(1. / 60.).unwrap();
+        }
+        (world, initial_energy)
+    };
+
+    for batch_tolerance in [None, Some(1e-3)] {
+        let (world, initial_energy) = run(batch_tolerance);
+        assert!(world.objects.iter().map(|object| object.collided).sum::<usize>() > 0);
+        // Elastic collisions, batching mustn't change that
+        assert!((world.total_energy() - initial_energy).abs() < initial_energy * 1e-3);
+
+        let (rerun, _) = run(batch_tolerance);
+        let positions = |world: &World| world.objects.iter().map(|object| object.position).collect::<Vec<_>>();
+        assert_eq!(positions(&world), positions(&rerun));
+    }
+}
+
+#[test]
+fn test_kinematic_push() {
+    let mut world = World::new();
+    world.collision_margin = 0.;
+    let mut wall = Object::new_kinematic(vec2(0., 0.), 0., Polygon::rectangle(vec2(1., 4.)), Kinematic::Constant);
+    wall.velocity = vec2(10., 0.);
+    world.objects.push(wall);
+    world.objects.push(Object::new(vec2(1., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(0.5, 0.5))));
+
+    world.step(0.1).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    // An elastic bounce off an infinite mass, the box leaves with twice the wall's velocity
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(20., 0.), 1e-3));
+    assert_eq!(world.objects[0].velocity, vec2(10., 0.));
+    assert!(world.total_energy().is_finite());
+}
+
+#[test]
+fn test_static_floor() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    // Neither a mass nor a velocity moves a static object
+    let mut floor = Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(20., 1.)));
+    floor.mass = 1.;
+    floor.velocity = vec2(1., 0.);
+    world.objects.push(floor);
+    for x in [-3., 0., 3.] {
+        world.objects.push(Object::new(vec2(x, 1.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1., 1.))));
+    }
+
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+        assert!(world.interpenetrating_pairs().is_empty());
+    }
+    let floor = &world.objects[0];
+    assert_eq!((floor.position, floor.velocity, floor.rotation), (vec2(0., -1.), Vec2::ZERO, 0.));
+    // Every box bounced twice, its stamp never changed so the impacts queued against it stayed valid
+    assert_eq!((floor.collided, floor.updated), (6, 0));
+}
+
+#[test]
+fn test_paused_object_acts_static() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    let square = || Polygon::rectangle(vec2(1., 1.));
+    let mut wall = Object::new(vec2(2., 0.), vec2(1., 0.), 0., square());
+    wall.rot_velocity = 2.;
+    wall.pause();
+    world.objects.push(wall);
+    let mut ball = Object::new(vec2(0., 0.), vec2(4., 0.), 0., square());
+    ball.gravity_scale = 0.;
+    world.objects.push(ball);
+
+    // Time stands still for the wall, the ball bounces off it as off infinite mass
+    world.step(0.5).unwrap();
+    let wall = &world.objects[0];
+    assert_eq!((wall.position, wall.rotation, wall.velocity), (vec2(2., 0.), 0., Vec2::ZERO));
+    assert_eq!(world.resolved.len(), 1);
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(-4., 0.), 1e-3));
+
+    // Resumed, it moves on with the velocities it had and falls again
+    world.objects[0].resume();
+    world.step(0.5).unwrap();
+    let wall = &world.objects[0];
+    assert!(wall.position.x > 2.4 && wall.position.y < -1. && wall.rotation > 0.9);
+}
+
+#[test]
+fn test_gravity_bends_the_sweep() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -20.);
+    world.objects.push(Object::new_kinematic(vec2(0., -6.), 0., Polygon::rectangle(vec2(40., 1.)), Kinematic::Constant));
+    world.objects.push(Object::new(vec2(0., 0.), vec2(5., 0.), 0., Polygon::rectangle(vec2(0.5, 0.5))));
+    let mut balloon = Object::new(vec2(0., 3.), vec2(5., 0.), 0., Polygon::rectangle(vec2(0.5, 0.5)));
+    balloon.gravity_scale = 0.;
+    world.objects.push(balloon);
+
+    // A single long step, the box reaches the floor along the parabola well before its end
+    world.step(1.).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    let impact = world.resolved[0].time;
+    assert!((impact - (5.25f32 / 10.).sqrt()).abs() < 1e-2, "hit the floor at {impact}");
+    assert!(world.objects[1].position.y > -5.5 && world.objects[1].velocity.y > 0.);
+    // The floor doesn't fall, the balloon floats
+    assert_eq!(world.objects[0].position.y, -6.);
+    assert_eq!(world.objects[2].position.y, 3.);
+}
+
+#[test]
+fn test_spawn_starts_separated() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 2.))));
+    world.step(0.5).unwrap();
+
+    let i = world.spawn(Object::new(vec2(0.5, 0.), Vec2::ZERO, 0.3, Polygon::regular(5)), 5.).unwrap();
+    assert!(world.interpenetrating_pairs().is_empty());
+    assert!(world.objects[i].position.length() < 5.);
+    assert_eq!(world.objects[i].cur_time, world.time_elapsed);
+
+    // Free spots are kept as they are
+    let free = Object::new(vec2(10., 0.), Vec2::ZERO, 0., Polygon::regular(3));
+    assert_eq!(world.free_position(&free, 1.), Some(vec2(10., 0.)));
+
+    // No room within reach, nothing is added
+    let inside = Object::new(vec2(0., 0.), Vec2::ZERO, 0., Polygon::regular(3));
+    assert_eq!(world.spawn(inside, 0.1), None);
+    assert_eq!(world.objects.len(), 2);
+
+    // Nor on top of an object yet to join during the coming step
+    world.spawn_mid_step(Object::new(vec2(10., 0.), Vec2::ZERO, 0., Polygon::regular(3)), world.time_elapsed);
+    assert_ne!(world.free_position(&free, 1.), Some(vec2(10., 0.)));
+}
+
+#[test]
+fn test_mid_step_spawn_collides_within_the_step() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 0.), vec2(10., 0.), 0., Polygon::regular(4)));
+    world.spawn_mid_step(Object::new(vec2(6., 0.), Vec2::ZERO, 0., Polygon::regular(4)), 0.2);
+    world.spawn_mid_step(Object::new(vec2(0., 20.), Vec2::ZERO, 0., Polygon::regular(4)), 1.5);
+
+    world.step(1.).unwrap();
+    assert_eq!(world.objects.len(), 2);
+    // Hit within the step, the spawned square takes over the velocity
+    assert!(world.resolved.iter().all(|toi| toi.time > 0.2 && toi.time < 1.));
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(10., 0.), 1e-3));
+    // Corner on corner through both centers, no lever arm to spin them
+    assert!(world.objects.iter().all(|object| object.rot_velocity.abs() < 1e-3));
+
+    // The later spawn waits for its step
+    assert_eq!(world.pending_spawns.len(), 1);
+    world.step(1.).unwrap();
+    assert_eq!(world.objects.len(), 3);
+    assert!(world.pending_spawns.is_empty());
+}
+
+#[test]
+fn test_ccd_modes() {
+    use object::CcdMode;
+
+    // Slow objects without continuous detection overlap for a moment and are pushed apart
+    let mut world = World::new();
+    let square = Polygon::rectangle(vec2(2., 2.));
+    world.objects.push(Object::new(vec2(0., 0.), vec2(1., 0.), 0., square.clone()));
+    world.objects.push(Object::new(vec2(2.5, 0.), Vec2::ZERO, 0., square));
+    for object in &mut world.objects {
+        object.ccd = CcdMode::Never;
+    }
+    world.step(1.).unwrap();
+    assert!(world.resolved.is_empty());
+    assert!(world.interpenetrating_pairs().is_empty());
+    assert!(world.objects[0].velocity.abs_diff_eq(Vec2::ZERO, 1e-6));
+    assert!(world.objects[1].velocity.abs_diff_eq(vec2(1., 0.), 1e-6));
+
+    // A fast bullet still can't tunnel through a wall that only collides discretely
+    let mut world = World::new();
+    let mut wall = Object::new(vec2(5., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(0.1, 4.)));
+    wall.ccd = CcdMode::Never;
+    let mut bullet = Object::new(vec2(0., 0.), vec2(100., 0.), 0., Polygon::rectangle(vec2(0.2, 0.2)));
+    bullet.ccd = CcdMode::FastOnly(10.);
+    world.objects.extend([wall, bullet]);
+    world.step(0.2).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    assert!(world.objects[1].position.x < 5.);
+}
+
+#[test]
+fn test_resolved_impulse() {
+    let mut world = World::new();
+    let square = Polygon::rectangle(vec2(2., 2.));
+    world.objects.push(Object::new(vec2(0., 0.), vec2(2., 0.), 0., square.clone()));
+    world.objects.push(Object::new(vec2(4., 0.5), Vec2::ZERO, 0., square));
+    world.step(1.5).unwrap();
+
+    let toi = world.resolved[0];
+    // The faces overlap from -0.5 to 1, the push through the middle of that is a quarter above
+    // the left square's center and below the right one's, so part of the impulse goes into spin
+    let (arm, inertia) = (0.25, world.objects[0].inertia);
+    let expected = 4. / (2. + 2. * arm * arm / inertia);
+    assert!((toi.impulse - expected).abs() < 1e-3, "{}", toi.impulse);
+    assert!(world.objects[toi.object_1].velocity.abs_diff_eq(vec2(2., 0.) + toi.impulse * toi.normal, 1e-4));
+    for object in &world.objects {
+        assert!((object.rot_velocity - arm * expected / inertia).abs() < 1e-3, "{}", object.rot_velocity);
+    }
+}
+
+#[test]
+fn test_impulse_never_pulls() {
+    let mut spinning = Object::new(Vec2::ZERO, vec2(1., 0.), 0., Polygon::rectangle(vec2(2., 2.)));
+    spinning.rot_velocity = 5.;
+    let wall = Object::new_static(vec2(2., 0.), 0., Polygon::rectangle(vec2(2., 2.)));
+    let normal = vec2(-1., 0.);
+    // The face comes in, but the spin turns its upper half away from the wall
+    assert!(contact_impulse(&spinning, &wall, vec2(1., -0.5), normal) > 0.);
+    assert!((spinning.velocity_at_point(vec2(1., 0.5)) - wall.velocity_at_point(vec2(1., 0.5))).dot(normal) > 0.);
+    assert_eq!(contact_impulse(&spinning, &wall, vec2(1., 0.5), normal), 0.);
+}
+
+#[test]
+fn test_collision_transfers_spin() {
+    let mut world = World::new();
+    world.objects.push(Object::new(vec2(0., 1.5), vec2(2., 0.), 0., Polygon::rectangle(vec2(2., 2.))));
+    world.objects.push(Object::new(vec2(4., 0.), Vec2::ZERO, 0., Polygon::rectangle(vec2(2., 4.))));
+    let angular_momentum = |world: &World| {
+        world
+            .objects
+            .iter()
+            .map(|object| object.inertia * object.rot_velocity + object.mass * object.position.perp_dot(object.velocity))
+            .sum::<f32>()
+    };
+    let (energy, momentum) = (world.total_energy(), angular_momentum(&world));
+
+    world.step(1.5).unwrap();
+    assert_eq!(world.resolved.len(), 1);
+    // Pushed right above its center the box turns clockwise, the square pushed back below its
+    // center too
+    assert!(world.objects[1].rot_velocity < -0.1);
+    assert!(world.objects[0].rot_velocity < 0.);
+    assert!((world.total_energy() - energy).abs() < 1e-4);
+    assert!((angular_momentum(&world) - momentum).abs() < 1e-3);
+}
+
+#[test]
+fn test_determinism_golden_hash() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // Recorded per target, float results may legitimately differ between architectures and math
+    // backends but must never change on one of them without anyone noticing. Targets without an
+    // entry fail with the hash to record, e.g. on aarch64 or with
+    // `cargo test --target wasm32-wasi`.
+    // (target_arch, std, hash)
+    const GOLDEN_HASHES: &[(&str, bool, u64)] = &[
+        ("x86_64", true, 0x9757_622d_579a_69ff),
+        ("x86_64", false, 0x88c3_99a9_85e9_3431),
+    ];
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut world = World::new();
+    for x in 0..6 {
+        for y in 0..6 {
+            let velocity = vec2(rng.gen_range(-4.0..4.0), rng.gen_range(-4.0..4.0));
+            let mut object = Object::new(vec2(x as f32, y as f32) * 2.2, velocity, rng.gen_range(0.0..PI), Polygon::regular(3 + (x + y) % 4));
+            object.rot_velocity = rng.gen_range(-2.0..2.0);
+            world.objects.push(object);
+        }
+    }
+    for _ in 0..120 {
+        world.step(1. / 60.).unwrap();
+    }
+    let hash = world.state_hash();
+
+    let arch = if cfg!(target_arch = "x86_64") {
+        "x86_64"
+    } else if cfg!(target_arch = "aarch64") {
+        "aarch64"
+    } else if cfg!(target_arch = "wasm32") {
+        "wasm32"
+    } else {
+        "other"
+    };
+    // Without std the trigonometry comes from libm
+    let target = (arch, cfg!(feature = "std"));
+    match GOLDEN_HASHES.iter().find(|(arch, std, _)| (*arch, *std) == target) {
+        Some(&(_, _, golden)) => assert_eq!(hash, golden, "state diverged from the golden run on {target:?}"),
+        None => panic!("no golden hash recorded for {target:?}, this run gives {hash:#x}"),
+    }
+}
+
+#[test]
+fn test_swept_bounds_of_a_resting_ball() {
+    // Resting on a planet, its poses over a step are a hair apart along a diagonal
+    let mut ball = Object::circle(vec2(-3.7491572, 3.7491572), vec2(-0.001937028, 0.001937028), 0.3);
+    ball.gravity = vec2(6.168743, -6.168743);
+    ball.cur_time = 1.1176664;
+    let bounds = SweptBounds::from_object(&ball, ball.total_acceleration(), 1.1166663 + 1. / 60., DEFAULT_COLLISION_MARGIN, false);
+    let obb = bounds.obb.unwrap();
+    assert!(obb.half_extents.min_element() > 0.3, "{obb:?}");
+}
+
+#[test]
+fn test_resting_contact_adds_no_energy() {
+    let mut world = World::new();
+    world.gravity = vec2(0., -9.81);
+    world.energy_audit = Some(EnergyAudit::default());
+    world.objects.push(Object::new_static(vec2(0., -1.), 0., Polygon::rectangle(vec2(10., 1.))));
+    let mut crate_ = Object::new(vec2(0., 1.), Vec2::ZERO, 0., Polygon::rectangle(vec2(1., 1.)));
+    crate_.restitution = 0.5;
+    world.objects.push(crate_);
+
+    for _ in 0..240 {
+        world.step(1. / 60.).unwrap();
+    }
+    // The bounces die out into rest instead of being kept up
+    let audit = world.energy_audit.as_ref().unwrap();
+    assert_eq!(audit.violations, 0, "{audit:?}");
+    assert!(world.objects[1].velocity.length() < 1e-3, "still moving at {}", world.objects[1].velocity);
+    assert!(world.interpenetrating_pairs().is_empty());
+}